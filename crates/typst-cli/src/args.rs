@@ -75,6 +75,12 @@ pub enum Command {
     /// Processes an input file to extract provided metadata.
     Query(QueryCommand),
 
+    /// Formats Typst source files.
+    Fmt(FmtCommand),
+
+    /// Runs a language server for editors over stdin and stdout.
+    Lsp(LspCommand),
+
     /// Lists all discovered fonts in system and custom font paths.
     Fonts(FontsCommand),
 
@@ -160,6 +166,47 @@ pub struct QueryCommand {
     pub process: ProcessArgs,
 }
 
+/// Formats Typst source files.
+#[derive(Debug, Clone, Parser)]
+pub struct FmtCommand {
+    /// Paths to Typst files or to directories, which are searched recursively
+    /// for Typst files. Use `-` to read from stdin and write to stdout.
+    #[clap(required = true, value_hint = ValueHint::AnyPath)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Checks that the files are formatted instead of formatting them.
+    ///
+    /// Lists the files that are not formatted and fails if there are any.
+    #[clap(long)]
+    pub check: bool,
+
+    /// The line width beyond which argument lists and other collections are
+    /// broken into one item per line.
+    #[clap(long, default_value_t = 80)]
+    pub width: usize,
+
+    /// The number of spaces per indentation level.
+    #[clap(long, default_value_t = 2)]
+    pub indent: usize,
+}
+
+/// Runs a language server for editors over stdin and stdout.
+#[derive(Debug, Clone, Parser)]
+pub struct LspCommand {
+    /// Path to the main input file, whose document provides diagnostics and
+    /// jump targets. Defaults to the file that was last opened or edited.
+    #[clap(value_hint = ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
 /// Lists all discovered fonts in system and custom font paths.
 #[derive(Debug, Clone, Parser)]
 pub struct FontsCommand {
@@ -205,8 +252,8 @@ pub struct CompileArgs {
     #[clap(value_parser = input_value_parser(), value_hint = ValueHint::FilePath)]
     pub input: Input,
 
-    /// Path to output file (PDF, PNG, SVG, or HTML). Use `-` to write output to
-    /// stdout.
+    /// Path to output file (PDF, PNG, SVG, HTML, or EPUB). Use `-` to write
+    /// output to stdout.
     ///
     /// For output formats emitting one file per page (PNG & SVG), a page number
     /// template must be present if the source document renders to multiple
@@ -246,10 +293,65 @@ pub struct CompileArgs {
     #[arg(long = "pdf-standard", value_delimiter = ',')]
     pub pdf_standard: Vec<PdfStandard>,
 
+    /// Encrypts the PDF and requires this password to open it.
+    #[arg(
+        long = "pdf-user-password",
+        env = "TYPST_PDF_USER_PASSWORD",
+        hide_env_values = true,
+        value_name = "PASSWORD"
+    )]
+    pub pdf_user_password: Option<String>,
+
+    /// Encrypts the PDF and requires this password to lift its permission
+    /// restrictions. Defaults to the user password.
+    #[arg(
+        long = "pdf-owner-password",
+        env = "TYPST_PDF_OWNER_PASSWORD",
+        hide_env_values = true,
+        value_name = "PASSWORD"
+    )]
+    pub pdf_owner_password: Option<String>,
+
+    /// One (or multiple comma-separated) actions that users of the encrypted
+    /// PDF may not perform unless they know the owner password.
+    #[arg(long = "pdf-deny", value_delimiter = ',', requires = "pdf_owner_password")]
+    pub pdf_deny: Vec<PdfPermission>,
+
+    /// The algorithm to encrypt the PDF with.
+    #[arg(long = "pdf-encryption", default_value_t = PdfEncryption::Aes256)]
+    pub pdf_encryption: PdfEncryption,
+
     /// The PPI (pixels per inch) to use for PNG export.
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
+    /// Exports an HTML document as a site with multiple files. The output path
+    /// is then a directory into which the files are written.
+    #[arg(long = "html-site")]
+    pub html_site: bool,
+
+    /// Top-level headings up to this level start a new file when exporting an
+    /// HTML site. Explicit `html.split` elements always start a new file.
+    #[arg(long = "html-split-level", value_name = "LEVEL", requires = "html_site")]
+    pub html_split_level: Option<NonZeroUsize>,
+
+    /// How to include images in HTML output.
+    #[arg(long = "html-images", default_value_t = HtmlImages::Embed)]
+    pub html_images: HtmlImages,
+
+    /// Top-level headings up to this level start a new chapter when exporting
+    /// an EPUB. Explicit `html.split` elements always start a new chapter.
+    /// Defaults to 1. With 0, headings never start a new chapter.
+    ///
+    /// EPUB export builds on HTML export and thus also requires
+    /// `--features html`.
+    #[arg(long = "epub-split-level", value_name = "LEVEL")]
+    pub epub_split_level: Option<usize>,
+
+    /// An image to use as the cover of an EPUB.
+    #[arg(long = "epub-cover", value_name = "PATH")]
+    pub epub_cover: Option<PathBuf>,
+
     /// File path to which a Makefile with the current compilation's
     /// dependencies will be written.
     #[clap(long = "make-deps", value_name = "PATH")]
@@ -273,8 +375,8 @@ pub struct CompileArgs {
     pub timings: Option<Option<PathBuf>>,
 }
 
-/// Arguments for the construction of a world. Shared by compile, watch, and
-/// query.
+/// Arguments for the construction of a world. Shared by compile, watch, query,
+/// and lsp.
 #[derive(Debug, Clone, Args)]
 pub struct WorldArgs {
     /// Configures the project root (for absolute paths).
@@ -441,6 +543,7 @@ pub enum OutputFormat {
     Png,
     Svg,
     Html,
+    Epub,
 }
 
 display_possible_values!(OutputFormat);
@@ -459,6 +562,9 @@ display_possible_values!(DiagnosticFormat);
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum Feature {
     Html,
+    /// Prepares the document for tagged PDF export. Enabled automatically
+    /// when exporting to PDF/A-2a, PDF/A-3a, or PDF/UA-1.
+    PdfTags,
 }
 
 display_possible_values!(Feature);
@@ -470,16 +576,82 @@ pub enum PdfStandard {
     /// PDF 1.7.
     #[value(name = "1.7")]
     V_1_7,
+    /// PDF 2.0.
+    #[value(name = "2.0")]
+    V_2_0,
+    /// PDF/A-1b.
+    #[value(name = "a-1b")]
+    A_1b,
     /// PDF/A-2b.
     #[value(name = "a-2b")]
     A_2b,
+    /// PDF/A-2a.
+    #[value(name = "a-2a")]
+    A_2a,
     /// PDF/A-3b.
     #[value(name = "a-3b")]
     A_3b,
+    /// PDF/A-3a.
+    #[value(name = "a-3a")]
+    A_3a,
+    /// PDF/A-4.
+    #[value(name = "a-4")]
+    A_4,
+    /// PDF/A-4f.
+    #[value(name = "a-4f")]
+    A_4f,
+    /// PDF/UA-1.
+    #[value(name = "ua-1")]
+    UA_1,
 }
 
 display_possible_values!(PdfStandard);
 
+/// An algorithm that Typst can encrypt PDFs with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfEncryption {
+    /// AES with a 128-bit key, for compatibility with older viewers.
+    #[value(name = "aes-128")]
+    Aes128,
+    /// AES with a 256-bit key.
+    #[value(name = "aes-256")]
+    Aes256,
+}
+
+display_possible_values!(PdfEncryption);
+
+/// How images are included in HTML output.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum HtmlImages {
+    /// Embed images into the HTML as data URLs.
+    Embed,
+    /// Write images into separate files next to the HTML output.
+    Files,
+}
+
+display_possible_values!(HtmlImages);
+
+/// An action that users of an encrypted PDF can be denied.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfPermission {
+    /// Printing the document at all.
+    Print,
+    /// Printing the document in full quality.
+    HighQualityPrint,
+    /// Modifying the document's contents.
+    Modify,
+    /// Copying text and graphics.
+    Copy,
+    /// Adding and modifying annotations.
+    Annotate,
+    /// Filling in forms.
+    FillForms,
+    /// Inserting, rotating, and deleting pages.
+    Assemble,
+}
+
+display_possible_values!(PdfPermission);
+
 // Output file format for query command
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum SerializationFormat {
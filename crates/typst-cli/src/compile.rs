@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::{self, Write};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Datelike, Timelike, Utc};
@@ -13,16 +15,18 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use typst::diag::{
     bail, At, Severity, SourceDiagnostic, SourceResult, StrResult, Warned,
 };
-use typst::foundations::{Datetime, Smart};
+use typst::foundations::{Bytes, Datetime, Smart};
 use typst::html::HtmlDocument;
 use typst::layout::{Frame, Page, PageRanges, PagedDocument};
 use typst::syntax::{FileId, Source, Span};
 use typst::WorldExt;
-use typst_pdf::{PdfOptions, PdfStandards, Timestamp};
+use typst_pdf::{
+    PdfEncryptionAlgorithm, PdfOptions, PdfPermissions, PdfStandards, Timestamp,
+};
 
 use crate::args::{
-    CompileArgs, CompileCommand, DiagnosticFormat, Input, Output, OutputFormat,
-    PdfStandard, WatchCommand,
+    CompileArgs, CompileCommand, DiagnosticFormat, Feature, HtmlImages, Input, Output,
+    OutputFormat, PdfEncryption, PdfPermission, PdfStandard, ProcessArgs, WatchCommand,
 };
 #[cfg(feature = "http-server")]
 use crate::server::HtmlServer;
@@ -39,7 +43,7 @@ type CodespanError = codespan_reporting::files::Error;
 pub fn compile(timer: &mut Timer, command: &CompileCommand) -> StrResult<()> {
     let mut config = CompileConfig::new(command)?;
     let mut world =
-        SystemWorld::new(&command.args.input, &command.args.world, &command.args.process)
+        SystemWorld::new(&command.args.input, &command.args.world, &config.process)
             .map_err(|err| eco_format!("{err}"))?;
     timer.record(&mut world, |world| compile_once(world, &mut config))?
 }
@@ -50,7 +54,7 @@ pub struct CompileConfig {
     pub watching: bool,
     /// Path to input Typst file or stdin.
     pub input: Input,
-    /// Path to output file (PDF, PNG, SVG, or HTML).
+    /// Path to output file (PDF, PNG, SVG, HTML, or EPUB).
     pub output: Output,
     /// The format of the output file.
     pub output_format: OutputFormat,
@@ -60,16 +64,29 @@ pub struct CompileConfig {
     pub creation_timestamp: Option<DateTime<Utc>>,
     /// The format to emit diagnostics in.
     pub diagnostic_format: DiagnosticFormat,
+    /// Arguments for the compilation process, including the features that the
+    /// export requires.
+    pub process: ProcessArgs,
     /// Opens the output file with the default viewer or a specific program after
     /// compilation.
     pub open: Option<Option<String>>,
     /// One (or multiple comma-separated) PDF standards that Typst will enforce
     /// conformance with.
     pub pdf_standards: PdfStandards,
+    /// How to encrypt the PDF, if at all.
+    pub pdf_encryption: Option<typst_pdf::PdfEncryption>,
     /// A path to write a Makefile rule describing the current compilation.
     pub make_deps: Option<PathBuf>,
     /// The PPI (pixels per inch) to use for PNG export.
     pub ppi: f32,
+    /// How to split an HTML document into a multi-file site, if at all.
+    pub html_site: Option<typst_html::SiteOptions>,
+    /// How to include images in HTML output.
+    pub html_images: HtmlImages,
+    /// Top-level headings up to this level start a new chapter in an EPUB.
+    pub epub_split_level: Option<NonZeroUsize>,
+    /// The path of the cover image for an EPUB.
+    pub epub_cover: Option<PathBuf>,
     /// The export cache for images, used for caching output files in `typst
     /// watch` sessions with images.
     pub export_cache: ExportCache,
@@ -96,12 +113,15 @@ impl CompileConfig {
 
         let output_format = if let Some(specified) = args.format {
             specified
+        } else if args.html_site {
+            OutputFormat::Html
         } else if let Some(Output::Path(output)) = &args.output {
             match output.extension() {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
                 Some(ext) if ext.eq_ignore_ascii_case("html") => OutputFormat::Html,
+                Some(ext) if ext.eq_ignore_ascii_case("epub") => OutputFormat::Epub,
                 _ => bail!(
                     "could not infer output format for path {}.\n\
                      consider providing the format manually with `--format/-f`",
@@ -112,16 +132,57 @@ impl CompileConfig {
             OutputFormat::Pdf
         };
 
+        let html_site = args
+            .html_site
+            .then_some(typst_html::SiteOptions { split_level: args.html_split_level });
+
+        if html_site.is_some() {
+            if output_format != OutputFormat::Html {
+                bail!("`--html-site` can only be used with HTML output");
+            }
+            if matches!(args.output, Some(Output::Stdout)) {
+                bail!("cannot export a multi-file HTML site to stdout");
+            }
+        }
+
+        if output_format != OutputFormat::Pdf {
+            if args.pdf_user_password.is_some() {
+                bail!("`--pdf-user-password` can only be used with PDF output");
+            }
+            if args.pdf_owner_password.is_some() {
+                bail!("`--pdf-owner-password` can only be used with PDF output");
+            }
+        }
+
+        if args.epub_split_level.is_some() && output_format != OutputFormat::Epub {
+            bail!("`--epub-split-level` can only be used with EPUB output");
+        }
+
+        if args.epub_cover.is_some() && output_format != OutputFormat::Epub {
+            bail!("`--epub-cover` can only be used with EPUB output");
+        }
+
+        if args.html_images == HtmlImages::Files
+            && matches!(args.output, Some(Output::Stdout))
+        {
+            bail!("cannot write images to separate files when exporting to stdout");
+        }
+
         let output = args.output.clone().unwrap_or_else(|| {
             let Input::Path(path) = &input else {
                 panic!("output must be specified when input is from stdin, as guarded by the CLI");
             };
+            // A site is written into a directory named after the input.
+            if html_site.is_some() {
+                return Output::Path(path.with_extension(""));
+            }
             Output::Path(path.with_extension(
                 match output_format {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
                     OutputFormat::Svg => "svg",
                     OutputFormat::Html => "html",
+                    OutputFormat::Epub => "epub",
                 },
             ))
         });
@@ -136,13 +197,61 @@ impl CompileConfig {
                 .iter()
                 .map(|standard| match standard {
                     PdfStandard::V_1_7 => typst_pdf::PdfStandard::V_1_7,
+                    PdfStandard::V_2_0 => typst_pdf::PdfStandard::V_2_0,
+                    PdfStandard::A_1b => typst_pdf::PdfStandard::A_1b,
                     PdfStandard::A_2b => typst_pdf::PdfStandard::A_2b,
+                    PdfStandard::A_2a => typst_pdf::PdfStandard::A_2a,
                     PdfStandard::A_3b => typst_pdf::PdfStandard::A_3b,
+                    PdfStandard::A_3a => typst_pdf::PdfStandard::A_3a,
+                    PdfStandard::A_4 => typst_pdf::PdfStandard::A_4,
+                    PdfStandard::A_4f => typst_pdf::PdfStandard::A_4f,
+                    PdfStandard::UA_1 => typst_pdf::PdfStandard::UA_1,
                 })
                 .collect::<Vec<_>>();
             PdfStandards::new(&list)?
         };
 
+        // Tagged PDFs need additional introspection tags during layout.
+        let mut process = args.process.clone();
+        if output_format == OutputFormat::Pdf
+            && args.pdf_standard.iter().any(|standard| {
+                matches!(
+                    standard,
+                    PdfStandard::A_2a | PdfStandard::A_3a | PdfStandard::UA_1
+                )
+            })
+            && !process.features.contains(&Feature::PdfTags)
+        {
+            process.features.push(Feature::PdfTags);
+        }
+
+        let pdf_encryption = (args.pdf_user_password.is_some()
+            || args.pdf_owner_password.is_some())
+        .then(|| {
+            let algorithm = match args.pdf_encryption {
+                PdfEncryption::Aes128 => PdfEncryptionAlgorithm::Aes128,
+                PdfEncryption::Aes256 => PdfEncryptionAlgorithm::Aes256,
+            };
+            let mut encryption = typst_pdf::PdfEncryption::new(algorithm);
+            encryption.user_password = args.pdf_user_password.clone().unwrap_or_default();
+            encryption.owner_password =
+                args.pdf_owner_password.clone().unwrap_or_default();
+            for permission in &args.pdf_deny {
+                encryption.permissions.remove(match permission {
+                    PdfPermission::Print => {
+                        PdfPermissions::PRINT | PdfPermissions::PRINT_HIGH_QUALITY
+                    }
+                    PdfPermission::HighQualityPrint => PdfPermissions::PRINT_HIGH_QUALITY,
+                    PdfPermission::Modify => PdfPermissions::MODIFY,
+                    PdfPermission::Copy => PdfPermissions::COPY,
+                    PdfPermission::Annotate => PdfPermissions::ANNOTATE,
+                    PdfPermission::FillForms => PdfPermissions::FILL_FORMS,
+                    PdfPermission::Assemble => PdfPermissions::ASSEMBLE,
+                });
+            }
+            encryption
+        });
+
         #[cfg(feature = "http-server")]
         let server = match watch {
             Some(command)
@@ -160,10 +269,18 @@ impl CompileConfig {
             output_format,
             pages,
             pdf_standards,
+            pdf_encryption,
             creation_timestamp: args.world.creation_timestamp,
             make_deps: args.make_deps.clone(),
             ppi: args.ppi,
+            html_site,
+            html_images: args.html_images,
+            epub_split_level: args
+                .epub_split_level
+                .map_or(Some(NonZeroUsize::MIN), NonZeroUsize::new),
+            epub_cover: args.epub_cover.clone(),
             diagnostic_format: args.process.diagnostic_format,
+            process,
             open: args.open.clone(),
             export_cache: ExportCache::new(),
             #[cfg(feature = "http-server")]
@@ -231,11 +348,18 @@ fn compile_and_export(
     match config.output_format {
         OutputFormat::Html => {
             let Warned { output, warnings } = typst::compile::<HtmlDocument>(world);
-            let result = output.and_then(|document| export_html(&document, config));
-            Warned {
-                output: result.map(|()| vec![config.output.clone()]),
-                warnings,
-            }
+            let result = output.and_then(|document| match &config.html_site {
+                Some(options) => export_html_site(&document, options, config),
+                None => export_html(&document, config),
+            });
+            Warned { output: result, warnings }
+        }
+        OutputFormat::Epub => {
+            let Warned { output, warnings } = typst::compile::<HtmlDocument>(world);
+            let result = output
+                .and_then(|document| export_epub(&document, world, config))
+                .map(|()| vec![config.output.clone()]);
+            Warned { output: result, warnings }
         }
         _ => {
             let Warned { output, warnings } = typst::compile::<PagedDocument>(world);
@@ -246,8 +370,20 @@ fn compile_and_export(
 }
 
 /// Export to HTML.
-fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<()> {
-    let html = typst_html::html(document)?;
+fn export_html(
+    document: &HtmlDocument,
+    config: &CompileConfig,
+) -> SourceResult<Vec<Output>> {
+    let mut outputs = vec![config.output.clone()];
+    let document = match &config.output {
+        Output::Path(path) => {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            write_html_images(document, dir, config, &mut outputs)?
+        }
+        Output::Stdout => Cow::Borrowed(document),
+    };
+
+    let html = typst_html::html(&document)?;
     let result = config.output.write(html.as_bytes());
 
     #[cfg(feature = "http-server")]
@@ -257,7 +393,105 @@ fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<
 
     result
         .map_err(|err| eco_format!("failed to write HTML file ({err})"))
-        .at(Span::detached())
+        .at(Span::detached())?;
+
+    Ok(outputs)
+}
+
+/// Export to a multi-file HTML site.
+fn export_html_site(
+    document: &HtmlDocument,
+    options: &typst_html::SiteOptions,
+    config: &CompileConfig,
+) -> SourceResult<Vec<Output>> {
+    let Output::Path(dir) = &config.output else {
+        bail!(Span::detached(), "cannot export a multi-file HTML site to stdout");
+    };
+
+    fs::create_dir_all(dir)
+        .map_err(|err| eco_format!("failed to create output directory ({err})"))
+        .at(Span::detached())?;
+
+    let mut outputs = vec![];
+    let document = write_html_images(document, dir, config, &mut outputs)?;
+    let files = typst_html::html_site(&document, options)?;
+    for file in &files {
+        let path = dir.join(file.path.as_str());
+        fs::write(&path, file.html.as_bytes())
+            .map_err(|err| eco_format!("failed to write HTML file ({err})"))
+            .at(Span::detached())?;
+        outputs.push(Output::Path(path));
+    }
+
+    // The server only serves a single page, so we show the entry point.
+    #[cfg(feature = "http-server")]
+    if let (Some(server), Some(index)) = (&config.server, files.into_iter().next()) {
+        server.update(index.html);
+    }
+
+    Ok(outputs)
+}
+
+/// Export to an EPUB.
+fn export_epub(
+    document: &HtmlDocument,
+    world: &SystemWorld,
+    config: &CompileConfig,
+) -> SourceResult<()> {
+    let cover = match &config.epub_cover {
+        Some(path) => Some(
+            fs::read(path)
+                .map_err(|err| eco_format!("failed to read cover image ({err})"))
+                .at(Span::detached())?,
+        ),
+        None => None,
+    };
+
+    // If the timestamp is provided through the CLI, use it, else use the
+    // current time.
+    let modified = convert_datetime(config.creation_timestamp.unwrap_or_else(Utc::now));
+    let options = typst_html::EpubOptions {
+        ident: Smart::Auto,
+        modified,
+        split_level: config.epub_split_level,
+        cover: cover.map(Bytes::new),
+    };
+    let buffer = typst_html::epub(document, world, &options)?;
+    config
+        .output
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write EPUB file ({err})"))
+        .at(Span::detached())?;
+    Ok(())
+}
+
+/// Write the document's images into separate files in the given directory, if
+/// requested, returning the document that refers to them.
+fn write_html_images<'a>(
+    document: &'a HtmlDocument,
+    dir: &Path,
+    config: &CompileConfig,
+    outputs: &mut Vec<Output>,
+) -> SourceResult<Cow<'a, HtmlDocument>> {
+    if config.html_images == HtmlImages::Embed {
+        return Ok(Cow::Borrowed(document));
+    }
+
+    let mut document = document.clone();
+    for asset in typst_html::externalize_images(&mut document.root) {
+        let path = dir.join(asset.path.as_str());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| eco_format!("failed to create image directory ({err})"))
+                .at(Span::detached())?;
+        }
+        fs::write(&path, &asset.data)
+            .map_err(|err| eco_format!("failed to write image file ({err})"))
+            .at(Span::detached())?;
+        outputs.push(Output::Path(path));
+    }
+
+    Ok(Cow::Owned(document))
 }
 
 /// Export to a paged target format.
@@ -275,7 +509,7 @@ fn export_paged(
         OutputFormat::Svg => {
             export_image(document, config, ImageExportFormat::Svg).at(Span::detached())
         }
-        OutputFormat::Html => unreachable!(),
+        OutputFormat::Html | OutputFormat::Epub => unreachable!(),
     }
 }
 
@@ -300,6 +534,7 @@ fn export_pdf(document: &PagedDocument, config: &CompileConfig) -> SourceResult<
         timestamp,
         page_ranges: config.pages.clone(),
         standards: config.pdf_standards.clone(),
+        encryption: config.pdf_encryption.clone(),
     };
     let buffer = typst_pdf::pdf(document, &options)?;
     config
@@ -622,6 +857,15 @@ fn open_output(config: &mut CompileConfig) -> StrResult<()> {
     // Can't open stdout.
     let Output::Path(path) = &config.output else { return Ok(()) };
 
+    // A site is opened at its entry point.
+    let index;
+    let path = if config.html_site.is_some() {
+        index = path.join("index.html");
+        &index
+    } else {
+        path
+    };
+
     // Some resource openers require the path to be canonicalized.
     let path = path
         .canonicalize()
@@ -0,0 +1,124 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use ecow::eco_format;
+use typst::diag::StrResult;
+use typst::syntax::{format, FormatConfig};
+
+use crate::args::FmtCommand;
+use crate::{print_error, set_failed};
+
+/// Execute a formatting command.
+pub fn fmt(command: &FmtCommand) -> StrResult<()> {
+    let config = FormatConfig { width: command.width, indent: command.indent };
+
+    let mut unformatted = false;
+    for input in &command.inputs {
+        if input.as_os_str() == "-" {
+            unformatted |= fmt_stdin(command, &config)?;
+            continue;
+        }
+
+        let mut paths = vec![];
+        collect(input, &mut paths)?;
+        for path in paths {
+            unformatted |= fmt_file(command, &config, &path)?;
+        }
+    }
+
+    if command.check && unformatted {
+        set_failed();
+    }
+
+    Ok(())
+}
+
+/// Format the source read from stdin, writing the result to stdout. Returns
+/// whether the source was not formatted already.
+fn fmt_stdin(command: &FmtCommand, config: &FormatConfig) -> StrResult<bool> {
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|err| eco_format!("failed to read from stdin ({err})"))?;
+
+    let Some(formatted) = try_format(Path::new("<stdin>"), &text, config) else {
+        return Ok(false);
+    };
+
+    let unformatted = formatted != text;
+    if command.check {
+        if unformatted {
+            eprintln!("<stdin> is not formatted");
+        }
+    } else {
+        io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|err| eco_format!("failed to write to stdout ({err})"))?;
+    }
+
+    Ok(unformatted)
+}
+
+/// Format a file in place. Returns whether the file was not formatted
+/// already.
+fn fmt_file(command: &FmtCommand, config: &FormatConfig, path: &Path) -> StrResult<bool> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| eco_format!("failed to read {} ({err})", path.display()))?;
+
+    let Some(formatted) = try_format(path, &text, config) else {
+        return Ok(false);
+    };
+
+    if formatted == text {
+        return Ok(false);
+    }
+
+    if command.check {
+        eprintln!("{} is not formatted", path.display());
+    } else {
+        fs::write(path, formatted)
+            .map_err(|err| eco_format!("failed to write {} ({err})", path.display()))?;
+    }
+
+    Ok(true)
+}
+
+/// Format a source, reporting failure without aborting the other files.
+fn try_format(path: &Path, text: &str, config: &FormatConfig) -> Option<String> {
+    match format(text, config) {
+        Ok(formatted) => Some(formatted),
+        Err(err) => {
+            set_failed();
+            print_error(&format!("failed to format {} ({err})", path.display()))
+                .expect("failed to print error");
+            None
+        }
+    }
+}
+
+/// Collect the Typst files at a path, searching directories recursively.
+fn collect(path: &Path, paths: &mut Vec<PathBuf>) -> StrResult<()> {
+    if !path.is_dir() {
+        paths.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let read_err =
+        |err| eco_format!("failed to read directory {} ({err})", path.display());
+    let mut entries = fs::read_dir(path)
+        .map_err(read_err)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(read_err)?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() || entry.extension() == Some(OsStr::new("typ")) {
+            collect(&entry, paths)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,807 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use ecow::{eco_format, EcoString, EcoVec};
+use serde_json::{json, Value};
+use typst::diag::{Severity, SourceDiagnostic, StrResult, Warned};
+use typst::layout::PagedDocument;
+use typst::syntax::{FileId, Side, Source};
+use typst::{World, WorldExt};
+use typst_ide::{
+    Completion, CompletionKind, Definition, IdeWorld, InlayHintKind, Tooltip,
+};
+
+use crate::args::{Input, LspCommand};
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// The JSON-RPC error code for a message that is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// The JSON-RPC error code for a request that is not allowed.
+const INVALID_REQUEST: i64 = -32600;
+/// The JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code for malformed parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// The LSP error code for requests before initialization.
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+/// The LSP error code for requests that are valid but failed.
+const REQUEST_FAILED: i64 = -32803;
+/// The LSP message type of warnings.
+const WARNING: i64 = 2;
+
+/// Execute a language server command.
+pub fn lsp(command: &LspCommand) -> StrResult<()> {
+    let mut input = io::stdin().lock();
+    let mut output = io::stdout().lock();
+    let mut server = Server::new(command);
+
+    while let Some(body) = read_message(&mut input)? {
+        let replies = match serde_json::from_slice(&body) {
+            Ok(message) => server.handle(message),
+            Err(err) => vec![failure(Value::Null, PARSE_ERROR, eco_format!("{err}"))],
+        };
+
+        for reply in &replies {
+            write_message(&mut output, reply)?;
+        }
+
+        if let Some(clean) = server.exit {
+            // Exiting without a prior shutdown request is an error.
+            if !clean {
+                set_failed();
+            }
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The state of a language server session.
+struct Server<'a> {
+    /// The command the server was started with.
+    command: &'a LspCommand,
+    /// The world, created upon initialization and kept alive between edits
+    /// so that memoized results stay warm.
+    world: Option<SystemWorld>,
+    /// The file that is compiled, if any is known yet.
+    main: Option<FileId>,
+    /// The last successfully compiled document.
+    document: Option<PagedDocument>,
+    /// The files for which non-empty diagnostics were last published.
+    diagnosed: HashSet<FileId>,
+    /// Whether the client watches the project's files for us. Otherwise, the
+    /// files are listed anew after each notification.
+    watch: bool,
+    /// Whether a shutdown request was received.
+    shutdown: bool,
+    /// Set when an exit notification was received, to whether the server
+    /// was shut down before.
+    exit: Option<bool>,
+}
+
+impl<'a> Server<'a> {
+    /// Create a new, uninitialized server.
+    fn new(command: &'a LspCommand) -> Self {
+        Self {
+            command,
+            world: None,
+            main: None,
+            document: None,
+            diagnosed: HashSet::new(),
+            watch: false,
+            shutdown: false,
+            exit: None,
+        }
+    }
+
+    /// Handle an incoming message and return the messages to send back.
+    fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) if !method.is_empty() => {
+                let reply = match self.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(Failure { code, message }) => failure(id.clone(), code, message),
+                };
+                vec![reply]
+            }
+            // A response to the registration of the file watcher, the only
+            // request we send.
+            Some(_) => vec![],
+            None => self.notify(method, params),
+        }
+    }
+
+    /// Answer a request.
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, Failure> {
+        if method == "initialize" {
+            return self.initialize(params);
+        } else if self.world.is_none() {
+            return Err(Failure::new(
+                SERVER_NOT_INITIALIZED,
+                "server is not initialized",
+            ));
+        } else if self.shutdown {
+            return Err(Failure::new(INVALID_REQUEST, "server is shutting down"));
+        }
+
+        match method {
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/inlayHint" => self.inlay_hints(params),
+            "typst/jumpFromCursor" => self.jump(params),
+            _ => Err(Failure::new(
+                METHOD_NOT_FOUND,
+                eco_format!("unknown method {method}"),
+            )),
+        }
+    }
+
+    /// React to a notification and return the resulting notifications.
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        if method == "exit" {
+            self.exit = Some(self.shutdown);
+            return vec![];
+        } else if self.world.is_none() || self.shutdown {
+            return vec![];
+        }
+
+        let mut replies = vec![];
+        match method {
+            "initialized" if self.watch => replies.push(register_watcher()),
+            "initialized" | "textDocument/didSave" => {}
+            "workspace/didChangeWatchedFiles" => {
+                // Changed files are reloaded by the next compilation anyway,
+                // but created and deleted ones change the project's files.
+                let mut changes = params["changes"].as_array().into_iter().flatten();
+                if changes.any(|change| change["type"].as_u64() != Some(2)) {
+                    self.world_mut().forget_files();
+                }
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let (Some(id), Some(text)) =
+                    (self.id(&document["uri"]), document["text"].as_str())
+                else {
+                    return vec![];
+                };
+                self.world_mut().open(id, text.into());
+                self.focus(id);
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let Some(id) = self.id(&params["textDocument"]["uri"]) else {
+                    return vec![log_message(
+                        WARNING,
+                        eco_format!(
+                            "ignored changes to {uri}, which is not in the project"
+                        ),
+                    )];
+                };
+
+                // Clients must open a document before changing it, as we
+                // don't know the text that the changes apply to otherwise.
+                let changes = params["contentChanges"].as_array().into_iter().flatten();
+                for change in changes {
+                    let text = change["text"].as_str().unwrap_or_default();
+                    let range = &change["range"];
+                    let edited = self.world_mut().edit(id, |source| {
+                        if range.is_object() {
+                            let start = offset(source, &range["start"]);
+                            let end = offset(source, &range["end"]).max(start);
+                            source.edit(start..end, text);
+                        } else {
+                            source.replace(text);
+                        }
+                    });
+                    if edited.is_none() {
+                        return vec![log_message(
+                            WARNING,
+                            eco_format!("ignored changes to {uri}, which is not open"),
+                        )];
+                    }
+                }
+                self.focus(id);
+            }
+            "textDocument/didClose" => {
+                let Some(id) = self.id(&params["textDocument"]["uri"]) else {
+                    return vec![];
+                };
+                self.world_mut().close(id);
+            }
+            _ => return vec![],
+        }
+
+        if !self.watch {
+            self.world_mut().forget_files();
+        }
+
+        replies.extend(self.compile());
+        replies
+    }
+
+    /// Create the world and announce the server's capabilities.
+    fn initialize(&mut self, params: &Value) -> Result<Value, Failure> {
+        if self.world.is_some() {
+            return Err(Failure::new(INVALID_REQUEST, "server is already initialized"));
+        }
+
+        // Without an explicit root, the client's workspace is the root.
+        let mut world_args = self.command.world.clone();
+        if world_args.root.is_none() {
+            world_args.root = params["rootUri"]
+                .as_str()
+                .and_then(uri_to_path)
+                .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+        }
+
+        let input = match &self.command.input {
+            Some(path) => Input::Path(path.clone()),
+            None => Input::Stdin,
+        };
+
+        let world = SystemWorld::new(&input, &world_args, &self.command.process)
+            .map_err(|err| Failure::new(REQUEST_FAILED, eco_format!("{err}")))?;
+
+        if self.command.input.is_some() {
+            self.main = Some(world.main());
+        }
+
+        self.watch = params["capabilities"]["workspace"]["didChangeWatchedFiles"]
+            ["dynamicRegistration"]
+            .as_bool()
+            .unwrap_or(false);
+
+        self.world = Some(world);
+
+        Ok(json!({
+            "capabilities": {
+                "positionEncoding": "utf-16",
+                "textDocumentSync": { "openClose": true, "change": 2 },
+                "completionProvider": {
+                    "triggerCharacters": ["#", ".", "@", "(", ",", ":", "/", "\""],
+                },
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "inlayHintProvider": true,
+            },
+            "serverInfo": { "name": "typst", "version": crate::typst_version() },
+        }))
+    }
+
+    /// Complete at the cursor.
+    fn completion(&self, params: &Value) -> Result<Value, Failure> {
+        let (source, cursor) = self.locate(params)?;
+
+        // Completions requested through a trigger character are implicit.
+        let explicit = params["context"]["triggerKind"].as_u64() != Some(2);
+        let Some((from, completions)) = typst_ide::autocomplete(
+            self.world(),
+            self.document.as_ref(),
+            &source,
+            cursor,
+            explicit,
+        ) else {
+            return Ok(Value::Null);
+        };
+
+        let range = lsp_range(&source, from..cursor);
+        let items: Vec<Value> = completions
+            .iter()
+            .map(|completion| completion_item(completion, &range))
+            .collect();
+
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    /// Describe the item under the cursor.
+    fn hover(&self, params: &Value) -> Result<Value, Failure> {
+        let (source, cursor) = self.locate(params)?;
+        let Some(tooltip) = typst_ide::tooltip(
+            self.world(),
+            self.document.as_ref(),
+            &source,
+            cursor,
+            Side::After,
+        ) else {
+            return Ok(Value::Null);
+        };
+
+        let value = match tooltip {
+            Tooltip::Text(text) => text.to_string(),
+            Tooltip::Code(code) => format!("```typst\n{code}\n```"),
+        };
+
+        Ok(json!({ "contents": { "kind": "markdown", "value": value } }))
+    }
+
+    /// Find the definition of the item under the cursor.
+    fn definition(&self, params: &Value) -> Result<Value, Failure> {
+        let (source, cursor) = self.locate(params)?;
+        let world = self.world();
+
+        // Definitions in the standard library have no location.
+        let Some(Definition::Span(span)) = typst_ide::definition(
+            world,
+            self.document.as_ref(),
+            &source,
+            cursor,
+            Side::After,
+        ) else {
+            return Ok(Value::Null);
+        };
+
+        let location = span.id().and_then(|id| {
+            let target = world.source(id).ok()?;
+            let range = world.range(span)?;
+            let path = world.path(id).ok()?;
+            Some(json!({ "uri": path_to_uri(&path), "range": lsp_range(&target, range) }))
+        });
+
+        Ok(location.unwrap_or(Value::Null))
+    }
+
+    /// Label arguments with their parameters and bindings with their values.
+    fn inlay_hints(&self, params: &Value) -> Result<Value, Failure> {
+        let (source, start) = self.locate_at(params, &params["range"]["start"])?;
+        let end = offset(&source, &params["range"]["end"]).max(start);
+
+        let hints: Vec<Value> = typst_ide::inlay_hints(self.world(), &source, start..end)
+            .into_iter()
+            .map(|hint| {
+                let (kind, left, right) = match hint.kind {
+                    InlayHintKind::Param => (2, false, true),
+                    InlayHintKind::Value => (1, true, false),
+                };
+                json!({
+                    "position": position(&source, hint.offset),
+                    "label": hint.label.as_str(),
+                    "kind": kind,
+                    "paddingLeft": left,
+                    "paddingRight": right,
+                })
+            })
+            .collect();
+
+        Ok(Value::Array(hints))
+    }
+
+    /// Find the positions in the document that the cursor corresponds to.
+    ///
+    /// Positions are given as a page number starting at 1 and coordinates
+    /// in points from the page's top left corner.
+    fn jump(&self, params: &Value) -> Result<Value, Failure> {
+        let (source, cursor) = self.locate(params)?;
+        let Some(document) = &self.document else {
+            return Ok(json!([]));
+        };
+
+        let positions: Vec<Value> =
+            typst_ide::jump_from_cursor(document, &source, cursor)
+                .into_iter()
+                .map(|position| {
+                    json!({
+                        "page": position.page.get(),
+                        "x": position.point.x.to_pt(),
+                        "y": position.point.y.to_pt(),
+                    })
+                })
+                .collect();
+
+        Ok(Value::Array(positions))
+    }
+
+    /// Compile the main file and return notifications that publish the
+    /// resulting diagnostics.
+    fn compile(&mut self) -> Vec<Value> {
+        let (Some(world), Some(main)) = (&mut self.world, self.main) else {
+            return vec![];
+        };
+
+        world.reset();
+        let Warned { output, warnings } = typst::compile::<PagedDocument>(&*world);
+        let errors = match output {
+            Ok(document) => {
+                self.document = Some(document);
+                EcoVec::new()
+            }
+            Err(errors) => errors,
+        };
+
+        // Evict cache entries that were not used for a while, but keep the
+        // recent ones warm for the next edit.
+        comemo::evict(10);
+
+        // Diagnostics without a location are attached to the main file.
+        let mut files: HashMap<FileId, Vec<Value>> = HashMap::new();
+        for diagnostic in errors.iter().chain(warnings.iter()) {
+            let (id, range) = match diagnostic.span.id() {
+                Some(id) => (id, world.range(diagnostic.span)),
+                None => (main, None),
+            };
+
+            let range = world
+                .source(id)
+                .ok()
+                .zip(range)
+                .map(|(source, range)| lsp_range(&source, range))
+                .unwrap_or_else(|| json!({ "start": zero(), "end": zero() }));
+
+            files.entry(id).or_default().push(lsp_diagnostic(diagnostic, range));
+        }
+
+        // Files that had diagnostics before but have none now must be
+        // cleared explicitly.
+        let cleared: Vec<FileId> = self
+            .diagnosed
+            .iter()
+            .filter(|id| !files.contains_key(*id))
+            .copied()
+            .collect();
+        self.diagnosed = files.keys().copied().collect();
+
+        let empty = cleared.into_iter().map(|id| (id, vec![]));
+        files
+            .into_iter()
+            .chain(empty)
+            .filter_map(|(id, diagnostics)| {
+                let path = world.path(id).ok()?;
+                Some(json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": path_to_uri(&path), "diagnostics": diagnostics },
+                }))
+            })
+            .collect()
+    }
+
+    /// Make a file the main file unless the main file is fixed or the file
+    /// is already part of the main file's compilation.
+    fn focus(&mut self, id: FileId) {
+        if self.command.input.is_some() || self.main == Some(id) {
+            return;
+        }
+
+        let world = self.world_mut();
+        if let Ok(path) = world.path(id) {
+            if world.dependencies().any(|dep| dep == path) {
+                return;
+            }
+        }
+
+        world.set_main(id);
+        self.main = Some(id);
+    }
+
+    /// Resolve the document and cursor of text document position parameters.
+    fn locate(&self, params: &Value) -> Result<(Source, usize), Failure> {
+        self.locate_at(params, &params["position"])
+    }
+
+    /// Resolve the text document of request parameters and a position in it.
+    fn locate_at(
+        &self,
+        params: &Value,
+        position: &Value,
+    ) -> Result<(Source, usize), Failure> {
+        let id = self
+            .id(&params["textDocument"]["uri"])
+            .ok_or_else(|| Failure::new(INVALID_PARAMS, "unknown text document"))?;
+        let source = self
+            .world()
+            .source(id)
+            .map_err(|err| Failure::new(REQUEST_FAILED, eco_format!("{err}")))?;
+        let cursor = offset(&source, position);
+        Ok((source, cursor))
+    }
+
+    /// Resolve a document URI to a file in the project.
+    fn id(&self, uri: &Value) -> Option<FileId> {
+        let path = uri_to_path(uri.as_str()?)?;
+        let path = path.canonicalize().unwrap_or(path);
+        self.world().id_for(&path)
+    }
+
+    /// The world of an initialized server.
+    fn world(&self) -> &SystemWorld {
+        self.world.as_ref().expect("server is not initialized")
+    }
+
+    /// Mutable access to the world of an initialized server.
+    fn world_mut(&mut self) -> &mut SystemWorld {
+        self.world.as_mut().expect("server is not initialized")
+    }
+}
+
+impl IdeWorld for SystemWorld {
+    fn upcast(&self) -> &dyn World {
+        self
+    }
+
+    fn files(&self) -> Vec<FileId> {
+        SystemWorld::files(self).to_vec()
+    }
+}
+
+/// A failed request.
+struct Failure {
+    /// The error code.
+    code: i64,
+    /// A message describing the error.
+    message: EcoString,
+}
+
+impl Failure {
+    /// Create a new failure.
+    fn new(code: i64, message: impl Into<EcoString>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Create an error response.
+fn failure(id: Value, code: i64, message: EcoString) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.as_str() },
+    })
+}
+
+/// Create a notification that shows a message in the client's log.
+fn log_message(kind: i64, message: EcoString) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "window/logMessage",
+        "params": { "type": kind, "message": message.as_str() },
+    })
+}
+
+/// Create a request that asks the client to report when files in the project
+/// are created, changed, or deleted.
+fn register_watcher() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": "watch",
+        "method": "client/registerCapability",
+        "params": {
+            "registrations": [{
+                "id": "watch",
+                "method": "workspace/didChangeWatchedFiles",
+                "registerOptions": { "watchers": [{ "globPattern": "**/*" }] },
+            }],
+        },
+    })
+}
+
+/// Read the body of the next message, if the input is not exhausted.
+fn read_message(input: &mut impl BufRead) -> StrResult<Option<Vec<u8>>> {
+    let mut length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        let read = input
+            .read_line(&mut header)
+            .map_err(|err| eco_format!("failed to read message ({err})"))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| eco_format!("invalid content length: {value}"))?,
+                );
+            }
+        }
+    }
+
+    let length = length.ok_or("message is missing its content length")?;
+    let mut body = vec![0; length];
+    input
+        .read_exact(&mut body)
+        .map_err(|err| eco_format!("failed to read message ({err})"))?;
+
+    Ok(Some(body))
+}
+
+/// Write a message to the output.
+fn write_message(output: &mut impl Write, message: &Value) -> StrResult<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())
+        .and_then(|_| output.flush())
+        .map_err(|err| eco_format!("failed to write message ({err})"))
+}
+
+/// Convert a completion into an LSP completion item that replaces the given
+/// range.
+fn completion_item(completion: &Completion, range: &Value) -> Value {
+    let kind = match completion.kind {
+        CompletionKind::Syntax => 15,
+        CompletionKind::Func => 3,
+        CompletionKind::Type => 7,
+        CompletionKind::Param => 6,
+        CompletionKind::Constant => 21,
+        CompletionKind::Path => 17,
+        CompletionKind::Package => 9,
+        CompletionKind::Label => 18,
+        CompletionKind::Font => 12,
+        CompletionKind::Symbol(_) => 1,
+    };
+
+    let apply = completion.apply.as_deref().unwrap_or(&completion.label);
+    json!({
+        "label": completion.label.as_str(),
+        "kind": kind,
+        "detail": completion.detail.as_deref(),
+        "textEdit": { "range": range, "newText": snippet(apply) },
+        "insertTextFormat": 2,
+    })
+}
+
+/// Convert Typst's snippet syntax, where `${name}` is a placeholder, into the
+/// LSP's, where placeholders are numbered and other dollar signs, closing
+/// braces and backslashes must be escaped.
+fn snippet(apply: &str) -> String {
+    fn escape(out: &mut String, text: &str) {
+        for c in text.chars() {
+            if matches!(c, '$' | '}' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+
+    let mut out = String::new();
+    let mut index = 0;
+    let mut rest = apply;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else { break };
+        escape(&mut out, &rest[..start]);
+        index += 1;
+        out.push_str(&format!("${{{index}:"));
+        escape(&mut out, &rest[start + 2..start + 2 + len]);
+        out.push('}');
+        rest = &rest[start + 3 + len..];
+    }
+
+    escape(&mut out, rest);
+    out
+}
+
+/// Convert a diagnostic into an LSP diagnostic.
+fn lsp_diagnostic(diagnostic: &SourceDiagnostic, range: Value) -> Value {
+    let mut message = diagnostic.message.to_string();
+    for hint in &diagnostic.hints {
+        message.push_str("\nhint: ");
+        message.push_str(hint);
+    }
+
+    let severity = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+    };
+
+    json!({ "range": range, "severity": severity, "source": "typst", "message": message })
+}
+
+/// Convert an LSP position into a byte offset, clamping it to the source.
+fn offset(source: &Source, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or_default() as usize;
+    let character = position["character"].as_u64().unwrap_or_default() as usize;
+    let Some(range) = source.line_to_range(line) else {
+        return source.len_bytes();
+    };
+
+    // Positions beyond the end of the line refer to its end, before the
+    // line break.
+    let text = source.get(range.clone()).unwrap_or_default();
+    let end = range.start + text.trim_end_matches(['\r', '\n']).len();
+
+    let start = source.byte_to_utf16(range.start).unwrap_or_default();
+    source
+        .utf16_to_byte(start + character)
+        .unwrap_or(end)
+        .clamp(range.start, end)
+}
+
+/// Convert a byte offset into an LSP position.
+fn position(source: &Source, offset: usize) -> Value {
+    let line = source.byte_to_line(offset).unwrap_or_default();
+    let start = source.line_to_byte(line).unwrap_or_default();
+    let character = source.byte_to_utf16(offset).unwrap_or_default()
+        - source.byte_to_utf16(start).unwrap_or_default();
+    json!({ "line": line, "character": character })
+}
+
+/// Convert a byte range into an LSP range.
+fn lsp_range(source: &Source, range: Range<usize>) -> Value {
+    json!({ "start": position(source, range.start), "end": position(source, range.end) })
+}
+
+/// The position at the start of a file.
+fn zero() -> Value {
+    json!({ "line": 0, "character": 0 })
+}
+
+/// Convert a `file:` URI into a path.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let (authority, path) = rest.split_at(rest.find('/')?);
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    let decoded = String::from_utf8(bytes).ok()?;
+    if !cfg!(windows) {
+        return Some(PathBuf::from(decoded));
+    }
+
+    // On Windows, a drive letter follows the slash, e.g. in `file:///C:/dir`
+    // or `file:///c%3A/dir`. Without one, the URI refers to a network share
+    // on the host given by the authority.
+    let path = decoded.replace('/', "\\");
+    match path.as_bytes() {
+        [b'\\', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(PathBuf::from(
+            format!("{}{}", drive.to_ascii_uppercase() as char, &path[2..]),
+        )),
+        _ if !authority.is_empty() && authority != "localhost" => {
+            Some(PathBuf::from(format!(r"\\{authority}{path}")))
+        }
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+/// Convert a path into a `file:` URI.
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let path = path.strip_prefix(r"\\?\").unwrap_or(&path);
+
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'\\' if cfg!(windows) => uri.push('/'),
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/'
+            | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    uri
+}
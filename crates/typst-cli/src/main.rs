@@ -1,9 +1,11 @@
 mod args;
 mod compile;
 mod download;
+mod fmt;
 mod fonts;
 mod greet;
 mod init;
+mod lsp;
 mod package;
 mod query;
 #[cfg(feature = "http-server")]
@@ -69,6 +71,8 @@ fn dispatch() -> HintedStrResult<()> {
         Command::Watch(command) => crate::watch::watch(&mut timer, command)?,
         Command::Init(command) => crate::init::init(command)?,
         Command::Query(command) => crate::query::query(command)?,
+        Command::Fmt(command) => crate::fmt::fmt(command)?,
+        Command::Lsp(command) => crate::lsp::lsp(command)?,
         Command::Fonts(command) => crate::fonts::fonts(command),
         Command::Update(command) => crate::update::update(command)?,
     }
@@ -33,11 +33,8 @@ pub fn watch(timer: &mut Timer, command: &WatchCommand) -> StrResult<()> {
     // Create the world that serves sources, files, and fonts.
     // Additionally, if any files do not exist, wait until they do.
     let mut world = loop {
-        match SystemWorld::new(
-            &command.args.input,
-            &command.args.world,
-            &command.args.process,
-        ) {
+        match SystemWorld::new(&command.args.input, &command.args.world, &config.process)
+        {
             Ok(world) => break world,
             Err(
                 ref err @ (WorldCreationError::InputNotFound(ref path)
@@ -44,6 +44,8 @@ pub struct SystemWorld {
     slots: Mutex<HashMap<FileId, FileSlot>>,
     /// Holds information about where packages are stored.
     package_storage: PackageStorage,
+    /// The files in the project, listed on first use.
+    files: OnceLock<Vec<FileId>>,
     /// The current datetime if requested. This is stored here to ensure it is
     /// always the same within one compilation.
     /// Reset between compilations if not [`Now::Fixed`].
@@ -117,6 +119,7 @@ impl SystemWorld {
                 .iter()
                 .map(|&feature| match feature {
                     Feature::Html => typst::Feature::Html,
+                    Feature::PdfTags => typst::Feature::PdfTags,
                 })
                 .collect();
 
@@ -141,6 +144,7 @@ impl SystemWorld {
             fonts: fonts.fonts,
             slots: Mutex::new(HashMap::new()),
             package_storage: package::storage(&world_args.package),
+            files: OnceLock::new(),
             now,
         })
     }
@@ -181,6 +185,79 @@ impl SystemWorld {
         }
     }
 
+    /// Change the main source file, e.g. to the document a language server
+    /// client last focused.
+    pub fn set_main(&mut self, id: FileId) {
+        self.main = id;
+    }
+
+    /// All files in the project, skipping hidden files and directories.
+    ///
+    /// The project is only walked once. Call [`forget_files`](Self::forget_files)
+    /// when files were created or deleted.
+    pub fn files(&self) -> &[FileId] {
+        self.files.get_or_init(|| {
+            let mut files = vec![];
+            let mut dirs = vec![self.root.clone()];
+            while let Some(dir) = dirs.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else { continue };
+                for entry in entries.flatten() {
+                    if entry.file_name().to_string_lossy().starts_with('.') {
+                        continue;
+                    }
+
+                    let path = entry.path();
+                    match entry.file_type() {
+                        Ok(kind) if kind.is_dir() => dirs.push(path),
+                        Ok(kind) if kind.is_file() => files.extend(self.id_for(&path)),
+                        _ => {}
+                    }
+                }
+            }
+            files
+        })
+    }
+
+    /// Forget the files in the project, so that they are listed anew.
+    pub fn forget_files(&mut self) {
+        self.files.take();
+    }
+
+    /// Resolve a path on the system to the id of a file in the project.
+    pub fn id_for(&self, path: &Path) -> Option<FileId> {
+        VirtualPath::within_root(path, &self.root).map(|vpath| FileId::new(None, vpath))
+    }
+
+    /// Resolve the path of a file id on the system.
+    pub fn path(&self, id: FileId) -> FileResult<PathBuf> {
+        system_path(&self.root, id, &self.package_storage)
+    }
+
+    /// Serve a file from memory instead of disk until it is closed again,
+    /// e.g. while it is being edited.
+    pub fn open(&mut self, id: FileId, text: String) {
+        let slot = self.slots.get_mut().entry(id).or_insert_with(|| FileSlot::new(id));
+        match &mut slot.overlay {
+            Some(source) => {
+                source.replace(&text);
+            }
+            None => slot.overlay = Some(Source::new(id, text)),
+        }
+    }
+
+    /// Modify the in-memory source of an open file. Returns `None` if the
+    /// file is not open.
+    pub fn edit<T>(&mut self, id: FileId, f: impl FnOnce(&mut Source) -> T) -> Option<T> {
+        self.slots.get_mut().get_mut(&id)?.overlay.as_mut().map(f)
+    }
+
+    /// Serve a file from disk again.
+    pub fn close(&mut self, id: FileId) {
+        if let Some(slot) = self.slots.get_mut().get_mut(&id) {
+            slot.overlay = None;
+        }
+    }
+
     /// Lookup a source file by id.
     #[track_caller]
     pub fn lookup(&self, id: FileId) -> Source {
@@ -257,12 +334,20 @@ struct FileSlot {
     source: SlotCell<Source>,
     /// The lazily loaded raw byte buffer.
     file: SlotCell<Bytes>,
+    /// An in-memory version of the file that takes precedence over the one
+    /// on disk.
+    overlay: Option<Source>,
 }
 
 impl FileSlot {
     /// Create a new file slot.
     fn new(id: FileId) -> Self {
-        Self { id, file: SlotCell::new(), source: SlotCell::new() }
+        Self {
+            id,
+            file: SlotCell::new(),
+            source: SlotCell::new(),
+            overlay: None,
+        }
     }
 
     /// Whether the file was accessed in the ongoing compilation.
@@ -283,6 +368,11 @@ impl FileSlot {
         project_root: &Path,
         package_storage: &PackageStorage,
     ) -> FileResult<Source> {
+        if let Some(source) = &self.overlay {
+            self.source.accessed = true;
+            return Ok(source.clone());
+        }
+
         self.source.get_or_init(
             || read(self.id, project_root, package_storage),
             |data, prev| {
@@ -303,6 +393,11 @@ impl FileSlot {
         project_root: &Path,
         package_storage: &PackageStorage,
     ) -> FileResult<Bytes> {
+        if let Some(source) = &self.overlay {
+            self.file.accessed = true;
+            return Ok(Bytes::from_string(source.text().to_string()));
+        }
+
         self.file.get_or_init(
             || read(self.id, project_root, package_storage),
             |data, _| Ok(Bytes::new(data)),
@@ -0,0 +1,67 @@
+//! Exports EPUBs with `typst compile` and inspects the written archives.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+#[test]
+fn test_epub_export() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("main.typ"), "= Intro\nHello\n== Detail\n= Outro\n")
+        .unwrap();
+
+    let output = compile(root, "out.epub", &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let data = std::fs::read(root.join("out.epub")).unwrap();
+
+    // Reading systems expect the first local file header to be for an
+    // uncompressed `mimetype` entry without extra fields, so that the media
+    // type is found at offset 38.
+    assert_eq!(&data[..4], b"PK\x03\x04");
+    assert_eq!(&data[8..10], [0, 0]);
+    assert_eq!(&data[26..30], [8, 0, 0, 0]);
+    assert_eq!(&data[30..38], b"mimetype");
+    assert_eq!(&data[38..58], b"application/epub+zip");
+
+    // By default, top-level headings start a new chapter.
+    assert!(contains(&data, b"META-INF/container.xml"));
+    assert!(contains(&data, b"EPUB/package.opf"));
+    assert!(contains(&data, b"EPUB/nav.xhtml"));
+    assert!(contains(&data, b"EPUB/index.xhtml"));
+    assert!(contains(&data, b"EPUB/outro.xhtml"));
+    assert!(!contains(&data, b"EPUB/detail.xhtml"));
+
+    let output = compile(root, "split.epub", &["--epub-split-level", "2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let data = std::fs::read(root.join("split.epub")).unwrap();
+    assert!(contains(&data, b"EPUB/detail.xhtml"));
+
+    // With a split level of zero, headings do not start a new chapter.
+    let output = compile(root, "single.epub", &["--epub-split-level", "0"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let data = std::fs::read(root.join("single.epub")).unwrap();
+    assert!(contains(&data, b"EPUB/index.xhtml"));
+    assert!(!contains(&data, b"EPUB/outro.xhtml"));
+
+    // The split level is rejected for other formats.
+    let output = compile(root, "out.pdf", &["--epub-split-level", "2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("`--epub-split-level` can only be used with EPUB output"));
+}
+
+/// Run `typst compile` on `main.typ` in the given directory.
+fn compile(root: &Path, output: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_typst"))
+        .current_dir(root)
+        .args(["compile", "main.typ", output])
+        .args(["--features", "html", "--ignore-system-fonts"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Whether the data contains the needle.
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|window| window == needle)
+}
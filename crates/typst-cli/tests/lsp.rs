@@ -0,0 +1,321 @@
+//! Drives `typst lsp` with a scripted JSON-RPC client.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+#[test]
+fn test_lsp_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let main = root.join("main.typ");
+    let text = "Hello\n#let value = 1\n#undefined\n";
+    std::fs::write(&main, text).unwrap();
+    let root_uri = uri(&root);
+    let uri = uri(&main);
+
+    let mut client = Client::start();
+
+    // Requests before initialization are rejected.
+    let error = client.request("textDocument/hover", json!({}));
+    assert_eq!(error["error"]["code"], -32002);
+
+    let response = client.request(
+        "initialize",
+        json!({ "processId": null, "rootUri": root_uri, "capabilities": {} }),
+    );
+    let capabilities = &response["result"]["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["textDocumentSync"]["change"], 2);
+    client.notify("initialized", json!({}));
+
+    // Opening the document compiles it and reports the unknown variable.
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": uri, "languageId": "typst", "version": 1, "text": text },
+        }),
+    );
+    let published = client.diagnostics();
+    assert_eq!(published["uri"], uri);
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(diagnostics[0]["message"], "unknown variable: undefined");
+    assert_eq!(diagnostics[0]["range"], range((2, 1), (2, 10)));
+
+    // An incremental edit fixes the error and clears the diagnostics.
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "range": range((2, 1), (2, 10)), "text": "value" }],
+        }),
+    );
+    let published = client.diagnostics();
+    assert_eq!(published["diagnostics"], json!([]));
+
+    let position = json!({ "textDocument": { "uri": uri }, "position": pos(2, 2) });
+
+    let hover = client.request("textDocument/hover", position.clone());
+    let contents = hover["result"]["contents"]["value"].as_str().unwrap();
+    assert!(contents.contains('1'), "{contents}");
+
+    let definition = client.request("textDocument/definition", position);
+    assert_eq!(definition["result"]["uri"], uri);
+    assert_eq!(definition["result"]["range"]["start"], pos(1, 5));
+
+    let completion = client.request(
+        "textDocument/completion",
+        json!({
+            "textDocument": { "uri": uri },
+            "position": pos(2, 4),
+            "context": { "triggerKind": 1 },
+        }),
+    );
+    let items = completion["result"]["items"].as_array().unwrap();
+    let item = items.iter().find(|item| item["label"] == "value").unwrap();
+    assert_eq!(item["textEdit"]["range"], range((2, 1), (2, 4)));
+
+    // Positional arguments are labelled with their parameters.
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri, "version": 3 },
+            "contentChanges": [{ "range": range((3, 0), (3, 0)), "text": "#lorem(2)" }],
+        }),
+    );
+    let hints = client.request(
+        "textDocument/inlayHint",
+        json!({ "textDocument": { "uri": uri }, "range": range((0, 0), (3, 9)) }),
+    );
+    let hints = hints["result"].as_array().unwrap();
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0]["label"], "words:");
+    assert_eq!(hints[0]["position"], pos(3, 7));
+
+    let jump = client.request(
+        "typst/jumpFromCursor",
+        json!({ "textDocument": { "uri": uri }, "position": pos(0, 2) }),
+    );
+    let positions = jump["result"].as_array().unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0]["page"], 1);
+
+    let unknown = client.request("textDocument/unknown", json!({}));
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let shutdown = client.request("shutdown", Value::Null);
+    assert_eq!(shutdown["result"], Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
+
+#[test]
+fn test_lsp_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    std::fs::create_dir(root.join("my dir")).unwrap();
+    let main = root.join("my dir").join("main.typ");
+    let text = "#include \"\"\n";
+    std::fs::write(&main, text).unwrap();
+    let other = root.join("my dir").join("other.typ");
+
+    let mut client = Client::start();
+    client.request(
+        "initialize",
+        json!({
+            "processId": null,
+            "rootUri": encoded_uri(&root),
+            "capabilities": {
+                "workspace": { "didChangeWatchedFiles": { "dynamicRegistration": true } },
+            },
+        }),
+    );
+
+    // The server asks the client to watch the project's files.
+    client.notify("initialized", json!({}));
+    let registration = client.receive_method("client/registerCapability");
+    let registrations = &registration["params"]["registrations"];
+    assert_eq!(registrations[0]["method"], "workspace/didChangeWatchedFiles");
+
+    // URIs with percent-encoded characters and, on Windows, drive letters
+    // resolve to files in the project.
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": encoded_uri(&main),
+                "languageId": "typst",
+                "version": 1,
+                "text": text,
+            },
+        }),
+    );
+    let published = client.diagnostics();
+    assert!(published["uri"].as_str().unwrap().ends_with("/my%20dir/main.typ"));
+
+    // The project's files are listed once and only listed anew once the
+    // watcher reports a new file.
+    assert!(!completes(&mut client, &main, "other.typ"));
+    std::fs::write(&other, "").unwrap();
+    assert!(!completes(&mut client, &main, "other.typ"));
+    client.notify(
+        "workspace/didChangeWatchedFiles",
+        json!({ "changes": [{ "uri": encoded_uri(&other), "type": 1 }] }),
+    );
+    assert!(completes(&mut client, &main, "other.typ"));
+
+    // Changes to documents that are not open are reported.
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": encoded_uri(&other), "version": 2 },
+            "contentChanges": [{ "text": "Hi" }],
+        }),
+    );
+    let log = client.receive_method("window/logMessage");
+    assert_eq!(log["params"]["type"], 2);
+    assert!(log["params"]["message"].as_str().unwrap().contains("not open"));
+
+    client.request("shutdown", Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
+
+/// A client talking to a language server process.
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_typst"))
+            .args(["lsp", "--ignore-system-fonts"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self { child, stdin, stdout, next_id: 0 }
+    }
+
+    /// Send a request and wait for its response, skipping notifications.
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(
+            json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        );
+        loop {
+            let message = self.receive();
+            if message["id"] == id {
+                return message;
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Wait for the next published diagnostics.
+    fn diagnostics(&mut self) -> Value {
+        self.receive_method("textDocument/publishDiagnostics")["params"].clone()
+    }
+
+    /// Wait for the next message from the server with the given method.
+    fn receive_method(&mut self, method: &str) -> Value {
+        loop {
+            let message = self.receive();
+            if message["method"] == method {
+                return message;
+            }
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            assert_ne!(self.stdout.read_line(&mut header).unwrap(), 0);
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+}
+
+fn uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+    if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        format!("file:///{path}")
+    }
+}
+
+/// Whether a file is suggested for the include path in the first line.
+fn completes(client: &mut Client, main: &Path, name: &str) -> bool {
+    let completion = client.request(
+        "textDocument/completion",
+        json!({
+            "textDocument": { "uri": encoded_uri(main) },
+            "position": pos(0, 10),
+            "context": { "triggerKind": 1 },
+        }),
+    );
+    completion["result"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|item| item["label"].as_str().unwrap().contains(name))
+}
+
+fn pos(line: u32, character: u32) -> Value {
+    json!({ "line": line, "character": character })
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+    json!({ "start": pos(start.0, start.1), "end": pos(end.0, end.1) })
+}
+
+/// Create a URI like VS Code does, with a lowercase drive letter and
+/// percent-encoded special characters.
+fn encoded_uri(path: &Path) -> String {
+    let uri = uri(path);
+    let (scheme, path) = uri.split_at("file://".len());
+    let mut encoded = String::from(scheme);
+    let drive = path.get(2..3) == Some(":");
+    for (i, c) in path.char_indices() {
+        match c {
+            ':' => encoded.push_str("%3A"),
+            ' ' => encoded.push_str("%20"),
+            _ if drive && i == 1 => encoded.push(c.to_ascii_lowercase()),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,38 @@
+//! Exports encrypted PDFs with `typst compile`.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+#[test]
+fn test_pdf_encryption() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("main.typ"), "Hello\n").unwrap();
+
+    let output = compile(root, "out.pdf", &["--pdf-user-password", "secret"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let data = std::fs::read(root.join("out.pdf")).unwrap();
+    assert!(data.windows(8).any(|window| window == b"/Encrypt"));
+
+    // The passwords are rejected for other formats.
+    for (flag, path) in
+        [("--pdf-user-password", "out.png"), ("--pdf-owner-password", "out.svg")]
+    {
+        let output = compile(root, path, &[flag, "secret"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains(&format!("`{flag}` can only be used with PDF output")));
+    }
+}
+
+/// Run `typst compile` on `main.typ` in the given directory.
+fn compile(root: &Path, output: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_typst"))
+        .current_dir(root)
+        .args(["compile", "main.typ", output, "--ignore-system-fonts"])
+        .args(args)
+        .env_remove("TYPST_PDF_USER_PASSWORD")
+        .env_remove("TYPST_PDF_OWNER_PASSWORD")
+        .output()
+        .unwrap()
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use base64::Engine;
+use ecow::{eco_format, EcoString};
+use typst_library::html::{attr, tag, HtmlElement, HtmlNode};
+
+/// A file that the HTML output refers to.
+#[derive(Debug, Clone)]
+pub struct HtmlAsset {
+    /// The asset's path, relative to the HTML file.
+    pub path: EcoString,
+    /// The asset's MIME type.
+    pub mime_type: EcoString,
+    /// The asset's contents.
+    pub data: Vec<u8>,
+}
+
+/// Moves images that are embedded as data URLs into separate files.
+///
+/// The `src` of each such `<img>` element is replaced with the path of the
+/// returned asset. The paths are derived from the image data, so that
+/// identical images share a file and changed images get a new name, which
+/// makes them safe to cache.
+pub fn externalize_images(root: &mut HtmlElement) -> Vec<HtmlAsset> {
+    let mut assets = vec![];
+    let mut seen = HashSet::new();
+    externalize_in(&mut root.children, &mut assets, &mut seen);
+    assets
+}
+
+/// Moves the images among the nodes and their descendants.
+fn externalize_in(
+    nodes: &mut [HtmlNode],
+    assets: &mut Vec<HtmlAsset>,
+    seen: &mut HashSet<EcoString>,
+) {
+    for node in nodes {
+        let HtmlNode::Element(elem) = node else { continue };
+        if elem.tag == tag::img {
+            for (key, value) in elem.attrs.0.make_mut() {
+                if *key != attr::src {
+                    continue;
+                }
+                let Some((mime_type, extension, data)) = decode_data_url(value) else {
+                    continue;
+                };
+                let path = eco_format!(
+                    "images/{:032x}.{extension}",
+                    typst_utils::hash128(&data)
+                );
+                if seen.insert(path.clone()) {
+                    assets.push(HtmlAsset { path: path.clone(), mime_type, data });
+                }
+                *value = path;
+            }
+        }
+        externalize_in(&mut elem.children, assets, seen);
+    }
+}
+
+/// Decodes a base64 data URL of an image, returning its MIME type and a file
+/// extension for it along with the data.
+fn decode_data_url(url: &str) -> Option<(EcoString, &'static str, Vec<u8>)> {
+    let (mime, payload) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    let extension = match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/avif" => "avif",
+        "image/bmp" => "bmp",
+        "image/svg+xml" => "svg",
+        _ => return None,
+    };
+    let data = base64::engine::general_purpose::STANDARD.decode(payload).ok()?;
+    Some((mime.into(), extension, data))
+}
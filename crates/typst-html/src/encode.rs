@@ -1,17 +1,47 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 
 use typst_library::diag::{bail, At, SourceResult, StrResult};
 use typst_library::foundations::Repr;
-use typst_library::html::{charsets, tag, HtmlDocument, HtmlElement, HtmlNode, HtmlTag};
-use typst_library::layout::Frame;
+use typst_library::html::{
+    attr, charsets, tag, HtmlDocument, HtmlElement, HtmlFrame, HtmlNode, HtmlTag,
+};
+use typst_svg::SvgFonts;
 use typst_syntax::Span;
 
 /// Encodes an HTML document into a string.
 pub fn html(document: &HtmlDocument) -> SourceResult<String> {
-    let mut w = Writer { pretty: true, ..Writer::default() };
+    encode_root(&document.root)
+}
+
+/// Encodes a root `<html>` element into a string.
+pub(crate) fn encode_root(root: &HtmlElement) -> SourceResult<String> {
+    let (root, fonts) = embed_fonts(root);
+    let mut w = Writer { pretty: true, fonts, ..Writer::default() };
     w.buf.push_str("<!DOCTYPE html>");
     write_indent(&mut w);
-    write_element(&mut w, &document.root)?;
+    write_element(&mut w, &root)?;
+    if w.pretty {
+        w.buf.push('\n');
+    }
+    Ok(w.buf)
+}
+
+/// Encodes a root `<html>` element into a string using the XML syntax of HTML.
+///
+/// The element is expected to carry the XHTML namespace already.
+pub(crate) fn encode_xhtml_root(root: &HtmlElement) -> SourceResult<String> {
+    let (root, fonts) = embed_fonts(root);
+    let mut w = Writer {
+        pretty: true,
+        xhtml: true,
+        fonts,
+        ..Writer::default()
+    };
+    w.buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    w.buf.push_str("<!DOCTYPE html>");
+    write_indent(&mut w);
+    write_element(&mut w, &root)?;
     if w.pretty {
         w.buf.push('\n');
     }
@@ -26,6 +56,51 @@ struct Writer {
     level: usize,
     /// Whether pretty printing is enabled.
     pretty: bool,
+    /// Whether to produce XML syntax.
+    xhtml: bool,
+    /// The fonts for the text in frames.
+    fonts: SvgFonts,
+}
+
+/// Embeds subsets of the fonts used by the text in the root's frames into its
+/// `<head>`.
+///
+/// All frames share the same subsets, so that each font is only embedded
+/// once.
+fn embed_fonts(root: &HtmlElement) -> (Cow<'_, HtmlElement>, SvgFonts) {
+    let mut frames = vec![];
+    collect_frames(&root.children, &mut frames);
+    let fonts = SvgFonts::new(frames.into_iter().map(|frame| &frame.inner));
+
+    let Some(css) = fonts.css() else { return (Cow::Borrowed(root), fonts) };
+    let style = HtmlElement::new(tag::style)
+        .with_children(vec![HtmlNode::text(css, Span::detached())]);
+
+    let mut root = root.clone();
+    let head = root.children.iter_mut().find_map(|node| match node {
+        HtmlNode::Element(elem) if elem.tag == tag::head => Some(elem),
+        _ => None,
+    });
+    match head {
+        Some(head) => head.children.push(style.into()),
+        None => root.children.insert(
+            0,
+            HtmlElement::new(tag::head).with_children(vec![style.into()]).into(),
+        ),
+    }
+
+    (Cow::Owned(root), fonts)
+}
+
+/// Collects all frames in the nodes.
+fn collect_frames<'a>(nodes: &'a [HtmlNode], frames: &mut Vec<&'a HtmlFrame>) {
+    for node in nodes {
+        match node {
+            HtmlNode::Element(elem) => collect_frames(&elem.children, frames),
+            HtmlNode::Frame(frame) => frames.push(frame),
+            HtmlNode::Tag(_) | HtmlNode::Text(..) => {}
+        }
+    }
 }
 
 /// Write a newline and indent, if pretty printing is enabled.
@@ -81,17 +156,18 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
         w.buf.push('"');
     }
 
-    w.buf.push('>');
-
     if tag::is_void(element.tag) {
+        w.buf.push_str(if w.xhtml { " />" } else { ">" });
         return Ok(());
     }
 
+    w.buf.push('>');
+
     let pretty = w.pretty;
     if !element.children.is_empty() {
         let pretty_inside = allows_pretty_inside(element.tag)
             && element.children.iter().any(|node| match node {
-                HtmlNode::Element(child) => wants_pretty_around(child.tag),
+                HtmlNode::Element(child) => wants_pretty_around(child),
                 _ => false,
             });
 
@@ -102,7 +178,7 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
         for c in &element.children {
             let pretty_around = match c {
                 HtmlNode::Tag(_) => continue,
-                HtmlNode::Element(child) => w.pretty && wants_pretty_around(child.tag),
+                HtmlNode::Element(child) => w.pretty && wants_pretty_around(child),
                 HtmlNode::Text(..) | HtmlNode::Frame(_) => false,
             };
 
@@ -143,9 +219,18 @@ fn allows_pretty_inside(tag: HtmlTag) -> bool {
 /// allows it.
 ///
 /// In contrast to `allows_pretty_inside`, which is purely spec-driven, this is
-/// more subjective and depends on preference.
-fn wants_pretty_around(tag: HtmlTag) -> bool {
-    allows_pretty_inside(tag) || tag::is_metadata(tag) || tag == tag::pre
+/// more subjective and depends on preference. Block equations are put on their
+/// own line like the blocks they are displayed as.
+fn wants_pretty_around(element: &HtmlElement) -> bool {
+    let tag = element.tag;
+    allows_pretty_inside(tag)
+        || tag::is_metadata(tag)
+        || tag == tag::pre
+        || (tag == tag::math
+            && element
+                .attrs
+                .get(attr::display)
+                .is_some_and(|display| display == "block"))
 }
 
 /// Escape a character.
@@ -166,9 +251,7 @@ fn write_escape(w: &mut Writer, c: char) -> StrResult<()> {
 }
 
 /// Encode a laid out frame into the writer.
-fn write_frame(w: &mut Writer, frame: &Frame) {
-    // FIXME: This string replacement is obviously a hack.
-    let svg = typst_svg::svg_frame(frame)
-        .replace("<svg class", "<svg style=\"overflow: visible;\" class");
+fn write_frame(w: &mut Writer, frame: &HtmlFrame) {
+    let svg = typst_svg::svg_html_frame(&frame.inner, &w.fonts, frame.alt.as_deref());
     w.buf.push_str(&svg);
 }
@@ -0,0 +1,602 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+use std::num::NonZeroUsize;
+
+use ecow::{eco_format, EcoString};
+use typst_library::diag::{bail, At, SourceResult};
+use typst_library::foundations::{Bytes, Datetime, Smart, StyleChain};
+use typst_library::html::{
+    attr, css, generated_element, tag, HtmlAttr, HtmlDocument, HtmlElement, HtmlNode,
+};
+use typst_library::introspection::Tag;
+use typst_library::model::HeadingElem;
+use typst_library::text::FontVariant;
+use typst_library::visualize::ImageFormat;
+use typst_library::World;
+use typst_syntax::Span;
+use typst_utils::{hash128, NonZeroExt};
+use xmlwriter::XmlWriter;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::asset::externalize_images;
+use crate::encode::encode_xhtml_root;
+use crate::site::{page_root, split, Page, SiteOptions};
+
+/// The path of the navigation document.
+const NAV: &str = "nav.xhtml";
+/// The path of the cover page.
+const COVER: &str = "cover.xhtml";
+/// The path of the stylesheet with the embedded fonts.
+const STYLE: &str = "style.css";
+/// The directory within the archive that holds the publication.
+const ROOT: &str = "EPUB";
+
+/// Settings for EPUB export.
+#[derive(Debug, Default)]
+pub struct EpubOptions<'a> {
+    /// A stable identifier for the publication. If `Smart::Auto`, an
+    /// identifier is derived from the document's title and author.
+    pub ident: Smart<&'a str>,
+    /// The time at which the publication was last modified. It is also used
+    /// as the publication date if `set document(date: ..)` is `auto`. Since
+    /// EPUB requires a modification time, the document's date is used if this
+    /// is `None`.
+    pub modified: Option<Datetime>,
+    /// Top-level headings up to this level start a new chapter. If `None`,
+    /// only `html.split` elements start a new chapter.
+    pub split_level: Option<NonZeroUsize>,
+    /// An image to use as the cover of the publication.
+    pub cover: Option<Bytes>,
+}
+
+/// Packages an HTML document as an EPUB 3 publication.
+///
+/// The document is split into XHTML chapters like an HTML site. The
+/// publication additionally contains a navigation document built from the
+/// outlined headings, the images of the document, and the fonts that its
+/// styles refer to.
+#[typst_macros::time(name = "epub")]
+pub fn epub(
+    document: &HtmlDocument,
+    world: &dyn World,
+    options: &EpubOptions,
+) -> SourceResult<Vec<u8>> {
+    let mut document = document.clone();
+    let images = externalize_images(&mut document.root);
+    let fonts = collect_fonts(&document.root, world);
+    let cover = options.cover.as_ref().map(cover_image).transpose()?;
+
+    let site_options = SiteOptions { split_level: options.split_level };
+    let site = split(&document, &site_options, "xhtml", &[NAV, COVER])?;
+    let title = document
+        .info
+        .title
+        .clone()
+        .or_else(|| site.pages.first().map(|page| page.title.clone()))
+        .unwrap_or_else(|| "Untitled".into());
+
+    let mut head = site.head.clone();
+    if !fonts.is_empty() {
+        head.children.push(
+            HtmlElement::new(tag::link)
+                .with_attr(attr::rel, "stylesheet")
+                .with_attr(attr::href, STYLE)
+                .into(),
+        );
+    }
+
+    let mut files = vec![];
+    let mut manifest = vec![];
+    let mut spine = vec![];
+
+    let nav = HtmlElement::new(tag::nav)
+        .with_attr(epub_attr("epub:type"), "toc")
+        .with_children(vec![HtmlElement::new(tag::ol)
+            .with_children(nav_items(&nav_entries(&site.pages)))
+            .into()]);
+    let root = page_root(&document, &head, site.body, &title, vec![nav.into()]);
+    files.push(File::new(NAV, encode_xhtml_root(&xhtml_root(root, &document))?));
+    manifest.push(Item::new("nav", NAV, "application/xhtml+xml").with("nav"));
+
+    if let Some(cover) = &cover {
+        let img = HtmlElement::new(tag::img)
+            .with_attr(attr::src, cover.path.clone())
+            .with_attr(attr::alt, title.clone());
+        let root = page_root(&document, &head, site.body, &title, vec![img.into()]);
+        files.push(File::new(COVER, encode_xhtml_root(&xhtml_root(root, &document))?));
+        files.push(File::new(cover.path.clone(), cover.data.to_vec()));
+        manifest.push(Item::new("cover", COVER, "application/xhtml+xml"));
+        manifest.push(
+            Item::new("cover-image", cover.path.clone(), cover.mime_type)
+                .with("cover-image"),
+        );
+        spine.push(EcoString::from("cover"));
+    }
+
+    for (i, page) in site.pages.iter().enumerate() {
+        let id = eco_format!("chapter-{}", i + 1);
+        let mut item = Item::new(id.clone(), page.path.clone(), "application/xhtml+xml");
+        if contains(&page.nodes, &|node| matches!(node, HtmlNode::Frame(_))) {
+            item = item.with("svg");
+        }
+        if contains(
+            &page.nodes,
+            &|node| matches!(node, HtmlNode::Element(elem) if elem.tag == tag::math),
+        ) {
+            item = item.with("mathml");
+        }
+
+        let root =
+            page_root(&document, &head, site.body, &page.title, page.nodes.clone());
+        files.push(File::new(
+            page.path.clone(),
+            encode_xhtml_root(&xhtml_root(root, &document))?,
+        ));
+        manifest.push(item);
+        spine.push(id);
+    }
+
+    for (i, image) in images.into_iter().enumerate() {
+        let id = eco_format!("image-{}", i + 1);
+        manifest.push(Item::new(id, image.path.clone(), image.mime_type));
+        files.push(File::new(image.path, image.data));
+    }
+
+    if !fonts.is_empty() {
+        let mut css = String::new();
+        for (i, font) in fonts.into_iter().enumerate() {
+            css.push_str(&css::font_face(&font.family, font.variant, &font.path));
+            let id = eco_format!("font-{}", i + 1);
+            manifest.push(Item::new(id, font.path.clone(), font.mime_type));
+            files.push(File::new(font.path, font.data.to_vec()));
+        }
+        manifest.push(Item::new("style", STYLE, "text/css"));
+        files.push(File::new(STYLE, css));
+    }
+
+    let package = package_document(&document, options, &title, &manifest, &spine);
+    files.push(File::new("package.opf", package));
+
+    archive(files)
+        .map_err(|err| eco_format!("failed to write EPUB archive ({err})"))
+        .at(Span::detached())
+}
+
+/// A file in the publication.
+struct File {
+    /// The file's path, relative to the publication's root directory.
+    path: EcoString,
+    /// The file's contents.
+    data: Vec<u8>,
+}
+
+impl File {
+    /// Create a new file.
+    fn new(path: impl Into<EcoString>, data: impl Into<Vec<u8>>) -> Self {
+        Self { path: path.into(), data: data.into() }
+    }
+}
+
+/// An entry in the manifest of the package document.
+struct Item {
+    /// The identifier by which the spine refers to the item.
+    id: EcoString,
+    /// The path of the item's file.
+    href: EcoString,
+    /// The MIME type of the item's file.
+    media_type: EcoString,
+    /// The properties of the item, like `nav` or `svg`.
+    properties: Vec<&'static str>,
+}
+
+impl Item {
+    /// Create a new item without properties.
+    fn new(
+        id: impl Into<EcoString>,
+        href: impl Into<EcoString>,
+        media_type: impl Into<EcoString>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            href: href.into(),
+            media_type: media_type.into(),
+            properties: vec![],
+        }
+    }
+
+    /// Add a property to the item.
+    fn with(mut self, property: &'static str) -> Self {
+        self.properties.push(property);
+        self
+    }
+}
+
+/// An entry of the navigation document.
+struct NavEntry {
+    /// The nesting level of the entry.
+    level: NonZeroUsize,
+    /// The text shown for the entry.
+    title: EcoString,
+    /// The link to the entry's target.
+    href: EcoString,
+}
+
+/// Collect the navigation entries from the outlined headings of the pages.
+///
+/// If there are no outlined headings, the pages themselves are listed since
+/// the navigation document must not be empty.
+fn nav_entries(pages: &[Page]) -> Vec<NavEntry> {
+    let mut entries = vec![];
+    for page in pages {
+        collect_headings(&page.nodes, &page.path, &mut entries);
+    }
+
+    if entries.is_empty() {
+        entries = pages
+            .iter()
+            .map(|page| NavEntry {
+                level: NonZeroUsize::ONE,
+                title: page.title.clone(),
+                href: page.path.clone(),
+            })
+            .collect();
+    }
+
+    entries
+}
+
+/// Collect navigation entries for the outlined headings among the nodes.
+fn collect_headings(nodes: &[HtmlNode], path: &EcoString, entries: &mut Vec<NavEntry>) {
+    for (i, node) in nodes.iter().enumerate() {
+        match node {
+            HtmlNode::Tag(Tag::Start(elem)) => {
+                let Some(heading) = elem.to_packed::<HeadingElem>() else { continue };
+                if !heading.outlined(StyleChain::default()) {
+                    continue;
+                }
+
+                let id = generated_element(nodes, i).and_then(|k| match &nodes[k] {
+                    HtmlNode::Element(generated) => generated.attrs.get(attr::id),
+                    _ => None,
+                });
+                entries.push(NavEntry {
+                    level: heading.resolve_level(StyleChain::default()),
+                    title: heading.body.plain_text(),
+                    href: match id {
+                        Some(id) => eco_format!("{path}#{id}"),
+                        None => path.clone(),
+                    },
+                });
+            }
+            HtmlNode::Element(elem) => collect_headings(&elem.children, path, entries),
+            _ => {}
+        }
+    }
+}
+
+/// Produce nested list items for the navigation entries.
+///
+/// Each entry contains the entries with a higher level that follow it.
+fn nav_items(entries: &[NavEntry]) -> Vec<HtmlNode> {
+    let mut items = vec![];
+    let mut i = 0;
+    while i < entries.len() {
+        let entry = &entries[i];
+        let end = entries[i + 1..]
+            .iter()
+            .position(|next| next.level <= entry.level)
+            .map_or(entries.len(), |k| i + 1 + k);
+
+        let mut children = vec![HtmlElement::new(tag::a)
+            .with_attr(attr::href, entry.href.clone())
+            .with_children(vec![HtmlNode::text(entry.title.clone(), Span::detached())])
+            .into()];
+        let nested = nav_items(&entries[i + 1..end]);
+        if !nested.is_empty() {
+            children.push(HtmlElement::new(tag::ol).with_children(nested).into());
+        }
+
+        items.push(HtmlElement::new(tag::li).with_children(children).into());
+        i = end;
+    }
+    items
+}
+
+/// Whether any of the nodes or their descendants satisfies the predicate.
+fn contains(nodes: &[HtmlNode], f: &dyn Fn(&HtmlNode) -> bool) -> bool {
+    nodes.iter().any(|node| {
+        f(node) || matches!(node, HtmlNode::Element(elem) if contains(&elem.children, f))
+    })
+}
+
+/// Add the namespaces and the language that XHTML documents in an EPUB need
+/// to the root element.
+fn xhtml_root(mut root: HtmlElement, document: &HtmlDocument) -> HtmlElement {
+    let lang = document.lang.as_str();
+    for (key, value) in [
+        (attr::xmlns, "http://www.w3.org/1999/xhtml"),
+        (epub_attr("xmlns:epub"), "http://www.idpf.org/2007/ops"),
+        (attr::lang, lang),
+        (epub_attr("xml:lang"), lang),
+    ] {
+        if root.attrs.get(key).is_none() {
+            root.attrs.push(key, value);
+        }
+    }
+    add_math_namespace(&mut root.children);
+    root
+}
+
+/// Declare the MathML namespace on `<math>` elements, which the XML syntax
+/// requires.
+fn add_math_namespace(nodes: &mut [HtmlNode]) {
+    for node in nodes {
+        let HtmlNode::Element(elem) = node else { continue };
+        if elem.tag == tag::math {
+            if elem.attrs.get(attr::xmlns).is_none() {
+                elem.attrs.push(attr::xmlns, "http://www.w3.org/1998/Math/MathML");
+            }
+        } else {
+            add_math_namespace(&mut elem.children);
+        }
+    }
+}
+
+/// Create a namespaced attribute, which can't be a compile-time constant.
+fn epub_attr(name: &str) -> HtmlAttr {
+    HtmlAttr::intern(name).expect("attribute name should be valid")
+}
+
+/// A font that is embedded into the publication.
+struct EmbeddedFont {
+    /// The font's path in the publication.
+    path: EcoString,
+    /// The MIME type of the font.
+    mime_type: &'static str,
+    /// The font's data.
+    data: Bytes,
+    /// The family name under which the font is made available.
+    family: String,
+    /// The variant of the family that the font provides.
+    variant: FontVariant,
+}
+
+/// Collect all fonts of the families that the document's styles refer to.
+fn collect_fonts(root: &HtmlElement, world: &dyn World) -> Vec<EmbeddedFont> {
+    let mut families = vec![];
+    collect_families(&root.children, &mut families);
+
+    let mut fonts = vec![];
+    let mut seen = HashSet::new();
+    for family in families {
+        for index in world.book().select_family(&family.to_lowercase()) {
+            let Some(font) = world.font(index) else { continue };
+            // Fonts from collections can't be referenced by CSS.
+            if font.index() != 0 {
+                continue;
+            }
+
+            let data = font.data().clone();
+            let Some((mime_type, extension)) = font_type(&data) else { continue };
+            let path = eco_format!("fonts/{:032x}.{extension}", hash128(&data));
+            if seen.insert(path.clone()) {
+                fonts.push(EmbeddedFont {
+                    path,
+                    mime_type,
+                    data,
+                    family: font.info().family.clone(),
+                    variant: font.info().variant,
+                });
+            }
+        }
+    }
+    fonts
+}
+
+/// Collect the font families from the `style` attributes of the nodes.
+fn collect_families(nodes: &[HtmlNode], families: &mut Vec<EcoString>) {
+    for node in nodes {
+        let HtmlNode::Element(elem) = node else { continue };
+        if let Some(style) = elem.attrs.get(attr::style) {
+            for family in css::font_families(style) {
+                if !families.contains(&family) {
+                    families.push(family);
+                }
+            }
+        }
+        collect_families(&elem.children, families);
+    }
+}
+
+/// Determine the MIME type and file extension of font data.
+fn font_type(data: &[u8]) -> Option<(&'static str, &'static str)> {
+    match data.get(..4)? {
+        b"\0\x01\0\0" | b"true" => Some(("font/ttf", "ttf")),
+        b"OTTO" => Some(("font/otf", "otf")),
+        b"wOFF" => Some(("font/woff", "woff")),
+        b"wOF2" => Some(("font/woff2", "woff2")),
+        _ => None,
+    }
+}
+
+/// A cover image.
+struct Cover<'a> {
+    /// The image's path in the publication.
+    path: EcoString,
+    /// The MIME type of the image.
+    mime_type: &'static str,
+    /// The image data.
+    data: &'a Bytes,
+}
+
+/// Prepare the cover image for inclusion into the publication.
+fn cover_image(data: &Bytes) -> SourceResult<Cover<'_>> {
+    let mime_type = ImageFormat::detect(data).and_then(|format| format.mime_type(data));
+    let (mime_type, extension) = match mime_type {
+        Some(mime_type @ "image/png") => (mime_type, "png"),
+        Some(mime_type @ "image/jpeg") => (mime_type, "jpg"),
+        Some(mime_type @ "image/gif") => (mime_type, "gif"),
+        Some(mime_type @ "image/webp") => (mime_type, "webp"),
+        Some(mime_type @ "image/svg+xml") => (mime_type, "svg"),
+        _ => bail!(
+            Span::detached(),
+            "unsupported cover image format";
+            hint: "use a PNG, JPEG, GIF, WebP, or SVG image"
+        ),
+    };
+    Ok(Cover {
+        path: eco_format!("images/cover.{extension}"),
+        mime_type,
+        data,
+    })
+}
+
+/// Write the package document, which describes the publication.
+fn package_document(
+    document: &HtmlDocument,
+    options: &EpubOptions,
+    title: &str,
+    manifest: &[Item],
+    spine: &[EcoString],
+) -> String {
+    let info = &document.info;
+    let ident = match options.ident {
+        Smart::Custom(ident) => hash128(ident),
+        Smart::Auto => hash128(&(&info.title, &info.author)),
+    };
+    let date = match info.date {
+        Smart::Custom(date) => date,
+        Smart::Auto => options.modified,
+    };
+    let modified = options
+        .modified
+        .or(date)
+        .and_then(|modified| format_timestamp(&modified))
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".into());
+
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("package");
+    xml.write_attribute("xmlns", "http://www.idpf.org/2007/opf");
+    xml.write_attribute("version", "3.0");
+    xml.write_attribute("unique-identifier", "uid");
+    xml.write_attribute("xml:lang", document.lang.as_str());
+
+    xml.start_element("metadata");
+    xml.write_attribute("xmlns:dc", "http://purl.org/dc/elements/1.1/");
+    xml.start_element("dc:identifier");
+    xml.write_attribute("id", "uid");
+    xml.write_text(&eco_format!("urn:typst:{ident:032x}"));
+    xml.end_element();
+    text_element(&mut xml, "dc:title", title);
+    text_element(&mut xml, "dc:language", document.lang.as_str());
+    for author in &info.author {
+        text_element(&mut xml, "dc:creator", author);
+    }
+    if let Some(date) = date.and_then(|date| format_date(&date)) {
+        text_element(&mut xml, "dc:date", &date);
+    }
+    if let Some(description) = &info.description {
+        text_element(&mut xml, "dc:description", description);
+    }
+    for keyword in &info.keywords {
+        text_element(&mut xml, "dc:subject", keyword);
+    }
+    xml.start_element("meta");
+    xml.write_attribute("property", "dcterms:modified");
+    xml.write_text(&modified);
+    xml.end_element();
+    xml.end_element();
+
+    xml.start_element("manifest");
+    for item in manifest {
+        xml.start_element("item");
+        xml.write_attribute("id", &item.id);
+        xml.write_attribute("href", &item.href);
+        xml.write_attribute("media-type", &item.media_type);
+        if !item.properties.is_empty() {
+            xml.write_attribute("properties", &item.properties.join(" "));
+        }
+        xml.end_element();
+    }
+    xml.end_element();
+
+    xml.start_element("spine");
+    for id in spine {
+        xml.start_element("itemref");
+        xml.write_attribute("idref", id);
+        xml.end_element();
+    }
+    xml.end_element();
+
+    xml.end_document()
+}
+
+/// Write an element that only contains text.
+fn text_element(xml: &mut XmlWriter, name: &str, text: &str) {
+    xml.start_element(name);
+    xml.write_text(text);
+    xml.end_element();
+}
+
+/// Format the date part of a datetime as `YYYY-MM-DD`.
+fn format_date(datetime: &Datetime) -> Option<EcoString> {
+    Some(eco_format!(
+        "{:04}-{:02}-{:02}",
+        datetime.year()?,
+        datetime.month()?,
+        datetime.day()?
+    ))
+}
+
+/// Format a datetime as a UTC timestamp, assuming midnight if it has no time.
+fn format_timestamp(datetime: &Datetime) -> Option<EcoString> {
+    Some(eco_format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(datetime)?,
+        datetime.hour().unwrap_or(0),
+        datetime.minute().unwrap_or(0),
+        datetime.second().unwrap_or(0),
+    ))
+}
+
+/// Pack the files into a ZIP archive.
+///
+/// As required by the specification, the archive starts with an uncompressed
+/// `mimetype` file and points to the package document through
+/// `META-INF/container.xml`.
+fn archive(files: Vec<File>) -> zip::result::ZipResult<Vec<u8>> {
+    let stored =
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated =
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(container().as_bytes())?;
+
+    for file in files {
+        zip.start_file(format!("{ROOT}/{}", file.path), deflated)?;
+        zip.write_all(&file.data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Write the container file, which points to the package document.
+fn container() -> String {
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("container");
+    xml.write_attribute("xmlns", "urn:oasis:names:tc:opendocument:xmlns:container");
+    xml.write_attribute("version", "1.0");
+    xml.start_element("rootfiles");
+    xml.start_element("rootfile");
+    xml.write_attribute_fmt("full-path", format_args!("{ROOT}/package.opf"));
+    xml.write_attribute("media-type", "application/oebps-package+xml");
+    xml.end_document()
+}
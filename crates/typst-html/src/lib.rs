@@ -1,23 +1,39 @@
 //! Typst's HTML exporter.
 
+mod asset;
 mod encode;
+mod epub;
+mod link;
+mod math;
+mod site;
 
+pub use self::asset::{externalize_images, HtmlAsset};
 pub use self::encode::html;
+pub use self::epub::{epub, EpubOptions};
+pub use self::site::{html_site, HtmlFile, SiteOptions};
+
+use std::collections::BTreeMap;
 
 use comemo::{Track, Tracked, TrackedMut};
+use ecow::{eco_format, EcoString};
 use typst_library::diag::{bail, warning, At, SourceResult};
 use typst_library::engine::{Engine, Route, Sink, Traced};
-use typst_library::foundations::{Content, StyleChain, Target, TargetElem};
+use typst_library::foundations::{
+    Content, NativeElement, Resolve, StyleChain, Target, TargetElem,
+};
 use typst_library::html::{
-    attr, tag, FrameElem, HtmlDocument, HtmlElem, HtmlElement, HtmlNode,
+    attr, css, tag, FrameElem, HtmlDocument, HtmlElem, HtmlElement, HtmlFrame, HtmlNode,
 };
 use typst_library::introspection::{
     Introspector, Locator, LocatorLink, SplitLocator, TagElem,
 };
-use typst_library::layout::{Abs, Axes, BlockBody, BlockElem, BoxElem, Region, Size};
-use typst_library::model::{DocumentInfo, ParElem};
+use typst_library::layout::{
+    Abs, Axes, BlockBody, BlockElem, BoxElem, HElem, Region, Size, Sizing,
+};
+use typst_library::model::{DocumentInfo, FootnoteElem, FootnoteEntry, ParElem};
 use typst_library::routines::{Arenas, FragmentKind, Pair, RealizationKind, Routines};
-use typst_library::text::{LinebreakElem, SmartQuoteElem, SpaceElem, TextElem};
+use typst_library::text::{Lang, LinebreakElem, SmartQuoteElem, SpaceElem, TextElem};
+use typst_library::visualize::Stroke;
 use typst_library::World;
 use typst_syntax::Span;
 
@@ -82,11 +98,54 @@ fn html_document_impl(
         styles,
     )?;
 
-    let output = handle_list(&mut engine, &mut locator, children.iter().copied())?;
+    let lang = primary_lang(&children);
+
+    // Text styles that all top-level elements share are declared on the
+    // `<body>` once instead of on each run of text. Runs of text only declare
+    // the styles that differ from the ones they inherit.
+    let base_text = css::TextStyle::resolve(styles);
+    let body_text = body_text(&children, &base_text);
+    let inherited = HtmlElem::set_body_text(Some(
+        body_text.clone().unwrap_or_else(|| base_text.clone()),
+    ))
+    .wrap();
+    let mut output = handle_list(
+        &mut engine,
+        &mut locator,
+        children
+            .iter()
+            .map(|(child, styles)| (*child, styles.chain(&inherited))),
+    )?;
+    let endnotes = endnotes(&mut engine, &mut locator, styles.chain(&inherited))?;
+    insert_endnotes(&mut output, endnotes);
+    link::identify_link_targets(&mut output);
     let introspector = Introspector::html(&output);
-    let root = root_element(output, &info)?;
+    let root = root_element(output, &info, body_text.as_ref(), &base_text)?;
 
-    Ok(HtmlDocument { info, root, introspector })
+    Ok(HtmlDocument { info, lang, root, introspector })
+}
+
+/// Determine the language that most top-level elements are written in.
+fn primary_lang(children: &[Pair]) -> Lang {
+    let mut counts = BTreeMap::new();
+    for (_, styles) in children {
+        *counts.entry(TextElem::lang_in(*styles)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+        .map_or(Lang::ENGLISH, |(lang, _)| lang)
+}
+
+/// Determine the text styles that all top-level elements share, if they differ
+/// from the ones the document starts out with.
+fn body_text(children: &[Pair], base: &css::TextStyle) -> Option<css::TextStyle> {
+    let (_, first) = children.first()?;
+    let style = css::TextStyle::resolve(*first);
+    let shared = children
+        .iter()
+        .all(|(_, styles)| css::TextStyle::resolve(*styles) == style);
+    (shared && style != *base).then_some(style)
 }
 
 /// Produce HTML nodes from content.
@@ -178,7 +237,12 @@ fn handle(
     } else if let Some(elem) = child.to_packed::<HtmlElem>() {
         let mut children = vec![];
         if let Some(body) = elem.body(styles) {
-            children = html_fragment(engine, body, locator.next(&elem.span()), styles)?;
+            let locator = locator.next(&elem.span());
+            children = if elem.tag == tag::math {
+                math::handle_math(engine, body, &mut locator.split(), styles)?
+            } else {
+                html_fragment(engine, body, locator, styles)?
+            };
         }
         if tag::is_void(elem.tag) && !children.is_empty() {
             bail!(elem.span(), "HTML void elements may not have children");
@@ -193,8 +257,10 @@ fn handle(
     } else if let Some(elem) = child.to_packed::<ParElem>() {
         let children =
             html_fragment(engine, &elem.body, locator.next(&elem.span()), styles)?;
+        let mut props = css::Properties::new();
+        css::align(&mut props, styles);
         output.push(
-            HtmlElement::new(tag::p)
+            styled(HtmlElement::new(tag::p), props)
                 .with_children(children)
                 .spanned(elem.span())
                 .into(),
@@ -204,9 +270,23 @@ fn handle(
         if let Some(body) = elem.body(styles) {
             let children =
                 html_fragment(engine, body, locator.next(&elem.span()), styles)?;
+            let mut props = css::Properties::new();
+            props.push("display", "inline-block");
+            css::container(
+                &mut props,
+                match elem.width(styles) {
+                    Sizing::Rel(rel) => Some(rel.resolve(styles)),
+                    _ => None,
+                },
+                elem.height(styles).custom().map(|rel| rel.resolve(styles)),
+                elem.fill(styles),
+                elem.stroke(styles)
+                    .unwrap_or_default()
+                    .map(|s| s.map(Stroke::unwrap_or_default)),
+                elem.inset(styles).unwrap_or_default(),
+            );
             output.push(
-                HtmlElement::new(tag::span)
-                    .with_attr(attr::style, "display: inline-block;")
+                styled(HtmlElement::new(tag::span), props)
                     .with_children(children)
                     .spanned(elem.span())
                     .into(),
@@ -222,23 +302,43 @@ fn handle(
     {
         // TODO: This is rather incomplete.
         let children = html_fragment(engine, body, locator.next(&elem.span()), styles)?;
+        let mut props = css::Properties::new();
+        css::container(
+            &mut props,
+            elem.width(styles).custom().map(|rel| rel.resolve(styles)),
+            match elem.height(styles) {
+                Sizing::Rel(rel) => Some(rel.resolve(styles)),
+                _ => None,
+            },
+            elem.fill(styles),
+            elem.stroke(styles)
+                .unwrap_or_default()
+                .map(|s| s.map(Stroke::unwrap_or_default)),
+            elem.inset(styles).unwrap_or_default(),
+        );
+        css::align(&mut props, styles);
         output.push(
-            HtmlElement::new(tag::div)
+            styled(HtmlElement::new(tag::div), props)
                 .with_children(children)
                 .spanned(elem.span())
                 .into(),
         );
+    } else if child.to_packed::<HElem>().is_some_and(|elem| elem.amount.is_zero()) {
+        // Zero-width spacing only serves to collapse adjacent spaces, which
+        // realization already took care of.
     } else if child.is::<SpaceElem>() {
-        output.push(HtmlNode::text(' ', child.span()));
+        push_text(output, ' ', child.span(), styles);
     } else if let Some(elem) = child.to_packed::<TextElem>() {
-        output.push(HtmlNode::text(elem.text.clone(), elem.span()));
+        push_text(output, elem.text.clone(), elem.span(), styles);
     } else if let Some(elem) = child.to_packed::<LinebreakElem>() {
         output.push(HtmlElement::new(tag::br).spanned(elem.span()).into());
     } else if let Some(elem) = child.to_packed::<SmartQuoteElem>() {
-        output.push(HtmlNode::text(
+        push_text(
+            output,
             if elem.double(styles) { '"' } else { '\'' },
             child.span(),
-        ));
+            styles,
+        );
     } else if let Some(elem) = child.to_packed::<FrameElem>() {
         let locator = locator.next(&elem.span());
         let style = TargetElem::set_target(Target::Paged).wrap();
@@ -249,7 +349,7 @@ fn handle(
             styles.chain(&style),
             Region::new(Size::splat(Abs::inf()), Axes::splat(false)),
         )?;
-        output.push(HtmlNode::Frame(frame));
+        output.push(HtmlNode::Frame(HtmlFrame { inner: frame, alt: elem.alt(styles) }));
     } else {
         engine.sink.warn(warning!(
             child.span(),
@@ -260,20 +360,167 @@ fn handle(
     Ok(())
 }
 
+/// Push text into the output, wrapped in a `<span>` if its styles translate to
+/// CSS.
+fn push_text(
+    output: &mut Vec<HtmlNode>,
+    text: impl Into<EcoString>,
+    span: Span,
+    styles: StyleChain,
+) {
+    let node = HtmlNode::text(text, span);
+    let Some(style) = css::text(styles).into_inner() else {
+        output.push(node);
+        return;
+    };
+
+    // Continue the span of directly preceding text with the same styles.
+    if let Some(HtmlNode::Element(prev)) = output.last_mut() {
+        if prev.tag == tag::span
+            && prev.attrs.0.len() == 1
+            && prev.attrs.get(attr::style) == Some(&style)
+        {
+            prev.children.push(node);
+            return;
+        }
+    }
+
+    output.push(
+        HtmlElement::new(tag::span)
+            .with_attr(attr::style, style)
+            .with_children(vec![node])
+            .spanned(span)
+            .into(),
+    );
+}
+
+/// Add a `style` attribute with the given CSS properties, if there are any.
+fn styled(element: HtmlElement, props: css::Properties) -> HtmlElement {
+    match props.into_inner() {
+        Some(style) => element.with_attr(attr::style, style),
+        None => element,
+    }
+}
+
+/// Produce a section with the entries of all footnotes in the document.
+///
+/// As there are no pages in HTML, footnotes are collected at the end of the
+/// document as endnotes.
+fn endnotes(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    styles: StyleChain,
+) -> SourceResult<Vec<HtmlNode>> {
+    let entries: Vec<Content> = engine
+        .introspector
+        .query(&FootnoteElem::elem().select())
+        .into_iter()
+        .filter_map(|elem| elem.into_packed::<FootnoteElem>().ok())
+        .filter(|note| !note.is_ref())
+        .map(|note| {
+            let span = note.span();
+            FootnoteEntry::new(note).pack().spanned(span)
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let list = HtmlElem::new(tag::ol)
+        .with_attr(attr::style, "list-style-type: none;")
+        .with_body(Some(Content::sequence(entries)))
+        .pack();
+    let section = HtmlElem::new(tag::section)
+        .with_attr(attr::role, "doc-endnotes")
+        .with_body(Some(list))
+        .pack();
+
+    html_fragment(engine, &section, locator.next(&()), styles)
+}
+
+/// Append the endnotes to the `<body>` if the user generated one themselves
+/// and to the top-level nodes otherwise.
+fn insert_endnotes(output: &mut Vec<HtmlNode>, endnotes: Vec<HtmlNode>) {
+    let mut nodes = output;
+    while let Some(i) = nodes.iter().position(|node| {
+        matches!(node, HtmlNode::Element(elem) if matches!(elem.tag, tag::html | tag::body))
+    }) {
+        let HtmlNode::Element(elem) = &mut nodes[i] else { unreachable!() };
+        nodes = &mut elem.children;
+    }
+    nodes.extend(endnotes);
+}
+
 /// Wrap the nodes in `<html>` and `<body>` if they are not yet rooted,
 /// supplying a suitable `<head>`.
-fn root_element(output: Vec<HtmlNode>, info: &DocumentInfo) -> SourceResult<HtmlElement> {
-    let body = match classify_output(output)? {
-        OutputKind::Html(element) => return Ok(element),
+///
+/// The text styles that the whole document shares are declared on the
+/// `<body>` or, if the user generated their own `<html>` element, on that one.
+fn root_element(
+    output: Vec<HtmlNode>,
+    info: &DocumentInfo,
+    body_text: Option<&css::TextStyle>,
+    base_text: &css::TextStyle,
+) -> SourceResult<HtmlElement> {
+    let mut body = match classify_output(output)? {
+        OutputKind::Html(element) => {
+            return Ok(declare_text(element, body_text, base_text))
+        }
         OutputKind::Body(body) => body,
         OutputKind::Leafs(leafs) => HtmlElement::new(tag::body).with_children(leafs),
     };
+    body = declare_text(body, body_text, base_text);
+    let mut extra = vec![];
+    take_head_contents(&mut body.children, &mut extra);
     Ok(HtmlElement::new(tag::html)
-        .with_children(vec![head_element(info).into(), body.into()]))
+        .with_children(vec![head_element(info, extra).into(), body.into()]))
+}
+
+/// Declare the text styles that differ from the base ones on an element, in
+/// front of its existing styles.
+fn declare_text(
+    mut element: HtmlElement,
+    style: Option<&css::TextStyle>,
+    base: &css::TextStyle,
+) -> HtmlElement {
+    let Some(props) = style.and_then(|style| css::text_diff(style, base).into_inner())
+    else {
+        return element;
+    };
+
+    match element
+        .attrs
+        .0
+        .make_mut()
+        .iter_mut()
+        .find(|(key, _)| *key == attr::style)
+    {
+        Some((_, value)) => *value = eco_format!("{props} {value}"),
+        None => element.attrs.push(attr::style, props),
+    }
+    element
+}
+
+/// Remove `<head>` elements from the nodes, collecting their children.
+///
+/// These stem from `html.head` and are moved into the generated `<head>`.
+fn take_head_contents(nodes: &mut Vec<HtmlNode>, contents: &mut Vec<HtmlNode>) {
+    nodes.retain_mut(|node| {
+        let HtmlNode::Element(elem) = node else { return true };
+        if elem.tag == tag::head {
+            contents.append(&mut elem.children);
+            return false;
+        }
+        take_head_contents(&mut elem.children, contents);
+        true
+    });
 }
 
 /// Generate a `<head>` element.
-fn head_element(info: &DocumentInfo) -> HtmlElement {
+///
+/// The `extra` nodes are appended after the generated metadata.
+fn head_element(info: &DocumentInfo, extra: Vec<HtmlNode>) -> HtmlElement {
     let mut children = vec![];
 
     children.push(HtmlElement::new(tag::meta).with_attr(attr::charset, "utf-8").into());
@@ -302,6 +549,8 @@ fn head_element(info: &DocumentInfo) -> HtmlElement {
         );
     }
 
+    children.extend(extra);
+
     HtmlElement::new(tag::head).with_children(children)
 }
 
@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+
+use ecow::{eco_format, EcoString};
+use typst_library::foundations::Content;
+use typst_library::html::{attr, generated_element, slugify, HtmlNode};
+use typst_library::introspection::Tag;
+use typst_library::model::{HeadingElem, Outlinable};
+
+/// Assigns an `id` to the HTML elements generated by elements that can be
+/// linked to.
+///
+/// These are elements with a label and elements that appear in an outline,
+/// like headings. The ids are derived from the label or, failing that, from
+/// the heading's text. Elements that already have an `id` keep it.
+pub fn identify_link_targets(output: &mut [HtmlNode]) {
+    let mut taken = HashSet::new();
+    collect_ids(output, &mut taken);
+    let mut counts = HashMap::new();
+    assign_ids(output, &mut taken, &mut counts);
+}
+
+/// Collects the ids that are already in use.
+fn collect_ids(nodes: &[HtmlNode], taken: &mut HashSet<EcoString>) {
+    for node in nodes {
+        if let HtmlNode::Element(elem) = node {
+            if let Some(id) = elem.attrs.get(attr::id) {
+                taken.insert(id.clone());
+            }
+            collect_ids(&elem.children, taken);
+        }
+    }
+}
+
+/// Assigns ids to the elements generated by link targets.
+fn assign_ids(
+    nodes: &mut [HtmlNode],
+    taken: &mut HashSet<EcoString>,
+    counts: &mut HashMap<&'static str, usize>,
+) {
+    for i in 0..nodes.len() {
+        let base = match &nodes[i] {
+            HtmlNode::Tag(Tag::Start(elem)) if is_link_target(elem) => {
+                Some(base_id(elem, counts))
+            }
+            _ => None,
+        };
+
+        if let Some(base) = base {
+            if let Some(k) = generated_element(nodes, i) {
+                let HtmlNode::Element(generated) = &mut nodes[k] else { unreachable!() };
+                if generated.attrs.get(attr::id).is_none() {
+                    generated.attrs.push(attr::id, unique_id(base, taken));
+                }
+            }
+        }
+
+        if let HtmlNode::Element(elem) = &mut nodes[i] {
+            assign_ids(&mut elem.children, taken, counts);
+        }
+    }
+}
+
+/// Whether an element can be linked to.
+fn is_link_target(elem: &Content) -> bool {
+    elem.label().is_some()
+        || elem
+            .with::<dyn Outlinable>()
+            .is_some_and(|outlinable| outlinable.outlined())
+}
+
+/// Derives the preferred `id` of an element.
+fn base_id(elem: &Content, counts: &mut HashMap<&'static str, usize>) -> EcoString {
+    let name = elem.func().name();
+    let count = counts.entry(name).or_default();
+    *count += 1;
+
+    let slug = if let Some(label) = elem.label() {
+        slugify(label.resolve().as_str())
+    } else if let Some(heading) = elem.to_packed::<HeadingElem>() {
+        slugify(&heading.body.plain_text())
+    } else {
+        EcoString::new()
+    };
+
+    if slug.is_empty() {
+        eco_format!("{name}-{count}")
+    } else {
+        slug
+    }
+}
+
+/// Makes an `id` unique by appending a number if it is already taken.
+fn unique_id(base: EcoString, taken: &mut HashSet<EcoString>) -> EcoString {
+    let mut id = base.clone();
+    let mut n = 1;
+    while taken.contains(&id) {
+        n += 1;
+        id = eco_format!("{base}-{n}");
+    }
+    taken.insert(id.clone());
+    id
+}
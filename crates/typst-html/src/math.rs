@@ -0,0 +1,759 @@
+//! Conversion of equations to MathML Core.
+
+use ecow::{eco_format, EcoString};
+use typst_library::diag::SourceResult;
+use typst_library::engine::Engine;
+use typst_library::foundations::{
+    Content, Packed, Smart, StyleChain, SymbolElem, Target, TargetElem,
+};
+use typst_library::html::{
+    attr, tag, HtmlElem, HtmlElement, HtmlFrame, HtmlNode, HtmlTag,
+};
+use typst_library::introspection::{SplitLocator, TagElem};
+use typst_library::layout::{
+    Abs, Axes, Em, FixedAlignment, HElem, Length, Region, Size, Spacing,
+};
+use typst_library::math::*;
+use typst_library::routines::{Arenas, RealizationKind};
+use typst_library::text::{LinebreakElem, SpaceElem, TextElem};
+use typst_syntax::Span;
+use typst_utils::{default_math_class, round_with_precision, Numeric};
+use unicode_math_class::MathClass;
+
+use crate::html_fragment;
+
+/// The width of a space next to text in an equation.
+const SPACE: &str = "0.25em";
+
+/// Produce the MathML children of a `<math>` element from an equation's body.
+///
+/// Multi-line equations and equations with alignment points become a table
+/// whose columns are alternately right- and left-aligned, just like in paged
+/// export.
+pub fn handle_math(
+    engine: &mut Engine,
+    body: &Content,
+    locator: &mut SplitLocator,
+    styles: StyleChain,
+) -> SourceResult<Vec<HtmlNode>> {
+    let mut rows = handle_grid(engine, locator, body, styles)?;
+    if rows.len() == 1 && rows[0].len() == 1 {
+        return Ok(rows.pop().unwrap().pop().unwrap());
+    }
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let align: EcoString = if columns > 1 {
+        (0..columns)
+            .map(|i| if i % 2 == 0 { "right" } else { "left" })
+            .collect::<Vec<_>>()
+            .join(" ")
+            .into()
+    } else {
+        "center".into()
+    };
+
+    let mut table = table(rows, align);
+    if EquationElem::size_in(styles) == MathSize::Display {
+        table = table.with_attr(attr::displaystyle, "true");
+    }
+
+    Ok(vec![table.spanned(body.span()).into()])
+}
+
+/// Convert math content into MathML nodes, ignoring linebreaks and alignment
+/// points.
+fn handle_list(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    content: &Content,
+    styles: StyleChain,
+) -> SourceResult<Vec<HtmlNode>> {
+    let rows = handle_grid(engine, locator, content, styles)?;
+    Ok(rows.into_iter().flatten().flatten().collect())
+}
+
+/// Convert math content into MathML nodes, split into rows at linebreaks and
+/// into cells at alignment points.
+fn handle_grid(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    content: &Content,
+    styles: StyleChain,
+) -> SourceResult<Vec<Vec<Vec<HtmlNode>>>> {
+    let arenas = Arenas::default();
+    let children = (engine.routines.realize)(
+        RealizationKind::Math,
+        engine,
+        locator,
+        &arenas,
+        content,
+        styles,
+    )?;
+
+    let mut rows = vec![vec![vec![]]];
+    let mut space = false;
+    for &(child, styles) in &children {
+        if child.is::<LinebreakElem>() {
+            rows.push(vec![vec![]]);
+            space = false;
+            continue;
+        } else if child.is::<AlignPointElem>() {
+            rows.last_mut().unwrap().push(vec![]);
+            space = false;
+            continue;
+        } else if child.is::<SpaceElem>() {
+            space = true;
+            continue;
+        }
+
+        let mut output = vec![];
+        handle(engine, locator, child, styles, &mut output)?;
+
+        let cell = rows.last_mut().unwrap().last_mut().unwrap();
+        if output.iter().any(|node| !matches!(node, HtmlNode::Tag(_))) {
+            // Like in paged export, spaces are only kept next to text.
+            if std::mem::take(&mut space)
+                && (last_element(cell).is_some_and(is_text)
+                    || output.iter().find_map(as_element).is_some_and(is_text))
+            {
+                cell.push(
+                    HtmlElement::new(tag::mspace).with_attr(attr::width, SPACE).into(),
+                );
+            }
+        }
+        cell.extend(output);
+    }
+
+    Ok(rows)
+}
+
+/// Convert a realized math element into MathML node(s).
+fn handle(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    child: &Content,
+    styles: StyleChain,
+    output: &mut Vec<HtmlNode>,
+) -> SourceResult<()> {
+    let span = child.span();
+    if let Some(elem) = child.to_packed::<TagElem>() {
+        output.push(HtmlNode::Tag(elem.tag.clone()));
+    } else if let Some(elem) = child.to_packed::<SymbolElem>() {
+        output.push(handle_symbol(elem.text, styles).spanned(span).into());
+    } else if let Some(elem) = child.to_packed::<TextElem>() {
+        output.push(handle_text(&elem.text, styles).spanned(span).into());
+    } else if let Some(elem) = child.to_packed::<HElem>() {
+        // MathML has no negative spacing.
+        if let Spacing::Rel(rel) = elem.amount {
+            let amount = rel.abs;
+            if rel.rel.is_zero()
+                && !amount.is_zero()
+                && amount.abs >= Abs::zero()
+                && amount.em >= Em::zero()
+            {
+                output.push(
+                    HtmlElement::new(tag::mspace)
+                        .with_attr(attr::width, length(amount))
+                        .spanned(span)
+                        .into(),
+                );
+            }
+        }
+    } else if let Some(elem) = child.to_packed::<ClassElem>() {
+        let style = EquationElem::set_class(Some(elem.class)).wrap();
+        output.extend(handle_list(engine, locator, &elem.body, styles.chain(&style))?);
+    } else if let Some(elem) = child.to_packed::<AccentElem>() {
+        let base = handle_list(engine, locator, &elem.base, styles)?;
+        let accent = HtmlElement::new(tag::mo)
+            .with_children(vec![HtmlNode::text(spacing_accent(elem.accent.0), span)]);
+        output.push(
+            HtmlElement::new(tag::mover)
+                .with_attr(attr::accent, "true")
+                .with_children(vec![row(base), accent.into()])
+                .spanned(span)
+                .into(),
+        );
+    } else if let Some(elem) = child.to_packed::<AttachElem>() {
+        output.push(handle_attach(engine, locator, elem, styles)?);
+    } else if let Some(elem) = child.to_packed::<PrimesElem>() {
+        let primes: EcoString = match elem.count {
+            1 => "′".into(),
+            2 => "″".into(),
+            3 => "‴".into(),
+            4 => "⁗".into(),
+            count => "′".repeat(count).into(),
+        };
+        output.push(
+            HtmlElement::new(tag::mo)
+                .with_children(vec![HtmlNode::Text(primes, span)])
+                .spanned(span)
+                .into(),
+        );
+    } else if let Some(elem) = child.to_packed::<ScriptsElem>() {
+        output.extend(handle_list(engine, locator, &elem.body, styles)?);
+    } else if let Some(elem) = child.to_packed::<LimitsElem>() {
+        output.extend(handle_list(engine, locator, &elem.body, styles)?);
+    } else if let Some(elem) = child.to_packed::<FracElem>() {
+        let num = handle_list(engine, locator, &elem.num, styles)?;
+        let denom = handle_list(engine, locator, &elem.denom, styles)?;
+        output.push(
+            HtmlElement::new(tag::mfrac)
+                .with_children(vec![row(num), row(denom)])
+                .spanned(span)
+                .into(),
+        );
+    } else if let Some(elem) = child.to_packed::<BinomElem>() {
+        let upper = handle_list(engine, locator, &elem.upper, styles)?;
+        let mut lower = vec![];
+        for (i, content) in elem.lower.iter().enumerate() {
+            if i > 0 {
+                lower.push(handle_symbol(',', styles).into());
+            }
+            lower.extend(handle_list(engine, locator, content, styles)?);
+        }
+        let frac = HtmlElement::new(tag::mfrac)
+            .with_attr(attr::linethickness, "0")
+            .with_children(vec![row(upper), row(lower)]);
+        output.push(fenced(frac.into(), Some('('), Some(')'), span).into());
+    } else if let Some(elem) = child.to_packed::<LrElem>() {
+        output.push(handle_lr(engine, locator, elem, styles)?.into());
+    } else if let Some(elem) = child.to_packed::<MidElem>() {
+        let mut nodes = handle_list(engine, locator, &elem.body, styles)?;
+        nodes.iter_mut().for_each(make_stretchy);
+        output.extend(nodes);
+    } else if let Some(elem) = child.to_packed::<VecElem>() {
+        let mut rows = vec![];
+        for content in &elem.children {
+            rows.push(vec![handle_list(engine, locator, content, styles)?]);
+        }
+        let table = table(rows, column_align(elem.align(styles)).into());
+        let delim = elem.delim(styles);
+        output.push(fenced(table.into(), delim.open(), delim.close(), span).into());
+    } else if let Some(elem) = child.to_packed::<MatElem>() {
+        let mut rows = vec![];
+        for cells in &elem.rows {
+            let mut row = vec![];
+            for content in cells {
+                row.push(handle_list(engine, locator, content, styles)?);
+            }
+            rows.push(row);
+        }
+        let table = table(rows, column_align(elem.align(styles)).into());
+        let delim = elem.delim(styles);
+        output.push(fenced(table.into(), delim.open(), delim.close(), span).into());
+    } else if let Some(elem) = child.to_packed::<CasesElem>() {
+        let mut rows = vec![];
+        for content in &elem.children {
+            let grid = handle_grid(engine, locator, content, styles)?;
+            rows.push(grid.into_iter().flatten().collect());
+        }
+        let table = table(rows, "left".into());
+        let delim = elem.delim(styles);
+        let (open, close) = if elem.reverse(styles) {
+            (None, delim.close())
+        } else {
+            (delim.open(), None)
+        };
+        output.push(fenced(table.into(), open, close, span).into());
+    } else if let Some(elem) = child.to_packed::<OpElem>() {
+        let mut nodes = handle_list(engine, locator, &elem.text, styles)?;
+        // Operator names like `lim` are identifiers that are displayed
+        // upright.
+        if let [HtmlNode::Element(element)] = nodes.as_mut_slice() {
+            if element.tag == tag::mtext {
+                element.tag = tag::mi;
+            }
+        }
+        output.push(row(nodes));
+    } else if let Some(elem) = child.to_packed::<RootElem>() {
+        let radicand = handle_list(engine, locator, &elem.radicand, styles)?;
+        let element = match elem.index(styles) {
+            Some(index) => {
+                let index = handle_list(engine, locator, &index, styles)?;
+                HtmlElement::new(tag::mroot)
+                    .with_children(vec![row(radicand), row(index)])
+            }
+            None => HtmlElement::new(tag::msqrt).with_children(radicand),
+        };
+        output.push(element.spanned(span).into());
+    } else if let Some(elem) = child.to_packed::<StretchElem>() {
+        let mut nodes = handle_list(engine, locator, &elem.body, styles)?;
+        nodes.iter_mut().for_each(make_stretchy);
+        output.extend(nodes);
+    } else if let Some(elem) = child.to_packed::<UnderlineElem>() {
+        output.push(under(engine, locator, &elem.body, '_', None, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<OverlineElem>() {
+        output.push(over(engine, locator, &elem.body, '‾', None, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<UnderbraceElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(under(engine, locator, &elem.body, '⏟', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<OverbraceElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(over(engine, locator, &elem.body, '⏞', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<UnderbracketElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(under(engine, locator, &elem.body, '⎵', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<OverbracketElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(over(engine, locator, &elem.body, '⎴', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<UnderparenElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(under(engine, locator, &elem.body, '⏝', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<OverparenElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(over(engine, locator, &elem.body, '⏜', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<UndershellElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(under(engine, locator, &elem.body, '⏡', annotation, styles, span)?);
+    } else if let Some(elem) = child.to_packed::<OvershellElem>() {
+        let annotation = elem.annotation(styles);
+        output.push(over(engine, locator, &elem.body, '⏠', annotation, styles, span)?);
+    } else if child.is::<CancelElem>() {
+        // MathML Core cannot express cancellation, so we embed the laid out
+        // element instead.
+        let style = TargetElem::set_target(Target::Paged).wrap();
+        let frame = (engine.routines.layout_frame)(
+            engine,
+            child,
+            locator.next(&span),
+            styles.chain(&style),
+            Region::new(Size::splat(Abs::inf()), Axes::splat(false)),
+        )?;
+        output.push(
+            HtmlElement::new(tag::mtext)
+                .with_children(vec![HtmlNode::Frame(HtmlFrame::new(frame))])
+                .spanned(span)
+                .into(),
+        );
+    } else if let Some(elem) = child.to_packed::<HtmlElem>() {
+        // Allows to write MathML by hand.
+        let mut children = vec![];
+        if let Some(body) = elem.body(styles) {
+            children = handle_list(engine, locator, body, styles)?;
+        }
+        output.push(
+            HtmlElement {
+                tag: elem.tag,
+                attrs: elem.attrs(styles).clone(),
+                children,
+                span,
+            }
+            .into(),
+        );
+    } else {
+        // Any other content (e.g. a box) is embedded as HTML, which is
+        // allowed in MathML token elements.
+        let children = html_fragment(engine, child, locator.next(&span), styles)?;
+        output.push(
+            HtmlElement::new(tag::mtext)
+                .with_children(children)
+                .spanned(span)
+                .into(),
+        );
+    }
+    Ok(())
+}
+
+/// Convert a single character into a token element.
+fn handle_symbol(c: char, styles: StyleChain) -> HtmlElement {
+    let class = EquationElem::class_in(styles)
+        .or_else(|| default_math_class(c))
+        .unwrap_or(MathClass::Normal);
+
+    if c.is_ascii_digit() {
+        return token(tag::mn, styled_char(styles, c, false));
+    }
+
+    match class {
+        MathClass::Normal | MathClass::Alphabetic | MathClass::Special => {
+            let variant = EquationElem::variant_in(styles);
+            let bold = EquationElem::bold_in(styles);
+            let italic = EquationElem::italic_in(styles);
+
+            // In the default style, we leave the italicization of single
+            // letters to the browser, so that the plain letter remains
+            // searchable.
+            if variant == MathVariant::Serif && !bold && italic == Smart::Auto {
+                return token(tag::mi, styled_char(styles, c, false));
+            }
+
+            let styled = styled_char(styles, c, true);
+            let mut element = token(tag::mi, styled);
+            if styled == c && c.is_alphabetic() {
+                element = element.with_attr(attr::mathvariant, "normal");
+            }
+            element
+        }
+        MathClass::Opening | MathClass::Closing | MathClass::Fence => {
+            // Outside of `lr`, delimiters do not grow in paged export either.
+            token(tag::mo, styled_char(styles, c, false))
+                .with_attr(attr::stretchy, "false")
+        }
+        _ => token(tag::mo, styled_char(styles, c, false)),
+    }
+}
+
+/// Convert a piece of text into a token element.
+fn handle_text(text: &str, styles: StyleChain) -> HtmlElement {
+    let styled: EcoString = text.chars().map(|c| styled_char(styles, c, false)).collect();
+    let tag = if text.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        tag::mn
+    } else {
+        tag::mtext
+    };
+    HtmlElement::new(tag).with_children(vec![HtmlNode::text(styled, Span::detached())])
+}
+
+/// Convert an [`AttachElem`] into scripts and limits.
+fn handle_attach(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    elem: &Packed<AttachElem>,
+    styles: StyleChain,
+) -> SourceResult<HtmlNode> {
+    let merged = elem.merge_base();
+    let elem = merged.as_ref().unwrap_or(elem);
+    let span = elem.span();
+
+    let kind = Limits::for_content(&elem.base, styles);
+    let limits = kind.active(styles);
+    let tr = elem.tr(styles);
+    let primed = tr.as_ref().is_some_and(|content| content.is::<PrimesElem>());
+
+    // Mirror how paged export distributes the attachments.
+    let (t, tr) = match (elem.t(styles), tr) {
+        (Some(t), Some(tr)) if primed && !limits => (None, Some(tr + t)),
+        (Some(t), None) if !limits => (None, Some(t)),
+        (t, tr) => (t, tr),
+    };
+    let (b, br) = match (elem.b(styles), elem.br(styles)) {
+        (b, br) if limits || br.is_some() => (b, br),
+        (b, _) => (None, b),
+    };
+
+    let mut base = row(handle_list(engine, locator, &elem.base, styles)?);
+    let mut convert = |content: Option<Content>| {
+        content
+            .map(|content| handle_list(engine, locator, &content, styles).map(row))
+            .transpose()
+    };
+
+    let (t, b) = (convert(t)?, convert(b)?);
+    let (tl, bl) = (convert(elem.tl(styles))?, convert(elem.bl(styles))?);
+    let (tr, br) = (convert(tr)?, convert(br)?);
+
+    if kind == Limits::Always && limits {
+        if let HtmlNode::Element(element) = &mut base {
+            if element.tag == tag::mo {
+                element.attrs.push(attr::movablelimits, "false");
+            }
+        }
+    }
+
+    base = match (t, b) {
+        (Some(t), Some(b)) => HtmlElement::new(tag::munderover)
+            .with_children(vec![base, b, t])
+            .into(),
+        (Some(t), None) => {
+            HtmlElement::new(tag::mover).with_children(vec![base, t]).into()
+        }
+        (None, Some(b)) => {
+            HtmlElement::new(tag::munder).with_children(vec![base, b]).into()
+        }
+        (None, None) => base,
+    };
+
+    let element = if tl.is_some() || bl.is_some() {
+        let empty = || HtmlNode::from(HtmlElement::new(tag::mrow));
+        HtmlElement::new(tag::mmultiscripts).with_children(vec![
+            base,
+            br.unwrap_or_else(empty),
+            tr.unwrap_or_else(empty),
+            HtmlElement::new(tag::mprescripts).into(),
+            bl.unwrap_or_else(empty),
+            tl.unwrap_or_else(empty),
+        ])
+    } else {
+        match (tr, br) {
+            (Some(tr), Some(br)) => {
+                HtmlElement::new(tag::msubsup).with_children(vec![base, br, tr])
+            }
+            (Some(tr), None) => HtmlElement::new(tag::msup).with_children(vec![base, tr]),
+            (None, Some(br)) => HtmlElement::new(tag::msub).with_children(vec![base, br]),
+            (None, None) => return Ok(base),
+        }
+    };
+
+    Ok(element.spanned(span).into())
+}
+
+/// Convert an [`LrElem`] into a row whose outer delimiters stretch.
+fn handle_lr(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    elem: &Packed<LrElem>,
+    styles: StyleChain,
+) -> SourceResult<HtmlElement> {
+    // Extract from an EquationElem.
+    let mut body = &elem.body;
+    if let Some(equation) = body.to_packed::<EquationElem>() {
+        body = &equation.body;
+    }
+
+    // Extract implicit LrElem.
+    if let Some(lr) = body.to_packed::<LrElem>() {
+        if lr.size(styles).is_one() {
+            body = &lr.body;
+        }
+    }
+
+    let mut nodes = handle_list(engine, locator, body, styles)?;
+    let mut elements = nodes.iter_mut().filter(|node| as_element(node).is_some());
+    if let Some(first) = elements.next() {
+        make_stretchy(first);
+    }
+    if let Some(last) = elements.last() {
+        make_stretchy(last);
+    }
+
+    Ok(HtmlElement::new(tag::mrow).with_children(nodes).spanned(elem.span()))
+}
+
+/// Place a stretchy character below content, with an optional annotation
+/// below that.
+fn under(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    body: &Content,
+    c: char,
+    annotation: Option<Content>,
+    styles: StyleChain,
+    span: Span,
+) -> SourceResult<HtmlNode> {
+    let body = handle_list(engine, locator, body, styles)?;
+    let mut element = HtmlElement::new(tag::munder)
+        .with_attr(attr::accentunder, "true")
+        .with_children(vec![row(body), stretchy_token(c)]);
+    if let Some(annotation) = annotation {
+        let annotation = handle_list(engine, locator, &annotation, styles)?;
+        element = HtmlElement::new(tag::munder)
+            .with_children(vec![element.into(), row(annotation)]);
+    }
+    Ok(element.spanned(span).into())
+}
+
+/// Place a stretchy character above content, with an optional annotation
+/// above that.
+fn over(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    body: &Content,
+    c: char,
+    annotation: Option<Content>,
+    styles: StyleChain,
+    span: Span,
+) -> SourceResult<HtmlNode> {
+    let body = handle_list(engine, locator, body, styles)?;
+    let mut element = HtmlElement::new(tag::mover)
+        .with_attr(attr::accent, "true")
+        .with_children(vec![row(body), stretchy_token(c)]);
+    if let Some(annotation) = annotation {
+        let annotation = handle_list(engine, locator, &annotation, styles)?;
+        element = HtmlElement::new(tag::mover)
+            .with_children(vec![element.into(), row(annotation)]);
+    }
+    Ok(element.spanned(span).into())
+}
+
+/// Where limits are placed by default, mirroring paged export.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Limits {
+    /// Always scripts.
+    Never,
+    /// Display limits only in `display` math.
+    Display,
+    /// Always limits.
+    Always,
+}
+
+impl Limits {
+    /// The limit configuration for the base of an attachment.
+    fn for_content(base: &Content, styles: StyleChain) -> Self {
+        if let Some(elem) = base.to_packed::<EquationElem>() {
+            Self::for_content(&elem.body, styles)
+        } else if let Some(elem) = base.to_packed::<LimitsElem>() {
+            if elem.inline(styles) {
+                Self::Always
+            } else {
+                Self::Display
+            }
+        } else if base.is::<ScriptsElem>() {
+            Self::Never
+        } else if let Some(elem) = base.to_packed::<OpElem>() {
+            if elem.limits(styles) {
+                Self::Display
+            } else {
+                Self::Never
+            }
+        } else if let Some(elem) = base.to_packed::<ClassElem>() {
+            Self::for_class(elem.class)
+        } else if let Some(elem) = base.to_packed::<SymbolElem>() {
+            match EquationElem::class_in(styles) {
+                Some(class) => Self::for_class(class),
+                None if is_integral_char(elem.text) => Self::Never,
+                None => {
+                    default_math_class(elem.text).map_or(Self::Never, Self::for_class)
+                }
+            }
+        } else {
+            Self::Never
+        }
+    }
+
+    /// The default limit configuration for a math class.
+    fn for_class(class: MathClass) -> Self {
+        match class {
+            MathClass::Large => Self::Display,
+            MathClass::Relation => Self::Always,
+            _ => Self::Never,
+        }
+    }
+
+    /// Whether limits should be displayed in this context.
+    fn active(self, styles: StyleChain) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Display => EquationElem::size_in(styles) == MathSize::Display,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Determines if the character is one of a variety of integral signs.
+fn is_integral_char(c: char) -> bool {
+    ('∫'..='∳').contains(&c) || ('⨋'..='⨜').contains(&c)
+}
+
+/// Build a table from rows of cells.
+fn table(rows: Vec<Vec<Vec<HtmlNode>>>, align: EcoString) -> HtmlElement {
+    let rows = rows
+        .into_iter()
+        .map(|cells| {
+            let cells = cells
+                .into_iter()
+                .map(|cell| HtmlElement::new(tag::mtd).with_children(cell).into())
+                .collect();
+            HtmlElement::new(tag::mtr).with_children(cells).into()
+        })
+        .collect();
+    HtmlElement::new(tag::mtable)
+        .with_attr(attr::columnalign, align)
+        .with_children(rows)
+}
+
+/// Surround a node with stretchy delimiters.
+fn fenced(
+    node: HtmlNode,
+    open: Option<char>,
+    close: Option<char>,
+    span: Span,
+) -> HtmlElement {
+    let mut children = vec![];
+    children.extend(open.map(stretchy_token));
+    children.push(node);
+    children.extend(close.map(stretchy_token));
+    HtmlElement::new(tag::mrow).with_children(children).spanned(span)
+}
+
+/// Create an operator that stretches to the size of its surroundings.
+fn stretchy_token(c: char) -> HtmlNode {
+    token(tag::mo, c).with_attr(attr::stretchy, "true").into()
+}
+
+/// Create a token element containing a single character.
+fn token(tag: HtmlTag, c: char) -> HtmlElement {
+    HtmlElement::new(tag).with_children(vec![HtmlNode::text(c, Span::detached())])
+}
+
+/// Make an operator stretch to the size of its surroundings.
+fn make_stretchy(node: &mut HtmlNode) {
+    if let HtmlNode::Element(element) = node {
+        if element.tag == tag::mo {
+            element.attrs.0.retain(|(key, _)| *key != attr::stretchy);
+            element.attrs.push(attr::stretchy, "true");
+        }
+    }
+}
+
+/// Wrap nodes into a single `<mrow>` unless they already form a single
+/// element.
+fn row(mut nodes: Vec<HtmlNode>) -> HtmlNode {
+    if let [HtmlNode::Element(_)] = nodes.as_slice() {
+        return nodes.pop().unwrap();
+    }
+    HtmlElement::new(tag::mrow).with_children(nodes).into()
+}
+
+/// The last element among some nodes.
+fn last_element(nodes: &[HtmlNode]) -> Option<&HtmlElement> {
+    nodes.iter().rev().find_map(as_element)
+}
+
+/// The element in a node, if it is one.
+fn as_element(node: &HtmlNode) -> Option<&HtmlElement> {
+    match node {
+        HtmlNode::Element(element) => Some(element),
+        _ => None,
+    }
+}
+
+/// Whether the element is text, which is spaced in paged export.
+fn is_text(element: &HtmlElement) -> bool {
+    element.tag == tag::mtext
+}
+
+/// Map the horizontal alignment of a table to a `columnalign` value.
+fn column_align(align: FixedAlignment) -> &'static str {
+    match align {
+        FixedAlignment::Start => "left",
+        FixedAlignment::Center => "center",
+        FixedAlignment::End => "right",
+    }
+}
+
+/// Format a length as a CSS length.
+fn length(length: Length) -> EcoString {
+    let em = round_with_precision(length.em.get(), 4);
+    let pt = round_with_precision(length.abs.to_pt(), 4);
+    if pt == 0.0 {
+        eco_format!("{em}em")
+    } else if em == 0.0 {
+        eco_format!("{pt}pt")
+    } else {
+        eco_format!("calc({em}em + {pt}pt)")
+    }
+}
+
+/// The non-combining version of an accent, as it is used in MathML.
+fn spacing_accent(c: char) -> char {
+    match c {
+        '\u{0300}' => '`',
+        '\u{0301}' => '´',
+        '\u{0302}' => '^',
+        '\u{0303}' => '~',
+        '\u{0304}' => '¯',
+        '\u{0305}' => '‾',
+        '\u{0306}' => '˘',
+        '\u{0307}' => '˙',
+        '\u{0308}' => '¨',
+        '\u{030a}' => '˚',
+        '\u{030b}' => '˝',
+        '\u{030c}' => 'ˇ',
+        '\u{20d6}' => '←',
+        '\u{20d7}' => '→',
+        '\u{20e1}' => '↔',
+        '\u{20d0}' => '↼',
+        '\u{20d1}' => '⇀',
+        c => c,
+    }
+}
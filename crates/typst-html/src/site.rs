@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+
+use ecow::{eco_format, EcoString};
+use typst_library::diag::{bail, SourceResult};
+use typst_library::foundations::{Content, StyleChain};
+use typst_library::html::{
+    attr, generated_element, tag, HtmlDocument, HtmlElement, HtmlNode, SplitElem,
+};
+use typst_library::introspection::Tag;
+use typst_library::model::HeadingElem;
+use typst_syntax::Span;
+
+use crate::encode::encode_root;
+
+/// Configures how an HTML document is split into multiple files.
+#[derive(Debug, Default, Clone)]
+pub struct SiteOptions {
+    /// Top-level headings up to this level start a new file. If `None`, only
+    /// [`SplitElem`]s start a new file.
+    pub split_level: Option<NonZeroUsize>,
+}
+
+/// A single file of a multi-file HTML site.
+#[derive(Debug, Clone)]
+pub struct HtmlFile {
+    /// The file's path, relative to the site's root directory.
+    pub path: EcoString,
+    /// The encoded HTML.
+    pub html: String,
+}
+
+/// Encodes an HTML document into multiple files.
+///
+/// The document's body is split at explicit splits and, if configured, at
+/// headings. Each file receives the document's `<head>` along with a table of
+/// contents and links to the previous and next file. Links to elements in
+/// other files are rewritten to point into the right file. The first file is
+/// always `index.html`.
+pub fn html_site(
+    document: &HtmlDocument,
+    options: &SiteOptions,
+) -> SourceResult<Vec<HtmlFile>> {
+    let Site { head, body, mut pages } = split(document, options, "html", &[])?;
+
+    let mut output = Vec::with_capacity(pages.len());
+    for i in 0..pages.len() {
+        let mut children = vec![];
+        if pages.len() > 1 {
+            children.push(table_of_contents(&pages, i).into());
+        }
+        children.push(
+            HtmlElement::new(tag::main)
+                .with_children(std::mem::take(&mut pages[i].nodes))
+                .into(),
+        );
+        if pages.len() > 1 {
+            children.push(pagination(&pages, i).into());
+        }
+
+        let root = page_root(document, &head, body, &pages[i].title, children);
+        output.push(HtmlFile {
+            path: pages[i].path.clone(),
+            html: encode_root(&root)?,
+        });
+    }
+
+    Ok(output)
+}
+
+/// A document split into pages.
+pub(crate) struct Site<'a> {
+    /// The document's `<head>`.
+    pub head: HtmlElement,
+    /// The document's `<body>`, whose children were distributed to the pages.
+    pub body: &'a HtmlElement,
+    /// The pages, with links between them already resolved.
+    pub pages: Vec<Page>,
+}
+
+/// Split a document's body into pages, whose paths end with the given
+/// extension and avoid the reserved paths.
+pub(crate) fn split<'a>(
+    document: &'a HtmlDocument,
+    options: &SiteOptions,
+    extension: &str,
+    reserved: &[&str],
+) -> SourceResult<Site<'a>> {
+    let (head, body) = head_and_body(&document.root)?;
+    let mut pages = paginate(&body.children, options, document, extension, reserved);
+
+    let mut files = HashMap::new();
+    for (i, page) in pages.iter().enumerate() {
+        collect_ids(&page.nodes, i, &mut files);
+    }
+    let paths: Vec<EcoString> = pages.iter().map(|page| page.path.clone()).collect();
+    for (i, page) in pages.iter_mut().enumerate() {
+        resolve_links(&mut page.nodes, i, &files, &paths);
+    }
+
+    Ok(Site { head, body, pages })
+}
+
+/// Produce the root element of a page with the given body children.
+pub(crate) fn page_root(
+    document: &HtmlDocument,
+    head: &HtmlElement,
+    body: &HtmlElement,
+    title: &EcoString,
+    children: Vec<HtmlNode>,
+) -> HtmlElement {
+    let mut page_body = HtmlElement::new(tag::body).with_children(children);
+    page_body.attrs = body.attrs.clone();
+    page_body.span = body.span;
+
+    let mut root = HtmlElement::new(tag::html)
+        .with_children(vec![page_head(head, title).into(), page_body.into()]);
+    root.attrs = document.root.attrs.clone();
+    root.span = document.root.span;
+    root
+}
+
+/// One file of the site before encoding.
+pub(crate) struct Page {
+    /// The file's path.
+    pub path: EcoString,
+    /// The title shown in the navigation and the `<title>` element.
+    pub title: EcoString,
+    /// The body nodes of the file.
+    pub nodes: Vec<HtmlNode>,
+}
+
+/// Find the `<head>` and `<body>` of the root element.
+fn head_and_body(root: &HtmlElement) -> SourceResult<(HtmlElement, &HtmlElement)> {
+    let find = |target| {
+        root.children.iter().find_map(|node| match node {
+            HtmlNode::Element(elem) if elem.tag == target => Some(elem),
+            _ => None,
+        })
+    };
+
+    let Some(body) = find(tag::body) else {
+        bail!(root.span, "cannot split a document without a `<body>` element");
+    };
+
+    let head = find(tag::head)
+        .cloned()
+        .unwrap_or_else(|| HtmlElement::new(tag::head));
+    Ok((head, body))
+}
+
+/// Split the body's nodes into pages.
+fn paginate(
+    nodes: &[HtmlNode],
+    options: &SiteOptions,
+    document: &HtmlDocument,
+    extension: &str,
+    reserved: &[&str],
+) -> Vec<Page> {
+    let mut chunks = vec![];
+    let mut current = vec![];
+    for node in nodes {
+        if let HtmlNode::Tag(Tag::Start(elem)) = node {
+            if starts_page(elem, options)
+                && current.iter().any(|node| !matches!(node, HtmlNode::Tag(_)))
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(node.clone());
+    }
+    chunks.push(current);
+
+    let mut taken: HashSet<EcoString> =
+        reserved.iter().map(|&path| path.into()).collect();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, nodes)| {
+            let heading = first_heading(&nodes);
+            let stem = if i == 0 {
+                "index".into()
+            } else if let Some((_, Some(id))) = &heading {
+                id.clone()
+            } else {
+                eco_format!("page-{}", i + 1)
+            };
+
+            let title = match heading {
+                Some((title, _)) if !title.is_empty() => title,
+                _ => match &document.info.title {
+                    Some(title) if i == 0 => title.clone(),
+                    _ => eco_format!("Page {}", i + 1),
+                },
+            };
+
+            Page {
+                path: unique_path(stem, extension, &mut taken),
+                title,
+                nodes,
+            }
+        })
+        .collect()
+}
+
+/// Whether the given element starts a new page.
+fn starts_page(elem: &Content, options: &SiteOptions) -> bool {
+    if elem.is::<SplitElem>() {
+        return true;
+    }
+
+    match (elem.to_packed::<HeadingElem>(), options.split_level) {
+        (Some(heading), Some(level)) => {
+            heading.resolve_level(StyleChain::default()) <= level
+        }
+        _ => false,
+    }
+}
+
+/// Find the text and `id` of the first top-level heading.
+fn first_heading(nodes: &[HtmlNode]) -> Option<(EcoString, Option<EcoString>)> {
+    nodes.iter().enumerate().find_map(|(i, node)| {
+        let HtmlNode::Tag(Tag::Start(elem)) = node else { return None };
+        let heading = elem.to_packed::<HeadingElem>()?;
+        let id = generated_element(nodes, i).and_then(|k| match &nodes[k] {
+            HtmlNode::Element(generated) => generated.attrs.get(attr::id).cloned(),
+            _ => None,
+        });
+        Some((heading.body.plain_text(), id))
+    })
+}
+
+/// Turn a file stem into a unique path.
+fn unique_path(
+    stem: EcoString,
+    extension: &str,
+    taken: &mut HashSet<EcoString>,
+) -> EcoString {
+    let mut path = eco_format!("{stem}.{extension}");
+    let mut n = 1;
+    while taken.contains(&path) {
+        n += 1;
+        path = eco_format!("{stem}-{n}.{extension}");
+    }
+    taken.insert(path.clone());
+    path
+}
+
+/// Record in which page each `id` is defined.
+fn collect_ids(nodes: &[HtmlNode], page: usize, files: &mut HashMap<EcoString, usize>) {
+    for node in nodes {
+        if let HtmlNode::Element(elem) = node {
+            if let Some(id) = elem.attrs.get(attr::id) {
+                files.insert(id.clone(), page);
+            }
+            collect_ids(&elem.children, page, files);
+        }
+    }
+}
+
+/// Rewrite fragment links that point into other pages.
+fn resolve_links(
+    nodes: &mut [HtmlNode],
+    page: usize,
+    files: &HashMap<EcoString, usize>,
+    paths: &[EcoString],
+) {
+    for node in nodes {
+        let HtmlNode::Element(elem) = node else { continue };
+        for (key, value) in elem.attrs.0.make_mut() {
+            if *key != attr::href {
+                continue;
+            }
+            let Some(id) = value.strip_prefix('#').map(EcoString::from) else {
+                continue;
+            };
+            if let Some(&target) = files.get(&id) {
+                if target != page {
+                    *value = eco_format!("{}#{id}", paths[target]);
+                }
+            }
+        }
+        resolve_links(&mut elem.children, page, files, paths);
+    }
+}
+
+/// Produce the `<head>` of a page.
+fn page_head(head: &HtmlElement, title: &EcoString) -> HtmlElement {
+    let mut head = head.clone();
+    let title = HtmlElement::new(tag::title)
+        .with_children(vec![HtmlNode::text(title.clone(), Span::detached())]);
+    match head
+        .children
+        .iter_mut()
+        .find(|node| matches!(node, HtmlNode::Element(elem) if elem.tag == tag::title))
+    {
+        Some(node) => *node = title.into(),
+        None => head.children.push(title.into()),
+    }
+    head
+}
+
+/// Produce a table of contents linking to all pages.
+fn table_of_contents(pages: &[Page], current: usize) -> HtmlElement {
+    let items = pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let mut link = page_link(page);
+            if i == current {
+                link.attrs.push(attr::aria_current, "page");
+            }
+            HtmlElement::new(tag::li).with_children(vec![link.into()]).into()
+        })
+        .collect();
+
+    HtmlElement::new(tag::nav)
+        .with_attr(attr::role, "doc-toc")
+        .with_children(vec![HtmlElement::new(tag::ol).with_children(items).into()])
+}
+
+/// Produce links to the previous and next page.
+fn pagination(pages: &[Page], current: usize) -> HtmlElement {
+    let mut children = vec![];
+    if let Some(prev) = current.checked_sub(1).map(|i| &pages[i]) {
+        children.push(page_link(prev).with_attr(attr::rel, "prev").into());
+    }
+    if let Some(next) = pages.get(current + 1) {
+        children.push(page_link(next).with_attr(attr::rel, "next").into());
+    }
+    HtmlElement::new(tag::nav).with_children(children)
+}
+
+/// Produce a link to a page.
+fn page_link(page: &Page) -> HtmlElement {
+    HtmlElement::new(tag::a)
+        .with_attr(attr::href, page.path.clone())
+        .with_children(vec![HtmlNode::text(page.title.clone(), Span::detached())])
+}
@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// A range of a source file that can be folded.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FoldingRange {
+    /// The byte range that can be folded.
+    pub range: Range<usize>,
+    /// What is folded.
+    pub kind: FoldingKind,
+}
+
+/// A kind of folding range.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FoldingKind {
+    /// A heading's section.
+    Section,
+    /// A code or content block.
+    Block,
+    /// A raw block.
+    Raw,
+    /// A block comment or a run of line comments.
+    Comment,
+}
+
+/// Find the ranges of a source file that can be folded.
+///
+/// Only ranges that span multiple lines are included. They are ordered by
+/// their start.
+pub fn folding_ranges(source: &Source) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+    collect_ranges(&LinkedNode::new(source.root()), &mut ranges);
+    ranges.retain(|folding| source.text()[folding.range.clone()].contains('\n'));
+    ranges.sort_by_key(|folding| folding.range.start);
+    ranges
+}
+
+/// Collect the folding ranges in a node and its descendants.
+fn collect_ranges(node: &LinkedNode, ranges: &mut Vec<FoldingRange>) {
+    let kind = match node.kind() {
+        SyntaxKind::CodeBlock | SyntaxKind::ContentBlock => Some(FoldingKind::Block),
+        SyntaxKind::Raw if node.cast::<ast::Raw>().is_some_and(|raw| raw.block()) => {
+            Some(FoldingKind::Raw)
+        }
+        SyntaxKind::BlockComment => Some(FoldingKind::Comment),
+        _ => None,
+    };
+
+    if let Some(kind) = kind {
+        ranges.push(FoldingRange { range: node.range(), kind });
+    }
+
+    if node.kind() == SyntaxKind::Markup {
+        collect_sections(node, ranges);
+    }
+
+    let mut comments: Option<Range<usize>> = None;
+    for child in node.children() {
+        // Merge consecutive line comments, which may only be separated by
+        // whitespace.
+        match child.kind() {
+            SyntaxKind::LineComment => {
+                let range = child.range();
+                comments = Some(match comments {
+                    Some(prev) => prev.start..range.end,
+                    None => range,
+                });
+                continue;
+            }
+            SyntaxKind::Space if comments.is_some() => continue,
+            _ => {}
+        }
+
+        if let Some(range) = comments.take() {
+            ranges.push(FoldingRange { range, kind: FoldingKind::Comment });
+        }
+
+        collect_ranges(&child, ranges);
+    }
+
+    if let Some(range) = comments {
+        ranges.push(FoldingRange { range, kind: FoldingKind::Comment });
+    }
+}
+
+/// Collect the sections of the headings in a markup node. A section ends
+/// before the next heading of the same or a lower depth.
+fn collect_sections(markup: &LinkedNode, ranges: &mut Vec<FoldingRange>) {
+    let children: Vec<_> = markup.children().collect();
+    for (i, child) in children.iter().enumerate() {
+        let Some(heading) = child.cast::<ast::Heading>() else { continue };
+        let depth = heading.depth();
+
+        let next = children[i + 1..]
+            .iter()
+            .position(|next| {
+                next.cast::<ast::Heading>().is_some_and(|next| next.depth() <= depth)
+            })
+            .map_or(children.len(), |k| i + 1 + k);
+
+        let end = children[i..next]
+            .iter()
+            .rev()
+            .find(|node| !matches!(node.kind(), SyntaxKind::Space | SyntaxKind::Parbreak))
+            .map_or(child.range().end, |node| node.range().end);
+
+        ranges.push(FoldingRange {
+            range: child.offset()..end,
+            kind: FoldingKind::Section,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::syntax::Source;
+
+    use super::{folding_ranges, FoldingKind};
+
+    #[track_caller]
+    fn test(text: &str) -> Vec<(FoldingKind, &str)> {
+        folding_ranges(&Source::detached(text))
+            .into_iter()
+            .map(|folding| (folding.kind, &text[folding.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_folding_ranges_sections() {
+        let text = "= A\nText\n\n== B\nMore\n\n= C\nEnd";
+        assert_eq!(
+            test(text),
+            [
+                (FoldingKind::Section, "= A\nText\n\n== B\nMore"),
+                (FoldingKind::Section, "== B\nMore"),
+                (FoldingKind::Section, "= C\nEnd"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folding_ranges_blocks_and_comments() {
+        let text = "// One\n// Two\n#{\n  1\n}\n```\nraw\n```\n#[single]";
+        assert_eq!(
+            test(text),
+            [
+                (FoldingKind::Comment, "// One\n// Two"),
+                (FoldingKind::Block, "{\n  1\n}"),
+                (FoldingKind::Raw, "```\nraw\n```"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+use typst::foundations::{Repr, Value};
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+use crate::signature::{func_params, positional_param};
+use crate::tokens::resolve_value;
+use crate::{analyze_expr, IdeWorld};
+
+/// A hint that is displayed inline with the source code.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InlayHint {
+    /// The byte offset at which the hint is displayed.
+    pub offset: usize,
+    /// The hint's text, e.g. `width:` or `= 3`.
+    pub label: EcoString,
+    /// What the hint shows.
+    pub kind: InlayHintKind,
+}
+
+/// A kind of inlay hint.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InlayHintKind {
+    /// The name of the parameter a positional argument is for, displayed
+    /// before the argument.
+    Param,
+    /// The value a `let` binding evaluates to, displayed after the binding.
+    Value,
+}
+
+/// Find the inlay hints within a range of a source file.
+///
+/// Positional arguments are labelled with the names of their parameters.
+/// Bindings of single variables are followed by their value if it is not
+/// apparent from the source, i.e. not a literal. Finding values requires
+/// tracing a compilation per binding, so callers that don't want to pay for
+/// this or only want to show them optionally should filter by
+/// [`InlayHintKind`] before presenting them.
+///
+/// The hints are ordered by their offset.
+pub fn inlay_hints(
+    world: &dyn IdeWorld,
+    source: &Source,
+    range: Range<usize>,
+) -> Vec<InlayHint> {
+    let mut hints = vec![];
+    collect_hints(world, &LinkedNode::new(source.root()), &range, &mut hints);
+    hints.retain(|hint| range.start <= hint.offset && hint.offset <= range.end);
+    hints.sort_by_key(|hint| hint.offset);
+    hints
+}
+
+/// Collect the hints in a node and its descendants that overlap the range.
+fn collect_hints(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    range: &Range<usize>,
+    hints: &mut Vec<InlayHint>,
+) {
+    if node.range().end < range.start || node.offset() > range.end {
+        return;
+    }
+
+    match node.kind() {
+        SyntaxKind::FuncCall => {
+            param_hints(world, node, hints);
+        }
+        SyntaxKind::LetBinding => {
+            value_hint(world, node, hints);
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_hints(world, &child, range, hints);
+    }
+}
+
+/// Label the positional arguments of a call with their parameters' names.
+fn param_hints(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    hints: &mut Vec<InlayHint>,
+) -> Option<()> {
+    let call = node.cast::<ast::FuncCall>()?;
+    let callee = node.find(call.callee().span())?;
+
+    // Most callees are known without evaluation, but the values of local
+    // closures must be traced.
+    let func = match resolve_value(world, &callee) {
+        Some(Value::Func(func)) => func,
+        _ => analyze_expr(world, &callee).into_iter().find_map(
+            |(value, _)| match value {
+                Value::Func(func) => Some(func),
+                _ => None,
+            },
+        )?,
+    };
+
+    let params = func_params(&func, false)?;
+    let args = node.find(call.args().span())?;
+
+    // Arguments after the parentheses are trailing content blocks.
+    let mut trailing = args
+        .children()
+        .next()
+        .is_none_or(|first| first.kind() != SyntaxKind::LeftParen);
+
+    let mut index = 0;
+    for child in args.children() {
+        if child.kind() == SyntaxKind::RightParen {
+            trailing = true;
+        }
+
+        let expr = match child.cast::<ast::Arg>() {
+            Some(ast::Arg::Pos(expr)) => expr,
+            // After a spread, it's unknown which parameters the following
+            // arguments are for.
+            Some(ast::Arg::Spread(_)) => break,
+            _ => continue,
+        };
+
+        let param = positional_param(&params, index).map(|i| &params[i]);
+        index += 1;
+
+        // Trailing content blocks, variadic arguments like a table's cells,
+        // and variables that are named like the parameter are clear enough.
+        let Some(param) = param else { break };
+        if trailing
+            || param.variadic
+            || matches!(expr, ast::Expr::Ident(ident) if param.name == ident.as_str())
+        {
+            continue;
+        }
+
+        hints.push(InlayHint {
+            offset: child.offset(),
+            label: eco_format!("{}:", param.name),
+            kind: InlayHintKind::Param,
+        });
+    }
+
+    Some(())
+}
+
+/// Show the value that a binding of a single variable evaluates to.
+fn value_hint(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    hints: &mut Vec<InlayHint>,
+) -> Option<()> {
+    let binding = node.cast::<ast::LetBinding>()?;
+    let ast::LetBindingKind::Normal(ast::Pattern::Normal(ast::Expr::Ident(ident))) =
+        binding.kind()
+    else {
+        return None;
+    };
+
+    if binding.init()?.is_literal() {
+        return None;
+    }
+
+    // Bindings that are evaluated multiple times, e.g. in a loop, have no
+    // single value to show.
+    let ident = node.find(ident.span())?;
+    let values = analyze_expr(world, &ident);
+    let [(value, _)] = values.as_slice() else { return None };
+    if matches!(value, Value::Func(_) | Value::Module(_)) {
+        return None;
+    }
+
+    hints.push(InlayHint {
+        offset: node.range().end,
+        label: eco_format!("= {}", truncate(value.repr())),
+        kind: InlayHintKind::Value,
+    });
+
+    Some(())
+}
+
+/// Shorten a value's representation so that it fits inline.
+fn truncate(repr: EcoString) -> EcoString {
+    const MAX_CHARS: usize = 30;
+    match repr.char_indices().nth(MAX_CHARS) {
+        Some((i, _)) => eco_format!("{}..", &repr[..i]),
+        None => repr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use typst::World;
+
+    use super::{inlay_hints, InlayHintKind};
+    use crate::tests::{TestWorld, WorldLike};
+
+    /// Render the hints of the given kind into the source, like `⟨a:⟩1`.
+    #[track_caller]
+    fn test(world: impl WorldLike, kind: InlayHintKind) -> String {
+        let world = world.acquire();
+        let world = world.borrow();
+        let source = world.source(TestWorld::main_id()).unwrap();
+        let mut text = source.text().to_string();
+        let hints = inlay_hints(world, &source, 0..source.len_bytes());
+        for hint in hints.iter().rev().filter(|hint| hint.kind == kind) {
+            text.insert_str(hint.offset, &format!("⟨{}⟩", hint.label));
+        }
+        text
+    }
+
+    #[test]
+    fn test_inlay_hints_native() {
+        assert_eq!(test("#lorem(5)", InlayHintKind::Param), "#lorem(⟨words:⟩5)");
+        assert_eq!(test("#box[A]", InlayHintKind::Param), "#box[A]");
+        assert_eq!(
+            test("#table(columns: 2, [A], [B])", InlayHintKind::Param),
+            "#table(columns: 2, [A], [B])",
+        );
+    }
+
+    #[test]
+    fn test_inlay_hints_closure() {
+        assert_eq!(
+            test("#let f(a, b, c: 3) = a\n#f(1, 2)", InlayHintKind::Param),
+            "#let f(a, b, c: 3) = a\n#f(⟨a:⟩1, ⟨b:⟩2)",
+        );
+        assert_eq!(
+            test("#let f(x, ..y) = x\n#let x = 1\n#f(x, 2, 3)", InlayHintKind::Param),
+            "#let f(x, ..y) = x\n#let x = 1\n#f(x, 2, 3)",
+        );
+    }
+
+    #[test]
+    fn test_inlay_hints_values() {
+        assert_eq!(
+            test(
+                "#let x = 1 + 2\n#let y = 4\n#let (a, b) = (1, 2)",
+                InlayHintKind::Value
+            ),
+            "#let x = 1 + 2⟨= 3⟩\n#let y = 4\n#let (a, b) = (1, 2)",
+        );
+        assert_eq!(
+            test("#let s = \"ab\" * 20", InlayHintKind::Value),
+            "#let s = \"ab\" * 20⟨= \"ababababababababababababababa..⟩",
+        );
+    }
+
+    #[test]
+    fn test_inlay_hints_range() {
+        let world = TestWorld::new("#lorem(1)\n#lorem(2)");
+        let source = world.source(TestWorld::main_id()).unwrap();
+        let hints = inlay_hints(&world, &source, 10..source.len_bytes());
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].offset, 17);
+    }
+}
@@ -104,6 +104,12 @@ pub fn jump_from_click(
                 return Jump::from_span(world, *span);
             }
 
+            FrameItem::Widget(widget, span)
+                if is_in_rect(pos, widget.appearance.size(), click) =>
+            {
+                return Jump::from_span(world, *span);
+            }
+
             _ => {}
         }
     }
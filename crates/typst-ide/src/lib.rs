@@ -3,16 +3,35 @@
 mod analyze;
 mod complete;
 mod definition;
+mod folding;
+mod inlay;
 mod jump;
 mod matchers;
+mod references;
+mod rename;
+mod selection;
+mod signature;
+mod symbols;
+mod tokens;
 mod tooltip;
 mod utils;
 
 pub use self::analyze::{analyze_expr, analyze_import, analyze_labels};
 pub use self::complete::{autocomplete, Completion, CompletionKind};
 pub use self::definition::{definition, Definition};
+pub use self::folding::{folding_ranges, FoldingKind, FoldingRange};
+pub use self::inlay::{inlay_hints, InlayHint, InlayHintKind};
 pub use self::jump::{jump_from_click, jump_from_cursor, Jump};
 pub use self::matchers::{deref_target, named_items, DerefTarget, NamedItem};
+pub use self::references::{references, Reference};
+pub use self::rename::{rename, TextEdit, WorkspaceEdit};
+pub use self::selection::selection_ranges;
+pub use self::signature::{signature_help, SignatureHelp, SignatureParam};
+pub use self::symbols::{document_symbols, DocumentSymbol, SymbolKind};
+pub use self::tokens::{
+    semantic_tokens, semantic_tokens_edit, SemanticToken, SemanticTokenKind,
+    SemanticTokenModifier, SemanticTokensEdit,
+};
 pub use self::tooltip::{tooltip, Tooltip};
 
 use ecow::EcoString;
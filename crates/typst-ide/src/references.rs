@@ -0,0 +1,301 @@
+use std::ffi::OsStr;
+use std::ops::Range;
+
+use ecow::EcoString;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, FileId, LinkedNode, Side, Source, Span, SyntaxKind};
+
+use crate::{named_items, IdeWorld};
+
+/// A reference to an item.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reference {
+    /// The file the reference is in.
+    pub id: FileId,
+    /// The byte range of the item's name in the file.
+    pub range: Range<usize>,
+    /// Whether this is where the item is defined rather than used.
+    pub definition: bool,
+}
+
+/// Find all references to the item under the cursor.
+///
+/// Supports variables, functions, parameters and imported items, whose uses
+/// are resolved to their binding, as well as labels, whose `<label>`
+/// definitions and `@label` references are matched by name. All Typst files
+/// reported by [`IdeWorld::files`] are searched, with `source` taking
+/// precedence over the world's version of the same file.
+pub fn references(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+    side: Side,
+) -> Vec<Reference> {
+    match target(world, source, cursor, side) {
+        Some(target) => find_references(world, &sources(world, source), &target),
+        None => vec![],
+    }
+}
+
+/// Something whose references can be searched.
+pub(crate) enum Target {
+    /// A binding with the given name and the span of its defining identifier.
+    Binding(EcoString, Span),
+    /// A label with the given name.
+    Label(EcoString),
+}
+
+/// Determine the target under the cursor.
+pub(crate) fn target(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+    side: Side,
+) -> Option<Target> {
+    let root = LinkedNode::new(source.root());
+    let leaf = root.leaf_at(cursor, side)?;
+    match leaf.kind() {
+        SyntaxKind::Label => Some(Target::Label(label_name(&leaf)?.0)),
+        SyntaxKind::RefMarker => Some(Target::Label(label_name(&leaf)?.0)),
+        SyntaxKind::Ident | SyntaxKind::MathIdent if is_identifier(&leaf) => {
+            let definition = resolve(world, &leaf)?;
+            Some(Target::Binding(leaf.text().clone(), definition))
+        }
+        _ => None,
+    }
+}
+
+/// The source itself followed by all other Typst files in the world.
+pub(crate) fn sources(world: &dyn IdeWorld, source: &Source) -> Vec<Source> {
+    let mut sources = vec![source.clone()];
+    for id in world.files() {
+        let ext = id.vpath().as_rooted_path().extension().and_then(OsStr::to_str);
+        if id != source.id() && ext == Some("typ") {
+            sources.extend(world.source(id).ok());
+        }
+    }
+    sources
+}
+
+/// Find all references to a target in the sources.
+pub(crate) fn find_references(
+    world: &dyn IdeWorld,
+    sources: &[Source],
+    target: &Target,
+) -> Vec<Reference> {
+    let mut references = vec![];
+    for source in sources {
+        walk(&LinkedNode::new(source.root()), &mut |node| {
+            let found = match target {
+                Target::Binding(name, definition) => (node.text() == name
+                    && is_identifier(node)
+                    && resolve(world, node) == Some(*definition))
+                .then(|| (node.range(), node.span() == *definition)),
+                Target::Label(name) => label_name(node)
+                    .filter(|(label, _)| label == name)
+                    .map(|(_, range)| (range, node.kind() == SyntaxKind::Label)),
+            };
+
+            if let Some((range, definition)) = found {
+                references.push(Reference { id: source.id(), range, definition });
+            }
+        });
+    }
+    references
+}
+
+/// Visit a node and all its descendants.
+pub(crate) fn walk(node: &LinkedNode, f: &mut impl FnMut(&LinkedNode)) {
+    f(node);
+    for child in node.children() {
+        walk(&child, f);
+    }
+}
+
+/// The name of the label defined or referenced by a label or reference
+/// marker, along with the range of the name.
+pub(crate) fn label_name(node: &LinkedNode) -> Option<(EcoString, Range<usize>)> {
+    let range = node.range();
+    let (name, start) = match node.kind() {
+        SyntaxKind::Label => (node.cast::<ast::Label>()?.get(), range.start + 1),
+        SyntaxKind::RefMarker => (node.text().trim_start_matches('@'), range.start + 1),
+        _ => return None,
+    };
+    Some((name.into(), start..start + name.len()))
+}
+
+/// Whether an identifier refers to or defines a binding.
+///
+/// This excludes the names of named arguments and dictionary entries.
+pub(crate) fn is_identifier(node: &LinkedNode) -> bool {
+    if !matches!(node.kind(), SyntaxKind::Ident | SyntaxKind::MathIdent) {
+        return false;
+    }
+
+    let Some(parent) = node.parent() else { return true };
+    match parent.cast::<ast::Named>() {
+        Some(named) if named.name().span() == node.span() => {
+            parent.parent_kind() == Some(SyntaxKind::Params)
+        }
+        _ => true,
+    }
+}
+
+/// Find the span of the identifier that defines the binding an identifier
+/// refers to.
+pub(crate) fn resolve(world: &dyn IdeWorld, node: &LinkedNode) -> Option<Span> {
+    if is_binding(node) {
+        return Some(node.span());
+    }
+
+    let name = node.text().clone();
+    let parent = node.parent()?;
+    if let Some(access) = parent.cast::<ast::FieldAccess>() {
+        if access.field().span() == node.span() {
+            return resolve_field(world, parent, access, &name);
+        }
+    }
+
+    named_items(world, node.clone(), |item| (*item.name() == name).then(|| item.span()))
+        .filter(|span| !span.is_detached())
+}
+
+/// Find the span of the binding a field of a module refers to.
+fn resolve_field(
+    world: &dyn IdeWorld,
+    parent: &LinkedNode,
+    access: ast::FieldAccess,
+    field: &str,
+) -> Option<Span> {
+    let target = parent.find(access.target().span())?;
+    let name = target.cast::<ast::Ident>()?.get().clone();
+    let value =
+        named_items(world, target, |item| (*item.name() == name).then(|| item.value()))??;
+    let span = value.scope()?.get(field)?.span();
+    (!span.is_detached()).then_some(span)
+}
+
+/// Whether an identifier is the place where a binding is defined.
+pub(crate) fn is_binding(node: &LinkedNode) -> bool {
+    let span = node.span();
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        let bindings = if let Some(binding) = parent.cast::<ast::LetBinding>() {
+            binding.kind().bindings()
+        } else if let Some(closure) = parent.cast::<ast::Closure>() {
+            let mut bindings: Vec<_> = closure.name().into_iter().collect();
+            for param in closure.params().children() {
+                match param {
+                    ast::Param::Pos(pattern) => bindings.extend(pattern.bindings()),
+                    ast::Param::Named(named) => bindings.push(named.name()),
+                    ast::Param::Spread(spread) => bindings.extend(spread.sink_ident()),
+                }
+            }
+            bindings
+        } else if let Some(for_loop) = parent.cast::<ast::ForLoop>() {
+            for_loop.pattern().bindings()
+        } else if let Some(import) = parent.cast::<ast::ModuleImport>() {
+            import.new_name().into_iter().collect()
+        } else if let Some(renamed) = parent.cast::<ast::RenamedImportItem>() {
+            vec![renamed.new_name()]
+        } else {
+            vec![]
+        };
+
+        if bindings.iter().any(|ident| ident.span() == span) {
+            return true;
+        }
+
+        ancestor = parent.parent();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::ops::Range;
+
+    use typst::syntax::Side;
+
+    use super::references;
+    use crate::tests::{FilePos, TestWorld, WorldLike};
+
+    type Response = Vec<(String, Range<usize>)>;
+
+    #[track_caller]
+    fn test(world: impl WorldLike, pos: impl FilePos, side: Side) -> Response {
+        let world = world.acquire();
+        let world = world.borrow();
+        let (source, cursor) = pos.resolve(world);
+        references(world, &source, cursor, side)
+            .into_iter()
+            .map(|reference| {
+                let path = reference.id.vpath().as_rootless_path();
+                (path.to_string_lossy().into(), reference.range)
+            })
+            .collect()
+    }
+
+    fn at<const N: usize>(refs: [(&str, Range<usize>); N]) -> Response {
+        refs.into_iter().map(|(path, range)| (path.into(), range)).collect()
+    }
+
+    #[test]
+    fn test_references_let() {
+        let s = "#let x = 1; #x; #(x + 1)";
+        let expected =
+            at([("main.typ", 5..6), ("main.typ", 13..14), ("main.typ", 18..19)]);
+        assert_eq!(test(s, 5, Side::After), expected);
+        assert_eq!(test(s, 18, Side::After), expected);
+    }
+
+    #[test]
+    fn test_references_shadowed() {
+        let s = "#let x = 1; #x; #let x = 2; #x";
+        assert_eq!(
+            test(s, 5, Side::After),
+            at([("main.typ", 5..6), ("main.typ", 13..14)])
+        );
+        assert_eq!(
+            test(s, -1, Side::Before),
+            at([("main.typ", 21..22), ("main.typ", 29..30)]),
+        );
+    }
+
+    #[test]
+    fn test_references_param() {
+        let s = "#let f(a) = a + 1";
+        assert_eq!(
+            test(s, 7, Side::After),
+            at([("main.typ", 7..8), ("main.typ", 12..13)])
+        );
+    }
+
+    #[test]
+    fn test_references_cross_file() {
+        let world = TestWorld::new("#import \"other.typ\": x; #x")
+            .with_source("other.typ", "#let x = 1");
+        let expected =
+            at([("main.typ", 21..22), ("main.typ", 25..26), ("other.typ", 5..6)]);
+        assert_eq!(test(&world, -2, Side::After), expected);
+    }
+
+    #[test]
+    fn test_references_field_access() {
+        let world = TestWorld::new("#import \"other.typ\"; #other.x")
+            .with_source("other.typ", "#let x = 1");
+        assert_eq!(
+            test(&world, ("other.typ", 5), Side::After),
+            at([("other.typ", 5..6), ("main.typ", 28..29)]),
+        );
+    }
+
+    #[test]
+    fn test_references_label() {
+        let s = "#figure[] <hi> See @hi";
+        let expected = at([("main.typ", 11..13), ("main.typ", 20..22)]);
+        assert_eq!(test(s, -2, Side::After), expected);
+        assert_eq!(test(s, 12, Side::After), expected);
+    }
+}
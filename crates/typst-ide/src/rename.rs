@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+use typst::diag::{bail, StrResult};
+use typst::syntax::{
+    is_ident, is_valid_label_literal_id, parse_code, FileId, LinkedNode, Side, Source,
+    Span, SyntaxKind,
+};
+
+use crate::references::{
+    find_references, is_binding, is_identifier, label_name, sources, target, walk, Target,
+};
+use crate::{named_items, IdeWorld, Reference};
+
+/// A replacement of a range of text in a file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TextEdit {
+    /// The byte range to replace.
+    pub range: Range<usize>,
+    /// The text to insert instead.
+    pub text: EcoString,
+}
+
+/// Edits to multiple files.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct WorkspaceEdit {
+    /// The edits per file, sorted by their ranges.
+    pub changes: BTreeMap<FileId, Vec<TextEdit>>,
+}
+
+/// Rename the item under the cursor everywhere it is referenced.
+///
+/// Bindings are renamed at their definition and all their uses, labels at
+/// their `<label>` definitions and `@label` references. Fails if the new name
+/// is invalid, if the item is defined in a package, or if the new name would
+/// shadow or be shadowed by another binding at one of the affected places.
+pub fn rename(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+    side: Side,
+    new_name: &str,
+) -> StrResult<WorkspaceEdit> {
+    let Some(target) = target(world, source, cursor, side) else {
+        bail!("there is nothing to rename here");
+    };
+
+    let sources = sources(world, source);
+    match &target {
+        Target::Binding(name, definition) => {
+            check_binding_rename(world, &sources, name, *definition, new_name)?
+        }
+        Target::Label(_) => check_label_rename(&sources, new_name)?,
+    }
+
+    let mut edit = WorkspaceEdit::default();
+    for Reference { id, range, .. } in find_references(world, &sources, &target) {
+        let text = new_name.into();
+        edit.changes.entry(id).or_default().push(TextEdit { range, text });
+    }
+    Ok(edit)
+}
+
+/// Ensure that a binding can be renamed without changing what any identifier
+/// refers to.
+fn check_binding_rename(
+    world: &dyn IdeWorld,
+    sources: &[Source],
+    name: &str,
+    definition: Span,
+    new_name: &str,
+) -> StrResult<()> {
+    if !is_ident(new_name)
+        || parse_code(new_name).children().next().map(|node| node.kind())
+            != Some(SyntaxKind::Ident)
+    {
+        bail!("`{new_name}` is not a valid identifier");
+    }
+
+    let Some(id) = definition.id() else { bail!("cannot rename this item") };
+    if id.package().is_some() {
+        bail!("cannot rename an item that is defined in a package");
+    }
+
+    // Items that were renamed on import are defined under another name.
+    let defined = sources
+        .iter()
+        .find(|source| source.id() == id)
+        .and_then(|source| LinkedNode::new(source.root()).find(definition))
+        .is_some_and(|node| node.kind() == SyntaxKind::Ident && node.text() == name);
+    if !defined {
+        bail!("cannot rename this item");
+    }
+
+    let references: HashSet<_> =
+        find_references(world, sources, &Target::Binding(name.into(), definition))
+            .into_iter()
+            .map(|reference| (reference.id, reference.range.start))
+            .collect();
+
+    let mut result: StrResult<()> = Ok(());
+    for source in sources {
+        walk(&LinkedNode::new(source.root()), &mut |node| {
+            if result.is_err() || !is_identifier(node) || is_binding(node) {
+                return;
+            }
+
+            // Field accesses are resolved through the module's scope.
+            if node.parent_kind() == Some(SyntaxKind::FieldAccess) && node.index() > 0 {
+                return;
+            }
+
+            let renamed = references.contains(&(source.id(), node.offset()));
+            if renamed
+                && node.kind() == SyntaxKind::MathIdent
+                && new_name.contains(['_', '-'])
+            {
+                result = Err(eco_format!("`{new_name}` cannot be used in math"));
+                return;
+            }
+
+            // After renaming, a use resolves to the nearest binding with the
+            // new name, which must be the renamed binding exactly for the
+            // renamed uses.
+            if renamed || node.text() == new_name {
+                let nearest = named_items(world, node.clone(), |item| {
+                    (*item.name() == new_name || item.span() == definition)
+                        .then(|| item.span())
+                });
+                if renamed && nearest != Some(definition) {
+                    result = Err(eco_format!(
+                        "`{new_name}` would be shadowed by another binding"
+                    ));
+                } else if !renamed && nearest == Some(definition) {
+                    result =
+                        Err(eco_format!("`{new_name}` would shadow an existing binding"));
+                }
+            }
+        });
+    }
+    result
+}
+
+/// Ensure that a label can be renamed without merging it with another label.
+fn check_label_rename(sources: &[Source], new_name: &str) -> StrResult<()> {
+    if !is_valid_label_literal_id(new_name) {
+        bail!("`{new_name}` is not a valid label");
+    }
+
+    // A reference drops trailing dots and colons from its label, so `@a.`
+    // refers to `<a>`. A label ending in one could not be referenced anymore.
+    if new_name.ends_with(['.', ':']) {
+        bail!("`{new_name}` cannot be referenced because it ends with `.` or `:`");
+    }
+
+    let mut exists = false;
+    for source in sources {
+        walk(&LinkedNode::new(source.root()), &mut |node| {
+            exists |= node.kind() == SyntaxKind::Label
+                && label_name(node).is_some_and(|(label, _)| label == new_name);
+        });
+    }
+
+    if exists {
+        bail!("the label `<{new_name}>` already exists");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::ops::Range;
+
+    use typst::diag::StrResult;
+    use typst::syntax::Side;
+
+    use super::rename;
+    use crate::tests::{FilePos, WorldLike};
+
+    type Edits = Vec<(String, Range<usize>, String)>;
+
+    #[track_caller]
+    fn test(
+        world: impl WorldLike,
+        pos: impl FilePos,
+        new_name: &str,
+    ) -> StrResult<Edits> {
+        let world = world.acquire();
+        let world = world.borrow();
+        let (source, cursor) = pos.resolve(world);
+        let edit = rename(world, &source, cursor, Side::After, new_name)?;
+        Ok(edit
+            .changes
+            .into_iter()
+            .flat_map(|(id, edits)| {
+                let path = id.vpath().as_rootless_path().to_string_lossy().into_owned();
+                edits
+                    .into_iter()
+                    .map(move |edit| (path.clone(), edit.range, edit.text.into()))
+            })
+            .collect())
+    }
+
+    fn edits<const N: usize>(edits: [(&str, Range<usize>, &str); N]) -> Edits {
+        edits
+            .into_iter()
+            .map(|(path, range, text)| (path.into(), range, text.into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_rename_let() {
+        assert_eq!(
+            test("#let x = 1; #x", 5, "y").unwrap(),
+            edits([("main.typ", 5..6, "y"), ("main.typ", 13..14, "y")]),
+        );
+    }
+
+    #[test]
+    fn test_rename_label() {
+        assert_eq!(
+            test("#figure[] <hi> See @hi", 12, "fig").unwrap(),
+            edits([("main.typ", 11..13, "fig"), ("main.typ", 20..22, "fig")]),
+        );
+        assert!(test("#figure[] <hi> <ho> See @hi", 12, "ho").is_err());
+        // References would drop the trailing punctuation.
+        assert!(test("#figure[] <hi> See @hi", 12, "fig.").is_err());
+        assert!(test("#figure[] <hi> See @hi", 12, "fig:").is_err());
+    }
+
+    #[test]
+    fn test_rename_invalid() {
+        assert!(test("#let x = 1; #x", 5, "let").is_err());
+        assert!(test("#let x = 1; #x", 5, "1x").is_err());
+        assert!(test("Hello", 1, "y").is_err());
+    }
+
+    #[test]
+    fn test_rename_collision() {
+        // The existing use of `y` would refer to the renamed binding.
+        assert!(test("#let y = 1; #let x = 2; #y", 17, "y").is_err());
+        // The renamed use would refer to the parameter.
+        assert!(test("#let x = 1; #let f(y) = x", 5, "y").is_err());
+        // Shadowing in a separate scope is fine.
+        assert!(test("#let x = 1; #x; #{ let y = 2; y }", 5, "y").is_ok());
+    }
+}
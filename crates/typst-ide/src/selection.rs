@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use typst::syntax::{LinkedNode, Side, Source, SyntaxKind};
+
+/// Find the ranges an editor can successively expand the selection to,
+/// starting from the cursor.
+///
+/// The ranges are those of the syntax node at the cursor and its ancestors,
+/// ordered from the innermost to the outermost. Ranges that equal the
+/// previous one are skipped, and for delimited nodes like strings and blocks,
+/// the range of their contents precedes the range including the delimiters.
+pub fn selection_ranges(source: &Source, cursor: usize) -> Vec<Range<usize>> {
+    let root = LinkedNode::new(source.root());
+    let Some(leaf) = root
+        .leaf_at(cursor, Side::After)
+        .filter(|leaf| !leaf.kind().is_trivia())
+        .or_else(|| root.leaf_at(cursor, Side::Before))
+    else {
+        return vec![];
+    };
+
+    let mut ranges: Vec<Range<usize>> = vec![];
+    let mut push = |range: Range<usize>| {
+        if ranges.last().is_none_or(|last| *last != range) {
+            ranges.push(range);
+        }
+    };
+
+    let mut node = Some(&leaf);
+    while let Some(current) = node {
+        if let Some(inner) = inner_range(current) {
+            push(inner);
+        }
+        push(current.range());
+        node = current.parent();
+    }
+
+    ranges
+}
+
+/// The range of a node's contents without its delimiters.
+fn inner_range(node: &LinkedNode) -> Option<Range<usize>> {
+    let range = node.range();
+    let delimited = match node.kind() {
+        SyntaxKind::Str => node.text().len() >= 2,
+        SyntaxKind::CodeBlock
+        | SyntaxKind::ContentBlock
+        | SyntaxKind::Parenthesized
+        | SyntaxKind::Array
+        | SyntaxKind::Dict
+        | SyntaxKind::Args
+        | SyntaxKind::Params => {
+            let mut children = node.children();
+            children.next().is_some_and(|first| first.len() == 1)
+                && children.next_back().is_some_and(|last| {
+                    matches!(
+                        last.kind(),
+                        SyntaxKind::RightBrace
+                            | SyntaxKind::RightBracket
+                            | SyntaxKind::RightParen
+                    )
+                })
+        }
+        _ => false,
+    };
+
+    (delimited && range.len() > 2).then(|| range.start + 1..range.end - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::syntax::Source;
+
+    use super::selection_ranges;
+
+    #[track_caller]
+    fn test(text: &str, cursor: usize) -> Vec<&str> {
+        selection_ranges(&Source::detached(text), cursor)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_selection_ranges_code() {
+        assert_eq!(
+            test("#f(1 + 2)", 3),
+            ["1", "1 + 2", "(1 + 2)", "f(1 + 2)", "#f(1 + 2)"]
+        );
+    }
+
+    #[test]
+    fn test_selection_ranges_string() {
+        assert_eq!(
+            test("#text(\"abc\")", 8),
+            ["abc", "\"abc\"", "(\"abc\")", "text(\"abc\")", "#text(\"abc\")"]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use ecow::EcoString;
+use typst::foundations::{CastInfo, Func, ParamInfo, Repr, Value};
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, LinkedNode, Side, Source, SyntaxKind};
+
+use crate::utils::plain_docs_sentence;
+use crate::{analyze_expr, IdeWorld};
+
+/// Help for calling the function whose arguments are under the cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureHelp {
+    /// The function's signature, e.g. `lorem(words: int)`.
+    pub label: EcoString,
+    /// The first sentence of the function's documentation, if any.
+    pub docs: Option<EcoString>,
+    /// The function's parameters.
+    pub params: Vec<SignatureParam>,
+    /// The index of the parameter the argument under the cursor is for, if
+    /// it corresponds to one.
+    pub active: Option<usize>,
+}
+
+/// A parameter in a [`SignatureHelp`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SignatureParam {
+    /// The parameter's name.
+    pub name: EcoString,
+    /// The byte range of the parameter in the signature's label.
+    pub range: Range<usize>,
+    /// The types and values the parameter accepts, if known.
+    pub ty: Option<EcoString>,
+    /// The parameter's default value, if it has one.
+    pub default: Option<EcoString>,
+    /// The first sentence of the parameter's documentation, if any.
+    pub docs: Option<EcoString>,
+    /// Whether the parameter can be given positionally.
+    pub positional: bool,
+    /// Whether the parameter can be given by name.
+    pub named: bool,
+    /// Whether the parameter can be given any number of times.
+    pub variadic: bool,
+}
+
+/// Describe the signature of the function whose arguments the cursor is in.
+///
+/// Native functions are described by their parameter metadata, user-defined
+/// functions by their closure's parameter list.
+pub fn signature_help(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+) -> Option<SignatureHelp> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor, Side::Before)?;
+    let (callee, set, args) = find_call(leaf, cursor)?;
+
+    let func =
+        analyze_expr(world, &callee)
+            .into_iter()
+            .find_map(|(value, _)| match value {
+                Value::Func(func) => Some(func),
+                _ => None,
+            })?;
+
+    let mut params = func_params(&func, set)?;
+
+    let mut label = callee.get().clone().into_text();
+    label.push('(');
+    for (i, param) in params.iter_mut().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let start = label.len();
+        if param.variadic {
+            label.push_str("..");
+        }
+        label.push_str(&param.name);
+        if let Some(ty) = &param.ty {
+            write!(label, ": {ty}").unwrap();
+        }
+        param.range = start..label.len();
+    }
+    label.push(')');
+
+    let active = active_param(&params, &args, cursor);
+    let docs = func.docs().map(plain_docs_sentence);
+    Some(SignatureHelp { label, docs, params, active })
+}
+
+/// Find the callee and arguments of the call whose parentheses contain the
+/// cursor, and whether the call is a set rule.
+fn find_call(leaf: LinkedNode, cursor: usize) -> Option<(LinkedNode, bool, LinkedNode)> {
+    let mut args = leaf;
+    while args.kind() != SyntaxKind::Args {
+        // Within blocks, the user is not writing arguments anymore.
+        if matches!(
+            args.kind(),
+            SyntaxKind::ContentBlock | SyntaxKind::CodeBlock | SyntaxKind::Closure
+        ) {
+            return None;
+        }
+        args = args.parent()?.clone();
+    }
+
+    let mut children = args.children();
+    let open = children.next().filter(|node| node.kind() == SyntaxKind::LeftParen)?;
+    let close = children.find(|node| node.kind() == SyntaxKind::RightParen);
+    if cursor < open.range().end || close.is_some_and(|close| cursor > close.offset()) {
+        return None;
+    }
+
+    let parent = args.parent()?;
+    let (callee, set) = match parent.cast::<ast::Expr>()? {
+        ast::Expr::FuncCall(call) => (parent.find(call.callee().span())?, false),
+        ast::Expr::SetRule(set) => (parent.find(set.target().span())?, true),
+        _ => return None,
+    };
+
+    Some((callee, set, args))
+}
+
+/// Describe the parameters of a function. For set rules, only settable
+/// parameters are included.
+pub(crate) fn func_params(func: &Func, set: bool) -> Option<Vec<SignatureParam>> {
+    match func.params() {
+        Some(infos) => Some(
+            infos
+                .iter()
+                .filter(|info| !set || info.settable)
+                .map(native_param)
+                .collect(),
+        ),
+        None => closure_params(func),
+    }
+}
+
+/// Describe a parameter of a native function.
+fn native_param(info: &ParamInfo) -> SignatureParam {
+    SignatureParam {
+        name: info.name.into(),
+        ty: Some(describe_cast(&info.input)),
+        default: info.default.map(|default| default().repr()),
+        docs: Some(plain_docs_sentence(info.docs)),
+        positional: info.positional,
+        named: info.named,
+        variadic: info.variadic,
+        ..Default::default()
+    }
+}
+
+/// Describe what a parameter accepts, e.g. `auto | relative`.
+fn describe_cast(info: &CastInfo) -> EcoString {
+    let mut parts: Vec<EcoString> = vec![];
+    info.walk(|info| match info {
+        CastInfo::Any => parts.push("any".into()),
+        CastInfo::Value(value, _) => parts.push(value.repr()),
+        CastInfo::Type(ty) => parts.push(ty.short_name().into()),
+        CastInfo::Union(_) => {}
+    });
+    parts.join(" | ").into()
+}
+
+/// Describe the parameters of a user-defined function.
+///
+/// Context expressions are closures without a parameter list, so they are
+/// not described.
+fn closure_params(func: &Func) -> Option<Vec<SignatureParam>> {
+    let closure = func.to_closure()?.node.cast::<ast::Closure>()?;
+    let params = closure
+        .params()
+        .children()
+        .map(|param| match param {
+            ast::Param::Pos(pattern) => SignatureParam {
+                name: pattern.to_untyped().clone().into_text(),
+                positional: true,
+                ..Default::default()
+            },
+            ast::Param::Named(named) => SignatureParam {
+                name: named.name().get().clone(),
+                default: Some(named.expr().to_untyped().clone().into_text()),
+                named: true,
+                ..Default::default()
+            },
+            ast::Param::Spread(spread) => SignatureParam {
+                name: spread
+                    .sink_ident()
+                    .map(|ident| ident.get().clone())
+                    .unwrap_or_default(),
+                positional: true,
+                variadic: true,
+                ..Default::default()
+            },
+        })
+        .collect();
+
+    Some(params)
+}
+
+/// Determine which parameter the argument under the cursor is for.
+fn active_param(
+    params: &[SignatureParam],
+    args: &LinkedNode,
+    cursor: usize,
+) -> Option<usize> {
+    let mut positional = 0;
+    for child in args.children() {
+        let Some(arg) = child.cast::<ast::Arg>() else { continue };
+        let range = child.range();
+        if range.start <= cursor && cursor <= range.end {
+            return match arg {
+                ast::Arg::Pos(_) => positional_param(params, positional),
+                ast::Arg::Named(named) => params
+                    .iter()
+                    .position(|param| param.named && param.name == named.name().as_str()),
+                ast::Arg::Spread(_) => None,
+            };
+        }
+
+        if range.end < cursor && matches!(arg, ast::Arg::Pos(_)) {
+            positional += 1;
+        }
+    }
+
+    positional_param(params, positional)
+}
+
+/// Find the parameter for the positional argument with the given index.
+pub(crate) fn positional_param(params: &[SignatureParam], index: usize) -> Option<usize> {
+    params
+        .iter()
+        .enumerate()
+        .filter(|(_, param)| param.positional)
+        .enumerate()
+        .find(|&(n, (_, param))| n == index || (n < index && param.variadic))
+        .map(|(_, (i, _))| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::{signature_help, SignatureHelp};
+    use crate::tests::{FilePos, WorldLike};
+
+    type Response = Option<SignatureHelp>;
+
+    trait ResponseExt {
+        fn must_be_active(&self, name: &str) -> &Self;
+        fn must_have_label(&self, label: &str) -> &Self;
+    }
+
+    impl ResponseExt for Response {
+        #[track_caller]
+        fn must_be_active(&self, name: &str) -> &Self {
+            let help = self.as_ref().expect("expected signature help");
+            let active = help.active.expect("expected an active parameter");
+            assert_eq!(help.params[active].name, name);
+            self
+        }
+
+        #[track_caller]
+        fn must_have_label(&self, label: &str) -> &Self {
+            let help = self.as_ref().expect("expected signature help");
+            assert_eq!(help.label, label);
+            self
+        }
+    }
+
+    #[track_caller]
+    fn test(world: impl WorldLike, pos: impl FilePos) -> Response {
+        let world = world.acquire();
+        let world = world.borrow();
+        let (source, cursor) = pos.resolve(world);
+        signature_help(world, &source, cursor)
+    }
+
+    #[test]
+    fn test_signature_help_native() {
+        test("#lorem()", -2)
+            .must_have_label("lorem(words: int)")
+            .must_be_active("words");
+    }
+
+    #[test]
+    fn test_signature_help_named() {
+        let help = test("#rect(width: 1pt)", -3);
+        help.must_be_active("width");
+        let help = help.unwrap();
+        let param = &help.params[help.active.unwrap()];
+        assert_eq!(param.default.as_deref(), Some("auto"));
+        assert!(param.ty.as_ref().is_some_and(|ty| ty.contains("relative")));
+    }
+
+    #[test]
+    fn test_signature_help_closure() {
+        let s = "#let f(a, b: 2) = a; #f(1, b: 3)";
+        test(s, -2).must_have_label("f(a, b)").must_be_active("b");
+        test(s, -8).must_be_active("a");
+    }
+
+    #[test]
+    fn test_signature_help_closure_with() {
+        let s = "#let f(a, b: 2) = a; #let g = f.with(1); #g(b: 3)";
+        test(s, -2).must_have_label("g(a, b)").must_be_active("b");
+    }
+
+    #[test]
+    fn test_signature_help_outside_args() {
+        assert_eq!(test("#text(1pt)", 2), None);
+        assert_eq!(test("#text(1pt)", -1), None);
+    }
+}
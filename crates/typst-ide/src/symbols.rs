@@ -0,0 +1,233 @@
+use std::num::NonZeroUsize;
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// An item in the outline of a source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSymbol {
+    /// The symbol's name.
+    pub name: EcoString,
+    /// The kind of the symbol.
+    pub kind: SymbolKind,
+    /// The byte range of the symbol, including its children.
+    pub range: Range<usize>,
+    /// The byte range of the symbol's name.
+    pub selection: Range<usize>,
+    /// Symbols that are nested in this one.
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A kind of symbol.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SymbolKind {
+    /// A heading of the given depth.
+    Heading(NonZeroUsize),
+    /// A function defined with `let`.
+    Function,
+    /// A variable defined with `let`.
+    Variable,
+    /// A label.
+    Label,
+    /// A show rule.
+    ShowRule,
+    /// A set rule.
+    SetRule,
+}
+
+/// Produce the outline of a source file.
+///
+/// Headings contain the symbols that follow them up to the next heading of
+/// the same or a lower depth. Other symbols contain the symbols in their
+/// syntax, e.g. a function contains the bindings in its body.
+pub fn document_symbols(source: &Source) -> Vec<DocumentSymbol> {
+    collect_symbols(&LinkedNode::new(source.root()))
+}
+
+/// Collect the symbols in a node's children.
+fn collect_symbols(node: &LinkedNode) -> Vec<DocumentSymbol> {
+    let mut symbols = vec![];
+    for child in node.children() {
+        match symbol(&child) {
+            Some(found) => {
+                let heading = child.kind() == SyntaxKind::Heading;
+                for mut symbol in found {
+                    symbol.children = collect_symbols(&child);
+                    symbols.push((symbol, heading));
+                }
+            }
+            None => {
+                symbols.extend(collect_symbols(&child).into_iter().map(|s| (s, false)))
+            }
+        }
+    }
+
+    if node.kind() == SyntaxKind::Markup {
+        nest_headings(symbols, node.range().end)
+    } else {
+        symbols.into_iter().map(|(symbol, _)| symbol).collect()
+    }
+}
+
+/// The symbols defined by a node, if it defines any.
+fn symbol(node: &LinkedNode) -> Option<Vec<DocumentSymbol>> {
+    let make = |name: EcoString, kind, selection: Range<usize>| DocumentSymbol {
+        name,
+        kind,
+        range: node.range(),
+        selection,
+        children: vec![],
+    };
+
+    let text = |expr: ast::Expr| expr.to_untyped().clone().into_text();
+    let range = |span| node.find(span).map(|found| found.range());
+
+    Some(match node.cast::<ast::Expr>()? {
+        ast::Expr::Heading(heading) => {
+            let body = heading.body();
+            let name = body.to_untyped().clone().into_text().trim().into();
+            let kind = SymbolKind::Heading(heading.depth());
+            vec![make(name, kind, range(body.span())?)]
+        }
+        ast::Expr::LetBinding(binding) => match binding.kind() {
+            ast::LetBindingKind::Closure(ident) => {
+                vec![make(
+                    ident.get().clone(),
+                    SymbolKind::Function,
+                    range(ident.span())?,
+                )]
+            }
+            ast::LetBindingKind::Normal(pattern) => {
+                let kind = match binding.init() {
+                    Some(ast::Expr::Closure(_)) => SymbolKind::Function,
+                    _ => SymbolKind::Variable,
+                };
+                pattern
+                    .bindings()
+                    .into_iter()
+                    .filter_map(|ident| {
+                        Some(make(ident.get().clone(), kind, range(ident.span())?))
+                    })
+                    .collect()
+            }
+        },
+        ast::Expr::Label(label) => {
+            vec![make(eco_format!("<{}>", label.get()), SymbolKind::Label, node.range())]
+        }
+        ast::Expr::ShowRule(show) => {
+            let name = match show.selector() {
+                Some(selector) => eco_format!("show {}", text(selector)),
+                None => "show".into(),
+            };
+            let selection = match show.selector() {
+                Some(selector) => range(selector.span())?,
+                None => node.range(),
+            };
+            vec![make(name, SymbolKind::ShowRule, selection)]
+        }
+        ast::Expr::SetRule(set) => {
+            let name = eco_format!("set {}", text(set.target()));
+            vec![make(name, SymbolKind::SetRule, range(set.target().span())?)]
+        }
+        _ => return None,
+    })
+}
+
+/// Move the symbols following each heading of a markup node into it, up to
+/// the next heading of the same or a lower depth. Headings' ranges are
+/// extended to cover their whole section.
+///
+/// The flag marks the symbols for headings that are direct children of the
+/// markup, as opposed to headings in nested content blocks.
+fn nest_headings(
+    symbols: Vec<(DocumentSymbol, bool)>,
+    end: usize,
+) -> Vec<DocumentSymbol> {
+    let mut output = vec![];
+    let mut open: Vec<(NonZeroUsize, DocumentSymbol)> = vec![];
+    for (symbol, heading) in symbols {
+        match symbol.kind {
+            SymbolKind::Heading(depth) if heading => {
+                while open.last().is_some_and(|&(d, _)| d >= depth) {
+                    close_heading(&mut open, &mut output, symbol.range.start);
+                }
+                open.push((depth, symbol));
+            }
+            _ => match open.last_mut() {
+                Some((_, heading)) => heading.children.push(symbol),
+                None => output.push(symbol),
+            },
+        }
+    }
+
+    while !open.is_empty() {
+        close_heading(&mut open, &mut output, end);
+    }
+
+    output
+}
+
+/// Close the innermost open heading at the given offset, moving it into its
+/// parent heading or the output.
+fn close_heading(
+    open: &mut Vec<(NonZeroUsize, DocumentSymbol)>,
+    output: &mut Vec<DocumentSymbol>,
+    end: usize,
+) {
+    let Some((_, mut heading)) = open.pop() else { return };
+    heading.range.end = heading.range.end.max(end);
+    match open.last_mut() {
+        Some((_, parent)) => parent.children.push(heading),
+        None => output.push(heading),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::syntax::Source;
+
+    use super::{document_symbols, DocumentSymbol};
+
+    /// Render the symbols' names and nesting compactly.
+    fn outline(symbols: &[DocumentSymbol]) -> String {
+        symbols
+            .iter()
+            .map(|symbol| {
+                if symbol.children.is_empty() {
+                    symbol.name.to_string()
+                } else {
+                    format!("{} [{}]", symbol.name, outline(&symbol.children))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[track_caller]
+    fn test(text: &str) -> String {
+        outline(&document_symbols(&Source::detached(text)))
+    }
+
+    #[test]
+    fn test_document_symbols_headings() {
+        let text = "= A\n#let f(x) = x\n== B\n#let y = 1\n<l>\n= C";
+        assert_eq!(test(text), "A [f, B [y, <l>]], C");
+    }
+
+    #[test]
+    fn test_document_symbols_nested() {
+        let text = "#let f() = { let g(x) = x }\n#show heading: set text(red)";
+        assert_eq!(test(text), "f [g], show heading [set text]");
+    }
+
+    #[test]
+    fn test_document_symbols_ranges() {
+        let text = "= Intro\nHello\n== Sub\nWorld";
+        let symbols = document_symbols(&Source::detached(text));
+        assert_eq!(symbols[0].range, 0..text.len());
+        assert_eq!(symbols[0].selection, 2..7);
+        assert_eq!(symbols[0].children[0].range, 14..text.len());
+    }
+}
@@ -0,0 +1,594 @@
+use std::ops::Range;
+
+use typst::foundations::Value;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, highlight, LinkedNode, Source, SyntaxKind, Tag};
+
+use crate::references::{is_binding, is_identifier};
+use crate::utils::globals;
+use crate::{named_items, IdeWorld, NamedItem};
+
+/// A semantic token in the LSP encoding.
+///
+/// Positions are relative to the previous token in the stream and measured
+/// in UTF-16 code units. A token never spans multiple lines.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SemanticToken {
+    /// The number of lines between the previous token and this one.
+    pub delta_line: u32,
+    /// The column of the token, relative to the start of the previous token
+    /// if both are on the same line.
+    pub delta_start: u32,
+    /// The length of the token.
+    pub length: u32,
+    /// What kind of token this is.
+    pub kind: SemanticTokenKind,
+    /// A bit set of the token's [modifiers](SemanticTokenModifier).
+    pub modifiers: u32,
+}
+
+impl SemanticToken {
+    /// Encode the token as the five integers the LSP expects.
+    pub fn encode(&self) -> [u32; 5] {
+        [self.delta_line, self.delta_start, self.length, self.kind as u32, self.modifiers]
+    }
+}
+
+/// A kind of semantic token.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SemanticTokenKind {
+    /// A line or block comment.
+    Comment,
+    /// Punctuation in code.
+    Punctuation,
+    /// An escape sequence or shorthand.
+    Escape,
+    /// Strong markup.
+    Strong,
+    /// Emphasized markup.
+    Emph,
+    /// A hyperlink.
+    Link,
+    /// Raw text.
+    Raw,
+    /// A label.
+    Label,
+    /// A reference to a label.
+    Ref,
+    /// A section heading.
+    Heading,
+    /// A marker of a list, enumeration, or term list.
+    ListMarker,
+    /// A term in a term list.
+    ListTerm,
+    /// The delimiters of an equation.
+    Delimiter,
+    /// An operator in code or math.
+    Operator,
+    /// A keyword.
+    Keyword,
+    /// A numeric literal.
+    Number,
+    /// A string literal.
+    String,
+    /// A function that is not an element function.
+    Function,
+    /// An element function, e.g. `heading`.
+    ElementFunction,
+    /// A type, e.g. `int`.
+    Type,
+    /// A module.
+    Module,
+    /// Any other variable.
+    Variable,
+}
+
+impl SemanticTokenKind {
+    /// The list of all kinds, in the same order as they are defined.
+    ///
+    /// Can be used as the counter-part to `kind as u32` and to build the
+    /// legend of an LSP server.
+    pub const LIST: &'static [SemanticTokenKind] = &[
+        Self::Comment,
+        Self::Punctuation,
+        Self::Escape,
+        Self::Strong,
+        Self::Emph,
+        Self::Link,
+        Self::Raw,
+        Self::Label,
+        Self::Ref,
+        Self::Heading,
+        Self::ListMarker,
+        Self::ListTerm,
+        Self::Delimiter,
+        Self::Operator,
+        Self::Keyword,
+        Self::Number,
+        Self::String,
+        Self::Function,
+        Self::ElementFunction,
+        Self::Type,
+        Self::Module,
+        Self::Variable,
+    ];
+
+    /// The name of the kind in an LSP legend. Kinds that the LSP predefines
+    /// use the predefined names.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Comment => "comment",
+            Self::Punctuation => "punctuation",
+            Self::Escape => "escape",
+            Self::Strong => "strong",
+            Self::Emph => "emph",
+            Self::Link => "link",
+            Self::Raw => "raw",
+            Self::Label => "label",
+            Self::Ref => "ref",
+            Self::Heading => "heading",
+            Self::ListMarker => "marker",
+            Self::ListTerm => "term",
+            Self::Delimiter => "delim",
+            Self::Operator => "operator",
+            Self::Keyword => "keyword",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Function => "function",
+            Self::ElementFunction => "element",
+            Self::Type => "type",
+            Self::Module => "namespace",
+            Self::Variable => "variable",
+        }
+    }
+
+    /// The kind for a syntax highlighting tag.
+    fn from_tag(tag: Tag) -> Option<Self> {
+        Some(match tag {
+            Tag::Comment => Self::Comment,
+            Tag::Punctuation => Self::Punctuation,
+            Tag::Escape => Self::Escape,
+            Tag::Strong => Self::Strong,
+            Tag::Emph => Self::Emph,
+            Tag::Link => Self::Link,
+            Tag::Raw => Self::Raw,
+            Tag::Label => Self::Label,
+            Tag::Ref => Self::Ref,
+            Tag::Heading => Self::Heading,
+            Tag::ListMarker => Self::ListMarker,
+            Tag::ListTerm => Self::ListTerm,
+            Tag::MathDelimiter => Self::Delimiter,
+            Tag::MathOperator | Tag::Operator => Self::Operator,
+            Tag::Keyword => Self::Keyword,
+            Tag::Number => Self::Number,
+            Tag::String => Self::String,
+            Tag::Function => Self::Function,
+            Tag::Interpolated => Self::Variable,
+            Tag::Error => return None,
+        })
+    }
+
+    /// The kind for a value an identifier refers to.
+    fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Func(func) if func.element().is_some() => Self::ElementFunction,
+            Value::Func(_) => Self::Function,
+            Value::Type(_) => Self::Type,
+            Value::Module(_) => Self::Module,
+            _ => Self::Variable,
+        }
+    }
+}
+
+/// A modifier of a semantic token.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SemanticTokenModifier {
+    /// The token is in an equation.
+    Math,
+    /// The token is in strong markup.
+    Strong,
+    /// The token is in emphasized markup.
+    Emph,
+    /// The token is an identifier that defines a binding.
+    Definition,
+}
+
+impl SemanticTokenModifier {
+    /// The list of all modifiers, in the same order as they are defined.
+    ///
+    /// The bit of a modifier is `1 << modifier as u32`.
+    pub const LIST: &'static [SemanticTokenModifier] =
+        &[Self::Math, Self::Strong, Self::Emph, Self::Definition];
+
+    /// The name of the modifier in an LSP legend.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Math => "math",
+            Self::Strong => "strong",
+            Self::Emph => "emph",
+            Self::Definition => "definition",
+        }
+    }
+
+    /// The modifier's bit in [`SemanticToken::modifiers`].
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// A replacement of a run of tokens in a previous token stream.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SemanticTokensEdit {
+    /// The index of the first replaced token.
+    ///
+    /// Multiply by five for the index in the LSP's integer encoding.
+    pub start: usize,
+    /// The number of replaced tokens.
+    pub delete_count: usize,
+    /// The tokens to insert instead.
+    pub tokens: Vec<SemanticToken>,
+}
+
+/// Produce the semantic tokens of a whole source file.
+///
+/// Besides the syntactic categories of [`highlight`], this resolves what
+/// identifiers refer to, distinguishing functions, element functions, types,
+/// modules and other variables.
+pub fn semantic_tokens(world: &dyn IdeWorld, source: &Source) -> Vec<SemanticToken> {
+    let mut tokenizer = Tokenizer { world, source, tokens: vec![], line: 0, column: 0 };
+    tokenizer.collect(&LinkedNode::new(source.root()), None, 0);
+    tokenizer.tokens
+}
+
+/// Produce the edit that turns a previous token stream into the one of the
+/// source file in its current state, e.g. after a [`Source::edit`].
+///
+/// This recomputes the tokens of the whole file, since an edit can change what
+/// identifiers anywhere else refer to. Only the result is minimized: the edit
+/// replaces the tokens between the longest common prefix and suffix of both
+/// streams, which saves sending unchanged tokens to the client. Because token
+/// positions are relative, this typically only covers the tokens in the
+/// edited range.
+///
+/// Returns `None` if the tokens did not change.
+pub fn semantic_tokens_edit(
+    world: &dyn IdeWorld,
+    source: &Source,
+    previous: &[SemanticToken],
+) -> Option<SemanticTokensEdit> {
+    let tokens = semantic_tokens(world, source);
+    let prefix = previous.iter().zip(&tokens).take_while(|(a, b)| a == b).count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(tokens[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let deleted = prefix..previous.len() - suffix;
+    let inserted = prefix..tokens.len() - suffix;
+    if deleted.is_empty() && inserted.is_empty() {
+        return None;
+    }
+
+    Some(SemanticTokensEdit {
+        start: prefix,
+        delete_count: deleted.len(),
+        tokens: tokens[inserted].to_vec(),
+    })
+}
+
+/// Builds the token stream of a source file.
+struct Tokenizer<'a> {
+    world: &'a dyn IdeWorld,
+    source: &'a Source,
+    tokens: Vec<SemanticToken>,
+    /// The line of the previous token.
+    line: usize,
+    /// The UTF-16 column of the previous token.
+    column: usize,
+}
+
+impl Tokenizer<'_> {
+    /// Collect the tokens of a node and its descendants. Leaves without a
+    /// kind of their own take the kind of their nearest highlighted ancestor.
+    fn collect(
+        &mut self,
+        node: &LinkedNode,
+        inherited: Option<SemanticTokenKind>,
+        mut modifiers: u32,
+    ) {
+        if node.kind() == SyntaxKind::Error {
+            return;
+        }
+
+        if node.children().len() == 0 {
+            if matches!(
+                node.kind(),
+                SyntaxKind::Space | SyntaxKind::Parbreak | SyntaxKind::RawTrimmed
+            ) {
+                return;
+            }
+
+            if is_identifier(node) && is_binding(node) {
+                modifiers |= SemanticTokenModifier::Definition.bit();
+            }
+
+            if let Some(kind) = token_kind(self.world, node).or(inherited) {
+                self.push(node.range(), kind, modifiers);
+            }
+            return;
+        }
+
+        modifiers |= match node.kind() {
+            SyntaxKind::Equation => SemanticTokenModifier::Math.bit(),
+            SyntaxKind::Strong => SemanticTokenModifier::Strong.bit(),
+            SyntaxKind::Emph => SemanticTokenModifier::Emph.bit(),
+            _ => 0,
+        };
+
+        let inherited =
+            highlight(node).and_then(SemanticTokenKind::from_tag).or(inherited);
+        for child in node.children() {
+            self.collect(&child, inherited, modifiers);
+        }
+    }
+
+    /// Push a token for each line of a range.
+    fn push(&mut self, range: Range<usize>, kind: SemanticTokenKind, modifiers: u32) {
+        let source = self.source;
+        let text = source.text();
+        let (Some(first), Some(last)) =
+            (source.byte_to_line(range.start), source.byte_to_line(range.end))
+        else {
+            return;
+        };
+
+        for line in first..=last {
+            let Some(bounds) = source.line_to_range(line) else { continue };
+            let start = range.start.max(bounds.start);
+            let end = range.end.min(bounds.end).max(start);
+            let segment = text[start..end].trim_end_matches(['\r', '\n']);
+            if segment.is_empty() {
+                continue;
+            }
+
+            let column = text[bounds.start..start].encode_utf16().count();
+            let delta_line = line - self.line;
+            let delta_start = if delta_line == 0 { column - self.column } else { column };
+            self.tokens.push(SemanticToken {
+                delta_line: delta_line as u32,
+                delta_start: delta_start as u32,
+                length: segment.encode_utf16().count() as u32,
+                kind,
+                modifiers,
+            });
+
+            self.line = line;
+            self.column = column;
+        }
+    }
+}
+
+/// Determine the kind of a leaf, resolving identifiers.
+fn token_kind(world: &dyn IdeWorld, leaf: &LinkedNode) -> Option<SemanticTokenKind> {
+    if is_identifier(leaf) {
+        if let Some(kind) = identifier_kind(world, leaf) {
+            return Some(kind);
+        }
+    }
+
+    // A hash takes the kind of the expression it introduces.
+    if leaf.kind() == SyntaxKind::Hash && highlight(leaf).is_some() {
+        let next = leaf.next_sibling()?.leftmost_leaf()?;
+        return token_kind(world, &next);
+    }
+
+    highlight(leaf).and_then(SemanticTokenKind::from_tag)
+}
+
+/// Determine the kind of the item an identifier defines or refers to.
+fn identifier_kind(
+    world: &dyn IdeWorld,
+    ident: &LinkedNode,
+) -> Option<SemanticTokenKind> {
+    let parent = ident.parent()?;
+    if is_binding(ident) {
+        let function = parent
+            .cast::<ast::Closure>()
+            .and_then(|closure| closure.name())
+            .is_some_and(|name| name.span() == ident.span());
+        return Some(if function {
+            SemanticTokenKind::Function
+        } else if parent.kind() == SyntaxKind::ModuleImport {
+            SemanticTokenKind::Module
+        } else {
+            SemanticTokenKind::Variable
+        });
+    }
+
+    if let Some(access) = parent.cast::<ast::FieldAccess>() {
+        if access.field().span() == ident.span() {
+            let target = parent.find(access.target().span())?;
+            let value = resolve_value(world, &target)?;
+            let binding = value.scope()?.get(ident.text())?;
+            return Some(SemanticTokenKind::from_value(binding.read()));
+        }
+    }
+
+    let name = ident.text();
+    let local = named_items(world, ident.clone(), |item| {
+        (item.name() == name).then(|| match item {
+            NamedItem::Fn(_) => SemanticTokenKind::Function,
+            NamedItem::Var(_) => SemanticTokenKind::Variable,
+            NamedItem::Module(..) => SemanticTokenKind::Module,
+            NamedItem::Import(_, _, value) => {
+                value.map_or(SemanticTokenKind::Variable, SemanticTokenKind::from_value)
+            }
+        })
+    });
+
+    local.or_else(|| {
+        let binding = globals(world, ident).get(name)?;
+        Some(SemanticTokenKind::from_value(binding.read()))
+    })
+}
+
+/// Determine the value an identifier or a field access of one refers to, if
+/// it is known without evaluating the document.
+pub(crate) fn resolve_value(world: &dyn IdeWorld, node: &LinkedNode) -> Option<Value> {
+    match node.cast::<ast::Expr>()? {
+        ast::Expr::Ident(ident) => {
+            let name = ident.get();
+            let local = named_items(world, node.clone(), |item| {
+                (item.name() == name).then(|| item.value())
+            });
+            match local {
+                Some(value) => value,
+                None => Some(globals(world, node).get(name)?.read().clone()),
+            }
+        }
+        ast::Expr::FieldAccess(access) => {
+            let target = node.find(access.target().span())?;
+            let value = resolve_value(world, &target)?;
+            let binding = value.scope()?.get(access.field().get())?;
+            Some(binding.read().clone())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use typst::syntax::Source;
+    use typst::World;
+
+    use super::{
+        semantic_tokens, semantic_tokens_edit, SemanticToken, SemanticTokenKind,
+        SemanticTokenModifier,
+    };
+    use crate::tests::{TestWorld, WorldLike};
+
+    /// Decode tokens into their lines, columns and lengths.
+    fn decode(tokens: &[SemanticToken]) -> Vec<(u32, u32, u32, SemanticTokenKind)> {
+        let (mut line, mut column) = (0, 0);
+        tokens
+            .iter()
+            .map(|token| {
+                if token.delta_line > 0 {
+                    column = 0;
+                }
+                line += token.delta_line;
+                column += token.delta_start;
+                (line, column, token.length, token.kind)
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn test(world: impl WorldLike) -> (Source, Vec<SemanticToken>) {
+        let world = world.acquire();
+        let world = world.borrow();
+        let source = world.source(TestWorld::main_id()).unwrap();
+        let tokens = semantic_tokens(world, &source);
+        (source, tokens)
+    }
+
+    /// The kinds of the tokens with the given text.
+    #[track_caller]
+    fn kinds_of(world: impl WorldLike, text: &str) -> Vec<SemanticTokenKind> {
+        let (source, tokens) = test(world);
+        decode(&tokens)
+            .into_iter()
+            .filter(|&(line, column, length, _)| {
+                let start = source.line_column_to_byte(line as usize, 0).unwrap();
+                let head = &source.text()[start..];
+                let offset = head
+                    .char_indices()
+                    .scan(0, |utf16, (i, c)| {
+                        let at = *utf16;
+                        *utf16 += c.len_utf16() as u32;
+                        Some((i, at))
+                    })
+                    .find(|&(_, at)| at == column)
+                    .map(|(i, _)| start + i);
+                offset.is_some_and(|offset| {
+                    source.text()[offset..].starts_with(text)
+                        && text.encode_utf16().count() as u32 == length
+                })
+            })
+            .map(|(_, _, _, kind)| kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_semantic_tokens_resolved() {
+        use SemanticTokenKind::*;
+        let text = "#let f(x) = x\n#let v = 1\n#import calc: pow\n\
+                    #f(v) #heading[A] #pow #calc #int #calc.max";
+        assert_eq!(kinds_of(text, "f"), [Function, Function]);
+        assert_eq!(kinds_of(text, "v"), [Variable, Variable]);
+        assert_eq!(kinds_of(text, "pow"), [Function, Function]);
+        assert_eq!(kinds_of(text, "heading"), [ElementFunction]);
+        assert_eq!(kinds_of(text, "calc"), [Module, Module, Module]);
+        assert_eq!(kinds_of(text, "int"), [Type]);
+        assert_eq!(kinds_of(text, "max"), [Function]);
+    }
+
+    #[test]
+    fn test_semantic_tokens_encoding() {
+        let (_, tokens) = test("= Hä\n```\nä\n```");
+        assert_eq!(
+            decode(&tokens),
+            [
+                (0, 0, 1, SemanticTokenKind::Heading),
+                (0, 2, 2, SemanticTokenKind::Heading),
+                (1, 0, 3, SemanticTokenKind::Raw),
+                (2, 0, 1, SemanticTokenKind::Raw),
+                (3, 0, 3, SemanticTokenKind::Raw),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_modifiers() {
+        let (_, tokens) = test("*#let x = 1;* $pi$");
+        let definition = SemanticTokenModifier::Definition.bit();
+        let strong = SemanticTokenModifier::Strong.bit();
+        let math = SemanticTokenModifier::Math.bit();
+        assert!(tokens.iter().any(|token| token.modifiers == definition | strong));
+        assert!(tokens.iter().any(|token| token.modifiers == math));
+    }
+
+    #[test]
+    fn test_semantic_tokens_edit() {
+        let world = TestWorld::new("#let x = 1\n\n#x\n\n#x");
+        let (mut source, previous) = test(&world);
+        source.edit(12..12, "#x #x ");
+        let edit = semantic_tokens_edit(&world, &source, &previous).unwrap();
+
+        // Only the new tokens and the one after them are replaced.
+        let new = semantic_tokens(&world, &source);
+        assert!(edit.delete_count <= 2);
+        assert_eq!(new.len(), previous.len() + 4);
+        assert_eq!(edit.tokens.len(), edit.delete_count + 4);
+        assert_eq!(new[edit.start..edit.start + edit.tokens.len()], edit.tokens[..]);
+        assert_eq!(semantic_tokens_edit(&world, &source, &new), None);
+    }
+
+    #[test]
+    fn test_semantic_tokens_edit_elsewhere() {
+        // Turning the variable into a function changes the kind of its use
+        // after the edited range.
+        let world = TestWorld::new("#let f = 1\n#f");
+        let (mut source, previous) = test(&world);
+        source.edit(6..6, "()");
+        let edit = semantic_tokens_edit(&world, &source, &previous).unwrap();
+        let new = semantic_tokens(&world, &source);
+        assert_eq!(new.last().map(|token| token.kind), Some(SemanticTokenKind::Function));
+        assert_eq!(edit.start + edit.delete_count, previous.len());
+        assert_eq!(edit.start + edit.tokens.len(), new.len());
+    }
+}
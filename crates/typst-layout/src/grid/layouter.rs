@@ -1097,8 +1097,8 @@ impl<'a> GridLayouter<'a> {
             {
                 if can_skip
                     && breakable
-                    && first.is_empty()
-                    && rest.iter().any(|frame| !frame.is_empty())
+                    && is_blank(engine, first)
+                    && rest.iter().any(|frame| !is_blank(engine, frame))
                 {
                     return Ok(None);
                 }
@@ -1582,3 +1582,13 @@ pub(super) fn in_last_with_offset(regions: Regions<'_>, offset: Abs) -> bool {
     regions.backlog.is_empty()
         && regions.last.is_none_or(|height| regions.size.y + offset == height)
 }
+
+/// Whether a cell's frame has no content. For tagged PDFs, introspection tags
+/// don't count, so that the tags emitted for table cells don't prevent
+/// skipping a region.
+fn is_blank(engine: &Engine, frame: &Frame) -> bool {
+    if !engine.pdf_tags() {
+        return frame.is_empty();
+    }
+    frame.items().all(|(_, item)| matches!(item, FrameItem::Tag(_)))
+}
@@ -8,10 +8,10 @@ pub use self::layouter::GridLayouter;
 use typst_library::diag::SourceResult;
 use typst_library::engine::Engine;
 use typst_library::foundations::{Packed, StyleChain};
-use typst_library::introspection::Locator;
+use typst_library::introspection::{Locator, Tag};
 use typst_library::layout::grid::resolve::{grid_to_cellgrid, table_to_cellgrid, Cell};
-use typst_library::layout::{Fragment, GridElem, Regions};
-use typst_library::model::TableElem;
+use typst_library::layout::{Fragment, FrameItem, GridElem, Point, Regions};
+use typst_library::model::{TableCell, TableElem};
 
 use self::layouter::RowPiece;
 use self::lines::{
@@ -36,7 +36,30 @@ pub fn layout_cell(
     if disambiguator > 0 {
         locator = locator.split().next_inner(disambiguator as u128);
     }
-    crate::layout_fragment(engine, &cell.body, locator, styles, regions)
+
+    // For tagged PDFs, table cells are tagged manually instead of being
+    // locatable so that the tags end up around the cell's frames rather than
+    // inside its flow, where they would interfere with alignment and the
+    // detection of empty cells.
+    if !cell.body.is::<TableCell>() || !engine.pdf_tags() {
+        return crate::layout_fragment(engine, &cell.body, locator, styles, regions);
+    }
+
+    let mut locator = locator.split();
+    let mut elem = cell.body.clone();
+    let key = typst_utils::hash128(&elem);
+    let loc = locator.next_location(engine.introspector, key);
+    elem.set_location(loc);
+
+    let mut fragment =
+        crate::layout_fragment(engine, &cell.body, locator.next(&()), styles, regions)?;
+    if let Some(first) = fragment.iter_mut().next() {
+        first.prepend(Point::zero(), FrameItem::Tag(Tag::Start(elem)));
+    }
+    if let Some(last) = fragment.iter_mut().last() {
+        last.push(Point::zero(), FrameItem::Tag(Tag::End(loc, key)));
+    }
+    Ok(fragment)
 }
 
 /// Layout the grid.
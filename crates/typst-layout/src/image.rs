@@ -1,16 +1,13 @@
-use std::ffi::OsStr;
-
-use typst_library::diag::{warning, At, SourceResult, StrResult};
+use typst_library::diag::{warning, At, SourceResult};
 use typst_library::engine::Engine;
-use typst_library::foundations::{Bytes, Derived, Packed, Smart, StyleChain};
+use typst_library::foundations::{Packed, StyleChain};
 use typst_library::introspection::Locator;
 use typst_library::layout::{
     Abs, Axes, FixedAlignment, Frame, FrameItem, Point, Region, Size,
 };
-use typst_library::loading::DataSource;
 use typst_library::text::families;
 use typst_library::visualize::{
-    Curve, ExchangeFormat, Image, ImageElem, ImageFit, ImageFormat, ImageKind,
+    Curve, Image, ImageElem, ImageFit, ImageFormat, ImageKind, PdfDocument, PdfImage,
     RasterImage, SvgImage, VectorFormat,
 };
 
@@ -27,11 +24,8 @@ pub fn layout_image(
 
     // Take the format that was explicitly defined, or parse the extension,
     // or try to detect the format.
-    let Derived { source, derived: data } = &elem.source;
-    let format = match elem.format(styles) {
-        Smart::Custom(v) => v,
-        Smart::Auto => determine_format(source, data).at(span)?,
-    };
+    let data = &elem.source.derived;
+    let format = elem.determine_format(styles).at(span)?;
 
     // Warn the user if the image contains a foreign object. Not perfect
     // because the svg could also be encoded, but that's an edge case.
@@ -67,6 +61,11 @@ pub fn layout_image(
             )
             .at(span)?,
         ),
+        ImageFormat::Vector(VectorFormat::Pdf) => {
+            let document = PdfDocument::new(data.clone()).at(span)?;
+            let page_index = elem.page(styles).get() - 1;
+            ImageKind::Pdf(PdfImage::new(document, page_index).at(span)?)
+        }
     };
 
     let image = Image::new(kind, elem.alt(styles), elem.scaling(styles));
@@ -132,24 +131,3 @@ pub fn layout_image(
 
     Ok(frame)
 }
-
-/// Try to determine the image format based on the data.
-fn determine_format(source: &DataSource, data: &Bytes) -> StrResult<ImageFormat> {
-    if let DataSource::Path(path) = source {
-        let ext = std::path::Path::new(path.as_str())
-            .extension()
-            .and_then(OsStr::to_str)
-            .unwrap_or_default()
-            .to_lowercase();
-
-        match ext.as_str() {
-            "png" => return Ok(ExchangeFormat::Png.into()),
-            "jpg" | "jpeg" => return Ok(ExchangeFormat::Jpg.into()),
-            "gif" => return Ok(ExchangeFormat::Gif.into()),
-            "svg" | "svgz" => return Ok(VectorFormat::Svg.into()),
-            _ => {}
-        }
-    }
-
-    Ok(ImageFormat::detect(data).ok_or("unknown image format")?)
-}
@@ -3,7 +3,7 @@ use smallvec::smallvec;
 use typst_library::diag::SourceResult;
 use typst_library::engine::Engine;
 use typst_library::foundations::{Content, Context, Depth, Packed, StyleChain};
-use typst_library::introspection::Locator;
+use typst_library::introspection::{Locator, SplitLocator, Tag, TagElem};
 use typst_library::layout::grid::resolve::{Cell, CellGrid};
 use typst_library::layout::{Axes, Fragment, HAlignment, Regions, Sizing, VAlignment};
 use typst_library::model::{EnumElem, ListElem, Numbering, ParElem, ParbreakElem};
@@ -48,13 +48,16 @@ pub fn layout_list(
             body += ParbreakElem::shared();
         }
 
-        cells.push(Cell::new(Content::empty(), locator.next(&())));
+        let (start, body) = tag_item(
+            engine,
+            &mut locator,
+            item.clone().pack(),
+            body.styled(ListElem::set_depth(Depth(1))),
+        );
+        cells.push(Cell::new(start, locator.next(&())));
         cells.push(Cell::new(marker.clone(), locator.next(&marker.span())));
         cells.push(Cell::new(Content::empty(), locator.next(&())));
-        cells.push(Cell::new(
-            body.styled(ListElem::set_depth(Depth(1))),
-            locator.next(&item.body.span()),
-        ));
+        cells.push(Cell::new(body, locator.next(&item.body.span())));
     }
 
     let grid = CellGrid::new(
@@ -138,13 +141,16 @@ pub fn layout_enum(
             body += ParbreakElem::shared();
         }
 
-        cells.push(Cell::new(Content::empty(), locator.next(&())));
+        let (start, body) = tag_item(
+            engine,
+            &mut locator,
+            item.clone().pack(),
+            body.styled(EnumElem::set_parents(smallvec![number])),
+        );
+        cells.push(Cell::new(start, locator.next(&())));
         cells.push(Cell::new(resolved, locator.next(&())));
         cells.push(Cell::new(Content::empty(), locator.next(&())));
-        cells.push(Cell::new(
-            body.styled(EnumElem::set_parents(smallvec![number])),
-            locator.next(&item.body.span()),
-        ));
+        cells.push(Cell::new(body, locator.next(&item.body.span())));
         number =
             if reversed { number.saturating_sub(1) } else { number.saturating_add(1) };
     }
@@ -163,3 +169,29 @@ pub fn layout_enum(
 
     layouter.layout(engine)
 }
+
+/// Surrounds a list item with start and end tags if tagged PDFs are requested.
+///
+/// List items are not realized on their own, so they would otherwise not be
+/// visible in the frames. The tags allow PDF export to find them. Items that
+/// already have a location (because they are labelled) have their tags emitted
+/// during realization and are skipped here.
+///
+/// Returns the content of the item's first cell and its body, which hold the
+/// start and end tag, respectively.
+fn tag_item(
+    engine: &Engine,
+    locator: &mut SplitLocator,
+    mut item: Content,
+    body: Content,
+) -> (Content, Content) {
+    if !engine.pdf_tags() || item.location().is_some() {
+        return (Content::empty(), body);
+    }
+
+    let key = typst_utils::hash128(&item);
+    let loc = locator.next_location(engine.introspector, key);
+    item.set_location(loc);
+    item.mark_prepared();
+    (TagElem::packed(Tag::Start(item)), body + TagElem::packed(Tag::End(loc, key)))
+}
@@ -1,6 +1,7 @@
-use typst_library::foundations::StyleChain;
+use typst_library::foundations::{StyleChain, Styles};
 use typst_library::layout::{Fragment, Frame, FrameItem, HideElem, Point};
 use typst_library::model::{Destination, LinkElem};
+use typst_library::pdf::{AnnotElem, Annotation};
 
 /// Frame-level modifications resulting from styles that do not impose any
 /// layout structure.
@@ -17,10 +18,13 @@ use typst_library::model::{Destination, LinkElem};
 /// Currently existing frame modifiers are:
 /// - `HideElem::hidden`
 /// - `LinkElem::dests`
+/// - `AnnotElem::current`
 #[derive(Debug, Clone)]
 pub struct FrameModifiers {
     /// A destination to link to.
     dest: Option<Destination>,
+    /// An annotation covering the frame.
+    annot: Option<Annotation>,
     /// Whether the contents of the frame should be hidden.
     hidden: bool,
 }
@@ -30,6 +34,7 @@ impl FrameModifiers {
     pub fn get_in(styles: StyleChain) -> Self {
         Self {
             dest: LinkElem::current_in(styles),
+            annot: AnnotElem::current_in(styles),
             hidden: HideElem::hidden_in(styles),
         }
     }
@@ -57,6 +62,11 @@ impl FrameModify for Frame {
             self.push(Point::zero(), FrameItem::Link(dest.clone(), size));
         }
 
+        if let Some(annot) = &modifiers.annot {
+            let size = self.size();
+            self.push(Point::zero(), FrameItem::Annotation(annot.clone(), size));
+        }
+
         if modifiers.hidden {
             self.hide();
         }
@@ -95,16 +105,18 @@ where
 {
     let modifiers = FrameModifiers::get_in(styles);
 
-    // Disable the current link internally since it's already applied at this
-    // level of layout. This means we don't generate redundant nested links,
-    // which may bloat the output considerably.
-    let reset;
-    let outer = styles;
-    let mut styles = styles;
+    // Disable the current link and annotation internally since they're
+    // already applied at this level of layout. This means we don't generate
+    // redundant nested links and annotations, which may bloat the output
+    // considerably.
+    let mut reset = Styles::new();
     if modifiers.dest.is_some() {
-        reset = LinkElem::set_current(None).wrap();
-        styles = outer.chain(&reset);
+        reset.set(LinkElem::set_current(None));
+    }
+    if modifiers.annot.is_some() {
+        reset.set(AnnotElem::set_current(None));
     }
+    let styles = styles.chain(&reset);
 
     layout(styles).modified(&modifiers)
 }
@@ -85,8 +85,9 @@ fn layout_document_impl(
 
     let pages = layout_pages(&mut engine, &mut children, &mut locator, styles)?;
     let introspector = Introspector::paged(&pages);
+    let tagged = engine.pdf_tags();
 
-    Ok(PagedDocument { pages, info, introspector })
+    Ok(PagedDocument { pages, info, introspector, tagged })
 }
 
 /// Layouts the document's pages.
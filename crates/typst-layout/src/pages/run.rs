@@ -13,6 +13,7 @@ use typst_library::layout::{
     VAlignment,
 };
 use typst_library::model::Numbering;
+use typst_library::pdf::{ArtifactElem, ArtifactKind};
 use typst_library::routines::{Pair, Routines};
 use typst_library::text::{LocalName, TextElem};
 use typst_library::visualize::Paint;
@@ -184,10 +185,20 @@ fn layout_page_run_impl(
         FlowMode::Root,
     )?;
 
-    // Layouts a single marginal.
-    let mut layout_marginal = |content: &Option<Content>, area, align| {
+    // Layouts a single marginal. For tagged PDFs, marginals are marked as
+    // artifacts so that they are excluded from the structure tree.
+    let pdf_tags = engine.pdf_tags();
+    let mut layout_marginal = |content: &Option<Content>, area, align, kind| {
         let Some(content) = content else { return Ok(None) };
-        let aligned = content.clone().styled(AlignElem::set_alignment(align));
+        let body = if pdf_tags {
+            ArtifactElem::new(content.clone())
+                .with_kind(kind)
+                .pack()
+                .spanned(content.span())
+        } else {
+            content.clone()
+        };
+        let aligned = body.styled(AlignElem::set_alignment(align));
         crate::layout_frame(
             &mut engine,
             &aligned,
@@ -210,10 +221,20 @@ fn layout_page_run_impl(
             fill: fill.clone(),
             numbering: numbering.clone(),
             supplement: supplement.clone(),
-            header: layout_marginal(header, header_size, Alignment::BOTTOM)?,
-            footer: layout_marginal(footer, footer_size, Alignment::TOP)?,
-            background: layout_marginal(background, full_size, mid)?,
-            foreground: layout_marginal(foreground, full_size, mid)?,
+            header: layout_marginal(
+                header,
+                header_size,
+                Alignment::BOTTOM,
+                ArtifactKind::Header,
+            )?,
+            footer: layout_marginal(
+                footer,
+                footer_size,
+                Alignment::TOP,
+                ArtifactKind::Footer,
+            )?,
+            background: layout_marginal(background, full_size, mid, ArtifactKind::Page)?,
+            foreground: layout_marginal(foreground, full_size, mid, ArtifactKind::Page)?,
             margin,
             binding,
             two_sided,
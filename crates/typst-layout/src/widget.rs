@@ -0,0 +1,36 @@
+use typst_library::diag::SourceResult;
+use typst_library::engine::Engine;
+use typst_library::foundations::{Packed, StyleChain};
+use typst_library::introspection::Locator;
+use typst_library::layout::{Frame, FrameItem, InlineItem, Point, Size};
+use typst_library::pdf::{Widget, WidgetElem};
+
+use crate::inline::layout_box;
+
+/// Layout a form field widget as part of inline layout.
+#[typst_macros::time(span = elem.span())]
+pub fn layout_widget(
+    elem: &Packed<WidgetElem>,
+    engine: &mut Engine,
+    locator: Locator,
+    styles: StyleChain,
+    region: Size,
+) -> SourceResult<Vec<InlineItem>> {
+    let mut locator = locator.split();
+    let appearance =
+        layout_box(&elem.appearance, engine, locator.next(&()), styles, region)?;
+    let toggled = elem
+        .toggled
+        .as_ref()
+        .map(|toggled| layout_box(toggled, engine, locator.next(&()), styles, region))
+        .transpose()?;
+
+    // The widget itself carries no visible content. Exporters draw its
+    // appearance or turn it into an interactive field.
+    let mut frame = Frame::soft(appearance.size());
+    frame.set_baseline(appearance.baseline());
+    let widget = Widget { field: elem.field.clone(), appearance, toggled };
+    frame.push(Point::zero(), FrameItem::Widget(widget, elem.span()));
+
+    Ok(vec![InlineItem::Frame(frame)])
+}
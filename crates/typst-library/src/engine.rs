@@ -12,7 +12,7 @@ use crate::diag::{bail, HintedStrResult, SourceDiagnostic, SourceResult, StrResu
 use crate::foundations::{Styles, Value};
 use crate::introspection::Introspector;
 use crate::routines::Routines;
-use crate::World;
+use crate::{Feature, World};
 
 /// Holds all data needed during compilation.
 pub struct Engine<'a> {
@@ -46,6 +46,12 @@ impl Engine<'_> {
         }
     }
 
+    /// Whether to emit the introspection tags that tagged PDF export needs,
+    /// which is controlled by the [`PdfTags`](Feature::PdfTags) feature.
+    pub fn pdf_tags(&self) -> bool {
+        self.world.library().features.is_enabled(Feature::PdfTags)
+    }
+
     /// Runs tasks on the engine in parallel.
     pub fn parallelize<P, I, T, U, F>(&mut self, iter: P, f: F) -> impl Iterator<Item = U>
     where
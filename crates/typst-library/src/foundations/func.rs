@@ -287,6 +287,16 @@ impl Func {
         }
     }
 
+    /// Extract the user-defined closure, if it is one. For a function with
+    /// pre-applied arguments, this is the closure it was derived from.
+    pub fn to_closure(&self) -> Option<&Closure> {
+        match &self.repr {
+            Repr::Closure(closure) => Some(closure),
+            Repr::With(with) => with.0.to_closure(),
+            _ => None,
+        }
+    }
+
     /// Call the function with the given context and arguments.
     pub fn call<A: IntoArgs>(
         &self,
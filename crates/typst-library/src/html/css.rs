@@ -0,0 +1,322 @@
+//! Conversion of Typst styles into CSS.
+
+use std::fmt::{self, Display, Write};
+
+use ecow::{eco_format, EcoString};
+use typst_utils::{round_with_precision, Numeric};
+
+use crate::foundations::StyleChain;
+use crate::html::HtmlElem;
+use crate::layout::{Abs, AlignElem, Alignment, HAlignment, Rel, Sides, VAlignment};
+use crate::text::{FontList, FontStyle, FontVariant, FontWeight, TextElem};
+use crate::visualize::{FixedStroke, Paint};
+
+/// A list of CSS declarations, suitable for a `style` attribute.
+#[derive(Debug, Default)]
+pub struct Properties(EcoString);
+
+impl Properties {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a declaration.
+    pub fn push(&mut self, property: &str, value: impl Display) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        write!(self.0, "{property}: {value};").unwrap();
+    }
+
+    /// The declarations as a string, if any were added.
+    pub fn into_inner(self) -> Option<EcoString> {
+        (!self.0.is_empty()).then_some(self.0)
+    }
+}
+
+/// The text styles that translate to CSS.
+///
+/// These are the fill, size, weight, style, and font family of the text.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct TextStyle {
+    fill: Paint,
+    size: Abs,
+    weight: FontWeight,
+    style: FontStyle,
+    font: FontList,
+}
+
+impl TextStyle {
+    /// Resolves the text styles in a style chain.
+    pub fn resolve(styles: StyleChain) -> Self {
+        Self {
+            fill: TextElem::fill_in(styles),
+            size: TextElem::size_in(styles),
+            weight: TextElem::weight_in(styles),
+            style: TextElem::style_in(styles),
+            font: TextElem::font_in(styles).clone(),
+        }
+    }
+}
+
+/// Translates the text styles that differ from the ones text inherits from the
+/// `<body>`.
+pub fn text(styles: StyleChain) -> Properties {
+    let outer = HtmlElem::body_text_in(styles)
+        .unwrap_or_else(|| TextStyle::resolve(StyleChain::default()));
+    text_diff(&TextStyle::resolve(styles), &outer)
+}
+
+/// Translates the text styles that differ from the outer ones.
+pub fn text_diff(style: &TextStyle, outer: &TextStyle) -> Properties {
+    let mut props = Properties::new();
+
+    if style.fill != outer.fill {
+        if let Some(color) = color(&style.fill) {
+            props.push("color", color);
+        }
+    }
+
+    if style.size != outer.size {
+        props.push("font-size", length(style.size));
+    }
+
+    if style.weight != outer.weight {
+        props.push("font-weight", style.weight.to_number());
+    }
+
+    if style.style != outer.style {
+        props.push("font-style", font_style(style.style));
+    }
+
+    if style.font != outer.font {
+        let families = style
+            .font
+            .into_iter()
+            .map(|family| FontFamily(family.as_str()))
+            .collect::<Vec<_>>();
+        props.push("font-family", Join(&families));
+    }
+
+    props
+}
+
+/// Translates the horizontal alignment into a `text-align` declaration, if it
+/// differs from the default.
+pub fn align(props: &mut Properties, styles: StyleChain) {
+    let x = AlignElem::alignment_in(styles).x();
+    if x == AlignElem::alignment_in(StyleChain::default()).x() {
+        return;
+    }
+
+    if let Some(x) = x {
+        props.push("text-align", h_alignment(x));
+    }
+}
+
+/// Translates an alignment on both axes into `text-align` and `vertical-align`
+/// declarations, as used for table cells.
+pub fn cell_align(props: &mut Properties, align: Alignment) {
+    if let Some(x) = align.x() {
+        props.push("text-align", h_alignment(x));
+    }
+
+    if let Some(y) = align.y() {
+        props.push(
+            "vertical-align",
+            match y {
+                VAlignment::Top => "top",
+                VAlignment::Horizon => "middle",
+                VAlignment::Bottom => "bottom",
+            },
+        );
+    }
+}
+
+/// The CSS keyword for a horizontal alignment.
+fn h_alignment(x: HAlignment) -> &'static str {
+    match x {
+        HAlignment::Start => "start",
+        HAlignment::Left => "left",
+        HAlignment::Center => "center",
+        HAlignment::Right => "right",
+        HAlignment::End => "end",
+    }
+}
+
+/// Translates the size, fill, stroke, and inset of a block or box.
+pub fn container(
+    props: &mut Properties,
+    width: Option<Rel<Abs>>,
+    height: Option<Rel<Abs>>,
+    fill: Option<Paint>,
+    stroke: Sides<Option<FixedStroke>>,
+    inset: Sides<Rel<Abs>>,
+) {
+    if let Some(width) = width {
+        props.push("width", rel(width));
+    }
+
+    if let Some(height) = height {
+        props.push("height", rel(height));
+    }
+
+    if let Some(color) = fill.as_ref().and_then(color) {
+        props.push("background-color", color);
+    }
+
+    for (name, side) in [
+        ("border-top", &stroke.top),
+        ("border-right", &stroke.right),
+        ("border-bottom", &stroke.bottom),
+        ("border-left", &stroke.left),
+    ] {
+        let Some(stroke) = side else { continue };
+        if let Some(color) = color(&stroke.paint) {
+            props.push(name, format_args!("{} solid {color}", length(stroke.thickness)));
+        }
+    }
+
+    if !inset.is_zero() {
+        props.push(
+            "padding",
+            format_args!(
+                "{} {} {} {}",
+                rel(inset.top),
+                rel(inset.right),
+                rel(inset.bottom),
+                rel(inset.left),
+            ),
+        );
+    }
+}
+
+/// Translates a solid paint into a CSS color.
+///
+/// Gradients and tilings have no direct CSS equivalent and are skipped.
+pub fn color(paint: &Paint) -> Option<EcoString> {
+    match paint {
+        Paint::Solid(color) => Some(color.to_hex()),
+        Paint::Gradient(_) | Paint::Tiling(_) => None,
+    }
+}
+
+/// Formats an absolute length in points.
+pub fn length(abs: Abs) -> EcoString {
+    eco_format!("{}pt", round_with_precision(abs.to_pt(), 2))
+}
+
+/// Formats a relative length, using `calc` if it has both components.
+pub fn rel(rel: Rel<Abs>) -> EcoString {
+    let percent = round_with_precision(rel.rel.get() * 100.0, 2);
+    if rel.rel.is_zero() {
+        length(rel.abs)
+    } else if rel.abs.is_zero() {
+        eco_format!("{percent}%")
+    } else {
+        eco_format!("calc({percent}% + {})", length(rel.abs))
+    }
+}
+
+/// Produces an `@font-face` rule that makes the font at the given URL
+/// available under the family name for the variant.
+pub fn font_face(family: &str, variant: FontVariant, url: &str) -> EcoString {
+    eco_format!(
+        "@font-face {{\n  font-family: {};\n  src: url(\"{url}\");\n  \
+         font-weight: {};\n  font-style: {};\n}}\n",
+        FontFamily(family),
+        variant.weight.to_number(),
+        font_style(variant.style),
+    )
+}
+
+/// Extracts the font families from the `font-family` declaration of a `style`
+/// attribute produced by [`text`].
+pub fn font_families(style: &str) -> Vec<EcoString> {
+    let Some((_, rest)) = style.split_once("font-family: ") else {
+        return vec![];
+    };
+
+    let mut families = vec![];
+    let mut current: Option<EcoString> = None;
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => match current.take() {
+                Some(family) => families.push(family),
+                None => current = Some(EcoString::new()),
+            },
+            ';' if current.is_none() => break,
+            '\\' => {
+                if let (Some(family), Some(c)) = (&mut current, chars.next()) {
+                    family.push(c);
+                }
+            }
+            c => {
+                if let Some(family) = &mut current {
+                    family.push(c);
+                }
+            }
+        }
+    }
+    families
+}
+
+/// The CSS keyword for a font style.
+fn font_style(style: FontStyle) -> &'static str {
+    match style {
+        FontStyle::Normal => "normal",
+        FontStyle::Italic => "italic",
+        FontStyle::Oblique => "oblique",
+    }
+}
+
+/// A quoted font family name.
+///
+/// Single quotes are used because double quotes would need to be escaped in
+/// the `style` attribute.
+struct FontFamily<'a>(&'a str);
+
+impl Display for FontFamily<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('\'')?;
+        for c in self.0.chars() {
+            if matches!(c, '\'' | '\\') {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        f.write_char('\'')
+    }
+}
+
+/// Displays items separated by commas.
+struct Join<'a, T>(&'a [T]);
+
+impl<T: Display> Display for Join<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            item.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_families_round_trip() {
+        let families = ["Libertinus Serif", "It's", "Back\\slash"];
+        let style = eco_format!(
+            "color: #000000; font-family: {}; font-size: 12pt;",
+            Join(&families.map(FontFamily))
+        );
+        assert_eq!(font_families(&style), families.map(EcoString::from));
+    }
+}
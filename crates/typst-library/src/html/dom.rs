@@ -9,6 +9,7 @@ use crate::foundations::{cast, Dict, Repr, Str};
 use crate::introspection::{Introspector, Tag};
 use crate::layout::Frame;
 use crate::model::DocumentInfo;
+use crate::text::Lang;
 
 /// An HTML document.
 #[derive(Debug, Clone)]
@@ -17,6 +18,8 @@ pub struct HtmlDocument {
     pub root: HtmlElement,
     /// Details about the document.
     pub info: DocumentInfo,
+    /// The language that most of the document is written in.
+    pub lang: Lang,
     /// Provides the ability to execute queries on the document.
     pub introspector: Introspector,
 }
@@ -31,7 +34,7 @@ pub enum HtmlNode {
     /// Another element.
     Element(HtmlElement),
     /// A frame that will be displayed as an embedded SVG.
-    Frame(Frame),
+    Frame(HtmlFrame),
 }
 
 impl HtmlNode {
@@ -47,6 +50,24 @@ impl From<HtmlElement> for HtmlNode {
     }
 }
 
+/// Finds the HTML element generated by an introspectable element, given the
+/// index of the element's start tag among its siblings.
+///
+/// This is the first HTML element between the start tag and the matching end
+/// tag, if any.
+pub fn generated_element(nodes: &[HtmlNode], start: usize) -> Option<usize> {
+    let HtmlNode::Tag(Tag::Start(elem)) = &nodes[start] else { return None };
+    let loc = elem.location()?;
+    for (i, node) in nodes.iter().enumerate().skip(start + 1) {
+        match node {
+            HtmlNode::Tag(Tag::End(end, _)) if *end == loc => return None,
+            HtmlNode::Element(_) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
 /// An HTML element.
 #[derive(Debug, Clone, Hash)]
 pub struct HtmlElement {
@@ -92,6 +113,22 @@ impl HtmlElement {
     }
 }
 
+/// A frame that will be displayed as an embedded SVG.
+#[derive(Debug, Clone, Hash)]
+pub struct HtmlFrame {
+    /// The laid-out content.
+    pub inner: Frame,
+    /// A description of the frame's contents for assistive technology.
+    pub alt: Option<EcoString>,
+}
+
+impl HtmlFrame {
+    /// Wraps a laid-out frame without a description.
+    pub fn new(inner: Frame) -> Self {
+        Self { inner, alt: None }
+    }
+}
+
 /// The tag of an HTML element.
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 pub struct HtmlTag(PicoStr);
@@ -169,6 +206,11 @@ impl HtmlAttrs {
     pub fn push(&mut self, attr: HtmlAttr, value: impl Into<EcoString>) {
         self.0.push((attr, value.into()));
     }
+
+    /// Get the value of an attribute.
+    pub fn get(&self, attr: HtmlAttr) -> Option<&EcoString> {
+        self.0.iter().find(|&&(key, _)| key == attr).map(|(_, value)| value)
+    }
 }
 
 cast! {
@@ -475,6 +517,37 @@ pub mod tag {
         wbr
     }
 
+    // MathML Core elements, which may be embedded into HTML.
+    tags! {
+        math
+        merror
+        mfrac
+        mi
+        mmultiscripts
+        mn
+        mo
+        mover
+        mpadded
+        mphantom
+        mprescripts
+        mroot
+        mrow
+        ms
+        mspace
+        msqrt
+        mstyle
+        msub
+        msubsup
+        msup
+        mtable
+        mtd
+        mtext
+        mtr
+        munder
+        munderover
+        semantics
+    }
+
     /// Whether this is a void tag whose associated element may not have a
     /// children.
     pub fn is_void(tag: HtmlTag) -> bool {
@@ -607,6 +680,7 @@ pub mod tag {
                 | self::sup
                 | self::var
                 | self::u
+                | self::math
         )
     }
 
@@ -646,19 +720,38 @@ pub mod attr {
     }
 
     attrs! {
+        accent
+        accentunder
+        alt
         charset
         cite
         colspan
+        columnalign
         content
+        display
+        displaystyle
+        height
         href
+        id
+        lang
+        linethickness
+        mathvariant
+        movablelimits
         name
+        rel
         reversed
         role
         rowspan
+        scope
+        src
         start
+        stretchy
         style
         value
+        width
+        xmlns
     }
 
+    pub const aria_current: HtmlAttr = HtmlAttr::constant("aria-current");
     pub const aria_level: HtmlAttr = HtmlAttr::constant("aria-level");
 }
@@ -1,12 +1,19 @@
 //! HTML output.
 
+pub mod css;
 mod dom;
 
 pub use self::dom::*;
 
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
+use typst_syntax::Span;
 
-use crate::foundations::{elem, Content, Module, Scope};
+use crate::diag::{warning, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Content, Module, NativeElement, Packed, Scope, Show, StyleChain, TargetElem,
+};
+use crate::introspection::{Locatable, Location};
 
 /// Create a module with all HTML definitions.
 pub fn module() -> Module {
@@ -14,6 +21,8 @@ pub fn module() -> Module {
     html.start_category(crate::Category::Html);
     html.define_elem::<HtmlElem>();
     html.define_elem::<FrameElem>();
+    html.define_elem::<SplitElem>();
+    html.define_elem::<HeadElem>();
     Module::new("html", html)
 }
 
@@ -54,6 +63,12 @@ pub struct HtmlElem {
     #[positional]
     #[borrowed]
     pub body: Option<Content>,
+
+    /// The text styles that all text inherits from the `<body>`. If this is
+    /// `None`, text inherits the default styles.
+    #[internal]
+    #[ghost]
+    pub body_text: Option<css::TextStyle>,
 }
 
 impl HtmlElem {
@@ -64,6 +79,46 @@ impl HtmlElem {
     }
 }
 
+/// Creates a link to the element at the given location in HTML export.
+///
+/// Link targets receive their `id` during export, so it is only known once the
+/// document has been introspected. Targets that do not generate an HTML
+/// element, like metadata, cannot carry an `id`. Links to them are dropped with
+/// a warning.
+pub fn link(
+    engine: &mut Engine,
+    location: Location,
+    body: Content,
+    span: Span,
+) -> Content {
+    let Some(id) = engine.introspector.html_id(location) else {
+        engine.sink.warn(warning!(
+            span, "link target does not generate an HTML element";
+            hint: "only elements that are exported as HTML elements can be linked to"
+        ));
+        return body;
+    };
+
+    HtmlElem::new(tag::a)
+        .with_attr(attr::href, eco_format!("#{id}"))
+        .with_body(Some(body))
+        .pack()
+        .spanned(span)
+}
+
+/// Turns text into a lowercase, hyphen-separated `id`.
+pub fn slugify(text: &str) -> EcoString {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').into()
+}
+
 /// An element that lays out its content as an inline SVG.
 ///
 /// Sometimes, converting Typst content to HTML is not desirable. This can be
@@ -80,4 +135,76 @@ pub struct FrameElem {
     #[positional]
     #[required]
     pub body: Content,
+
+    /// A text describing the frame's contents, which is made available to
+    /// assistive technology.
+    ///
+    /// Text in the frame stays selectable, but it is positioned like in the
+    /// laid-out content and thus not necessarily read in a sensible order.
+    /// The description should therefore convey everything the frame shows.
+    pub alt: Option<EcoString>,
+}
+
+/// Starts a new file when exporting the document as a multi-file HTML site.
+///
+/// When the document is exported as a single HTML file, this element has no
+/// effect. To additionally split the site at headings, pass
+/// `--html-split-level` to `typst compile`.
+///
+/// The element must be placed at the top level of the document, i.e. not
+/// within a paragraph or another element.
+///
+/// ```typ
+/// = Introduction
+/// ...
+///
+/// #html.split()
+///
+/// = Appendix
+/// ...
+/// ```
+#[elem(Locatable, Show)]
+pub struct SplitElem {}
+
+impl Show for Packed<SplitElem> {
+    fn show(&self, _: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
+
+/// Adds content to the `<head>` of the generated HTML document.
+///
+/// This is the way to attach stylesheets, scripts, and other metadata to a
+/// document whose `<head>` is generated by Typst. The content is moved into the
+/// `<head>`, no matter where in the document the element is placed. Outside of
+/// HTML export, the element has no effect.
+///
+/// If you generate your own `<html>` element, Typst does not generate a
+/// `<head>`. Then, this element produces a plain `<head>` element in place.
+///
+/// ```typ
+/// #html.head({
+///   html.elem("link", attrs: (rel: "stylesheet", href: "style.css"))
+///   html.elem("script", attrs: (src: "script.js"))
+///   html.elem("style", "p { hyphens: auto; }")
+/// })
+/// ```
+#[elem(Show)]
+pub struct HeadElem {
+    /// The content to add to the `<head>`.
+    #[required]
+    pub body: Content,
+}
+
+impl Show for Packed<HeadElem> {
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        if !TargetElem::target_in(styles).is_html() {
+            return Ok(Content::empty());
+        }
+
+        Ok(HtmlElem::new(tag::head)
+            .with_body(Some(self.body.clone()))
+            .pack()
+            .spanned(self.span()))
+    }
 }
@@ -4,13 +4,13 @@ use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::sync::RwLock;
 
-use ecow::EcoVec;
+use ecow::{EcoString, EcoVec};
 use smallvec::SmallVec;
 use typst_utils::NonZeroExt;
 
 use crate::diag::{bail, StrResult};
 use crate::foundations::{Content, Label, Repr, Selector};
-use crate::html::HtmlNode;
+use crate::html::{attr, generated_element, HtmlNode};
 use crate::introspection::{Location, Tag};
 use crate::layout::{Frame, FrameItem, Page, Point, Position, Transform};
 use crate::model::Numbering;
@@ -35,6 +35,9 @@ pub struct Introspector {
     locations: HashMap<Location, usize>,
     /// Accelerates lookup of elements by label.
     labels: MultiMap<Label, usize>,
+    /// The `id` attributes of the HTML elements generated by introspectable
+    /// elements.
+    html_ids: HashMap<Location, EcoString>,
 
     /// Caches queries done on the introspector. This is important because
     /// even if all top-level queries are distinct, they often have shared
@@ -275,6 +278,12 @@ impl Introspector {
             .and_then(|slot| slot.as_ref())
     }
 
+    /// Gets the `id` of the HTML element generated by the element at the given
+    /// location, if any.
+    pub fn html_id(&self, location: Location) -> Option<&EcoString> {
+        self.html_ids.get(&location)
+    }
+
     /// Gets the page supplement for the given location, if any.
     pub fn page_supplement(&self, location: Location) -> Content {
         let page = self.page(location);
@@ -361,6 +370,7 @@ struct IntrospectorBuilder {
     keys: MultiMap<u128, Location>,
     locations: HashMap<Location, usize>,
     labels: MultiMap<Label, usize>,
+    html_ids: HashMap<Location, EcoString>,
 }
 
 impl IntrospectorBuilder {
@@ -435,18 +445,23 @@ impl IntrospectorBuilder {
 
     /// Processes the tags in the HTML element.
     fn discover_in_html(&mut self, sink: &mut Vec<Pair>, nodes: &[HtmlNode]) {
-        for node in nodes {
+        for (i, node) in nodes.iter().enumerate() {
             match node {
-                HtmlNode::Tag(tag) => self.discover_in_tag(
-                    sink,
-                    tag,
-                    Position { page: NonZeroUsize::ONE, point: Point::zero() },
-                ),
+                HtmlNode::Tag(tag) => {
+                    if let Tag::Start(elem) = tag {
+                        self.discover_html_id(elem, nodes, i);
+                    }
+                    self.discover_in_tag(
+                        sink,
+                        tag,
+                        Position { page: NonZeroUsize::ONE, point: Point::zero() },
+                    )
+                }
                 HtmlNode::Text(_, _) => {}
                 HtmlNode::Element(elem) => self.discover_in_html(sink, &elem.children),
                 HtmlNode::Frame(frame) => self.discover_in_frame(
                     sink,
-                    frame,
+                    &frame.inner,
                     NonZeroUsize::ONE,
                     Transform::identity(),
                 ),
@@ -454,6 +469,19 @@ impl IntrospectorBuilder {
         }
     }
 
+    /// Remembers the `id` of the HTML element generated by the element whose
+    /// start tag is at index `start` in `nodes`.
+    fn discover_html_id(&mut self, elem: &Content, nodes: &[HtmlNode], start: usize) {
+        let Some(HtmlNode::Element(generated)) =
+            generated_element(nodes, start).map(|i| &nodes[i])
+        else {
+            return;
+        };
+        if let Some(id) = generated.attrs.get(attr::id) {
+            self.html_ids.insert(elem.location().unwrap(), id.clone());
+        }
+    }
+
     /// Handle a tag.
     fn discover_in_tag(&mut self, sink: &mut Vec<Pair>, tag: &Tag, position: Position) {
         match tag {
@@ -488,6 +516,7 @@ impl IntrospectorBuilder {
             keys: self.keys,
             locations: self.locations,
             labels: self.labels,
+            html_ids: self.html_ids,
             queries: QueryCache::default(),
         }
     }
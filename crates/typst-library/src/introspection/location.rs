@@ -108,6 +108,13 @@ impl Repr for Location {
 /// Makes this element as locatable through the introspector.
 pub trait Locatable {}
 
+/// Marks this element as part of the structure of tagged PDFs.
+///
+/// Such elements are only located (and thus surrounded by introspection tags)
+/// if the [`PdfTags`](crate::Feature::PdfTags) feature is enabled. They can't be
+/// queried unless they are also [`Locatable`].
+pub trait Tagged {}
+
 /// Marks this element as not being queryable even though it is locatable for
 /// internal reasons.
 pub trait Unqueriable {}
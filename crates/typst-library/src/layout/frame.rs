@@ -11,6 +11,7 @@ use crate::foundations::{cast, dict, Dict, Label, Value};
 use crate::introspection::{Location, Tag};
 use crate::layout::{Abs, Axes, FixedAlignment, Length, Point, Size, Transform};
 use crate::model::Destination;
+use crate::pdf::{Annotation, Widget};
 use crate::text::TextItem;
 use crate::visualize::{Color, Curve, FixedStroke, Geometry, Image, Paint, Shape};
 
@@ -476,6 +477,10 @@ pub enum FrameItem {
     Link(Destination, Size),
     /// An introspectable element that produced something within this frame.
     Tag(Tag),
+    /// An interactive form field.
+    Widget(Widget, Span),
+    /// An annotation covering an area of the given size.
+    Annotation(Annotation, Size),
 }
 
 impl Debug for FrameItem {
@@ -487,6 +492,8 @@ impl Debug for FrameItem {
             Self::Image(image, _, _) => write!(f, "{image:?}"),
             Self::Link(dest, _) => write!(f, "Link({dest:?})"),
             Self::Tag(tag) => write!(f, "{tag:?}"),
+            Self::Widget(widget, _) => write!(f, "Widget({:?})", widget.field.name),
+            Self::Annotation(annot, _) => write!(f, "Annotation({:?})", annot.kind),
         }
     }
 }
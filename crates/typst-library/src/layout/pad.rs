@@ -1,7 +1,9 @@
 use crate::diag::SourceResult;
 use crate::engine::Engine;
-use crate::foundations::{elem, Content, NativeElement, Packed, Show, StyleChain};
-use crate::layout::{BlockElem, Length, Rel};
+use crate::foundations::{
+    elem, Content, NativeElement, Packed, Show, StyleChain, TargetElem,
+};
+use crate::layout::{BlockBody, BlockElem, Length, Rel, Sides};
 
 /// Adds spacing around content.
 ///
@@ -57,7 +59,22 @@ pub struct PadElem {
 }
 
 impl Show for Packed<PadElem> {
-    fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+    fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        // In HTML, the padding becomes the inset of a block.
+        if TargetElem::target_in(styles).is_html() {
+            let inset = Sides::new(
+                Some(self.left(styles)),
+                Some(self.top(styles)),
+                Some(self.right(styles)),
+                Some(self.bottom(styles)),
+            );
+            return Ok(BlockElem::new()
+                .with_inset(inset)
+                .with_body(Some(BlockBody::Content(self.body.clone())))
+                .pack()
+                .spanned(self.span()));
+        }
+
         Ok(BlockElem::multi_layouter(self.clone(), engine.routines.layout_pad)
             .pack()
             .spanned(self.span()))
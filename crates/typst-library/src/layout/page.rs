@@ -461,6 +461,9 @@ pub struct PagedDocument {
     pub info: DocumentInfo,
     /// Provides the ability to execute queries on the document.
     pub introspector: Introspector,
+    /// Whether the document was laid out with the introspection tags that
+    /// tagged PDF export needs.
+    pub tagged: bool,
 }
 
 /// A finished page.
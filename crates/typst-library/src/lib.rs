@@ -235,6 +235,9 @@ impl FromIterator<Feature> for Features {
 #[non_exhaustive]
 pub enum Feature {
     Html,
+    /// Emits the introspection tags that tagged PDF export needs to build a
+    /// complete structure tree, e.g. for paragraphs, lists, and tables.
+    PdfTags,
 }
 
 /// A group of related standard library definitions.
@@ -1,5 +1,6 @@
 use std::num::NonZeroUsize;
 
+use ecow::EcoString;
 use typst_utils::NonZeroExt;
 use unicode_math_class::MathClass;
 
@@ -7,8 +8,9 @@ use crate::diag::SourceResult;
 use crate::engine::Engine;
 use crate::foundations::{
     elem, Content, NativeElement, Packed, Show, ShowSet, Smart, StyleChain, Styles,
-    Synthesize,
+    Synthesize, TargetElem,
 };
+use crate::html::{attr, tag, HtmlElem};
 use crate::introspection::{Count, Counter, CounterUpdate, Locatable};
 use crate::layout::{
     AlignElem, Alignment, BlockElem, InlineElem, OuterHAlignment, SpecificAlignment,
@@ -101,6 +103,21 @@ pub struct EquationElem {
     /// ```
     pub supplement: Smart<Option<Supplement>>,
 
+    /// An alternative description of the equation.
+    ///
+    /// This text is used by assistive technology in tagged PDFs and is
+    /// required when exporting to PDF/UA-1.
+    ///
+    /// ```example
+    /// #math.equation(
+    ///   alt: "a squared plus b squared equals c squared",
+    ///   block: true,
+    ///   $a^2 + b^2 = c^2$,
+    /// )
+    /// ```
+    #[borrowed]
+    pub alt: Option<EcoString>,
+
     /// The contents of the equation.
     #[required]
     pub body: Content,
@@ -167,6 +184,16 @@ impl Synthesize for Packed<EquationElem> {
 
 impl Show for Packed<EquationElem> {
     fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        if TargetElem::target_in(styles).is_html() {
+            // The body is converted to MathML when the `<math>` element is
+            // exported.
+            let mut elem = HtmlElem::new(tag::math).with_body(Some(self.body.clone()));
+            if self.block(styles) {
+                elem = elem.with_attr(attr::display, "block");
+            }
+            return Ok(elem.pack().spanned(self.span()));
+        }
+
         if self.block(styles) {
             Ok(BlockElem::multi_layouter(
                 self.clone(),
@@ -186,7 +213,10 @@ impl ShowSet for Packed<EquationElem> {
     fn show_set(&self, styles: StyleChain) -> Styles {
         let mut out = Styles::new();
         if self.block(styles) {
-            out.set(AlignElem::set_alignment(Alignment::CENTER));
+            // In HTML, block-level MathML is centered by default.
+            if !TargetElem::target_in(styles).is_html() {
+                out.set(AlignElem::set_alignment(Alignment::CENTER));
+            }
             out.set(BlockElem::set_breakable(false));
             out.set(ParLine::set_numbering(None));
             out.set(EquationElem::set_size(MathSize::Display));
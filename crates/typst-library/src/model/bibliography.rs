@@ -24,8 +24,9 @@ use crate::engine::Engine;
 use crate::foundations::{
     elem, Bytes, CastInfo, Content, Derived, FromValue, IntoValue, Label, NativeElement,
     OneOrMultiple, Packed, Reflect, Scope, Show, ShowSet, Smart, StyleChain, Styles,
-    Synthesize, Value,
+    Synthesize, TargetElem, Value,
 };
+use crate::html::{attr, slugify, tag, HtmlElem};
 use crate::introspection::{Introspector, Locatable, Location};
 use crate::layout::{
     BlockBody, BlockElem, Em, GridCell, GridChild, GridElem, GridItem, HElem, PadElem,
@@ -38,8 +39,8 @@ use crate::model::{
 };
 use crate::routines::{EvalMode, Routines};
 use crate::text::{
-    FontStyle, Lang, LocalName, Region, Smallcaps, SubElem, SuperElem, TextElem,
-    WeightDelta,
+    FontStyle, Lang, LocalName, Region, Smallcaps, SpaceElem, SubElem, SuperElem,
+    TextElem, WeightDelta,
 };
 use crate::World;
 
@@ -220,18 +221,43 @@ impl Show for Packed<BibliographyElem> {
             );
         }
 
-        let works = Works::generate(engine).at(span)?;
+        let works = Works::generate(engine, styles).at(span)?;
         let references = works
             .references
             .as_ref()
             .ok_or("CSL style is not suitable for bibliographies")
             .at(span)?;
 
-        if references.iter().any(|(prefix, _)| prefix.is_some()) {
+        if TargetElem::target_in(styles).is_html() {
+            let items = references.iter().map(|(key, prefix, reference)| {
+                let mut body = reference.clone();
+                if let Some(prefix) = prefix {
+                    body = prefix.clone() + SpaceElem::shared().clone() + body;
+                }
+                HtmlElem::new(tag::li)
+                    .with_attr(attr::id, html_id(key))
+                    .with_body(Some(body))
+                    .pack()
+                    .spanned(span)
+            });
+            seq.push(
+                HtmlElem::new(tag::ol)
+                    .with_attr(attr::style, "list-style-type: none;")
+                    .with_body(Some(Content::sequence(items)))
+                    .pack()
+                    .spanned(span),
+            );
+            let section = HtmlElem::new(tag::section)
+                .with_attr(attr::role, "doc-bibliography")
+                .with_body(Some(Content::sequence(seq)));
+            return Ok(section.pack().spanned(span));
+        }
+
+        if references.iter().any(|(_, prefix, _)| prefix.is_some()) {
             let row_gutter = ParElem::spacing_in(styles);
 
             let mut cells = vec![];
-            for (prefix, reference) in references {
+            for (_, prefix, reference) in references {
                 cells.push(GridChild::Item(GridItem::Cell(
                     Packed::new(GridCell::new(prefix.clone().unwrap_or_default()))
                         .spanned(span),
@@ -249,7 +275,7 @@ impl Show for Packed<BibliographyElem> {
                     .spanned(span),
             );
         } else {
-            for (_, reference) in references {
+            for (_, _, reference) in references {
                 let realized = reference.clone();
                 let block = if works.hanging_indent {
                     let body = HElem::new((-INDENT).into()).pack() + realized;
@@ -542,17 +568,19 @@ impl IntoValue for CslSource {
 pub(super) struct Works {
     /// Maps from the location of a citation group to its rendered content.
     pub citations: HashMap<Location, SourceResult<Content>>,
-    /// Lists all references in the bibliography, with optional prefix, or
-    /// `None` if the citation style can't be used for bibliographies.
-    pub references: Option<Vec<(Option<Content>, Content)>>,
+    /// Lists all references in the bibliography, with their key and optional
+    /// prefix, or `None` if the citation style can't be used for
+    /// bibliographies.
+    pub references: Option<Vec<(EcoString, Option<Content>, Content)>>,
     /// Whether the bibliography should have hanging indent.
     pub hanging_indent: bool,
 }
 
 impl Works {
     /// Generate all citations and the whole bibliography.
-    pub fn generate(engine: &Engine) -> StrResult<Arc<Works>> {
-        Self::generate_impl(engine.routines, engine.world, engine.introspector)
+    pub fn generate(engine: &Engine, styles: StyleChain) -> StrResult<Arc<Works>> {
+        let html = TargetElem::target_in(styles).is_html();
+        Self::generate_impl(engine.routines, engine.world, engine.introspector, html)
     }
 
     /// The internal implementation of [`Works::generate`].
@@ -561,8 +589,9 @@ impl Works {
         routines: &Routines,
         world: Tracked<dyn World + '_>,
         introspector: Tracked<Introspector>,
+        html: bool,
     ) -> StrResult<Arc<Works>> {
-        let mut generator = Generator::new(routines, world, introspector)?;
+        let mut generator = Generator::new(routines, world, introspector, html)?;
         let rendered = generator.drive();
         let works = generator.display(&rendered)?;
         Ok(Arc::new(works))
@@ -584,6 +613,8 @@ struct Generator<'a> {
     infos: Vec<GroupInfo>,
     /// Citations with unresolved keys.
     failures: HashMap<Location, SourceResult<Content>>,
+    /// Whether the output is meant for HTML export.
+    html: bool,
 }
 
 /// Details about a group of merged citations. All citations are put into groups
@@ -616,6 +647,7 @@ impl<'a> Generator<'a> {
         routines: &'a Routines,
         world: Tracked<'a, dyn World + 'a>,
         introspector: Tracked<Introspector>,
+        html: bool,
     ) -> StrResult<Self> {
         let bibliography = BibliographyElem::find(introspector)?;
         let groups = introspector.query(&CiteGroup::elem().select());
@@ -627,6 +659,7 @@ impl<'a> Generator<'a> {
             groups,
             infos,
             failures: HashMap::new(),
+            html,
         })
     }
 
@@ -774,13 +807,15 @@ impl<'a> Generator<'a> {
         for (info, citation) in self.infos.iter().zip(&rendered.citations) {
             let supplement = |i: usize| info.subinfos.get(i)?.supplement.clone();
             let link = |i: usize| {
-                links.get(info.subinfos.get(i)?.key.resolve().as_str()).copied()
+                let key = info.subinfos.get(i)?.key;
+                Some((*links.get(key.resolve().as_str())?, key))
             };
 
             let renderer = ElemRenderer {
                 routines: self.routines,
                 world: self.world,
                 span: info.span,
+                html: self.html,
                 supplement: &supplement,
                 link: &link,
             };
@@ -812,7 +847,7 @@ impl<'a> Generator<'a> {
     fn display_references(
         &self,
         rendered: &hayagriva::Rendered,
-    ) -> StrResult<Option<Vec<(Option<Content>, Content)>>> {
+    ) -> StrResult<Option<Vec<(EcoString, Option<Content>, Content)>>> {
         let Some(rendered) = &rendered.bibliography else { return Ok(None) };
 
         // Determine for each citation key where it first occurred, so that we
@@ -834,6 +869,7 @@ impl<'a> Generator<'a> {
                 routines: self.routines,
                 world: self.world,
                 span: self.bibliography.span(),
+                html: self.html,
                 supplement: &|_| None,
                 link: &|_| None,
             };
@@ -850,9 +886,13 @@ impl<'a> Generator<'a> {
                 .map(|elem| {
                     let mut content =
                         renderer.display_elem_child(elem, &mut None, false)?;
+                    // Citations have no anchors in HTML export, so there is
+                    // nothing to link back to.
                     if let Some(location) = first_occurrences.get(item.key.as_str()) {
-                        let dest = Destination::Location(*location);
-                        content = content.linked(dest);
+                        if !self.html {
+                            let dest = Destination::Location(*location);
+                            content = content.linked(dest);
+                        }
                     }
                     StrResult::Ok(content)
                 })
@@ -866,7 +906,7 @@ impl<'a> Generator<'a> {
             // we can link to the bibliography entry.
             prefix.as_mut().unwrap_or(&mut reference).set_location(backlink);
 
-            output.push((prefix, reference));
+            output.push((item.key.as_str().into(), prefix, reference));
         }
 
         Ok(Some(output))
@@ -881,10 +921,13 @@ struct ElemRenderer<'a> {
     world: Tracked<'a, dyn World + 'a>,
     /// The span that is attached to all of the resulting content.
     span: Span,
+    /// Whether the output is meant for HTML export.
+    html: bool,
     /// Resolves the supplement of i-th citation in the request.
     supplement: &'a dyn Fn(usize) -> Option<Content>,
-    /// Resolves where the i-th citation in the request should link to.
-    link: &'a dyn Fn(usize) -> Option<Location>,
+    /// Resolves where the i-th citation in the request should link to, along
+    /// with the cited key.
+    link: &'a dyn Fn(usize) -> Option<(Location, Label)>,
 }
 
 impl ElemRenderer<'_> {
@@ -985,9 +1028,19 @@ impl ElemRenderer<'_> {
         }
 
         if let Some(hayagriva::ElemMeta::Entry(i)) = elem.meta {
-            if let Some(location) = (self.link)(i) {
-                let dest = Destination::Location(location);
-                content = content.linked(dest);
+            if let Some((location, key)) = (self.link)(i) {
+                content = if self.html {
+                    HtmlElem::new(tag::a)
+                        .with_attr(
+                            attr::href,
+                            eco_format!("#{}", html_id(key.resolve().as_str())),
+                        )
+                        .with_body(Some(content))
+                        .pack()
+                        .spanned(self.span)
+                } else {
+                    content.linked(Destination::Location(location))
+                };
             }
         }
 
@@ -1039,6 +1092,14 @@ impl ElemRenderer<'_> {
     }
 }
 
+/// The `id` of a bibliography entry in HTML export.
+///
+/// Keys may contain spaces and other characters that are not valid in an `id`,
+/// so the key is turned into a slug.
+fn html_id(key: &str) -> EcoString {
+    eco_format!("bib-{}", slugify(key))
+}
+
 /// Applies formatting to content.
 fn apply_formatting(mut content: Content, format: &hayagriva::Formatting) -> Content {
     match format.font_style {
@@ -163,10 +163,10 @@ pub struct CiteGroup {
 
 impl Show for Packed<CiteGroup> {
     #[typst_macros::time(name = "cite", span = self.span())]
-    fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+    fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
         let location = self.location().unwrap();
         let span = self.span();
-        Works::generate(engine)
+        Works::generate(engine, styles)
             .at(span)?
             .citations
             .get(&location)
@@ -1,10 +1,11 @@
 use ecow::EcoString;
+use typst_syntax::Spanned;
 
 use crate::diag::{bail, HintedStrResult, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Args, Array, Construct, Content, Datetime, Fields, OneOrMultiple, Smart,
-    StyleChain, Styles, Value,
+    cast, elem, Args, Array, Cast, Construct, Content, Datetime, Duration, Fields,
+    OneOrMultiple, Smart, StyleChain, Styles, Value,
 };
 
 /// The root element of a document and its metadata.
@@ -58,6 +59,57 @@ pub struct DocumentElem {
     /// something other than `{auto}`.
     #[ghost]
     pub date: Smart<Option<Datetime>>,
+
+    /// How PDF viewers should arrange the pages when opening the document.
+    ///
+    /// If this is `{auto}` (default), the viewer's own setting is used.
+    #[ghost]
+    pub page_layout: Smart<PageLayout>,
+
+    /// Which panel PDF viewers should show when opening the document.
+    ///
+    /// If this is `{auto}` (default), the viewer's own setting is used.
+    #[ghost]
+    pub initial_view: Smart<InitialView>,
+
+    /// Whether PDF viewers should hide their toolbars while the document is
+    /// open.
+    #[ghost]
+    #[default(false)]
+    pub hide_toolbar: bool,
+
+    /// Whether PDF viewers should show the document's [title]($document.title)
+    /// instead of the file name in the window's title bar.
+    ///
+    /// This is always enabled when exporting to PDF/UA.
+    #[ghost]
+    #[default(false)]
+    pub display_title: bool,
+
+    /// The effect with which presentation software moves from one page to the
+    /// next.
+    ///
+    /// Combine this with `{initial-view: "full-screen"}` to open the document
+    /// as a slide show.
+    #[ghost]
+    pub transition: Option<PageTransition>,
+
+    /// How long each page [transition]($document.transition) takes.
+    ///
+    /// If this is `{auto}` (default), transitions take one second. The duration
+    /// must not be negative.
+    #[parse({
+        let duration: Option<Spanned<Smart<Duration>>> =
+            args.named("transition-duration")?;
+        if let Some(Spanned { v: Smart::Custom(v), span }) = &duration {
+            if v.seconds() < 0.0 {
+                bail!(*span, "transition duration must not be negative");
+            }
+        }
+        duration.map(|duration| duration.v)
+    })]
+    #[ghost]
+    pub transition_duration: Smart<Duration>,
 }
 
 impl Construct for DocumentElem {
@@ -66,6 +118,67 @@ impl Construct for DocumentElem {
     }
 }
 
+/// How PDF viewers arrange the pages of a document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageLayout {
+    /// One page at a time.
+    Single,
+    /// A single, continuously scrolling column of pages.
+    OneColumn,
+    /// Two continuously scrolling columns with odd pages on the left.
+    TwoColumnLeft,
+    /// Two continuously scrolling columns with odd pages on the right, like in
+    /// a book bound on the left.
+    TwoColumnRight,
+    /// Two pages at a time with odd pages on the left.
+    TwoPageLeft,
+    /// Two pages at a time with odd pages on the right, like in a book bound
+    /// on the left.
+    TwoPageRight,
+}
+
+/// What PDF viewers show when opening a document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum InitialView {
+    /// Only the pages.
+    Pages,
+    /// The pages and the outline panel.
+    Outline,
+    /// The pages and a panel with page thumbnails.
+    Thumbnails,
+    /// The pages and the attachments panel.
+    Attachments,
+    /// The pages in full screen mode, without any viewer interface.
+    FullScreen,
+}
+
+/// An effect for moving from one page to the next in presentation software.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageTransition {
+    /// Two lines sweep across the screen from the middle.
+    Split,
+    /// Multiple lines sweep across the screen like window blinds.
+    Blinds,
+    /// A box grows from the center to reveal the new page.
+    Box,
+    /// A single line sweeps across the screen from left to right.
+    Wipe,
+    /// The old page dissolves gradually into the new one.
+    Dissolve,
+    /// Like `{"dissolve"}`, but sweeping from left to right.
+    Glitter,
+    /// The new page flies in.
+    Fly,
+    /// The new page pushes the old one off the screen.
+    Push,
+    /// The new page slides in over the old one.
+    Cover,
+    /// The old page slides out to uncover the new one.
+    Uncover,
+    /// The old page fades into the new one.
+    Fade,
+}
+
 /// A list of authors.
 #[derive(Debug, Default, Clone, PartialEq, Hash)]
 pub struct Author(Vec<EcoString>);
@@ -101,6 +214,18 @@ pub struct DocumentInfo {
     pub keywords: Vec<EcoString>,
     /// The document's creation date.
     pub date: Smart<Option<Datetime>>,
+    /// How viewers should arrange the pages.
+    pub page_layout: Smart<PageLayout>,
+    /// What viewers should show when opening the document.
+    pub initial_view: Smart<InitialView>,
+    /// Whether viewers should hide their toolbars.
+    pub hide_toolbar: bool,
+    /// Whether viewers should show the title instead of the file name.
+    pub display_title: bool,
+    /// The effect for moving from one page to the next.
+    pub transition: Option<PageTransition>,
+    /// How long the page transition takes.
+    pub transition_duration: Smart<Duration>,
 }
 
 impl DocumentInfo {
@@ -127,5 +252,23 @@ impl DocumentInfo {
         if has(<DocumentElem as Fields>::Enum::Date) {
             self.date = DocumentElem::date_in(chain);
         }
+        if has(<DocumentElem as Fields>::Enum::PageLayout) {
+            self.page_layout = DocumentElem::page_layout_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::InitialView) {
+            self.initial_view = DocumentElem::initial_view_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::HideToolbar) {
+            self.hide_toolbar = DocumentElem::hide_toolbar_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::DisplayTitle) {
+            self.display_title = DocumentElem::display_title_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::Transition) {
+            self.transition = DocumentElem::transition_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::TransitionDuration) {
+            self.transition_duration = DocumentElem::transition_duration_in(chain);
+        }
     }
 }
@@ -1,18 +1,20 @@
 use std::num::NonZeroUsize;
 use std::str::FromStr;
 
+use ecow::{eco_format, EcoString};
 use typst_utils::NonZeroExt;
 
 use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
     cast, elem, scope, Content, Label, NativeElement, Packed, Show, ShowSet, Smart,
-    StyleChain, Styles,
+    StyleChain, Styles, TargetElem,
 };
-use crate::introspection::{Count, Counter, CounterUpdate, Locatable, Location};
+use crate::html::{attr, tag, HtmlElem};
+use crate::introspection::{Count, Counter, CounterUpdate, Locatable, Location, Tagged};
 use crate::layout::{Abs, Em, HElem, Length, Ratio};
 use crate::model::{Destination, Numbering, NumberingPattern, ParElem};
-use crate::text::{SuperElem, TextElem, TextSize};
+use crate::text::{SpaceElem, SuperElem, TextElem, TextSize};
 use crate::visualize::{LineElem, Stroke};
 
 /// A footnote.
@@ -47,6 +49,9 @@ use crate::visualize::{LineElem, Stroke};
 /// And the online app. #footnote(<fn>)
 /// ```
 ///
+/// In HTML export, there are no pages. Instead, the footnotes are collected
+/// into a list of endnotes at the end of the document.
+///
 /// _Note:_ Set and show rules in the scope where `footnote` is called may not
 /// apply to the footnote's content. See [here][issue] for more information.
 ///
@@ -144,6 +149,24 @@ impl Show for Packed<FootnoteElem> {
         let numbering = self.numbering(styles);
         let counter = Counter::of(FootnoteElem::elem());
         let num = counter.display_at_loc(engine, loc, styles, numbering)?;
+
+        if TargetElem::target_in(styles).is_html() {
+            // Only the declaration is the target of the entry's back-link.
+            let id = html_id(engine, loc);
+            let mut link = HtmlElem::new(tag::a);
+            if !self.is_ref() {
+                link = link.with_attr(attr::id, eco_format!("{id}-ref"));
+            }
+            let link = link
+                .with_attr(attr::href, eco_format!("#{id}"))
+                .with_attr(attr::role, "doc-noteref")
+                .with_body(Some(num))
+                .pack()
+                .spanned(span);
+            let sup = HtmlElem::new(tag::sup).with_body(Some(link)).pack().spanned(span);
+            return Ok(HElem::hole().pack() + sup);
+        }
+
         let sup = SuperElem::new(num).pack().spanned(span);
         let loc = loc.variant(1);
         // Add zero-width weak spacing to make the footnote "sticky".
@@ -151,6 +174,15 @@ impl Show for Packed<FootnoteElem> {
     }
 }
 
+/// The `id` of the entry of the footnote declared at the given location in
+/// HTML export. The footnote marker has the same `id` with a `-ref` suffix.
+fn html_id(engine: &Engine, loc: Location) -> EcoString {
+    let n = engine
+        .introspector
+        .query_count_before(&FootnoteElem::elem().select(), loc);
+    eco_format!("footnote-{n}")
+}
+
 impl Count for Packed<FootnoteElem> {
     fn update(&self) -> Option<CounterUpdate> {
         (!self.is_ref()).then(|| CounterUpdate::Step(NonZeroUsize::ONE))
@@ -192,7 +224,7 @@ cast! {
 /// page run is a sequence of pages without an explicit pagebreak in between).
 /// For this reason, set and show rules for footnote entries should be defined
 /// before any page content, typically at the very start of the document.
-#[elem(name = "entry", title = "Footnote Entry", Show, ShowSet)]
+#[elem(name = "entry", title = "Footnote Entry", Show, ShowSet, Tagged)]
 pub struct FootnoteEntry {
     /// The footnote for this entry. Its location can be used to determine
     /// the footnote counter state.
@@ -293,6 +325,26 @@ impl Show for Packed<FootnoteEntry> {
         };
 
         let num = counter.display_at_loc(engine, loc, styles, numbering)?;
+
+        if TargetElem::target_in(styles).is_html() {
+            let id = html_id(engine, loc);
+            let sup = HtmlElem::new(tag::sup).with_body(Some(num)).pack().spanned(span);
+            let backlink = HtmlElem::new(tag::a)
+                .with_attr(attr::href, eco_format!("#{id}-ref"))
+                .with_attr(attr::role, "doc-backlink")
+                .with_body(Some(sup))
+                .pack()
+                .spanned(span);
+            let body = backlink
+                + SpaceElem::shared().clone()
+                + self.note.body_content().unwrap().clone();
+            return Ok(HtmlElem::new(tag::li)
+                .with_attr(attr::id, id)
+                .with_body(Some(body))
+                .pack()
+                .spanned(span));
+        }
+
         let sup = SuperElem::new(num)
             .pack()
             .spanned(span)
@@ -309,14 +361,18 @@ impl Show for Packed<FootnoteEntry> {
 }
 
 impl ShowSet for Packed<FootnoteEntry> {
-    fn show_set(&self, _: StyleChain) -> Styles {
+    fn show_set(&self, styles: StyleChain) -> Styles {
         let mut out = Styles::new();
         out.set(ParElem::set_leading(Em::new(0.5).into()));
-        out.set(TextElem::set_size(TextSize(Em::new(0.85).into())));
+        if !TargetElem::target_in(styles).is_html() {
+            out.set(TextElem::set_size(TextSize(Em::new(0.85).into())));
+        }
         out
     }
 }
 
+impl Tagged for Packed<FootnoteEntry> {}
+
 cast! {
     FootnoteElem,
     v: Content => v.unpack::<Self>().unwrap_or_else(Self::with_content)
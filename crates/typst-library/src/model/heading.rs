@@ -319,8 +319,11 @@ impl ShowSet for Packed<HeadingElem> {
         let below = Em::new(0.75) / scale;
 
         let mut out = Styles::new();
-        out.set(TextElem::set_size(TextSize(size.into())));
-        out.set(TextElem::set_weight(FontWeight::BOLD));
+        // In HTML, the browser already styles headings.
+        if !TargetElem::target_in(styles).is_html() {
+            out.set(TextElem::set_size(TextSize(size.into())));
+            out.set(TextElem::set_weight(FontWeight::BOLD));
+        }
         out.set(BlockElem::set_above(Smart::Custom(above.into())));
         out.set(BlockElem::set_below(Smart::Custom(below.into())));
         out.set(BlockElem::set_sticky(true));
@@ -8,8 +8,8 @@ use crate::foundations::{
     cast, elem, Content, Label, NativeElement, Packed, Repr, Show, ShowSet, Smart,
     StyleChain, Styles, TargetElem,
 };
-use crate::html::{attr, tag, HtmlElem};
-use crate::introspection::Location;
+use crate::html::{self, attr, tag, HtmlElem};
+use crate::introspection::{Location, Tagged};
 use crate::layout::Position;
 use crate::text::TextElem;
 
@@ -38,7 +38,7 @@ use crate::text::TextElem;
 /// # Syntax
 /// This function also has dedicated syntax: Text that starts with `http://` or
 /// `https://` is automatically turned into a link.
-#[elem(Show)]
+#[elem(Show, Tagged)]
 pub struct LinkElem {
     /// The destination the link points to.
     ///
@@ -109,18 +109,27 @@ impl Show for Packed<LinkElem> {
         let body = self.body.clone();
 
         Ok(if TargetElem::target_in(styles).is_html() {
-            if let LinkTarget::Dest(Destination::Url(url)) = &self.dest {
-                HtmlElem::new(tag::a)
+            match &self.dest {
+                LinkTarget::Dest(Destination::Url(url)) => HtmlElem::new(tag::a)
                     .with_attr(attr::href, url.clone().into_inner())
                     .with_body(Some(body))
                     .pack()
-                    .spanned(self.span())
-            } else {
-                engine.sink.warn(warning!(
-                    self.span(),
-                    "non-URL links are not yet supported by HTML export"
-                ));
-                body
+                    .spanned(self.span()),
+                LinkTarget::Dest(Destination::Location(loc)) => {
+                    html::link(engine, *loc, body, self.span())
+                }
+                LinkTarget::Label(label) => {
+                    let elem = engine.introspector.query_label(*label).at(self.span())?;
+                    let loc = elem.location().unwrap();
+                    html::link(engine, loc, body, self.span())
+                }
+                LinkTarget::Dest(Destination::Position(_)) => {
+                    engine.sink.warn(warning!(
+                        self.span(),
+                        "links to positions are not supported by HTML export"
+                    ));
+                    body
+                }
             }
         } else {
             match &self.dest {
@@ -143,6 +152,8 @@ impl ShowSet for Packed<LinkElem> {
     }
 }
 
+impl Tagged for Packed<LinkElem> {}
+
 fn body_from_url(url: &Url) -> Content {
     let text = ["mailto:", "tel:"]
         .into_iter()
@@ -11,17 +11,20 @@ use crate::engine::Engine;
 use crate::foundations::{
     cast, elem, func, scope, select_where, Args, Construct, Content, Context, Func,
     LocatableSelector, NativeElement, Packed, Resolve, Show, ShowSet, Smart, StyleChain,
-    Styles,
+    Styles, TargetElem,
 };
+use crate::html::{self, attr, tag, HtmlElem};
 use crate::introspection::{
-    Counter, CounterKey, Introspector, Locatable, Location, Locator, LocatorLink,
+    Counter, CounterKey, Introspector, Locatable, Location, Locator, LocatorLink, Tagged,
 };
 use crate::layout::{
     Abs, Axes, BlockBody, BlockElem, BoxElem, Dir, Em, Fr, HElem, Length, Region, Rel,
     RepeatElem, Sides,
 };
 use crate::math::EquationElem;
-use crate::model::{Destination, HeadingElem, NumberingPattern, ParElem, Refable};
+use crate::model::{
+    Destination, HeadingElem, NumberingPattern, ParElem, ParbreakElem, Refable,
+};
 use crate::text::{LocalName, SpaceElem, TextElem};
 
 /// A table of contents, figures, or other elements.
@@ -276,6 +279,7 @@ impl Show for Packed<OutlineElem> {
         let depth = self.depth(styles).unwrap_or(NonZeroUsize::MAX);
 
         // Build the outline entries.
+        let mut entries = vec![];
         for elem in elems {
             let Some(outlinable) = elem.with::<dyn Outlinable>() else {
                 bail!(span, "cannot outline {}", elem.func().name());
@@ -284,14 +288,58 @@ impl Show for Packed<OutlineElem> {
             let level = outlinable.level();
             if outlinable.outlined() && level <= depth {
                 let entry = OutlineEntry::new(level, elem);
-                seq.push(entry.pack().spanned(span));
+                entries.push((level, entry.pack().spanned(span)));
             }
         }
 
+        if TargetElem::target_in(styles).is_html() {
+            seq.push(
+                HtmlElem::new(tag::nav)
+                    .with_attr(attr::role, "doc-toc")
+                    .with_body(Some(nest_entries(&entries, span)))
+                    .pack()
+                    .spanned(span),
+            );
+        } else {
+            seq.extend(entries.into_iter().map(|(_, entry)| entry));
+        }
+
         Ok(Content::sequence(seq))
     }
 }
 
+/// Nests outline entries into ordered lists according to their levels.
+fn nest_entries(entries: &[(NonZeroUsize, Content)], span: Span) -> Content {
+    let mut items = vec![];
+    let mut i = 0;
+    while i < entries.len() {
+        let (level, entry) = &entries[i];
+
+        // The entries up to the next one at the same or a lower level are
+        // nested into this one.
+        let end = entries[i + 1..]
+            .iter()
+            .position(|(other, _)| other <= level)
+            .map_or(entries.len(), |k| i + 1 + k);
+
+        // The entry always becomes a paragraph, so that entries with and
+        // without nested entries are structured the same way.
+        let mut body = entry.clone();
+        body += ParbreakElem::shared();
+        if end > i + 1 {
+            body += nest_entries(&entries[i + 1..end], span);
+        }
+
+        items.push(HtmlElem::new(tag::li).with_body(Some(body)).pack().spanned(span));
+        i = end;
+    }
+
+    HtmlElem::new(tag::ol)
+        .with_body(Some(Content::sequence(items)))
+        .pack()
+        .spanned(span)
+}
+
 impl ShowSet for Packed<OutlineElem> {
     fn show_set(&self, styles: StyleChain) -> Styles {
         let mut out = Styles::new();
@@ -368,7 +416,7 @@ pub trait Outlinable: Refable {
 /// With show-set and show rules on outline entries, you can richly customize
 /// the outline's appearance. See the
 /// [section on styling the outline]($outline/#styling-the-outline) for details.
-#[elem(scope, name = "entry", title = "Outline Entry", Show)]
+#[elem(scope, name = "entry", title = "Outline Entry", Show, Tagged)]
 pub struct OutlineEntry {
     /// The nesting level of this outline entry. Starts at `{1}` for top-level
     /// entries.
@@ -422,6 +470,18 @@ impl Show for Packed<OutlineEntry> {
         let context = context.track();
 
         let prefix = self.prefix(engine, context, span)?;
+
+        // There are no pages in HTML, so the entry is just a link to the
+        // element.
+        if TargetElem::target_in(styles).is_html() {
+            let mut body = self.body().at(span)?;
+            if let Some(prefix) = prefix {
+                body = prefix + SpaceElem::shared().clone() + body;
+            }
+            let loc = self.element_location().at(span)?;
+            return Ok(html::link(engine, loc, body, span));
+        }
+
         let inner = self.inner(engine, context, span)?;
         let block = if self.element.is::<EquationElem>() {
             let body = prefix.unwrap_or_default() + inner;
@@ -438,6 +498,8 @@ impl Show for Packed<OutlineEntry> {
     }
 }
 
+impl Tagged for Packed<OutlineEntry> {}
+
 #[scope]
 impl OutlineEntry {
     /// A helper function for producing an indented entry layout: Lays out a
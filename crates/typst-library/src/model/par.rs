@@ -6,7 +6,7 @@ use crate::foundations::{
     cast, dict, elem, scope, Args, Cast, Construct, Content, Dict, NativeElement, Packed,
     Smart, Unlabellable, Value,
 };
-use crate::introspection::{Count, CounterUpdate, Locatable};
+use crate::introspection::{Count, CounterUpdate, Locatable, Tagged};
 use crate::layout::{Em, HAlignment, Length, OuterHAlignment};
 use crate::model::Numbering;
 
@@ -93,7 +93,7 @@ use crate::model::Numbering;
 /// let $a$ be the smallest of the
 /// three integers. Then, we ...
 /// ```
-#[elem(scope, title = "Paragraph")]
+#[elem(scope, title = "Paragraph", Tagged)]
 pub struct ParElem {
     /// The spacing between lines.
     ///
@@ -227,6 +227,8 @@ impl ParElem {
     type ParLine;
 }
 
+impl Tagged for Packed<ParElem> {}
+
 /// How to determine line breaks in a paragraph.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
 pub enum Linebreaks {
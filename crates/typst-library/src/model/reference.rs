@@ -5,8 +5,9 @@ use crate::diag::{bail, At, Hint, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
     cast, elem, Cast, Content, Context, Func, IntoValue, Label, NativeElement, Packed,
-    Show, Smart, StyleChain, Synthesize,
+    Show, Smart, StyleChain, Synthesize, TargetElem,
 };
+use crate::html;
 use crate::introspection::{Counter, CounterKey, Locatable};
 use crate::math::EquationElem;
 use crate::model::{
@@ -308,6 +309,10 @@ fn show_reference(
         content = supplement + TextElem::packed("\u{a0}") + content;
     }
 
+    if TargetElem::target_in(styles).is_html() {
+        return Ok(html::link(engine, loc, content, reference.span()));
+    }
+
     Ok(content.linked(Destination::Location(loc)))
 }
 
@@ -9,8 +9,8 @@ use crate::foundations::{
     cast, elem, scope, Content, NativeElement, Packed, Show, Smart, StyleChain,
     TargetElem,
 };
-use crate::html::{attr, tag, HtmlAttrs, HtmlElem, HtmlTag};
-use crate::introspection::Locator;
+use crate::html::{attr, css, tag, HtmlAttrs, HtmlElem, HtmlTag};
+use crate::introspection::{Locator, Tagged};
 use crate::layout::grid::resolve::{table_to_cellgrid, Cell, CellGrid, Entry};
 use crate::layout::{
     show_grid_cell, Abs, Alignment, BlockElem, Celled, GridCell, GridFooter, GridHLine,
@@ -121,7 +121,7 @@ use crate::visualize::{Paint, Stroke};
 ///   [Robert], b, a, b,
 /// )
 /// ```
-#[elem(scope, Show, LocalName, Figurable)]
+#[elem(scope, Show, Tagged, LocalName, Figurable)]
 pub struct TableElem {
     /// The column sizes. See the [grid documentation]($grid) for more
     /// information on track sizing.
@@ -263,27 +263,46 @@ impl TableElem {
 }
 
 fn show_cell_html(tag: HtmlTag, cell: &Cell, styles: StyleChain) -> Content {
-    let cell = cell.body.clone();
-    let Some(cell) = cell.to_packed::<TableCell>() else { return cell };
+    let Some(elem) = cell.body.to_packed::<TableCell>() else { return cell.body.clone() };
     let mut attrs = HtmlAttrs::default();
     let span = |n: NonZeroUsize| (n != NonZeroUsize::MIN).then(|| n.to_string());
-    if let Some(colspan) = span(cell.colspan(styles)) {
+    if let Some(colspan) = span(cell.colspan) {
         attrs.push(attr::colspan, colspan);
     }
-    if let Some(rowspan) = span(cell.rowspan(styles)) {
+    if let Some(rowspan) = span(cell.rowspan) {
         attrs.push(attr::rowspan, rowspan);
     }
+    if tag == tag::th {
+        let scope = if cell.colspan.get() > 1 { "colgroup" } else { "col" };
+        attrs.push(attr::scope, scope);
+    }
+
+    let mut props = css::Properties::new();
+    if let Smart::Custom(align) = elem.align(styles) {
+        css::cell_align(&mut props, align);
+    }
+    if let Some(color) = cell.fill.as_ref().and_then(css::color) {
+        props.push("background-color", color);
+    }
+    if let Some(style) = props.into_inner() {
+        attrs.push(attr::style, style);
+    }
+
     HtmlElem::new(tag)
-        .with_body(Some(cell.body.clone()))
+        .with_body(Some(elem.body.clone()))
         .with_attrs(attrs)
         .pack()
-        .spanned(cell.span())
+        .spanned(elem.span())
 }
 
 fn show_cellgrid_html(grid: CellGrid, styles: StyleChain) -> Content {
     let elem = |tag, body| HtmlElem::new(tag).with_body(Some(body)).pack();
     let mut rows: Vec<_> = grid.entries.chunks(grid.non_gutter_column_count()).collect();
 
+    // The header and footer bounds count gutter rows, but the entries don't
+    // contain any.
+    let to_row = |y: usize| if grid.has_gutter { y.div_ceil(2) } else { y };
+
     let tr = |tag, row: &[Entry]| {
         let row = row
             .iter()
@@ -292,12 +311,12 @@ fn show_cellgrid_html(grid: CellGrid, styles: StyleChain) -> Content {
         elem(tag::tr, Content::sequence(row))
     };
 
-    let footer = grid.footer.map(|ft| {
-        let rows = rows.drain(ft.unwrap().start..);
+    let footer = grid.footer.as_ref().map(|ft| {
+        let rows = rows.drain(to_row(ft.unwrap().start)..);
         elem(tag::tfoot, Content::sequence(rows.map(|row| tr(tag::td, row))))
     });
-    let header = grid.header.map(|hd| {
-        let rows = rows.drain(..hd.unwrap().end);
+    let header = grid.header.as_ref().map(|hd| {
+        let rows = rows.drain(..to_row(hd.unwrap().end));
         elem(tag::thead, Content::sequence(rows.map(|row| tr(tag::th, row))))
     });
 
@@ -330,6 +349,8 @@ impl LocalName for Packed<TableElem> {
 
 impl Figurable for Packed<TableElem> {}
 
+impl Tagged for Packed<TableElem> {}
+
 /// Any child of a table element.
 #[derive(Debug, PartialEq, Clone, Hash)]
 pub enum TableChild {
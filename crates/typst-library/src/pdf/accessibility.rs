@@ -0,0 +1,57 @@
+use crate::diag::SourceResult;
+use crate::engine::Engine;
+use crate::foundations::{elem, Cast, Content, Packed, Show, StyleChain};
+use crate::introspection::Tagged;
+
+/// Marks content as a PDF artifact.
+///
+/// Artifacts are parts of the document that are not meant to be read by
+/// assistive technology, like decorative graphics, running headers, and page
+/// numbers. When exporting a tagged PDF (e.g. for PDF/UA-1), the content of
+/// artifacts is excluded from the document's structure tree.
+///
+/// Page headers, footers, backgrounds, and foregrounds are automatically
+/// marked as artifacts.
+///
+/// # Example
+/// ```typ
+/// #pdf.artifact[
+///   #line(length: 100%)
+/// ]
+/// ```
+///
+/// # Notes
+/// - This element has no effect if exporting to a format other than PDF.
+#[elem(Show, Tagged)]
+pub struct ArtifactElem {
+    /// The kind of artifact.
+    #[default(ArtifactKind::Other)]
+    pub kind: ArtifactKind,
+
+    /// The content that is an artifact.
+    #[required]
+    pub body: Content,
+}
+
+impl Show for Packed<ArtifactElem> {
+    #[typst_macros::time(name = "pdf.artifact", span = self.span())]
+    fn show(&self, _: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(self.body.clone())
+    }
+}
+
+impl Tagged for Packed<ArtifactElem> {}
+
+/// The type of artifact.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum ArtifactKind {
+    /// Repeated content in the header of a page.
+    Header,
+    /// Repeated content in the footer of a page.
+    Footer,
+    /// Other page decorations, like a background.
+    Page,
+    /// Any other kind of artifact.
+    #[default]
+    Other,
+}
@@ -0,0 +1,192 @@
+use ecow::EcoString;
+use typst_syntax::{Span, Spanned};
+
+use crate::diag::{bail, warning, At, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Bytes, Cast, Content, Datetime, Derived, NativeElement, Packed, Show, Smart,
+    StyleChain, TargetElem,
+};
+use crate::introspection::{Locatable, Location};
+use crate::layout::BoxElem;
+use crate::visualize::Color;
+use crate::World;
+
+/// An annotation that PDF viewers show on top of the page, such as a comment
+/// or a highlight.
+///
+/// The annotation covers its body: A highlight marks the body's text, while
+/// the other kinds of annotations show an icon or a text box at the body's
+/// position. Without a body, a comment or file attachment is shown at the
+/// position where the annotation appears in the text.
+///
+/// # Example
+/// ```typ
+/// #pdf.annot(
+///   kind: "highlight",
+///   author: "Reviewer",
+///   date: datetime(year: 2025, month: 3, day: 14),
+///   contents: "Is this number up to date?",
+/// )[Revenue grew by 12%]
+/// in the last quarter.
+/// #pdf.annot(contents: "Add a chart here.")
+/// ```
+///
+/// # Notes
+/// - This element is ignored if exporting to a format other than PDF.
+/// - Annotations are not currently supported for PDF/A because Typst does not
+///   generate appearances for them.
+#[elem(Show, Locatable)]
+pub struct AnnotElem {
+    /// The kind of annotation.
+    ///
+    /// If set to `{auto}`, the annotation attaches a file if one is given and
+    /// is a comment otherwise.
+    pub kind: Smart<AnnotKind>,
+
+    /// The text of the annotation, which viewers show in a popup or, for free
+    /// text annotations, directly on the page.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub contents: Option<EcoString>,
+
+    /// The author of the annotation.
+    #[borrowed]
+    pub author: Option<EcoString>,
+
+    /// When the annotation was last modified.
+    pub date: Option<Datetime>,
+
+    /// The color of the highlight, the icon, or the border of the text box.
+    #[default(Color::YELLOW)]
+    pub color: Color,
+
+    /// The [path]($syntax/#paths) of a file to attach to the annotation.
+    ///
+    /// Only annotations of kind `{"file"}` can have a file.
+    #[parse(match args.named::<Spanned<EcoString>>("file")? {
+        Some(Spanned { v: path, span }) => {
+            let id = span.resolve_path(&path).at(span)?;
+            let data = engine.world.file(id).at(span)?;
+            Some(Some(Derived::new(path, data)))
+        }
+        None => None,
+    })]
+    #[borrowed]
+    pub file: Option<Derived<EcoString, Bytes>>,
+
+    /// The content the annotation covers.
+    #[positional]
+    #[borrowed]
+    pub body: Option<Content>,
+
+    /// An annotation style that should be applied to elements.
+    #[internal]
+    #[ghost]
+    pub current: Option<Annotation>,
+}
+
+impl Show for Packed<AnnotElem> {
+    #[typst_macros::time(name = "pdf.annot", span = self.span())]
+    fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let body = self.body(styles).clone();
+        if TargetElem::target_in(styles).is_html() {
+            engine
+                .sink
+                .warn(warning!(self.span(), "annot was ignored during HTML export"));
+            return Ok(body.unwrap_or_default());
+        }
+
+        let file = self.file(styles).as_ref();
+        let kind = self.kind(styles).unwrap_or(match file {
+            Some(_) => AnnotKind::File,
+            None => AnnotKind::Text,
+        });
+
+        let file = match (kind, file) {
+            (AnnotKind::File, Some(file)) => {
+                let name = file.source.rsplit(['/', '\\']).next().unwrap_or_default();
+                Some((name.into(), file.derived.clone()))
+            }
+            (AnnotKind::File, None) => bail!(
+                self.span(),
+                "file annotations need a file";
+                hint: "specify the file with the `file` argument"
+            ),
+            (_, Some(_)) => bail!(
+                self.span(),
+                "only file annotations can have a file";
+                hint: "set `kind` to `\"file\"` or remove the `file` argument"
+            ),
+            (_, None) => None,
+        };
+
+        if body.is_none() {
+            match kind {
+                AnnotKind::Highlight => bail!(
+                    self.span(),
+                    "highlight annotations need a body";
+                    hint: "pass the content to highlight as the body"
+                ),
+                AnnotKind::FreeText => bail!(
+                    self.span(),
+                    "free text annotations need a body";
+                    hint: "the body determines the area of the text box";
+                    hint: "try passing an empty box like `box(width: 4cm, height: 1cm)`"
+                ),
+                AnnotKind::Text | AnnotKind::File => {}
+            }
+        }
+
+        let annotation = Annotation {
+            kind,
+            contents: self.contents(styles).clone(),
+            author: self.author(styles).clone(),
+            date: self.date(styles),
+            color: self.color(styles),
+            file,
+            location: self.location().unwrap(),
+            span: self.span(),
+        };
+
+        let body = body.unwrap_or_else(|| BoxElem::new().pack().spanned(self.span()));
+        Ok(body.styled(AnnotElem::set_current(Some(annotation))))
+    }
+}
+
+/// A kind of annotation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum AnnotKind {
+    /// A comment that viewers show as an icon, which opens a popup with the
+    /// annotation's contents.
+    Text,
+    /// A highlight of the body's text.
+    Highlight,
+    /// A text box that shows the annotation's contents directly on the page.
+    FreeText,
+    /// An attached file that viewers show as an icon.
+    File,
+}
+
+/// An annotation, as it is exported to PDF.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Annotation {
+    /// The kind of annotation.
+    pub kind: AnnotKind,
+    /// The text of the annotation.
+    pub contents: Option<EcoString>,
+    /// The author of the annotation.
+    pub author: Option<EcoString>,
+    /// When the annotation was last modified.
+    pub date: Option<Datetime>,
+    /// The color of the annotation.
+    pub color: Color,
+    /// The name and data of the attached file.
+    pub file: Option<(EcoString, Bytes)>,
+    /// The location of the annotation element. It identifies the annotation
+    /// across the frames its body is split into.
+    pub location: Location,
+    /// The span of the annotation element.
+    pub span: Span,
+}
@@ -0,0 +1,525 @@
+use ecow::EcoString;
+use typst_syntax::Span;
+
+use crate::diag::{bail, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Args, Construct, Content, NativeElement, Packed, Show, Smart, StyleChain,
+};
+use crate::introspection::Locatable;
+use crate::layout::{
+    Abs, Axes, BoxElem, Corners, Em, Fr, Frame, HElem, InlineElem, Length, Ratio, Rel,
+    Sides, Sizing,
+};
+use crate::text::TextElem;
+use crate::visualize::{Color, EllipseElem, PolygonElem, Stroke};
+
+/// A text field in a fillable PDF form.
+///
+/// When exporting to PDF, the field can be filled in by the reader of the
+/// document. In other formats, its initial value is shown in a box.
+///
+/// # Example
+/// ```typ
+/// Name: #pdf.text-field("name") \
+/// Notes: #pdf.text-field(
+///   "notes",
+///   multiline: true,
+///   width: 100%,
+/// )
+/// ```
+///
+/// # Notes
+/// - Fields are exported as interactive AcroForm fields only if exporting to
+///   PDF. Other formats just show their static appearance.
+/// - Fields with the same name share their value. They must be of the same
+///   type.
+#[elem(Show, Locatable)]
+pub struct TextFieldElem {
+    /// The name of the field. This is the key under which the filled-in value
+    /// is stored.
+    #[required]
+    pub name: EcoString,
+
+    /// The initial value of the field.
+    #[borrowed]
+    pub value: EcoString,
+
+    /// Whether the field accepts multiple lines of text.
+    #[default(false)]
+    pub multiline: bool,
+
+    /// The maximum number of characters that can be entered.
+    pub max_length: Option<usize>,
+
+    /// The width of the field.
+    #[default(Em::new(10.0).into())]
+    pub width: Rel<Length>,
+
+    /// The height of the field.
+    ///
+    /// If set to `{auto}`, the field fits one line of text, or four lines if
+    /// it is multiline.
+    pub height: Smart<Rel<Length>>,
+
+    /// A description of the field that is shown when hovering over it and
+    /// read by assistive technology.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub tooltip: Option<EcoString>,
+
+    /// Whether the field must be filled in before the form can be submitted.
+    #[default(false)]
+    pub required: bool,
+
+    /// Whether the field can't be changed by the reader.
+    #[default(false)]
+    pub read_only: bool,
+}
+
+impl Show for Packed<TextFieldElem> {
+    #[typst_macros::time(name = "pdf.text-field", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let value = self.value(styles);
+        let multiline = self.multiline(styles);
+        let height = self
+            .height(styles)
+            .unwrap_or_else(|| Em::new(if multiline { 5.2 } else { 1.6 }).into());
+
+        let appearance = field_box(self.width(styles), Smart::Custom(height))
+            .with_clip(true)
+            .with_body(Some(TextElem::packed(value.clone())));
+
+        let field = FormField {
+            name: self.name.clone(),
+            tooltip: self.tooltip(styles).clone(),
+            required: self.required(styles),
+            read_only: self.read_only(styles),
+            font_size: TextElem::size_in(styles),
+            kind: FormFieldKind::Text {
+                value: value.clone(),
+                multiline,
+                max_length: self.max_length(styles),
+            },
+        };
+
+        Ok(widget(self.span(), field, appearance, None))
+    }
+}
+
+/// A checkbox in a fillable PDF form.
+///
+/// # Example
+/// ```typ
+/// #pdf.checkbox("newsletter", checked: true)
+/// Subscribe to the newsletter
+/// ```
+///
+/// # Notes
+/// - Fields are exported as interactive AcroForm fields only if exporting to
+///   PDF. Other formats just show their static appearance.
+#[elem(Show, Locatable)]
+pub struct CheckboxElem {
+    /// The name of the field.
+    #[required]
+    pub name: EcoString,
+
+    /// Whether the checkbox is initially checked.
+    #[default(false)]
+    pub checked: bool,
+
+    /// A description of the field that is shown when hovering over it and
+    /// read by assistive technology.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub tooltip: Option<EcoString>,
+
+    /// Whether the checkbox must be checked before the form can be submitted.
+    #[default(false)]
+    pub required: bool,
+
+    /// Whether the checkbox can't be changed by the reader.
+    #[default(false)]
+    pub read_only: bool,
+}
+
+impl Show for Packed<CheckboxElem> {
+    #[typst_macros::time(name = "pdf.checkbox", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let checked = self.checked(styles);
+        let field = FormField {
+            name: self.name.clone(),
+            tooltip: self.tooltip(styles).clone(),
+            required: self.required(styles),
+            read_only: self.read_only(styles),
+            font_size: TextElem::size_in(styles),
+            kind: FormFieldKind::Checkbox { checked },
+        };
+
+        let size = Em::new(0.8).into();
+        let unchecked = field_box(size, Smart::Custom(size));
+        let tick = PolygonElem::new(
+            [
+                (0.0, 0.55),
+                (0.15, 0.4),
+                (0.38, 0.62),
+                (0.85, 0.1),
+                (1.0, 0.25),
+                (0.38, 0.9),
+            ]
+            .into_iter()
+            .map(|(x, y)| Axes::new(Ratio::new(x).into(), Ratio::new(y).into()))
+            .collect(),
+        )
+        .with_fill(Some(Color::BLACK.into()))
+        .with_stroke(Smart::Custom(None));
+        let checked_box = unchecked.clone().with_body(Some(tick.pack()));
+
+        let (appearance, toggled) =
+            if checked { (checked_box, unchecked) } else { (unchecked, checked_box) };
+        Ok(widget(self.span(), field, appearance, Some(toggled)))
+    }
+}
+
+/// A radio button in a fillable PDF form.
+///
+/// Radio buttons with the same name form a group in which at most one button
+/// can be selected.
+///
+/// # Example
+/// ```typ
+/// #pdf.radio("size", "small") Small
+/// #pdf.radio("size", "large", checked: true) Large
+/// ```
+///
+/// # Notes
+/// - Fields are exported as interactive AcroForm fields only if exporting to
+///   PDF. Other formats just show their static appearance.
+#[elem(Show, Locatable)]
+pub struct RadioElem {
+    /// The name of the radio group.
+    #[required]
+    pub name: EcoString,
+
+    /// The value the group takes when this button is selected.
+    #[required]
+    pub value: EcoString,
+
+    /// Whether the button is initially selected.
+    #[default(false)]
+    pub checked: bool,
+
+    /// A description of the button that is shown when hovering over it and
+    /// read by assistive technology.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub tooltip: Option<EcoString>,
+
+    /// Whether a button of the group must be selected before the form can be
+    /// submitted.
+    #[default(false)]
+    pub required: bool,
+
+    /// Whether the button can't be changed by the reader.
+    #[default(false)]
+    pub read_only: bool,
+}
+
+impl Show for Packed<RadioElem> {
+    #[typst_macros::time(name = "pdf.radio", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let checked = self.checked(styles);
+        let field = FormField {
+            name: self.name.clone(),
+            tooltip: self.tooltip(styles).clone(),
+            required: self.required(styles),
+            read_only: self.read_only(styles),
+            font_size: TextElem::size_in(styles),
+            kind: FormFieldKind::Radio { value: self.value.clone(), checked },
+        };
+
+        let size = Em::new(0.8).into();
+        let unchecked = field_box(size, Smart::Custom(size))
+            .with_radius(Corners::splat(Some(Ratio::new(0.5).into())))
+            .with_inset(Sides::splat(Some(Length::from(Em::new(0.2)).into())));
+        let dot = EllipseElem::new()
+            .with_width(Smart::Custom(Ratio::one().into()))
+            .with_height(Sizing::Rel(Ratio::one().into()))
+            .with_fill(Some(Color::BLACK.into()))
+            .with_stroke(Smart::Custom(None));
+        let checked_box = unchecked.clone().with_body(Some(dot.pack()));
+
+        let (appearance, toggled) =
+            if checked { (checked_box, unchecked) } else { (unchecked, checked_box) };
+        Ok(widget(self.span(), field, appearance, Some(toggled)))
+    }
+}
+
+/// A dropdown list in a fillable PDF form.
+///
+/// # Example
+/// ```typ
+/// Country: #pdf.dropdown(
+///   "country",
+///   ("Germany", "France", "Italy"),
+///   selected: "France",
+/// )
+/// ```
+///
+/// # Notes
+/// - Fields are exported as interactive AcroForm fields only if exporting to
+///   PDF. Other formats just show their static appearance.
+#[elem(Show, Locatable)]
+pub struct DropdownElem {
+    /// The name of the field.
+    #[required]
+    pub name: EcoString,
+
+    /// The options to choose from.
+    #[required]
+    pub options: Vec<EcoString>,
+
+    /// The initially selected option.
+    #[borrowed]
+    pub selected: Option<EcoString>,
+
+    /// Whether the reader can also enter a value that is not one of the
+    /// options.
+    #[default(false)]
+    pub editable: bool,
+
+    /// The width of the field.
+    #[default(Em::new(10.0).into())]
+    pub width: Rel<Length>,
+
+    /// A description of the field that is shown when hovering over it and
+    /// read by assistive technology.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub tooltip: Option<EcoString>,
+
+    /// Whether an option must be selected before the form can be submitted.
+    #[default(false)]
+    pub required: bool,
+
+    /// Whether the selection can't be changed by the reader.
+    #[default(false)]
+    pub read_only: bool,
+}
+
+impl Show for Packed<DropdownElem> {
+    #[typst_macros::time(name = "pdf.dropdown", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let selected = self.selected(styles).clone();
+        let editable = self.editable(styles);
+        if let Some(selected) = &selected {
+            if !editable && !self.options.contains(selected) {
+                bail!(
+                    self.span(),
+                    "selected value is not one of the options";
+                    hint: "set `editable: true` to allow arbitrary values"
+                );
+            }
+        }
+
+        let arrow = BoxElem::new().with_body(Some(
+            PolygonElem::new(vec![
+                Axes::new(Abs::zero().into(), Abs::zero().into()),
+                Axes::new(Length::from(Em::new(0.6)).into(), Abs::zero().into()),
+                Axes::new(
+                    Length::from(Em::new(0.3)).into(),
+                    Length::from(Em::new(0.4)).into(),
+                ),
+            ])
+            .with_fill(Some(Color::BLACK.into()))
+            .with_stroke(Smart::Custom(None))
+            .pack(),
+        ));
+        let body = TextElem::packed(selected.clone().unwrap_or_default())
+            + HElem::new(Fr::one().into()).pack()
+            + arrow.pack();
+        let appearance =
+            field_box(self.width(styles), Smart::Custom(Em::new(1.6).into()))
+                .with_clip(true)
+                .with_body(Some(body));
+
+        let field = FormField {
+            name: self.name.clone(),
+            tooltip: self.tooltip(styles).clone(),
+            required: self.required(styles),
+            read_only: self.read_only(styles),
+            font_size: TextElem::size_in(styles),
+            kind: FormFieldKind::Dropdown {
+                options: self.options.clone(),
+                selected,
+                editable,
+            },
+        };
+
+        Ok(widget(self.span(), field, appearance, None))
+    }
+}
+
+/// A placeholder for a digital signature in a PDF form.
+///
+/// The exported field is unsigned. It can be signed by the reader of the
+/// document with a suitable PDF viewer.
+///
+/// # Example
+/// ```typ
+/// Signature: #pdf.signature("signature")
+/// ```
+///
+/// # Notes
+/// - Fields are exported as interactive AcroForm fields only if exporting to
+///   PDF. Other formats just show an empty box.
+#[elem(Show, Locatable)]
+pub struct SignatureElem {
+    /// The name of the field.
+    #[required]
+    pub name: EcoString,
+
+    /// The width of the field.
+    #[default(Em::new(12.0).into())]
+    pub width: Rel<Length>,
+
+    /// The height of the field.
+    #[default(Em::new(3.0).into())]
+    pub height: Rel<Length>,
+
+    /// A description of the field that is shown when hovering over it and
+    /// read by assistive technology.
+    ///
+    /// This is required for PDF/UA-1.
+    #[borrowed]
+    pub tooltip: Option<EcoString>,
+
+    /// Whether the document must be signed before the form can be submitted.
+    #[default(false)]
+    pub required: bool,
+}
+
+impl Show for Packed<SignatureElem> {
+    #[typst_macros::time(name = "pdf.signature", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let appearance =
+            field_box(self.width(styles), Smart::Custom(self.height(styles)));
+        let field = FormField {
+            name: self.name.clone(),
+            tooltip: self.tooltip(styles).clone(),
+            required: self.required(styles),
+            read_only: false,
+            font_size: TextElem::size_in(styles),
+            kind: FormFieldKind::Signature,
+        };
+        Ok(widget(self.span(), field, appearance, None))
+    }
+}
+
+/// An interactive form field, as it is exported to PDF.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct FormField {
+    /// The fully qualified name of the field.
+    pub name: EcoString,
+    /// A description of the field.
+    pub tooltip: Option<EcoString>,
+    /// Whether the field must be filled in.
+    pub required: bool,
+    /// Whether the field can't be changed.
+    pub read_only: bool,
+    /// The font size to use for text entered into the field.
+    pub font_size: Abs,
+    /// The type of the field and its value.
+    pub kind: FormFieldKind,
+}
+
+/// The type of a form field, with its initial value.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum FormFieldKind {
+    /// A text field.
+    Text { value: EcoString, multiline: bool, max_length: Option<usize> },
+    /// A checkbox.
+    Checkbox { checked: bool },
+    /// A button of a radio group.
+    Radio { value: EcoString, checked: bool },
+    /// A dropdown list.
+    Dropdown { options: Vec<EcoString>, selected: Option<EcoString>, editable: bool },
+    /// A signature field.
+    Signature,
+}
+
+/// A laid-out form field in a frame.
+#[derive(Debug, Clone, Hash)]
+pub struct Widget {
+    /// The field the widget belongs to.
+    pub field: FormField,
+    /// How the widget looks initially.
+    pub appearance: Frame,
+    /// How the widget looks after being toggled, for checkboxes and radio
+    /// buttons.
+    pub toggled: Option<Frame>,
+}
+
+/// The layoutable part of a form field.
+///
+/// This is produced by the public form field elements. It holds the field and
+/// its appearance, which is laid out into a [`Widget`].
+#[elem(Construct, Show)]
+pub struct WidgetElem {
+    /// The field.
+    #[required]
+    #[internal]
+    pub field: FormField,
+
+    /// How the field looks initially.
+    #[required]
+    #[internal]
+    pub appearance: Packed<BoxElem>,
+
+    /// How the field looks after being toggled.
+    #[required]
+    #[internal]
+    pub toggled: Option<Packed<BoxElem>>,
+}
+
+impl Construct for WidgetElem {
+    fn construct(_: &mut Engine, args: &mut Args) -> SourceResult<Content> {
+        bail!(args.span, "cannot be constructed manually");
+    }
+}
+
+impl Show for Packed<WidgetElem> {
+    fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(InlineElem::layouter(self.clone(), engine.routines.layout_widget)
+            .pack()
+            .spanned(self.span()))
+    }
+}
+
+/// Create the widget for a form field.
+fn widget(
+    span: Span,
+    field: FormField,
+    appearance: BoxElem,
+    toggled: Option<BoxElem>,
+) -> Content {
+    let pack = |elem: BoxElem| Packed::new(elem).spanned(span);
+    WidgetElem::new(field, pack(appearance), toggled.map(pack))
+        .pack()
+        .spanned(span)
+}
+
+/// The bordered box in which a field is drawn.
+fn field_box(width: Rel<Length>, height: Smart<Rel<Length>>) -> BoxElem {
+    let stroke = Stroke::from_pair(Color::GRAY, Abs::pt(0.5).into());
+    BoxElem::new()
+        .with_width(Sizing::Rel(width))
+        .with_height(height)
+        .with_fill(Some(Color::WHITE.into()))
+        .with_stroke(Sides::splat(Some(Some(stroke))))
+        .with_inset(Sides::splat(Some(Length::from(Em::new(0.2)).into())))
+}
@@ -1,8 +1,14 @@
 //! PDF-specific functionality.
 
+mod accessibility;
+mod annot;
 mod embed;
+mod form;
 
+pub use self::accessibility::*;
+pub use self::annot::*;
 pub use self::embed::*;
+pub use self::form::*;
 
 use crate::foundations::{Module, Scope};
 
@@ -11,5 +17,12 @@ pub fn module() -> Module {
     let mut pdf = Scope::deduplicating();
     pdf.start_category(crate::Category::Pdf);
     pdf.define_elem::<EmbedElem>();
+    pdf.define_elem::<AnnotElem>();
+    pdf.define_elem::<ArtifactElem>();
+    pdf.define_elem::<TextFieldElem>();
+    pdf.define_elem::<CheckboxElem>();
+    pdf.define_elem::<RadioElem>();
+    pdf.define_elem::<DropdownElem>();
+    pdf.define_elem::<SignatureElem>();
     Module::new("pdf", pdf)
 }
@@ -20,6 +20,7 @@ use crate::layout::{
 };
 use crate::math::EquationElem;
 use crate::model::{DocumentInfo, EnumElem, ListElem, TableElem};
+use crate::pdf::WidgetElem;
 use crate::visualize::{
     CircleElem, CurveElem, EllipseElem, ImageElem, LineElem, PathElem, PolygonElem,
     RectElem, SquareElem,
@@ -309,6 +310,15 @@ routines! {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment>
+
+    /// Lays out a [`WidgetElem`] in a paragraph.
+    fn layout_widget(
+        elem: &Packed<WidgetElem>,
+        engine: &mut Engine,
+        locator: Locator,
+        styles: StyleChain,
+        region: Size,
+    ) -> SourceResult<Vec<InlineItem>>
 }
 
 /// In which mode to evaluate a string.
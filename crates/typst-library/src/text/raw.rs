@@ -475,8 +475,13 @@ impl ShowSet for Packed<RawElem> {
         out.set(TextElem::set_overhang(false));
         out.set(TextElem::set_lang(Lang::ENGLISH));
         out.set(TextElem::set_hyphenate(Smart::Custom(false)));
-        out.set(TextElem::set_size(TextSize(Em::new(0.8).into())));
-        out.set(TextElem::set_font(FontList(vec![FontFamily::new("DejaVu Sans Mono")])));
+        // In HTML, the browser already uses a monospace font for code.
+        if !TargetElem::target_in(styles).is_html() {
+            out.set(TextElem::set_size(TextSize(Em::new(0.8).into())));
+            out.set(TextElem::set_font(FontList(vec![FontFamily::new(
+                "DejaVu Sans Mono",
+            )])));
+        }
         out.set(TextElem::set_cjk_latin_spacing(Smart::Custom(None)));
         if self.block(styles) {
             out.set(ParElem::set_justify(false));
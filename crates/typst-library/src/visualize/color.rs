@@ -38,13 +38,14 @@ static CMYK_TO_XYZ: LazyLock<Box<Profile>> = LazyLock::new(|| {
 });
 
 /// The target sRGB profile.
-static SRGB_PROFILE: LazyLock<Box<Profile>> = LazyLock::new(|| {
+pub(super) static SRGB_PROFILE: LazyLock<Box<Profile>> = LazyLock::new(|| {
     let mut out = Profile::new_sRGB();
     out.precache_output_transform();
     out
 });
 
-static TO_SRGB: LazyLock<qcms::Transform> = LazyLock::new(|| {
+/// Converts from device CMYK to sRGB.
+pub(super) static TO_SRGB: LazyLock<qcms::Transform> = LazyLock::new(|| {
     qcms::Transform::new_to(
         &CMYK_TO_XYZ,
         &SRGB_PROFILE,
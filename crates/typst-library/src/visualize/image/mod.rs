@@ -1,27 +1,35 @@
 //! Image handling.
 
+mod pdf;
 mod raster;
 mod svg;
 
+pub use self::pdf::{PdfDocument, PdfImage};
 pub use self::raster::{
     ExchangeFormat, PixelEncoding, PixelFormat, RasterFormat, RasterImage,
 };
 pub use self::svg::SvgImage;
 
+use std::ffi::OsStr;
 use std::fmt::{self, Debug, Formatter};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
-use ecow::EcoString;
+use base64::Engine as _;
+use ecow::{eco_format, EcoString};
 use typst_syntax::{Span, Spanned};
-use typst_utils::LazyHash;
+use typst_utils::{LazyHash, NonZeroExt};
 
-use crate::diag::{SourceResult, StrResult};
+use crate::diag::{
+    bail, error, At, HintedStrResult, HintedString, SourceResult, StrResult,
+};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, func, scope, Bytes, Cast, Content, Derived, NativeElement, Packed, Show,
-    Smart, StyleChain,
+    cast, elem, func, scope, Bytes, Cast, Content, Derived, NativeElement, Packed,
+    Resolve, Show, Smart, StyleChain, TargetElem,
 };
-use crate::layout::{BlockElem, Length, Rel, Sizing};
+use crate::html::{attr, css, tag, FrameElem, HtmlElem};
+use crate::layout::{Abs, BlockElem, Length, Rel, Sizing};
 use crate::loading::{DataSource, Load, Readable};
 use crate::model::Figurable;
 use crate::text::LocalName;
@@ -77,9 +85,10 @@ pub struct ImageElem {
     /// [`source`]($image.source) (even then, Typst will try to figure out the
     /// format automatically, but that's not always possible).
     ///
-    /// Supported formats are `{"png"}`, `{"jpg"}`, `{"gif"}`, `{"svg"}` as well
-    /// as raw pixel data. Embedding PDFs as images is
-    /// [not currently supported](https://github.com/typst/typst/issues/145).
+    /// Supported formats are `{"png"}`, `{"jpg"}`, `{"gif"}`, `{"webp"}`,
+    /// `{"tiff"}`, `{"bmp"}`, `{"svg"}`, `{"pdf"}` as well as raw pixel data.
+    /// `{"avif"}` is supported if Typst was built with AVIF support. EPS files
+    /// are not supported and must be converted to PDF or SVG first.
     ///
     /// When providing raw pixel data as the `source`, you must specify a
     /// dictionary with the following keys as the `format`:
@@ -116,6 +125,17 @@ pub struct ImageElem {
     /// ```
     pub format: Smart<ImageFormat>,
 
+    /// The page of a PDF document to show.
+    ///
+    /// Pages are numbered starting from one. This has no effect on images in
+    /// other formats.
+    ///
+    /// ```typ
+    /// #image("diagrams.pdf", page: 2)
+    /// ```
+    #[default(NonZeroUsize::ONE)]
+    pub page: NonZeroUsize,
+
     /// The width of the image.
     pub width: Smart<Rel<Length>>,
 
@@ -218,8 +238,25 @@ impl ImageElem {
     }
 }
 
+impl ImageElem {
+    /// Determines the image's format. An explicitly given format takes
+    /// precedence over the file extension, which takes precedence over the
+    /// data.
+    pub fn determine_format(&self, styles: StyleChain) -> HintedStrResult<ImageFormat> {
+        let Derived { source, derived: data } = &self.source;
+        match self.format(styles) {
+            Smart::Custom(v) => Ok(v),
+            Smart::Auto => determine_format(source, data),
+        }
+    }
+}
+
 impl Show for Packed<ImageElem> {
     fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        if TargetElem::target_in(styles).is_html() {
+            return show_html(self, styles);
+        }
+
         Ok(BlockElem::single_layouter(self.clone(), engine.routines.layout_image)
             .with_width(self.width(styles))
             .with_height(self.height(styles))
@@ -228,6 +265,107 @@ impl Show for Packed<ImageElem> {
     }
 }
 
+/// Produces an `<img>` element with the image embedded as a data URL.
+///
+/// Formats that browsers can't display, like PDF pages, are laid out into a
+/// frame instead.
+fn show_html(elem: &Packed<ImageElem>, styles: StyleChain) -> SourceResult<Content> {
+    let span = elem.span();
+    let data = &elem.source.derived;
+    let format = elem.determine_format(styles).at(span)?;
+    let Some(mime) = format.mime_type(data) else {
+        return Ok(FrameElem::new(elem.clone().pack())
+            .with_alt(elem.alt(styles))
+            .pack()
+            .spanned(span));
+    };
+
+    let mut url = eco_format!("data:{mime};base64,");
+    url.push_str(&base64::engine::general_purpose::STANDARD.encode(data));
+
+    let mut img = HtmlElem::new(tag::img).with_attr(attr::src, url);
+    if let Some(alt) = elem.alt(styles) {
+        img = img.with_attr(attr::alt, alt);
+    }
+
+    // Absolute sizes become attributes, which let the browser reserve space
+    // for the image before loading it. Relative ones are expressed in CSS.
+    let mut props = css::Properties::new();
+    let width = elem.width(styles).custom();
+    let height = match elem.height(styles) {
+        Sizing::Rel(rel) => Some(rel),
+        _ => None,
+    };
+    for (key, name, size) in
+        [(attr::width, "width", width), (attr::height, "height", height)]
+    {
+        let Some(size) = size.map(|size| size.resolve(styles)) else { continue };
+        if size.rel.is_zero() {
+            img = img.with_attr(key, eco_format!("{}", css_pixels(size.abs)));
+        } else {
+            props.push(name, css::rel(size));
+        }
+    }
+    if let Some(style) = props.into_inner() {
+        img = img.with_attr(attr::style, style);
+    }
+
+    Ok(img.pack().spanned(span))
+}
+
+/// Converts an absolute length into whole CSS pixels, of which there are 96
+/// per inch.
+fn css_pixels(abs: Abs) -> i64 {
+    (abs.to_inches() * 96.0).round() as i64
+}
+
+/// Try to determine the image format based on the data.
+fn determine_format(source: &DataSource, data: &Bytes) -> HintedStrResult<ImageFormat> {
+    if let DataSource::Path(path) = source {
+        let ext = std::path::Path::new(path.as_str())
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match ext.as_str() {
+            "png" => return Ok(ExchangeFormat::Png.into()),
+            "jpg" | "jpeg" => return Ok(ExchangeFormat::Jpg.into()),
+            "gif" => return Ok(ExchangeFormat::Gif.into()),
+            "webp" => return Ok(ExchangeFormat::Webp.into()),
+            "avif" => return Ok(ExchangeFormat::Avif.into()),
+            "tif" | "tiff" => return Ok(ExchangeFormat::Tiff.into()),
+            "bmp" => return Ok(ExchangeFormat::Bmp.into()),
+            "svg" | "svgz" => return Ok(VectorFormat::Svg.into()),
+            "pdf" => return Ok(VectorFormat::Pdf.into()),
+            "eps" | "epsf" | "epsi" => return Err(eps_error()),
+            _ => {}
+        }
+    }
+
+    if let Some(format) = ImageFormat::detect(data) {
+        return Ok(format);
+    }
+
+    // Encapsulated PostScript, either plain or with a DOS binary header.
+    if data.starts_with(b"%!PS-Adobe") || data.starts_with(&[0xC5, 0xD0, 0xD3, 0xC6]) {
+        return Err(eps_error());
+    }
+
+    bail!("unknown image format")
+}
+
+/// The error for EPS images, which can't be embedded.
+///
+/// Showing them would need a PostScript interpreter for PNG and SVG export,
+/// and PDF 2.0 and PDF/A forbid PostScript in PDFs.
+fn eps_error() -> HintedString {
+    error!(
+        "EPS images are not supported";
+        hint: "convert the image to PDF or SVG, for example with `ps2pdf -dEPSCrop`"
+    )
+}
+
 impl LocalName for Packed<ImageElem> {
     const KEY: &'static str = "figure";
 }
@@ -276,7 +414,7 @@ impl Image {
     /// Should always be the same as the default DPI used by usvg.
     pub const USVG_DEFAULT_DPI: f64 = 96.0;
 
-    /// Create an image from a `RasterImage` or `SvgImage`.
+    /// Create an image from a `RasterImage`, `SvgImage`, or `PdfImage`.
     pub fn new(
         kind: impl Into<ImageKind>,
         alt: Option<EcoString>,
@@ -306,6 +444,7 @@ impl Image {
         match &self.0.kind {
             ImageKind::Raster(raster) => raster.format().into(),
             ImageKind::Svg(_) => VectorFormat::Svg.into(),
+            ImageKind::Pdf(_) => VectorFormat::Pdf.into(),
         }
     }
 
@@ -314,6 +453,7 @@ impl Image {
         match &self.0.kind {
             ImageKind::Raster(raster) => raster.width() as f64,
             ImageKind::Svg(svg) => svg.width(),
+            ImageKind::Pdf(pdf) => pdf.width(),
         }
     }
 
@@ -322,6 +462,7 @@ impl Image {
         match &self.0.kind {
             ImageKind::Raster(raster) => raster.height() as f64,
             ImageKind::Svg(svg) => svg.height(),
+            ImageKind::Pdf(pdf) => pdf.height(),
         }
     }
 
@@ -330,6 +471,7 @@ impl Image {
         match &self.0.kind {
             ImageKind::Raster(raster) => raster.dpi(),
             ImageKind::Svg(_) => Some(Image::USVG_DEFAULT_DPI),
+            ImageKind::Pdf(_) => Some(Image::DEFAULT_DPI),
         }
     }
 
@@ -368,6 +510,8 @@ pub enum ImageKind {
     Raster(RasterImage),
     /// An SVG image.
     Svg(SvgImage),
+    /// A page of a PDF document.
+    Pdf(PdfImage),
 }
 
 impl From<RasterImage> for ImageKind {
@@ -382,6 +526,12 @@ impl From<SvgImage> for ImageKind {
     }
 }
 
+impl From<PdfImage> for ImageKind {
+    fn from(image: PdfImage) -> Self {
+        Self::Pdf(image)
+    }
+}
+
 /// A raster or vector image format.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum ImageFormat {
@@ -402,8 +552,34 @@ impl ImageFormat {
             return Some(Self::Vector(VectorFormat::Svg));
         }
 
+        if is_pdf(data) {
+            return Some(Self::Vector(VectorFormat::Pdf));
+        }
+
         None
     }
+
+    /// The MIME type under which browsers can display images of this format,
+    /// if they can display them at all.
+    pub fn mime_type(self, data: &[u8]) -> Option<&'static str> {
+        Some(match self {
+            Self::Raster(RasterFormat::Exchange(format)) => match format {
+                ExchangeFormat::Png => "image/png",
+                ExchangeFormat::Jpg => "image/jpeg",
+                ExchangeFormat::Gif => "image/gif",
+                ExchangeFormat::Webp => "image/webp",
+                ExchangeFormat::Avif => "image/avif",
+                ExchangeFormat::Bmp => "image/bmp",
+                ExchangeFormat::Tiff => return None,
+            },
+            // Compressed SVGs are only understood when served with the
+            // appropriate content encoding.
+            Self::Vector(VectorFormat::Svg) if !data.starts_with(&[0x1f, 0x8b]) => {
+                "image/svg+xml"
+            }
+            Self::Raster(RasterFormat::Pixel(_)) | Self::Vector(_) => return None,
+        })
+    }
 }
 
 /// Checks whether the data looks like an SVG or a compressed SVG.
@@ -421,11 +597,21 @@ fn is_svg(data: &[u8]) -> bool {
     memchr::memmem::find(head, b"http://www.w3.org/2000/svg").is_some()
 }
 
+/// Checks whether the data looks like a PDF.
+fn is_pdf(data: &[u8]) -> bool {
+    // The header must be at the start, but we tolerate a byte order mark and
+    // leading whitespace.
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    data.trim_ascii_start().starts_with(b"%PDF-")
+}
+
 /// A vector graphics format.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
 pub enum VectorFormat {
     /// The vector graphics format of the web.
     Svg,
+    /// The Portable Document Format. Only a single page is shown.
+    Pdf,
 }
 
 impl<R> From<R> for ImageFormat
@@ -0,0 +1,138 @@
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use ecow::EcoString;
+use hayro_syntax::page::Page;
+use hayro_syntax::{LoadPdfError, Pdf};
+
+use crate::diag::{bail, HintedStrResult, StrResult};
+use crate::foundations::Bytes;
+use crate::layout::Axes;
+
+/// A loaded PDF document.
+///
+/// A document can contain multiple pages, each of which can be embedded as
+/// a [`PdfImage`].
+#[derive(Clone, Hash)]
+pub struct PdfDocument(Arc<DocumentRepr>);
+
+/// The internal representation of a [`PdfDocument`].
+struct DocumentRepr {
+    data: Bytes,
+    pdf: Pdf,
+}
+
+impl PdfDocument {
+    /// Load a PDF document.
+    #[comemo::memoize]
+    #[typst_macros::time(name = "load pdf document")]
+    pub fn new(data: Bytes) -> StrResult<PdfDocument> {
+        let pdf = Pdf::new(Arc::new(data.clone())).map_err(format_pdf_error)?;
+
+        if pdf.pages().is_empty() {
+            bail!("the PDF does not contain any pages");
+        }
+
+        Ok(Self(Arc::new(DocumentRepr { data, pdf })))
+    }
+
+    /// The raw PDF data.
+    pub fn data(&self) -> &Bytes {
+        &self.0.data
+    }
+
+    /// Accesses the parsed PDF.
+    pub fn pdf(&self) -> &Pdf {
+        &self.0.pdf
+    }
+
+    /// The number of pages in the document.
+    pub fn len(&self) -> usize {
+        self.0.pdf.pages().len()
+    }
+
+    /// Whether the document has no pages. Always false for a loaded document.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Hash for DocumentRepr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The parsed document is fully determined by the raw data.
+        self.data.hash(state);
+    }
+}
+
+/// Format the user-facing PDF loading error message.
+fn format_pdf_error(error: LoadPdfError) -> EcoString {
+    match error {
+        LoadPdfError::Decryption(_) => {
+            "the PDF is encrypted or password-protected".into()
+        }
+        LoadPdfError::Invalid => "the PDF could not be loaded".into(),
+    }
+}
+
+/// A single page of a PDF document, used as an image.
+#[derive(Clone, Hash)]
+pub struct PdfImage(Arc<ImageRepr>);
+
+/// The internal representation of a [`PdfImage`].
+struct ImageRepr {
+    document: PdfDocument,
+    page_index: usize,
+    size: Axes<f64>,
+}
+
+impl PdfImage {
+    /// Select a page of a PDF document, given its zero-based index.
+    #[comemo::memoize]
+    pub fn new(document: PdfDocument, page_index: usize) -> HintedStrResult<PdfImage> {
+        let Some(page) = document.pdf().pages().get(page_index) else {
+            let count = document.len();
+            bail!(
+                "page {} does not exist", page_index + 1;
+                hint: "the document has {count} page{}",
+                if count == 1 { "" } else { "s" },
+            );
+        };
+
+        let (width, height) = page.render_dimensions();
+        let size = Axes::new(width as f64, height as f64);
+        Ok(Self(Arc::new(ImageRepr { document, page_index, size })))
+    }
+
+    /// The document the page belongs to.
+    pub fn document(&self) -> &PdfDocument {
+        &self.0.document
+    }
+
+    /// The zero-based index of the page in its document.
+    pub fn page_index(&self) -> usize {
+        self.0.page_index
+    }
+
+    /// Accesses the parsed page.
+    pub fn page(&self) -> &Page<'_> {
+        &self.0.document.pdf().pages()[self.0.page_index]
+    }
+
+    /// The page's width in points, taking its rotation into account.
+    pub fn width(&self) -> f64 {
+        self.0.size.x
+    }
+
+    /// The page's height in points, taking its rotation into account.
+    pub fn height(&self) -> f64 {
+        self.0.size.y
+    }
+}
+
+impl Hash for ImageRepr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The size is determined by the page.
+        self.document.hash(state);
+        self.page_index.hash(state);
+    }
+}
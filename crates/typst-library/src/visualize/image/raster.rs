@@ -4,15 +4,29 @@ use std::io;
 use std::sync::Arc;
 
 use ecow::{eco_format, EcoString};
+#[cfg(feature = "avif")]
+use image::codecs::avif::AvifDecoder;
+use image::codecs::bmp::BmpDecoder;
 use image::codecs::gif::GifDecoder;
 use image::codecs::jpeg::JpegDecoder;
 use image::codecs::png::PngDecoder;
+use image::codecs::tiff::TiffDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::error::{
+    DecodingError, ImageFormatHint, LimitError, LimitErrorKind, UnsupportedError,
+    UnsupportedErrorKind,
+};
 use image::{
-    guess_format, DynamicImage, ImageBuffer, ImageDecoder, ImageResult, Limits, Pixel,
+    guess_format, DynamicImage, ImageBuffer, ImageDecoder, ImageError, ImageResult,
+    Limits, Pixel,
 };
+use qcms::{DataType, Intent, Profile, Transform};
+use tiff::decoder::DecodingResult;
+use tiff::tags::Tag;
 
 use crate::diag::{bail, StrResult};
 use crate::foundations::{cast, dict, Bytes, Cast, Dict, Smart, Value};
+use crate::visualize::color::{SRGB_PROFILE, TO_SRGB};
 
 /// A decoded raster image.
 #[derive(Clone, Hash)]
@@ -25,6 +39,9 @@ struct Repr {
     dynamic: image::DynamicImage,
     icc: Option<Bytes>,
     dpi: Option<f64>,
+    /// The original samples of a CMYK image, stored as four 8-bit channels.
+    cmyk: Option<image::DynamicImage>,
+    rotated: bool,
 }
 
 impl RasterImage {
@@ -50,7 +67,7 @@ impl RasterImage {
         format: RasterFormat,
         icc: Smart<Bytes>,
     ) -> StrResult<RasterImage> {
-        let (dynamic, icc, dpi) = match format {
+        let (dynamic, icc, dpi, cmyk, rotated) = match format {
             RasterFormat::Exchange(format) => {
                 fn decode<T: ImageDecoder>(
                     decoder: ImageResult<T>,
@@ -71,10 +88,26 @@ impl RasterImage {
                 }
 
                 let cursor = io::Cursor::new(&data);
+                let mut cmyk = None;
                 let (mut dynamic, icc) = match format {
                     ExchangeFormat::Jpg => decode(JpegDecoder::new(cursor), icc),
                     ExchangeFormat::Png => decode(PngDecoder::new(cursor), icc),
                     ExchangeFormat::Gif => decode(GifDecoder::new(cursor), icc),
+                    ExchangeFormat::Webp => decode(WebPDecoder::new(cursor), icc),
+                    #[cfg(feature = "avif")]
+                    ExchangeFormat::Avif => decode(AvifDecoder::new(cursor), icc),
+                    #[cfg(not(feature = "avif"))]
+                    ExchangeFormat::Avif => {
+                        bail!("this build of Typst does not support AVIF images")
+                    }
+                    ExchangeFormat::Tiff if is_cmyk_tiff(&data) => {
+                        decode_cmyk_tiff(&data, icc).map(|(dynamic, icc, samples)| {
+                            cmyk = Some(samples);
+                            (dynamic, icc)
+                        })
+                    }
+                    ExchangeFormat::Tiff => decode(TiffDecoder::new(cursor), icc),
+                    ExchangeFormat::Bmp => decode(BmpDecoder::new(cursor), icc),
                 }
                 .map_err(format_image_error)?;
 
@@ -83,14 +116,18 @@ impl RasterImage {
                     .ok();
 
                 // Apply rotation from EXIF metadata.
-                if let Some(rotation) = exif.as_ref().and_then(exif_rotation) {
+                let rotation = exif.as_ref().and_then(exif_rotation);
+                if let Some(rotation) = rotation {
                     apply_rotation(&mut dynamic, rotation);
+                    if let Some(cmyk) = &mut cmyk {
+                        apply_rotation(cmyk, rotation);
+                    }
                 }
 
                 // Extract pixel density.
                 let dpi = determine_dpi(&data, exif.as_ref());
 
-                (dynamic, icc, dpi)
+                (dynamic, icc, dpi, cmyk, rotation.is_some_and(|r| r != 1))
             }
 
             RasterFormat::Pixel(format) => {
@@ -132,11 +169,11 @@ impl RasterImage {
                     PixelEncoding::Lumaa8 => to::<image::LumaA<u8>>(&data, format).into(),
                 };
 
-                (dynamic, icc.custom(), None)
+                (dynamic, icc.custom(), None, None, false)
             }
         };
 
-        Ok(Self(Arc::new(Repr { data, format, dynamic, icc, dpi })))
+        Ok(Self(Arc::new(Repr { data, format, dynamic, icc, dpi, cmyk, rotated })))
     }
 
     /// The raw image data.
@@ -175,6 +212,21 @@ impl RasterImage {
     pub fn icc(&self) -> Option<&Bytes> {
         self.0.icc.as_ref()
     }
+
+    /// The original samples of an image in the CMYK color model, if any.
+    ///
+    /// The samples are interleaved with eight bits per component. The
+    /// [dynamic image](Self::dynamic) holds a conversion to sRGB in this case.
+    pub fn cmyk(&self) -> Option<&[u8]> {
+        self.0.cmyk.as_ref().map(DynamicImage::as_bytes)
+    }
+
+    /// Whether the decoded pixels were rotated or flipped to apply the
+    /// image's EXIF orientation, so that they are no longer laid out like
+    /// in the encoded data.
+    pub fn is_rotated(&self) -> bool {
+        self.0.rotated
+    }
 }
 
 impl Hash for Repr {
@@ -227,12 +279,32 @@ pub enum ExchangeFormat {
     /// Raster format that is typically used for short animated clips. Typst can
     /// load GIFs, but they will become static.
     Gif,
+    /// Raster format for photos and illustrations with lossy or lossless
+    /// compression. Typst can load animated WebPs, but they will become static.
+    Webp,
+    /// Raster format with efficient lossy compression. Only available if Typst
+    /// was built with AVIF support.
+    Avif,
+    /// Raster format common in print and scanning workflows. Supports images
+    /// in the CMYK color model.
+    Tiff,
+    /// Raster format that stores pixels mostly uncompressed.
+    Bmp,
 }
 
 impl ExchangeFormat {
     /// Try to detect the format of data in a buffer.
     pub fn detect(data: &[u8]) -> Option<Self> {
-        guess_format(data).ok().and_then(|format| format.try_into().ok())
+        match guess_format(data).ok().and_then(|format| format.try_into().ok()) {
+            // The signatures of these formats are very short and also match
+            // unrelated files.
+            Some(Self::Webp) if data.get(8..12) != Some(b"WEBP".as_slice()) => None,
+            Some(Self::Bmp) if !is_bmp(data) => None,
+            // The `image` crate only recognizes AVIF files whose first box has
+            // one of two common sizes.
+            None if is_avif(data) => Some(Self::Avif),
+            format => format,
+        }
     }
 }
 
@@ -242,6 +314,10 @@ impl From<ExchangeFormat> for image::ImageFormat {
             ExchangeFormat::Png => image::ImageFormat::Png,
             ExchangeFormat::Jpg => image::ImageFormat::Jpeg,
             ExchangeFormat::Gif => image::ImageFormat::Gif,
+            ExchangeFormat::Webp => image::ImageFormat::WebP,
+            ExchangeFormat::Avif => image::ImageFormat::Avif,
+            ExchangeFormat::Tiff => image::ImageFormat::Tiff,
+            ExchangeFormat::Bmp => image::ImageFormat::Bmp,
         }
     }
 }
@@ -254,6 +330,10 @@ impl TryFrom<image::ImageFormat> for ExchangeFormat {
             image::ImageFormat::Png => ExchangeFormat::Png,
             image::ImageFormat::Jpeg => ExchangeFormat::Jpg,
             image::ImageFormat::Gif => ExchangeFormat::Gif,
+            image::ImageFormat::WebP => ExchangeFormat::Webp,
+            image::ImageFormat::Avif => ExchangeFormat::Avif,
+            image::ImageFormat::Tiff => ExchangeFormat::Tiff,
+            image::ImageFormat::Bmp => ExchangeFormat::Bmp,
             _ => bail!("format not yet supported"),
         })
     }
@@ -307,6 +387,122 @@ impl From<PixelFormat> for Dict {
     }
 }
 
+/// Checks whether the data starts with a BMP file header followed by one of
+/// the known info headers.
+fn is_bmp(data: &[u8]) -> bool {
+    let Some(size) = data.get(14..18) else { return false };
+    let size = u32::from_le_bytes(size.try_into().unwrap());
+    data.starts_with(b"BM") && matches!(size, 12 | 40 | 52 | 56 | 64 | 108 | 124)
+}
+
+/// Checks whether the data starts with an ISOBMFF file type box that
+/// declares an AVIF image or image sequence.
+fn is_avif(data: &[u8]) -> bool {
+    data.get(4..8) == Some(b"ftyp".as_slice())
+        && matches!(data.get(8..12), Some(b"avif" | b"avis"))
+}
+
+/// Checks whether the data is a TIFF image in the CMYK color model.
+fn is_cmyk_tiff(data: &[u8]) -> bool {
+    tiff::decoder::Decoder::new(io::Cursor::new(data))
+        .and_then(|mut decoder| decoder.colortype())
+        .is_ok_and(|color| matches!(color, tiff::ColorType::CMYK(_)))
+}
+
+/// Decode a TIFF image in the CMYK color model.
+///
+/// The `image` crate converts such images to RGB without taking their ICC
+/// profile into account and discards the original samples. We keep the
+/// samples around so that PDF export can embed them as they are.
+///
+/// Returns the image converted to sRGB, its ICC profile, and the samples.
+fn decode_cmyk_tiff(
+    data: &[u8],
+    icc: Smart<Bytes>,
+) -> ImageResult<(DynamicImage, Option<Bytes>, DynamicImage)> {
+    let mut decoder =
+        tiff::decoder::Decoder::new(io::Cursor::new(data)).map_err(format_tiff_error)?;
+    let (width, height) = decoder.dimensions().map_err(format_tiff_error)?;
+
+    let planar = decoder
+        .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)
+        .map_err(format_tiff_error)?;
+    if planar == Some(2) {
+        return Err(unsupported_tiff_error("planar CMYK images"));
+    }
+
+    let icc = icc.custom().or_else(|| {
+        decoder
+            .get_tag_u8_vec(Tag::Unknown(34675))
+            .ok()
+            .filter(|icc| !icc.is_empty())
+            .map(Bytes::new)
+    });
+
+    let samples = match decoder.read_image().map_err(format_tiff_error)? {
+        DecodingResult::U8(samples) => samples,
+        DecodingResult::U16(samples) => {
+            samples.into_iter().map(|sample| (sample >> 8) as u8).collect()
+        }
+        _ => return Err(unsupported_tiff_error("CMYK images of this bit depth")),
+    };
+
+    let Some(cmyk) = ImageBuffer::<image::Rgba<u8>, _>::from_raw(width, height, samples)
+    else {
+        return Err(format_tiff_error(tiff::TiffError::FormatError(
+            tiff::TiffFormatError::InconsistentSizesEncountered,
+        )));
+    };
+
+    // Prefer the image's own profile for the conversion, but fall back to the
+    // one we also use for CMYK colors.
+    let transform = icc
+        .as_ref()
+        .filter(|icc| icc.get(16..20) == Some(b"CMYK".as_slice()))
+        .and_then(|icc| Profile::new_from_slice(icc, false))
+        .and_then(|profile| {
+            Transform::new_to(
+                &profile,
+                &SRGB_PROFILE,
+                DataType::CMYK,
+                DataType::RGB8,
+                Intent::Perceptual,
+            )
+        });
+
+    let mut rgb = vec![0; width as usize * height as usize * 3];
+    transform
+        .as_ref()
+        .unwrap_or(&*TO_SRGB)
+        .convert(cmyk.as_raw(), &mut rgb);
+    let dynamic = ImageBuffer::<image::Rgb<u8>, _>::from_raw(width, height, rgb)
+        .unwrap()
+        .into();
+
+    Ok((dynamic, icc, cmyk.into()))
+}
+
+/// Convert an error of the `tiff` crate into one of the `image` crate.
+fn format_tiff_error(error: tiff::TiffError) -> ImageError {
+    match error {
+        tiff::TiffError::LimitsExceeded => {
+            ImageError::Limits(LimitError::from_kind(LimitErrorKind::InsufficientMemory))
+        }
+        err => ImageError::Decoding(DecodingError::new(
+            ImageFormatHint::Exact(image::ImageFormat::Tiff),
+            err,
+        )),
+    }
+}
+
+/// Create an error for an unsupported feature of a TIFF image.
+fn unsupported_tiff_error(feature: &str) -> ImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(image::ImageFormat::Tiff),
+        UnsupportedErrorKind::GenericFeature(feature.into()),
+    ))
+}
+
 /// Try to get the rotation from the EXIF metadata.
 fn exif_rotation(exif: &exif::Exif) -> Option<u32> {
     exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
@@ -341,11 +537,13 @@ fn apply_rotation(image: &mut DynamicImage, rotation: u32) {
 /// unspecified.
 fn determine_dpi(data: &[u8], exif: Option<&exif::Exif>) -> Option<f64> {
     // Try to extract the DPI from the EXIF metadata. If that doesn't yield
-    // anything, fall back to specialized procedures for extracting JPEG or PNG
-    // DPI metadata. GIF does not have any.
+    // anything, fall back to specialized procedures for extracting JPEG, PNG,
+    // or BMP DPI metadata. GIF does not have any and WebP, AVIF, and TIFF
+    // store it in their EXIF metadata.
     exif.and_then(exif_dpi)
         .or_else(|| jpeg_dpi(data))
         .or_else(|| png_dpi(data))
+        .or_else(|| bmp_dpi(data))
         .filter(|&dpi| dpi > 0.0)
 }
 
@@ -357,10 +555,20 @@ fn exif_dpi(exif: &exif::Exif) -> Option<f64> {
         Some(rational.first()?.to_f64())
     };
 
-    [axis(exif::Tag::XResolution), axis(exif::Tag::YResolution)]
+    let dpu = [axis(exif::Tag::XResolution), axis(exif::Tag::YResolution)]
         .into_iter()
         .flatten()
-        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))?;
+
+    let unit = exif
+        .get_field(exif::Tag::ResolutionUnit, exif::In::PRIMARY)
+        .and_then(|unit| unit.value.get_uint(0));
+
+    match unit {
+        None | Some(2) => Some(dpu), // inches, the default
+        Some(3) => Some(dpu * 2.54), // cm -> inches
+        _ => None,
+    }
 }
 
 /// Tries to extract the DPI from raw JPEG data (by inspecting the JFIF APP0
@@ -420,6 +628,23 @@ fn png_dpi(mut data: &[u8]) -> Option<f64> {
     }
 }
 
+/// Tries to extract the DPI from raw BMP data.
+fn bmp_dpi(data: &[u8]) -> Option<f64> {
+    let i32_at = |index: usize| -> Option<i32> {
+        data.get(index..index + 4)?.try_into().ok().map(i32::from_le_bytes)
+    };
+
+    // The oldest info header, which is 12 bytes long, has no resolution.
+    if !is_bmp(data) || i32_at(14)? == 12 {
+        return None;
+    }
+
+    let x = i32_at(38)?;
+    let y = i32_at(42)?;
+    let dpu = x.max(y) as f64;
+    Some(dpu * 0.0254) // meter -> inches
+}
+
 /// Format the user-facing raster graphic decoding error message.
 fn format_image_error(error: image::ImageError) -> EcoString {
     match error {
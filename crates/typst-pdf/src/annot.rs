@@ -0,0 +1,152 @@
+//! Markup annotations like comments, highlights, and file attachments.
+//!
+//! An annotation whose body is split across several frames is written as a
+//! single annotation per page. Its rectangle covers all fragments on the page
+//! and, for highlights, each fragment contributes one quadrilateral.
+
+use pdf_writer::types::{AnnotationFlags, AnnotationIcon, AnnotationType};
+use pdf_writer::{Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use typst_library::pdf::{AnnotKind, Annotation};
+use typst_library::visualize::ColorSpace;
+
+use crate::catalog::pdf_date;
+use crate::{deflate, PdfChunk, TextStrExt};
+
+/// The size of the icon of comments and file attachments without a body.
+const ICON_SIZE: f32 = 20.0;
+
+/// An annotation in the PDF coordinate system.
+#[derive(Clone)]
+pub struct EncodedAnnotation {
+    /// The annotation.
+    pub annotation: Annotation,
+    /// The area of the annotation on the page.
+    pub rect: Rect,
+    /// The corners of each fragment of the annotation's body, in the order
+    /// top left, top right, bottom left, bottom right.
+    pub quads: Vec<[f32; 8]>,
+    /// The annotation's key in the parent tree, if the document is tagged.
+    pub key: Option<i32>,
+}
+
+impl EncodedAnnotation {
+    /// Create an annotation from the first fragment of its body.
+    pub fn new(
+        annotation: Annotation,
+        rect: Rect,
+        quad: [f32; 8],
+        key: Option<i32>,
+    ) -> Self {
+        let rect = Rect::new(
+            rect.x1.min(rect.x2),
+            rect.y1.min(rect.y2),
+            rect.x1.max(rect.x2),
+            rect.y1.max(rect.y2),
+        );
+        Self { annotation, rect, quads: vec![quad], key }
+    }
+
+    /// Extend the annotation by another fragment of its body.
+    pub fn extend(&mut self, rect: Rect, quad: [f32; 8]) {
+        self.rect = Rect::new(
+            self.rect.x1.min(rect.x1).min(rect.x2),
+            self.rect.y1.min(rect.y1).min(rect.y2),
+            self.rect.x2.max(rect.x1).max(rect.x2),
+            self.rect.y2.max(rect.y1).max(rect.y2),
+        );
+        self.quads.push(quad);
+    }
+}
+
+/// Write an annotation on a page. Returns the reference of the annotation.
+pub fn write_annotation(
+    chunk: &mut PdfChunk,
+    page_ref: Ref,
+    encoded: &EncodedAnnotation,
+) -> Ref {
+    let id = chunk.alloc();
+    let annot = &encoded.annotation;
+
+    // Comments and file attachments without a body get an area that is large
+    // enough for viewers to show their icon.
+    let mut rect = encoded.rect;
+    if matches!(annot.kind, AnnotKind::Text | AnnotKind::File)
+        && (rect.x2 - rect.x1 < ICON_SIZE || rect.y2 - rect.y1 < ICON_SIZE)
+    {
+        rect = Rect::new(rect.x1, rect.y2 - ICON_SIZE, rect.x1 + ICON_SIZE, rect.y2);
+    }
+
+    // The attached file is written before the annotation since chunk writers
+    // can't be interleaved.
+    let file = annot.file.as_ref().map(|(name, data)| {
+        let file_ref = chunk.alloc();
+        let compressed = deflate(data.as_slice());
+        let mut embedded = chunk.embedded_file(file_ref, &compressed);
+        embedded.filter(Filter::FlateDecode);
+        embedded.params().size(data.len() as i32);
+        (name, file_ref)
+    });
+
+    let mut writer = chunk.packed().annotation(id);
+    match annot.kind {
+        AnnotKind::Text => {
+            writer.subtype(AnnotationType::Text);
+        }
+        AnnotKind::Highlight => {
+            writer.subtype(AnnotationType::Highlight);
+        }
+        AnnotKind::FreeText => {
+            writer.pair(Name(b"Subtype"), Name(b"FreeText"));
+        }
+        AnnotKind::File => {
+            writer.subtype(AnnotationType::FileAttachment);
+        }
+    }
+
+    writer.rect(rect).page(page_ref).flags(AnnotationFlags::PRINT);
+    if let Some(contents) = &annot.contents {
+        writer.contents(TextStr::trimmed(contents));
+    }
+    if let Some(author) = &annot.author {
+        writer.author(TextStr::trimmed(author));
+    }
+    if let Some(date) = annot.date.and_then(|date| pdf_date(date, None)) {
+        writer.modified(date);
+    }
+
+    let [r, g, b, _] = annot.color.to_space(ColorSpace::Srgb).to_vec4();
+    writer.color_rgb(r, g, b);
+
+    match annot.kind {
+        AnnotKind::Text => {
+            writer.icon(AnnotationIcon::Comment);
+        }
+        AnnotKind::Highlight => {
+            writer.quad_points(encoded.quads.iter().flatten().copied());
+        }
+        AnnotKind::FreeText => {
+            // The default appearance, which viewers use to lay out the text.
+            let da = format!("/Helv 10 Tf {r} {g} {b} rg");
+            writer.pair(Name(b"DA"), Str(da.as_bytes()));
+        }
+        AnnotKind::File => {
+            writer.icon(AnnotationIcon::PushPin);
+            if let Some((name, file_ref)) = file {
+                let mut spec = writer.file_spec();
+                spec.path(Str(name.as_bytes()));
+                spec.unic_file(TextStr::trimmed(name));
+                spec.insert(Name(b"EF"))
+                    .dict()
+                    .pair(Name(b"F"), file_ref)
+                    .pair(Name(b"UF"), file_ref);
+                spec.finish();
+            }
+        }
+    }
+
+    if let Some(key) = encoded.key {
+        writer.struct_parent(key);
+    }
+
+    id
+}
@@ -1,23 +1,28 @@
 use std::num::NonZeroUsize;
 
 use ecow::eco_format;
-use pdf_writer::types::Direction;
+use pdf_writer::types::{Direction, PageMode};
 use pdf_writer::writers::PageLabel;
-use pdf_writer::{Finish, Name, Pdf, Ref, Str, TextStr};
+use pdf_writer::{Chunk, Finish, Name, Pdf, Ref, Str, TextStr};
 use typst_library::diag::{bail, SourceResult};
 use typst_library::foundations::{Datetime, Smart};
 use typst_library::layout::Dir;
+use typst_library::model::{InitialView, PageLayout};
 use typst_library::text::Lang;
 use typst_syntax::Span;
-use xmp_writer::{DateTime, LangId, RenditionClass, XmpWriter};
+use xmp_writer::{DateTime, LangId, Namespace, RenditionClass, XmpWriter};
 
 use crate::page::PdfPageLabel;
-use crate::{hash_base64, outline, TextStrExt, Timestamp, Timezone, WithEverything};
+use crate::{
+    form, hash_base64, outline, PdfEncryptionAlgorithm, PdfOptions, PdfVersion,
+    TextStrExt, Timestamp, Timezone, WithEverything,
+};
 
 /// Write the document catalog.
 pub fn write_catalog(
     ctx: WithEverything,
     pdf: &mut Pdf,
+    packed: &[Chunk],
     alloc: &mut Ref,
 ) -> SourceResult<()> {
     let lang = ctx
@@ -43,13 +48,22 @@ pub fn write_catalog(
     let info_ref = alloc.bump();
     let mut info = pdf.document_info(info_ref);
     let mut xmp = XmpWriter::new();
+
+    // PDF 2.0 deprecates the document information dictionary in favor of the
+    // XMP metadata and PDF/A-4 only permits the modification date in it.
+    let full_info = !matches!(ctx.options.standards.pdfa_part, Some((4, _)));
+
     if let Some(title) = &ctx.document.info.title {
-        info.title(TextStr::trimmed(title));
+        if full_info {
+            info.title(TextStr::trimmed(title));
+        }
         xmp.title([(None, title.as_str())]);
     }
 
     if let Some(description) = &ctx.document.info.description {
-        info.subject(TextStr::trimmed(description));
+        if full_info {
+            info.subject(TextStr::trimmed(description));
+        }
         xmp.description([(None, description.as_str())]);
     }
 
@@ -72,23 +86,31 @@ pub fn write_catalog(
         // bit weird to not use the array (and it makes Acrobat show the author
         // list in quotes), but there's not much we can do about that.
         let joined = authors.join(", ");
-        info.author(TextStr::trimmed(&joined));
+        if full_info {
+            info.author(TextStr::trimmed(&joined));
+        }
         xmp.creator([joined.as_str()]);
     }
 
     let creator = eco_format!("Typst {}", env!("CARGO_PKG_VERSION"));
-    info.creator(TextStr(&creator));
+    if full_info {
+        info.creator(TextStr(&creator));
+    }
     xmp.creator_tool(&creator);
 
     let keywords = &ctx.document.info.keywords;
     if !keywords.is_empty() {
         let joined = keywords.join(", ");
-        info.keywords(TextStr::trimmed(&joined));
+        if full_info {
+            info.keywords(TextStr::trimmed(&joined));
+        }
         xmp.pdf_keywords(&joined);
     }
     let (date, tz) = document_date(ctx.document.info.date, ctx.options.timestamp);
     if let Some(pdf_date) = date.and_then(|date| pdf_date(date, tz)) {
-        info.creation_date(pdf_date);
+        if full_info {
+            info.creation_date(pdf_date);
+        }
         info.modified_date(pdf_date);
     }
 
@@ -96,7 +118,8 @@ pub fn write_catalog(
 
     // A unique ID for this instance of the document. Changes if anything
     // changes in the frames.
-    let instance_id = hash_base64(&pdf.as_bytes());
+    let packed_bytes: Vec<&[u8]> = packed.iter().map(Chunk::as_bytes).collect();
+    let instance_id = hash_base64(&(pdf.as_bytes(), packed_bytes));
 
     // Determine the document's ID. It should be as stable as possible.
     const PDF_VERSION: &str = "PDF-1.7";
@@ -115,7 +138,7 @@ pub fn write_catalog(
     xmp.document_id(&doc_id);
     xmp.instance_id(&instance_id);
     xmp.format("application/pdf");
-    xmp.pdf_version("1.7");
+    xmp.pdf_version(ctx.options.standards.version.as_str());
     xmp.language(ctx.resources.languages.keys().map(|lang| LangId(lang.as_str())));
     xmp.num_pages(ctx.document.pages.len() as u32);
     xmp.rendition_class(RenditionClass::Proof);
@@ -150,7 +173,18 @@ pub fn write_catalog(
         extension_schemas.pdf().properties().describe_all();
         extension_schemas.finish();
         xmp.pdfa_part(part);
-        xmp.pdfa_conformance(conformance);
+        if part >= 4 {
+            // PDF/A-4 identifies the revision of the standard and only has
+            // conformance levels for its variants.
+            xmp.element("rev", Namespace::PdfAId).value(2020);
+        }
+        if !conformance.is_empty() {
+            xmp.pdfa_conformance(conformance);
+        }
+    }
+
+    if ctx.options.standards.ua {
+        xmp.pdfua_part(1);
     }
 
     let xmp_buf = xmp.finish(None);
@@ -166,9 +200,72 @@ pub fn write_catalog(
     let catalog_ref = alloc.bump();
     let mut catalog = pdf.catalog(catalog_ref);
     catalog.pages(ctx.page_tree_ref);
-    catalog.viewer_preferences().direction(dir);
+    let info = &ctx.document.info;
+    let mut viewer_preferences = catalog.viewer_preferences();
+    viewer_preferences.direction(dir);
+    // PDF/UA-1 requires viewers to show the title instead of the file name.
+    if info.display_title || ctx.options.standards.ua {
+        viewer_preferences.display_doc_title(true);
+    }
+    if info.hide_toolbar {
+        viewer_preferences.hide_toolbar(true);
+    }
+    viewer_preferences.finish();
     catalog.metadata(meta_ref);
 
+    if let Smart::Custom(layout) = info.page_layout {
+        if matches!(layout, PageLayout::TwoPageLeft | PageLayout::TwoPageRight) {
+            check_version(ctx.options, "a two-page layout", 5)?;
+        }
+        catalog.page_layout(match layout {
+            PageLayout::Single => pdf_writer::types::PageLayout::SinglePage,
+            PageLayout::OneColumn => pdf_writer::types::PageLayout::OneColumn,
+            PageLayout::TwoColumnLeft => pdf_writer::types::PageLayout::TwoColumnLeft,
+            PageLayout::TwoColumnRight => pdf_writer::types::PageLayout::TwoColumnRight,
+            PageLayout::TwoPageLeft => pdf_writer::types::PageLayout::TwoPageLeft,
+            PageLayout::TwoPageRight => pdf_writer::types::PageLayout::TwoPageRight,
+        });
+    }
+
+    if let Smart::Custom(view) = info.initial_view {
+        if view == InitialView::Attachments {
+            check_version(ctx.options, "the attachments view", 6)?;
+        }
+        catalog.page_mode(match view {
+            InitialView::Pages => PageMode::UseNone,
+            InitialView::Outline => PageMode::UseOutlines,
+            InitialView::Thumbnails => PageMode::UseThumbs,
+            InitialView::Attachments => PageMode::UseAttachments,
+            InitialView::FullScreen => PageMode::FullScreen,
+        });
+    }
+
+    // AES-256 encryption is an Adobe extension to PDF 1.7.
+    let aes_256 =
+        ctx.options.encryption.as_ref().is_some_and(|encryption| {
+            encryption.algorithm == PdfEncryptionAlgorithm::Aes256
+        });
+    if aes_256 && ctx.options.standards.version != PdfVersion::V2_0 {
+        catalog
+            .insert(Name(b"Extensions"))
+            .dict()
+            .insert(Name(b"ADBE"))
+            .dict()
+            .pair(Name(b"BaseVersion"), Name(b"1.7"))
+            .pair(Name(b"ExtensionLevel"), 8);
+    }
+
+    // Write the reference to the structure tree of tagged documents.
+    if let Some(struct_tree_ref) = ctx.struct_tree_ref {
+        catalog.pair(Name(b"StructTreeRoot"), struct_tree_ref);
+        catalog.mark_info().marked(true);
+    }
+
+    // Write the interactive form.
+    if !ctx.form_fields.is_empty() {
+        form::write_acro_form(&mut catalog, &ctx.form_fields);
+    }
+
     let has_dests = !ctx.references.named_destinations.dests.is_empty();
     let has_embeddings = !ctx.references.embedded_files.is_empty();
 
@@ -231,13 +328,30 @@ pub fn write_catalog(
 
     catalog.finish();
 
-    if ctx.options.standards.pdfa && pdf.refs().count() > 8388607 {
+    if ctx.options.standards.pdfa && pdf.refs().count() + packed.len() > 8388607 {
         bail!(Span::detached(), "too many PDF objects");
     }
 
     Ok(())
 }
 
+/// Ensure that a viewer feature is available in the PDF version being written.
+pub(crate) fn check_version(
+    options: &PdfOptions,
+    feature: &str,
+    minor: u8,
+) -> SourceResult<()> {
+    let (major, current) = options.standards.version.numbers();
+    if major == 1 && current < minor {
+        bail!(
+            Span::detached(),
+            "{feature} is not available in PDF {major}.{current}";
+            hint: "it requires PDF 1.{minor}, which the selected PDF standard does not permit"
+        );
+    }
+    Ok(())
+}
+
 /// Write the page labels.
 pub(crate) fn write_page_labels(
     chunk: &mut Pdf,
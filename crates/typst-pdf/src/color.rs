@@ -6,7 +6,9 @@ use typst_library::diag::{bail, SourceResult};
 use typst_library::visualize::{Color, ColorSpace, Paint};
 use typst_syntax::Span;
 
-use crate::{content, deflate, PdfChunk, PdfOptions, Renumber, WithResources};
+use crate::{
+    content, deflate, PdfChunk, PdfOptions, PdfVersion, Renumber, WithResources,
+};
 
 // The names of the color spaces.
 pub const SRGB: Name<'static> = Name(b"srgb");
@@ -19,6 +21,12 @@ static SRGB_ICC_DEFLATED: LazyLock<Vec<u8>> =
 static GRAY_ICC_DEFLATED: LazyLock<Vec<u8>> =
     LazyLock::new(|| deflate(typst_assets::icc::S_GREY_V4));
 
+// Version 2 of the ICC profiles for PDF/A-1, which is based on PDF 1.4.
+static SRGB_ICC_V2_DEFLATED: LazyLock<Vec<u8>> =
+    LazyLock::new(|| deflate(&icc_v2_profile(true)));
+static GRAY_ICC_V2_DEFLATED: LazyLock<Vec<u8>> =
+    LazyLock::new(|| deflate(&icc_v2_profile(false)));
+
 /// The color spaces present in the PDF document
 #[derive(Default)]
 pub struct ColorSpaces {
@@ -65,11 +73,22 @@ impl ColorSpaces {
 
     /// Write the necessary color spaces functions and ICC profiles to the
     /// PDF file.
-    pub fn write_functions(&self, chunk: &mut Chunk, refs: &ColorFunctionRefs) {
+    pub fn write_functions(
+        &self,
+        chunk: &mut Chunk,
+        refs: &ColorFunctionRefs,
+        options: &PdfOptions,
+    ) {
+        let (srgb_icc, gray_icc) = if options.standards.version == PdfVersion::V1_4 {
+            (&*SRGB_ICC_V2_DEFLATED, &*GRAY_ICC_V2_DEFLATED)
+        } else {
+            (&*SRGB_ICC_DEFLATED, &*GRAY_ICC_DEFLATED)
+        };
+
         // Write the sRGB color space.
         if let Some(id) = refs.srgb {
             chunk
-                .icc_profile(id, &SRGB_ICC_DEFLATED)
+                .icc_profile(id, srgb_icc)
                 .n(3)
                 .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
                 .filter(Filter::FlateDecode);
@@ -78,7 +97,7 @@ impl ColorSpaces {
         // Write the gray color space.
         if let Some(id) = refs.d65_gray {
             chunk
-                .icc_profile(id, &GRAY_ICC_DEFLATED)
+                .icc_profile(id, gray_icc)
                 .n(1)
                 .range([0.0, 1.0])
                 .filter(Filter::FlateDecode);
@@ -392,3 +411,87 @@ pub(super) fn check_cmyk_allowed(options: &PdfOptions) -> SourceResult<()> {
     }
     Ok(())
 }
+
+/// Creates an ICC profile of version 2 for sRGB or, if `rgb` is `false`, for
+/// grayscale with the sRGB transfer curve.
+///
+/// The profiles shipped with Typst are of version 4, which PDF 1.4 does not
+/// support. The colorants are the sRGB primaries adapted to the D50 white
+/// point of the profile connection space.
+fn icc_v2_profile(rgb: bool) -> Vec<u8> {
+    const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+    let xyz = |values: [f64; 3]| {
+        let mut data = b"XYZ \0\0\0\0".to_vec();
+        for v in values {
+            data.extend(((v * 65536.0).round() as i32).to_be_bytes());
+        }
+        data
+    };
+
+    // The sRGB transfer curve, sampled at 1024 points.
+    let mut trc = b"curv\0\0\0\0".to_vec();
+    trc.extend(1024u32.to_be_bytes());
+    for i in 0..1024 {
+        let v = f64::from(i) / 1023.0;
+        let linear =
+            if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+        trc.extend(((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+
+    let name = if rgb { "sRGB IEC61966-2.1" } else { "sGrey" };
+    let mut desc = b"desc\0\0\0\0".to_vec();
+    desc.extend((name.len() as u32 + 1).to_be_bytes());
+    desc.extend(name.as_bytes());
+    desc.push(0);
+    // Empty Unicode and ScriptCode descriptions.
+    desc.extend([0; 8 + 3 + 67]);
+
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend(b"No copyright, use freely\0");
+
+    let mut tags = vec![(b"desc", desc), (b"cprt", cprt), (b"wtpt", xyz(D50))];
+    if rgb {
+        tags.push((b"rXYZ", xyz([0.4360747, 0.2225045, 0.0139322])));
+        tags.push((b"gXYZ", xyz([0.3850649, 0.7168786, 0.0971045])));
+        tags.push((b"bXYZ", xyz([0.1430804, 0.0606169, 0.7141733])));
+        tags.push((b"rTRC", trc.clone()));
+        tags.push((b"gTRC", trc.clone()));
+        tags.push((b"bTRC", trc));
+    } else {
+        tags.push((b"kTRC", trc));
+    }
+
+    // Write the tag table and the tag data, aligned to four bytes.
+    let data_start = 128 + 4 + 12 * tags.len();
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = vec![];
+    for (signature, tag) in &tags {
+        table.extend(*signature);
+        table.extend(((data_start + data.len()) as u32).to_be_bytes());
+        table.extend((tag.len() as u32).to_be_bytes());
+        data.extend(tag);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+
+    let size = data_start + data.len();
+    let mut profile = Vec::with_capacity(size);
+    profile.extend((size as u32).to_be_bytes());
+    profile.extend([0; 4]); // Preferred CMM type.
+    profile.extend([0x02, 0x10, 0, 0]); // Version 2.1.
+    profile.extend(b"mntr");
+    profile.extend(if rgb { b"RGB " } else { b"GRAY" });
+    profile.extend(b"XYZ ");
+    for part in [2025u16, 1, 1, 0, 0, 0] {
+        profile.extend(part.to_be_bytes()); // Creation date.
+    }
+    profile.extend(b"acsp");
+    profile.extend([0; 28]); // Platform, flags, device, attributes, intent.
+    for v in D50 {
+        profile.extend(((v * 65536.0).round() as i32).to_be_bytes());
+    }
+    profile.extend([0; 48]); // Creator and reserved bytes.
+    profile.extend(table);
+    profile.extend(data);
+    profile
+}
@@ -256,6 +256,8 @@ impl ColorFontMap<()> {
                 &frame,
                 None,
                 Some(width as f32),
+                None,
+                None,
             )?;
             color_font.glyphs.push(ColorGlyph { gid: glyph.id, instructions });
             color_font.glyph_indices.insert(glyph.id, index);
@@ -4,18 +4,25 @@
 //!
 //! See also [`pdf_writer::Content`].
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use comemo::Tracked;
 use ecow::eco_format;
+use indexmap::{IndexMap, IndexSet};
 use pdf_writer::types::{
     ColorSpaceOperand, LineCapStyle, LineJoinStyle, TextRenderingMode,
 };
 use pdf_writer::writers::PositionedItems;
 use pdf_writer::{Content, Finish, Name, Rect, Str};
+use subsetter::GlyphRemapper;
 use typst_library::diag::{bail, error, SourceDiagnostic, SourceResult};
 use typst_library::foundations::Repr;
 use typst_library::layout::{
     Abs, Em, Frame, FrameItem, GroupItem, Point, Ratio, Size, Transform,
 };
 use typst_library::model::Destination;
+use typst_library::pdf::{Annotation, Widget};
 use typst_library::text::color::should_outline;
 use typst_library::text::{Font, Glyph, TextItem, TextItemView};
 use typst_library::visualize::{
@@ -25,11 +32,15 @@ use typst_library::visualize::{
 use typst_syntax::Span;
 use typst_utils::{Deferred, Numeric, SliceExt};
 
+use crate::annot::EncodedAnnotation;
 use crate::color::PaintEncode;
 use crate::color_font::ColorFontMap;
 use crate::extg::ExtGState;
+use crate::form::EncodedWidget;
+use crate::gradient;
 use crate::image::deferred_image;
 use crate::resources::Resources;
+use crate::tags::{begin_marked, LinkTag, Mark, Marked};
 use crate::{deflate_deferred, AbsExt, ContentExt, EmExt, PdfOptions, StrExt};
 
 /// Encode a [`Frame`] into a content stream.
@@ -39,6 +50,12 @@ use crate::{deflate_deferred, AbsExt, ContentExt, EmExt, PdfOptions, StrExt};
 /// `color_glyph_width` should be `None` unless the `Frame` represents a [color
 /// glyph].
 ///
+/// If `marks` is given, the content is marked for a tagged PDF with the marks
+/// that were recorded while building the structure tree.
+///
+/// If `cids` is given, the glyphs are encoded with the CIDs fixed there instead
+/// of with the glyph remappers of `resources`.
+///
 /// [color glyph]: `crate::color_font`
 pub fn build(
     options: &PdfOptions,
@@ -46,9 +63,13 @@ pub fn build(
     frame: &Frame,
     fill: Option<Paint>,
     color_glyph_width: Option<f32>,
+    marks: Option<&[Mark]>,
+    cids: Option<Tracked<Cids>>,
 ) -> SourceResult<Encoded> {
     let size = frame.size();
     let mut ctx = Builder::new(options, resources, size);
+    ctx.marks = marks.map(|marks| marks.iter());
+    ctx.cids = cids;
 
     if let Some(width) = color_glyph_width {
         ctx.content.start_color_glyph(width);
@@ -64,7 +85,10 @@ pub fn build(
 
     if let Some(fill) = fill {
         let shape = Geometry::Rect(frame.size()).filled(fill);
-        write_shape(&mut ctx, Point::zero(), &shape)?;
+        let marked = ctx.marked();
+        write_marked(&mut ctx, marked, |ctx| {
+            write_shape(ctx, Point::zero(), &shape, Span::detached())
+        })?;
     }
 
     // Encode the frame into the content stream.
@@ -75,10 +99,13 @@ pub fn build(
         content: deflate_deferred(ctx.content.finish()),
         uses_opacities: ctx.uses_opacities,
         links: ctx.links,
+        widgets: ctx.widgets,
+        annotations: ctx.annotations,
     })
 }
 
 /// An encoded content stream.
+#[derive(Clone)]
 pub struct Encoded {
     /// The dimensions of the content.
     pub size: Size,
@@ -86,8 +113,95 @@ pub struct Encoded {
     pub content: Deferred<Vec<u8>>,
     /// Whether the content opacities.
     pub uses_opacities: bool,
-    /// Links in the PDF coordinate system.
-    pub links: Vec<(Destination, Rect)>,
+    /// Links in the PDF coordinate system, with their tagging information if
+    /// the content is tagged.
+    pub links: Vec<(Destination, Rect, Option<LinkTag>)>,
+    /// Form field widgets in the PDF coordinate system, in document order.
+    pub widgets: Vec<EncodedWidget>,
+    /// Annotations in the PDF coordinate system, in document order.
+    pub annotations: Vec<EncodedAnnotation>,
+}
+
+/// The glyphs that a page draws with outlines and whether the page can be
+/// encoded independently of the other pages.
+pub struct Scan {
+    /// The glyphs per font, in order of their first use.
+    pub glyphs: IndexMap<Font, IndexSet<u16>>,
+    /// Whether the page neither contains tilings nor color glyphs. Their
+    /// content streams are shared across the document, so pages containing
+    /// them must be encoded with the document's resources.
+    pub standalone: bool,
+}
+
+/// Find the glyphs that a page draws with outlines.
+///
+/// This covers the glyphs in form field appearances, but not the ones in
+/// tilings or color glyphs, which have resources of their own.
+#[comemo::memoize]
+pub fn scan(frame: &Frame) -> Arc<Scan> {
+    fn visit(scan: &mut Scan, frame: &Frame) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => visit(scan, &group.frame),
+                FrameItem::Text(text) => {
+                    let paints = text.stroke.iter().map(|stroke| &stroke.paint);
+                    if std::iter::once(&text.fill).chain(paints).any(is_tiling) {
+                        scan.standalone = false;
+                    }
+
+                    let glyphs = scan.glyphs.entry(text.font.clone()).or_default();
+                    for glyph in &text.glyphs {
+                        if should_outline(&text.font, glyph) {
+                            glyphs.insert(glyph.id);
+                        } else {
+                            scan.standalone = false;
+                        }
+                    }
+                }
+                FrameItem::Shape(shape, _) => {
+                    let paints = shape.stroke.iter().map(|stroke| &stroke.paint);
+                    if shape.fill.iter().chain(paints).any(is_tiling) {
+                        scan.standalone = false;
+                    }
+                }
+                FrameItem::Widget(widget, _) => {
+                    visit(scan, &widget.appearance);
+                    if let Some(toggled) = &widget.toggled {
+                        visit(scan, toggled);
+                    }
+                }
+                FrameItem::Image(..)
+                | FrameItem::Link(..)
+                | FrameItem::Tag(_)
+                | FrameItem::Annotation(..) => {}
+            }
+        }
+    }
+
+    fn is_tiling(paint: &Paint) -> bool {
+        matches!(paint, Paint::Tiling(_))
+    }
+
+    let mut scan = Scan { glyphs: IndexMap::new(), standalone: true };
+    visit(&mut scan, frame);
+    Arc::new(scan)
+}
+
+/// The CIDs of the glyphs in the embedded font subsets.
+///
+/// They are fixed for all glyphs found by [`scan`] before the pages are
+/// encoded, so that pages can be encoded independently of each other.
+pub struct Cids(pub HashMap<Font, GlyphRemapper>);
+
+#[comemo::track]
+impl Cids {
+    /// The CID of a glyph.
+    fn get(&self, font: &Font, glyph: u16) -> u16 {
+        self.0
+            .get(font)
+            .and_then(|remapper| remapper.get(glyph))
+            .expect("glyph should have been scanned")
+    }
 }
 
 /// An exporter for a single PDF content stream.
@@ -111,7 +225,15 @@ pub struct Builder<'a, R = ()> {
     /// Whether any stroke or fill was not totally opaque.
     uses_opacities: bool,
     /// All clickable links that are present in this content.
-    links: Vec<(Destination, Rect)>,
+    links: Vec<(Destination, Rect, Option<LinkTag>)>,
+    /// All form field widgets that are present in this content.
+    widgets: Vec<EncodedWidget>,
+    /// All annotations that are present in this content.
+    annotations: Vec<EncodedAnnotation>,
+    /// The remaining marks of the content, if it is tagged.
+    marks: Option<std::slice::Iter<'a, Mark>>,
+    /// The CIDs to encode glyphs with, if they are fixed upfront.
+    cids: Option<Tracked<'a, Cids>>,
 }
 
 impl<'a, R> Builder<'a, R> {
@@ -129,6 +251,34 @@ impl<'a, R> Builder<'a, R> {
             state: State::new(size),
             saves: vec![],
             links: vec![],
+            widgets: vec![],
+            annotations: vec![],
+            marks: None,
+            cids: None,
+        }
+    }
+
+    /// Take the next mark, if the content is tagged.
+    fn mark(&mut self) -> Option<Mark> {
+        let marks = self.marks.as_mut()?;
+        Some(marks.next().expect("marks were not recorded for all items").clone())
+    }
+
+    /// Take the next mark of a text run, shape, or image, if the content is
+    /// tagged.
+    fn marked(&mut self) -> Option<Marked> {
+        match self.mark()? {
+            Mark::Content(marked) => Some(marked),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Take the parent tree key of the next form field widget or annotation,
+    /// if the content is tagged.
+    fn annotation_key(&mut self) -> Option<i32> {
+        match self.mark()? {
+            Mark::Annotation(key) => key,
+            _ => unreachable!(),
         }
     }
 }
@@ -214,8 +364,7 @@ impl Builder<'_, ()> {
     fn set_external_graphics_state(&mut self, graphics_state: &ExtGState) {
         let current_state = &self.state.external_graphics_state;
         if current_state != graphics_state {
-            let index = self.resources.ext_gs.insert(*graphics_state);
-            let name = eco_format!("Gs{index}");
+            let name = self.resources.ext_gs.insert(*graphics_state);
             self.content.set_parameters(Name(name.as_bytes()));
 
             self.state.external_graphics_state = *graphics_state;
@@ -225,7 +374,12 @@ impl Builder<'_, ()> {
         }
     }
 
-    fn set_opacities(&mut self, stroke: Option<&FixedStroke>, fill: Option<&Paint>) {
+    fn set_opacities(
+        &mut self,
+        stroke: Option<&FixedStroke>,
+        fill: Option<&Paint>,
+        span: Span,
+    ) -> SourceResult<()> {
         let get_opacity = |paint: &Paint| {
             let color = match paint {
                 Paint::Solid(color) => *color,
@@ -237,7 +391,26 @@ impl Builder<'_, ()> {
 
         let stroke_opacity = stroke.map_or(255, |stroke| get_opacity(&stroke.paint));
         let fill_opacity = fill.map_or(255, get_opacity);
-        self.set_external_graphics_state(&ExtGState { stroke_opacity, fill_opacity });
+        let state = ExtGState { stroke_opacity, fill_opacity };
+        if !self.options.standards.transparency {
+            if state.uses_opacities() {
+                bail!(
+                    span,
+                    "transparent colors are not allowed in PDF/A-1";
+                    hint: "PDF/A-2 and later support transparency"
+                );
+            }
+
+            let paints = stroke.map(|stroke| &stroke.paint).into_iter().chain(fill);
+            for paint in paints {
+                if let Paint::Gradient(gradient) = paint {
+                    gradient::check_transparency(gradient, span)?;
+                }
+            }
+        }
+
+        self.set_external_graphics_state(&state);
+        Ok(())
     }
 
     fn reset_opacities(&mut self) {
@@ -270,8 +443,7 @@ impl Builder<'_, ()> {
 
     fn set_font(&mut self, font: &Font, size: Abs) {
         if self.state.font.as_ref().map(|(f, s)| (f, *s)) != Some((font, size)) {
-            let index = self.resources.fonts.insert(font.clone());
-            let name = eco_format!("F{index}");
+            let name = self.resources.fonts.insert(font.clone());
             self.content.set_font(Name(name.as_bytes()), size.to_f32());
             self.state.font = Some((font.clone(), size));
         }
@@ -374,18 +546,56 @@ pub(crate) fn write_frame(ctx: &mut Builder, frame: &Frame) -> SourceResult<()>
         let y = pos.y.to_f32();
         match item {
             FrameItem::Group(group) => write_group(ctx, pos, group)?,
-            FrameItem::Text(text) => write_text(ctx, pos, text)?,
-            FrameItem::Shape(shape, _) => write_shape(ctx, pos, shape)?,
+            FrameItem::Text(text) => {
+                let marked = ctx.marked();
+                write_marked(ctx, marked, |ctx| write_text(ctx, pos, text))?
+            }
+            FrameItem::Shape(shape, span) => {
+                let marked = ctx.marked();
+                write_marked(ctx, marked, |ctx| write_shape(ctx, pos, shape, *span))?
+            }
             FrameItem::Image(image, size, span) => {
-                write_image(ctx, x, y, image, *size, *span)?
+                let marked = ctx.marked();
+                if ctx.options.standards.ua
+                    && image.alt().is_none()
+                    && matches!(marked, Some(Marked::Content { .. }))
+                {
+                    bail!(
+                        *span,
+                        "PDF/UA-1 requires images to have alt text";
+                        hint: "use the `alt` parameter of `image` to describe it";
+                        hint: "decorative images can be wrapped in `pdf.artifact`"
+                    );
+                }
+                write_marked(ctx, marked, |ctx| {
+                    write_image(ctx, x, y, image, *size, *span)
+                })?
             }
             FrameItem::Link(dest, size) => write_link(ctx, pos, dest, *size),
+            FrameItem::Widget(widget, span) => write_widget(ctx, pos, widget, *span)?,
+            FrameItem::Annotation(annot, size) => {
+                write_annotation(ctx, pos, annot, *size)?
+            }
             FrameItem::Tag(_) => {}
         }
     }
     Ok(())
 }
 
+/// Encode content into a marked-content sequence, if it should be marked for
+/// a tagged PDF.
+fn write_marked(
+    ctx: &mut Builder,
+    marked: Option<Marked>,
+    f: impl FnOnce(&mut Builder) -> SourceResult<()>,
+) -> SourceResult<()> {
+    let Some(marked) = marked else { return f(ctx) };
+    begin_marked(&mut ctx.content, &marked);
+    f(ctx)?;
+    ctx.content.end_marked_content();
+    Ok(())
+}
+
 /// Encode a group into the content stream.
 fn write_group(ctx: &mut Builder, pos: Point, group: &GroupItem) -> SourceResult<()> {
     let translation = Transform::translate(pos.x, pos.y);
@@ -497,7 +707,8 @@ fn write_normal_text(
     }
 
     ctx.set_font(&text.item.font, text.item.size);
-    ctx.set_opacities(text.item.stroke.as_ref(), Some(&text.item.fill));
+    let span = Span::find(text.glyphs().iter().map(|g| g.span.0));
+    ctx.set_opacities(text.item.stroke.as_ref(), Some(&text.item.fill), span)?;
     ctx.content.begin_text();
 
     // Position the text.
@@ -508,6 +719,7 @@ fn write_normal_text(
     let mut adjustment = Em::zero();
     let mut encoded = vec![];
 
+    let cids = ctx.cids;
     let glyph_remapper = ctx
         .resources
         .glyph_remappers
@@ -551,7 +763,10 @@ fn write_normal_text(
         //
         // Because of this, we can always use the remapped GID as the CID,
         // regardless of which type of font we are actually embedding.
-        let cid = glyph_remapper.remap(glyph.id);
+        let cid = match cids {
+            Some(cids) => cids.get(&text.item.font, glyph.id),
+            None => glyph_remapper.remap(glyph.id),
+        };
         encoded.push((cid >> 8) as u8);
         encoded.push((cid & 0xff) as u8);
 
@@ -637,7 +852,12 @@ fn write_complex_glyphs(
 }
 
 /// Encode a geometrical shape into the content stream.
-fn write_shape(ctx: &mut Builder, pos: Point, shape: &Shape) -> SourceResult<()> {
+fn write_shape(
+    ctx: &mut Builder,
+    pos: Point,
+    shape: &Shape,
+    span: Span,
+) -> SourceResult<()> {
     let x = pos.x.to_f32();
     let y = pos.y.to_f32();
 
@@ -665,7 +885,7 @@ fn write_shape(ctx: &mut Builder, pos: Point, shape: &Shape) -> SourceResult<()>
         )?;
     }
 
-    ctx.set_opacities(stroke, shape.fill.as_ref());
+    ctx.set_opacities(stroke, shape.fill.as_ref(), span)?;
 
     match &shape.geometry {
         Geometry::Line(target) => {
@@ -726,8 +946,8 @@ fn write_image(
     size: Size,
     span: Span,
 ) -> SourceResult<()> {
-    let index = ctx.resources.images.insert(image.clone());
-    ctx.resources.deferred_images.entry(index).or_insert_with(|| {
+    let name = ctx.resources.images.insert(image.clone());
+    ctx.resources.deferred_images.entry(image.clone()).or_insert_with(|| {
         let (image, color_space) =
             deferred_image(image.clone(), ctx.options.standards.pdfa);
         if let Some(color_space) = color_space {
@@ -738,7 +958,6 @@ fn write_image(
 
     ctx.reset_opacities();
 
-    let name = eco_format!("Im{index}");
     let w = size.x.to_f32();
     let h = size.y.to_f32();
     ctx.content.save_state_checked()?;
@@ -768,12 +987,117 @@ fn write_image(
 
 /// Save a link for later writing in the annotations dictionary.
 fn write_link(ctx: &mut Builder, pos: Point, dest: &Destination, size: Size) {
+    let rect = bounding_rect(ctx, pos, size);
+    let tag = match ctx.mark() {
+        Some(Mark::Link(tag)) => tag,
+        Some(_) => unreachable!(),
+        None => None,
+    };
+    ctx.links.push((dest.clone(), rect, tag));
+}
+
+/// Save a form field widget for later writing.
+fn write_widget(
+    ctx: &mut Builder,
+    pos: Point,
+    widget: &Widget,
+    span: Span,
+) -> SourceResult<()> {
+    if ctx.options.standards.ua && widget.field.tooltip.is_none() {
+        bail!(
+            span,
+            "PDF/UA-1 requires form fields to have a tooltip";
+            hint: "describe the field with the `tooltip` argument"
+        );
+    }
+
+    let rect = bounding_rect(ctx, pos, widget.appearance.size());
+    let cids = ctx.cids;
+    let mut encode =
+        |frame: &Frame| build(ctx.options, ctx.resources, frame, None, None, None, cids);
+    let appearance = encode(&widget.appearance)?;
+    let toggled = widget.toggled.as_ref().map(encode).transpose()?;
+    let key = ctx.annotation_key();
+
+    ctx.widgets.push(EncodedWidget {
+        field: widget.field.clone(),
+        rect,
+        appearance,
+        toggled,
+        key,
+        span,
+    });
+
+    Ok(())
+}
+
+/// Save an annotation for later writing.
+///
+/// Fragments of an annotation whose body was split into multiple frames are
+/// merged into the annotation of the first fragment.
+fn write_annotation(
+    ctx: &mut Builder,
+    pos: Point,
+    annot: &Annotation,
+    size: Size,
+) -> SourceResult<()> {
+    let rect = bounding_rect(ctx, pos, size);
+    let mut quad = [0.0; 8];
+    for (i, point) in [
+        pos,
+        pos + Point::with_x(size.x),
+        pos + Point::with_y(size.y),
+        pos + size.to_point(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let t = point.transform(ctx.state.transform);
+        quad[2 * i] = t.x.to_f32();
+        quad[2 * i + 1] = t.y.to_f32();
+    }
+
+    if let Some(encoded) = ctx
+        .annotations
+        .iter_mut()
+        .find(|encoded| encoded.annotation.location == annot.location)
+    {
+        encoded.extend(rect, quad);
+        return Ok(());
+    }
+
+    if ctx.options.standards.pdfa {
+        bail!(
+            annot.span,
+            "annotations are not currently supported in PDF/A";
+            hint: "Typst does not generate appearances for annotations"
+        );
+    }
+
+    if ctx.options.standards.ua && annot.contents.is_none() {
+        bail!(
+            annot.span,
+            "PDF/UA-1 requires annotations to have contents";
+            hint: "describe the annotation with the `contents` argument"
+        );
+    }
+
+    let key = ctx.annotation_key();
+    ctx.annotations
+        .push(EncodedAnnotation::new(annot.clone(), rect, quad, key));
+
+    Ok(())
+}
+
+/// Compute the bounding box of an item with the given size in the PDF
+/// coordinate system.
+fn bounding_rect(ctx: &Builder, pos: Point, size: Size) -> Rect {
     let mut min_x = Abs::inf();
     let mut min_y = Abs::inf();
     let mut max_x = -Abs::inf();
     let mut max_y = -Abs::inf();
 
-    // Compute the bounding box of the transformed link.
+    // Compute the bounding box of the transformed item.
     for point in [
         pos,
         pos + Point::with_x(size.x),
@@ -791,9 +1115,7 @@ fn write_link(ctx: &mut Builder, pos: Point, dest: &Destination, size: Size) {
     let x2 = max_x.to_f32();
     let y1 = max_y.to_f32();
     let y2 = min_y.to_f32();
-    let rect = Rect::new(x1, y1, x2, y2);
-
-    ctx.links.push((dest.clone(), rect));
+    Rect::new(x1, y1, x2, y2)
 }
 
 fn to_pdf_line_cap(cap: LineCap) -> LineCapStyle {
@@ -23,12 +23,14 @@ pub fn write_embedded_files(
     let elements = ctx.document.introspector.query(&EmbedElem::elem().select());
     for elem in &elements {
         if !ctx.options.standards.embedded_files {
-            // PDF/A-2 requires embedded files to be PDF/A-1 or PDF/A-2,
-            // which we don't currently check.
+            // PDF/A-2 and PDF/A-4 require embedded files to be PDF/A files
+            // themselves, which we don't currently check. PDF/A-1 does not
+            // allow embedded files at all.
+            let (part, _) = ctx.options.standards.pdfa_part.unwrap();
             bail!(
                 elem.span(),
-                "file embeddings are not currently supported for PDF/A-2";
-                hint: "PDF/A-3 supports arbitrary embedded files"
+                "file embeddings are not currently supported for PDF/A-{part}";
+                hint: "PDF/A-3 and PDF/A-4f support arbitrary embedded files"
             );
         }
 
@@ -70,8 +72,8 @@ fn embed_file(
             bail!(embed.span(), "embedded file MIME type is too long");
         }
         embedded_file.subtype(Name(mime_type.as_bytes()));
-    } else if ctx.options.standards.pdfa {
-        bail!(embed.span(), "embedded files must have a MIME type in PDF/A-3");
+    } else if let Some((part, _)) = ctx.options.standards.pdfa_part {
+        bail!(embed.span(), "embedded files must have a MIME type in PDF/A-{part}");
     }
 
     let mut params = embedded_file.params();
@@ -80,10 +82,10 @@ fn embed_file(
     let (date, tz) = document_date(ctx.document.info.date, ctx.options.timestamp);
     if let Some(pdf_date) = date.and_then(|date| pdf_date(date, tz)) {
         params.modification_date(pdf_date);
-    } else if ctx.options.standards.pdfa {
+    } else if let Some((part, _)) = ctx.options.standards.pdfa_part {
         bail!(
             embed.span(),
-            "the document must have a date when embedding files in PDF/A-3";
+            "the document must have a date when embedding files in PDF/A-{part}";
             hint: "`set document(date: none)` must not be used in this case"
         );
     }
@@ -102,6 +104,7 @@ fn embed_file(
 
     if ctx.options.standards.pdfa {
         // PDF 2.0, but ISO 19005-3 (PDF/A-3) Annex E allows it for PDF/A-3.
+        // PDF/A-4f is based on PDF 2.0 anyway.
         file_spec.association_kind(match embed.relationship(StyleChain::default()) {
             Some(EmbeddedFileRelationship::Source) => AssociationKind::Source,
             Some(EmbeddedFileRelationship::Data) => AssociationKind::Data,
@@ -0,0 +1,644 @@
+//! Encryption with the standard security handler.
+//!
+//! Encryption is applied when the finished file is assembled (see
+//! [`crate::file`]): Each top-level object is copied with its strings and
+//! stream data encrypted.
+
+use aes::cipher::block_padding::{NoPadding, Pkcs7};
+use aes::cipher::{BlockEncrypt, BlockEncryptMut, KeyInit, KeyIvInit};
+use aes::{Aes128, Aes256};
+use md5::{Digest, Md5};
+use pdf_writer::{Chunk, Finish, Name, Ref, Str};
+use sha2::{Sha256, Sha384, Sha512};
+use typst_library::diag::{bail, SourceResult};
+use typst_syntax::Span;
+
+use crate::file::{is_delimiter, is_whitespace, parse_int, parse_string};
+use crate::{PdfEncryption, PdfEncryptionAlgorithm, PdfStandards, PdfVersion};
+
+/// The padding that passwords are extended with by the AES-128 handler.
+const PASSWORD_PADDING: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA,
+    0x01, 0x08, 0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE,
+    0x64, 0x53, 0x69, 0x7A,
+];
+
+/// The bits of the permission flags that are reserved and must be set.
+const RESERVED_PERMISSIONS: u32 = 0xFFFF_F0C0;
+
+/// Permits extracting text and graphics for accessibility purposes. Always
+/// granted, as PDF 2.0 deprecates it and PDF/UA requires it.
+const EXTRACT_FOR_ACCESSIBILITY: u32 = 1 << 9;
+
+/// Check that the document can be encrypted as requested.
+pub fn validate(
+    encryption: &PdfEncryption,
+    standards: &PdfStandards,
+) -> SourceResult<()> {
+    if let Some((part, level)) = standards.pdfa_part {
+        bail!(
+            Span::detached(),
+            "PDF/A-{part}{level} does not allow encryption";
+            hint: "remove the passwords and permissions or export without a PDF/A standard",
+        );
+    }
+
+    if encryption.algorithm == PdfEncryptionAlgorithm::Aes128 {
+        if standards.version == PdfVersion::V2_0 {
+            bail!(
+                Span::detached(),
+                "PDF 2.0 does not allow AES-128 encryption";
+                hint: "use AES-256 encryption instead",
+            );
+        }
+
+        if [&encryption.user_password, &encryption.owner_password]
+            .iter()
+            .any(|password| password.chars().any(|c| u32::from(c) > 0xFF))
+        {
+            bail!(
+                Span::detached(),
+                "AES-128 encryption only supports passwords with Latin-1 characters";
+                hint: "use AES-256 encryption instead",
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Encrypts objects with the standard security handler.
+pub struct Handler {
+    /// The encryption algorithm.
+    algorithm: PdfEncryptionAlgorithm,
+    /// The file encryption key.
+    key: Vec<u8>,
+    /// The permission flags.
+    permissions: u32,
+    /// The `/O` and `/U` entries, which verify the passwords.
+    owner: Vec<u8>,
+    user: Vec<u8>,
+    /// The `/OE`, `/UE`, and `/Perms` entries, which store the file key for
+    /// AES-256.
+    owner_key: Vec<u8>,
+    user_key: Vec<u8>,
+    perms: Vec<u8>,
+    /// A random prefix for the initialization vectors.
+    nonce: [u8; 8],
+    /// The number of initialization vectors generated so far.
+    ivs: u64,
+}
+
+impl Handler {
+    /// Compute the file encryption key and the password entries.
+    ///
+    /// `id` is the first part of the file identifier.
+    pub fn new(encryption: &PdfEncryption, id: &[u8]) -> SourceResult<Self> {
+        let permissions = RESERVED_PERMISSIONS
+            | EXTRACT_FOR_ACCESSIBILITY
+            | encryption.permissions.bits();
+        let user_password = encryption.user_password.as_str();
+        let owner_password = match encryption.owner_password.as_str() {
+            "" => user_password,
+            owner => owner,
+        };
+
+        let mut nonce = [0; 8];
+        random(&mut nonce)?;
+
+        let mut handler = Self {
+            algorithm: encryption.algorithm,
+            key: vec![],
+            permissions,
+            owner: vec![],
+            user: vec![],
+            owner_key: vec![],
+            user_key: vec![],
+            perms: vec![],
+            nonce,
+            ivs: 0,
+        };
+
+        match encryption.algorithm {
+            PdfEncryptionAlgorithm::Aes128 => {
+                handler.init_aes_128(&latin1(user_password), &latin1(owner_password), id)
+            }
+            PdfEncryptionAlgorithm::Aes256 => handler
+                .init_aes_256(user_password.as_bytes(), owner_password.as_bytes())?,
+        }
+
+        Ok(handler)
+    }
+
+    /// Revision 4: Algorithms 2, 3, and 5 of ISO 32000-2.
+    fn init_aes_128(&mut self, user: &[u8], owner: &[u8], id: &[u8]) {
+        // The owner entry is the padded user password, encrypted with a key
+        // derived from the owner password.
+        let mut hash = Md5::digest(pad(owner));
+        for _ in 0..50 {
+            hash = Md5::digest(hash);
+        }
+        let mut owner_entry = pad(user);
+        rc4_rounds(&hash, &mut owner_entry);
+
+        let key = aes_128_key(user, &owner_entry, self.permissions, id);
+
+        // The user entry is a hash of the padding and the ID, encrypted with
+        // the file key, and then padded to 32 bytes with arbitrary bytes.
+        let mut hasher = Md5::new();
+        hasher.update(PASSWORD_PADDING);
+        hasher.update(id);
+        let mut user_entry = hasher.finalize().to_vec();
+        rc4_rounds(&key, &mut user_entry);
+        user_entry.resize(32, 0);
+
+        self.key = key.to_vec();
+        self.owner = owner_entry.to_vec();
+        self.user = user_entry;
+    }
+
+    /// Revision 6: Algorithms 8, 9, and 10 of ISO 32000-2.
+    fn init_aes_256(&mut self, user: &[u8], owner: &[u8]) -> SourceResult<()> {
+        let user = &user[..user.len().min(127)];
+        let owner = &owner[..owner.len().min(127)];
+
+        // The file key, the validation and key salts of both passwords, and
+        // the last bytes of the permissions entry must be random.
+        let mut key = [0; 32];
+        let mut salts = [0; 36];
+        random(&mut key)?;
+        random(&mut salts)?;
+        let (user_salts, rest) = salts.split_at(16);
+        let (owner_salts, perms_random) = rest.split_at(16);
+
+        let mut user_entry = hash_2b(user, &user_salts[..8], &[]).to_vec();
+        user_entry.extend(user_salts);
+        let user_key = hash_2b(user, &user_salts[8..], &[]);
+
+        let mut owner_entry = hash_2b(owner, &owner_salts[..8], &user_entry).to_vec();
+        owner_entry.extend(owner_salts);
+        let owner_key = hash_2b(owner, &owner_salts[8..], &user_entry);
+
+        let wrap = |wrapping: [u8; 32]| {
+            cbc::Encryptor::<Aes256>::new(&wrapping.into(), &[0; 16].into())
+                .encrypt_padded_vec_mut::<NoPadding>(&key)
+        };
+
+        let mut perms = [0xFF; 16];
+        perms[..4].copy_from_slice(&self.permissions.to_le_bytes());
+        perms[8..12].copy_from_slice(b"Tadb");
+        perms[12..].copy_from_slice(perms_random);
+        let mut block = perms.into();
+        Aes256::new(&key.into()).encrypt_block(&mut block);
+
+        self.user_key = wrap(user_key);
+        self.owner_key = wrap(owner_key);
+        self.perms = block.to_vec();
+        self.key = key.to_vec();
+        self.user = user_entry;
+        self.owner = owner_entry;
+        Ok(())
+    }
+
+    /// Write the encryption dictionary.
+    pub fn write_dict(&self, chunk: &mut Chunk, id: Ref) {
+        let (version, revision, method) = match self.algorithm {
+            PdfEncryptionAlgorithm::Aes128 => (4, 4, Name(b"AESV2")),
+            PdfEncryptionAlgorithm::Aes256 => (5, 6, Name(b"AESV3")),
+        };
+        let bits = 8 * self.key.len() as i32;
+
+        let mut dict = chunk.indirect(id).dict();
+        dict.pair(Name(b"Filter"), Name(b"Standard"));
+        dict.pair(Name(b"V"), version);
+        dict.pair(Name(b"R"), revision);
+        dict.pair(Name(b"Length"), bits);
+
+        let mut filters = dict.insert(Name(b"CF")).dict();
+        filters
+            .insert(Name(b"StdCF"))
+            .dict()
+            .pair(Name(b"Type"), Name(b"CryptFilter"))
+            .pair(Name(b"CFM"), method)
+            .pair(Name(b"AuthEvent"), Name(b"DocOpen"))
+            .pair(Name(b"Length"), bits / 8);
+        filters.finish();
+        dict.pair(Name(b"StmF"), Name(b"StdCF"));
+        dict.pair(Name(b"StrF"), Name(b"StdCF"));
+
+        dict.pair(Name(b"O"), Str(&self.owner));
+        dict.pair(Name(b"U"), Str(&self.user));
+        if self.algorithm == PdfEncryptionAlgorithm::Aes256 {
+            dict.pair(Name(b"OE"), Str(&self.owner_key));
+            dict.pair(Name(b"UE"), Str(&self.user_key));
+            dict.pair(Name(b"Perms"), Str(&self.perms));
+        }
+        dict.pair(Name(b"P"), self.permissions as i32);
+        dict.pair(Name(b"EncryptMetadata"), true);
+    }
+
+    /// Copy the interior of an indirect object written by pdf-writer,
+    /// encrypting its strings and its stream data.
+    ///
+    /// Returns `None` if the object is malformed.
+    pub fn encrypt_object(
+        &mut self,
+        id: i32,
+        data: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Option<()> {
+        let key = self.object_key(id);
+        let mut depth = 0;
+        let mut length = None;
+        let mut i = 0;
+        while i < data.len() {
+            match data[i] {
+                b'<' if data.get(i + 1) == Some(&b'<') => {
+                    depth += 1;
+                    buf.extend(b"<<");
+                    i += 2;
+                }
+                b'>' if data.get(i + 1) == Some(&b'>') => {
+                    depth -= 1;
+                    buf.extend(b">>");
+                    i += 2;
+                }
+                b'(' | b'<' => {
+                    let (string, len) = parse_string(&data[i..])?;
+                    let encrypted = self.encrypt_data(&key, &string);
+                    write_hex(buf, &encrypted);
+                    i += len;
+                }
+                b')' | b'>' => return None,
+                b'/' => {
+                    let len = 1 + data[i + 1..]
+                        .iter()
+                        .position(|&b| is_whitespace(b) || is_delimiter(b))
+                        .unwrap_or(data.len() - i - 1);
+                    let name = &data[i..i + len];
+                    buf.extend(name);
+                    i += len;
+
+                    // Remember the length of the stream and where it is
+                    // written, so that we can update it once the data is
+                    // encrypted.
+                    if depth == 1 && name == b"/Length" {
+                        let value = data[i..].trim_ascii_start();
+                        let space = data.len() - i - value.len();
+                        let digits =
+                            value.iter().take_while(|b| b.is_ascii_digit()).count();
+                        buf.extend(&data[i..i + space]);
+                        length = Some((parse_int(value)?, buf.len()..buf.len() + digits));
+                        buf.extend(&value[..digits]);
+                        i += space + digits;
+                    }
+                }
+                b's' if depth == 0 && data[i..].starts_with(b"stream") => {
+                    // The keyword is followed by an end-of-line marker and
+                    // exactly as many bytes of data as the dictionary says.
+                    let (len, range) = length?;
+                    let rest = &data[i + 6..];
+                    let start = if rest.starts_with(b"\r\n") { 2 } else { 1 };
+                    let stream = rest.get(start..start + len)?;
+                    if rest[start + len..].trim_ascii() != b"endstream" {
+                        return None;
+                    }
+
+                    let encrypted = self.encrypt_data(&key, stream);
+                    buf.splice(range, encrypted.len().to_string().into_bytes());
+                    buf.extend(b"stream\n");
+                    buf.extend(encrypted);
+                    buf.extend(b"\nendstream");
+                    return Some(());
+                }
+                byte => {
+                    buf.push(byte);
+                    i += 1;
+                }
+            }
+        }
+
+        (depth == 0).then_some(())
+    }
+
+    /// The key for encrypting the strings and streams of an object.
+    fn object_key(&self, id: i32) -> Vec<u8> {
+        match self.algorithm {
+            // Algorithm 1 of ISO 32000-2, with generation number zero.
+            PdfEncryptionAlgorithm::Aes128 => {
+                let mut hasher = Md5::new();
+                hasher.update(&self.key);
+                hasher.update(&id.to_le_bytes()[..3]);
+                hasher.update([0, 0]);
+                hasher.update(b"sAlT");
+                hasher.finalize().to_vec()
+            }
+            PdfEncryptionAlgorithm::Aes256 => self.key.clone(),
+        }
+    }
+
+    /// Encrypt a string or stream with AES in CBC mode, prefixed with its
+    /// initialization vector.
+    fn encrypt_data(&mut self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let iv = self.next_iv();
+        let mut encrypted = iv.to_vec();
+        encrypted.extend(match self.algorithm {
+            PdfEncryptionAlgorithm::Aes128 => {
+                cbc::Encryptor::<Aes128>::new_from_slices(key, &iv)
+                    .unwrap()
+                    .encrypt_padded_vec_mut::<Pkcs7>(data)
+            }
+            PdfEncryptionAlgorithm::Aes256 => {
+                cbc::Encryptor::<Aes256>::new_from_slices(key, &iv)
+                    .unwrap()
+                    .encrypt_padded_vec_mut::<Pkcs7>(data)
+            }
+        });
+        encrypted
+    }
+
+    /// Generate a fresh initialization vector by encrypting the nonce and a
+    /// counter with the file key. The vectors are unique and, without the
+    /// key, unpredictable.
+    fn next_iv(&mut self) -> [u8; 16] {
+        self.ivs += 1;
+        let mut block = [0; 16];
+        block[..8].copy_from_slice(&self.nonce);
+        block[8..].copy_from_slice(&self.ivs.to_be_bytes());
+        let mut block = block.into();
+        match self.algorithm {
+            PdfEncryptionAlgorithm::Aes128 => {
+                Aes128::new_from_slice(&self.key).unwrap().encrypt_block(&mut block)
+            }
+            PdfEncryptionAlgorithm::Aes256 => {
+                Aes256::new_from_slice(&self.key).unwrap().encrypt_block(&mut block)
+            }
+        }
+        block.into()
+    }
+}
+
+/// Fill a buffer with bytes from the operating system's secure random number
+/// generator.
+fn random(buf: &mut [u8]) -> SourceResult<()> {
+    if let Err(err) = getrandom::getrandom(buf) {
+        bail!(Span::detached(), "failed to generate encryption key ({err})");
+    }
+    Ok(())
+}
+
+/// Pad or truncate a password to 32 bytes for the AES-128 handler.
+fn pad(password: &[u8]) -> [u8; 32] {
+    let len = password.len().min(32);
+    let mut padded = [0; 32];
+    padded[..len].copy_from_slice(&password[..len]);
+    padded[len..].copy_from_slice(&PASSWORD_PADDING[..32 - len]);
+    padded
+}
+
+/// Compute the file key of revision 4 from the user password: Algorithm 2 of
+/// ISO 32000-2.
+fn aes_128_key(user: &[u8], owner_entry: &[u8], permissions: u32, id: &[u8]) -> [u8; 16] {
+    let mut hasher = Md5::new();
+    hasher.update(pad(user));
+    hasher.update(owner_entry);
+    hasher.update(permissions.to_le_bytes());
+    hasher.update(id);
+    let mut key = hasher.finalize();
+    for _ in 0..50 {
+        key = Md5::digest(key);
+    }
+    key.into()
+}
+
+/// The hardened hash of revision 6: Algorithm 2.B of ISO 32000-2.
+fn hash_2b(password: &[u8], salt: &[u8], user: &[u8]) -> [u8; 32] {
+    let mut k = Sha256::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(user)
+        .finalize()
+        .to_vec();
+
+    let mut round = 0;
+    loop {
+        let sequence = [password, &k, user].concat();
+        let repeated = sequence.repeat(64);
+        let e = cbc::Encryptor::<Aes128>::new_from_slices(&k[..16], &k[16..32])
+            .unwrap()
+            .encrypt_padded_vec_mut::<NoPadding>(&repeated);
+
+        let sum: u32 = e[..16].iter().map(|&b| u32::from(b)).sum();
+        k = match sum % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+
+        round += 1;
+        if round >= 64 && u32::from(*e.last().unwrap()) <= round - 32 {
+            break;
+        }
+    }
+
+    k[..32].try_into().unwrap()
+}
+
+/// Encrypt data with RC4 twenty times, each time with the key XORed with the
+/// round number, as done for the password entries of revision 4.
+fn rc4_rounds(key: &[u8], data: &mut [u8]) {
+    for round in 0..20 {
+        let key: Vec<u8> = key.iter().map(|&b| b ^ round).collect();
+        rc4(&key, data);
+    }
+}
+
+/// Encrypt data in place with the RC4 stream cipher.
+fn rc4(key: &[u8], data: &mut [u8]) {
+    let mut s: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, j as usize);
+    }
+
+    let (mut i, mut j) = (0u8, 0u8);
+    for byte in data {
+        i = i.wrapping_add(1);
+        j = j.wrapping_add(s[i as usize]);
+        s.swap(i as usize, j as usize);
+        *byte ^= s[s[i as usize].wrapping_add(s[j as usize]) as usize];
+    }
+}
+
+/// Encode a password with Latin-1, which matches PDFDocEncoding for the
+/// printable characters. Checked by [`validate`].
+fn latin1(password: &str) -> Vec<u8> {
+    password.chars().map(|c| c as u8).collect()
+}
+
+/// Write bytes as a hexadecimal string.
+fn write_hex(buf: &mut Vec<u8>, bytes: &[u8]) {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    buf.reserve(2 * bytes.len() + 2);
+    buf.push(b'<');
+    for &byte in bytes {
+        buf.push(DIGITS[usize::from(byte >> 4)]);
+        buf.push(DIGITS[usize::from(byte & 0xF)]);
+    }
+    buf.push(b'>');
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::cipher::{BlockDecrypt, BlockDecryptMut, KeyIvInit};
+    use pdf_writer::TextStr;
+
+    use super::*;
+    use crate::file::{find, interior};
+    use crate::PdfPermissions;
+
+    /// The value of a dictionary entry.
+    fn entry<'a>(dict: &'a [u8], key: &str) -> &'a [u8] {
+        let key = format!("/{key} ");
+        let start = find(dict, key.as_bytes()).unwrap() + key.len();
+        &dict[start..]
+    }
+
+    /// Decrypt a string or stream like a viewer.
+    fn decrypt(algorithm: PdfEncryptionAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let (iv, data) = data.split_at(16);
+        match algorithm {
+            PdfEncryptionAlgorithm::Aes128 => {
+                cbc::Decryptor::<Aes128>::new_from_slices(key, iv)
+                    .unwrap()
+                    .decrypt_padded_vec_mut::<Pkcs7>(data)
+                    .unwrap()
+            }
+            PdfEncryptionAlgorithm::Aes256 => {
+                cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+                    .unwrap()
+                    .decrypt_padded_vec_mut::<Pkcs7>(data)
+                    .unwrap()
+            }
+        }
+    }
+
+    /// Authenticate with the user password and compute the key for the
+    /// object with the given ID, like a viewer.
+    fn authenticate(dict: &[u8], password: &[u8], file_id: &[u8], id: i32) -> Vec<u8> {
+        let (owner, _) = parse_string(entry(dict, "O")).unwrap();
+        let (user, _) = parse_string(entry(dict, "U")).unwrap();
+        let permissions = parse_int(&entry(dict, "P")[1..]).unwrap() as u32;
+        let permissions = permissions.wrapping_neg();
+
+        if find(dict, b"/R 4").is_some() {
+            // Algorithms 2, 5, and 1.
+            let key = aes_128_key(password, &owner, permissions, file_id);
+            let mut hasher = Md5::new();
+            hasher.update(PASSWORD_PADDING);
+            hasher.update(file_id);
+            let mut expected = hasher.finalize().to_vec();
+            rc4_rounds(&key, &mut expected);
+            assert_eq!(user[..16], expected);
+
+            let mut hasher = Md5::new();
+            hasher.update(key);
+            hasher.update(&id.to_le_bytes()[..3]);
+            hasher.update([0, 0, b's', b'A', b'l', b'T']);
+            hasher.finalize().to_vec()
+        } else {
+            // Algorithms 2.A and 13.
+            assert_eq!(hash_2b(password, &user[32..40], &[]), user[..32]);
+            let wrapping = hash_2b(password, &user[40..48], &[]);
+            let (wrapped, _) = parse_string(entry(dict, "UE")).unwrap();
+            let key = cbc::Decryptor::<Aes256>::new(&wrapping.into(), &[0; 16].into())
+                .decrypt_padded_vec_mut::<NoPadding>(&wrapped)
+                .unwrap();
+
+            let (perms, _) = parse_string(entry(dict, "Perms")).unwrap();
+            let mut block = *aes::Block::from_slice(&perms);
+            Aes256::new_from_slice(&key).unwrap().decrypt_block(&mut block);
+            assert_eq!(block[..4], permissions.to_le_bytes());
+            assert_eq!(&block[9..12], b"adb");
+            key
+        }
+    }
+
+    #[track_caller]
+    fn test_round_trip(algorithm: PdfEncryptionAlgorithm) {
+        let mut encryption = PdfEncryption::new(algorithm);
+        encryption.user_password = "user".into();
+        encryption.owner_password = "owner".into();
+        encryption.permissions.remove(PdfPermissions::COPY);
+
+        let file_id = b"document";
+        let mut handler = Handler::new(&encryption, file_id).unwrap();
+        let mut dict = Chunk::new();
+        handler.write_dict(&mut dict, Ref::new(1));
+
+        let mut chunk = Chunk::new();
+        chunk
+            .stream(Ref::new(7), b"BT (Secret) Tj ET")
+            .pair(Name(b"Title"), TextStr("Secret (title)"));
+        let mut encrypted = vec![];
+        handler
+            .encrypt_object(7, interior(chunk.as_bytes()).unwrap(), &mut encrypted)
+            .unwrap();
+        assert!(find(&encrypted, b"Secret").is_none());
+
+        let key = authenticate(dict.as_bytes(), b"user", file_id, 7);
+        let (title, _) = parse_string(entry(&encrypted, "Title")).unwrap();
+        assert_eq!(decrypt(algorithm, &key, &title), b"Secret (title)");
+
+        // The stream's length must match its encrypted data: The IV and two
+        // blocks for the 17 bytes of content.
+        assert_eq!(parse_int(entry(&encrypted, "Length")), Some(48));
+        let start = find(&encrypted, b">>\nstream\n").unwrap() + 10;
+        let end = find(&encrypted, b"\nendstream").unwrap();
+        assert_eq!(end - start, 48);
+        assert_eq!(
+            decrypt(algorithm, &key, &encrypted[start..end]),
+            b"BT (Secret) Tj ET"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_aes_128() {
+        test_round_trip(PdfEncryptionAlgorithm::Aes128);
+    }
+
+    #[test]
+    fn test_round_trip_aes_256() {
+        test_round_trip(PdfEncryptionAlgorithm::Aes256);
+    }
+
+    #[test]
+    fn test_random_key() {
+        let encryption = PdfEncryption::new(PdfEncryptionAlgorithm::Aes256);
+        let first = Handler::new(&encryption, b"document").unwrap();
+        let second = Handler::new(&encryption, b"document").unwrap();
+        assert_ne!(first.key, second.key);
+        assert_ne!(first.user, second.user);
+    }
+
+    #[test]
+    fn test_malformed_object() {
+        let encryption = PdfEncryption::new(PdfEncryptionAlgorithm::Aes256);
+        let mut handler = Handler::new(&encryption, b"document").unwrap();
+        for data in [
+            &b"<< /Title (Unterminated >>"[..],
+            b"<< /Length 100 >>\nstream\nshort\nendstream",
+            b"<< /Title (A) ",
+        ] {
+            assert_eq!(handler.encrypt_object(1, data, &mut vec![]), None);
+        }
+    }
+
+    #[test]
+    fn test_rc4() {
+        let mut data = *b"Plaintext";
+        rc4(b"Key", &mut data);
+        assert_eq!(data, [0xBB, 0xF3, 0x16, 0xE8, 0xD9, 0x40, 0xAF, 0x0A, 0xD3]);
+    }
+}
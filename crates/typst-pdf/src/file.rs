@@ -0,0 +1,616 @@
+//! Final assembly of the file.
+//!
+//! pdf-writer writes every indirect object at the top level of the file and
+//! indexes them with a cross-reference table. From PDF 1.5 on, the objects that
+//! were written with [`PdfChunk::packed`](crate::PdfChunk::packed) are instead
+//! stored in compressed object streams and the file is indexed with a
+//! cross-reference stream. This shrinks the many small dictionaries of long
+//! documents (pages, annotations, structure elements, ...) considerably.
+//!
+//! If the file is encrypted, each top-level object is copied with its strings
+//! and stream data encrypted. Packed objects are covered by the encryption of
+//! their object stream.
+
+use std::io::Write;
+
+use pdf_writer::{Chunk, Filter, Name, Pdf, Ref};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSlice;
+use typst_library::diag::{bail, SourceResult};
+use typst_syntax::Span;
+
+use crate::encrypt::Handler;
+use crate::{deflate, PdfOptions};
+
+/// The maximum number of objects that are packed into one object stream.
+const OBJECTS_PER_STREAM: usize = 100;
+
+/// Assemble the final file from the document written by pdf-writer and the
+/// objects that go into object streams.
+#[typst_macros::time(name = "finish file")]
+pub fn finish(
+    mut pdf: Pdf,
+    packed: Vec<Chunk>,
+    options: &PdfOptions,
+) -> SourceResult<Vec<u8>> {
+    // Object streams are not available before PDF 1.5, so there, the packed
+    // objects are written at the top level like all others.
+    let object_streams = options.standards.version.numbers() >= (1, 5);
+    if !object_streams {
+        for chunk in &packed {
+            pdf.extend(chunk);
+        }
+        if options.encryption.is_none() {
+            return Ok(pdf.finish());
+        }
+    }
+
+    let pdf = pdf.finish();
+    let Some(file) = File::parse(&pdf) else {
+        bail!(
+            Span::detached(),
+            "failed to read the structure of the PDF – this is a bug"
+        );
+    };
+
+    // The object streams are compressed in parallel since they are
+    // independent.
+    let size = packed
+        .iter()
+        .flat_map(Chunk::refs)
+        .map(|id| id.get() + 1)
+        .fold(file.size, i32::max);
+    let mut streams = vec![];
+    if object_streams {
+        packed
+            .par_chunks(OBJECTS_PER_STREAM)
+            .enumerate()
+            .map(|(i, objects)| pack(Ref::new(size + i as i32), objects))
+            .collect_into_vec(&mut streams);
+    }
+
+    let handler = match &options.encryption {
+        Some(encryption) => Some(Handler::new(encryption, &file.id)?),
+        None => None,
+    };
+    let mut writer = Writer {
+        buf: Vec::with_capacity(pdf.len()),
+        entries: vec![Entry::Free; size as usize + streams.len()],
+        handler,
+    };
+
+    writer.buf.extend(file.header);
+    for &(id, data) in &file.objects {
+        writer.write_object(id, data)?;
+    }
+
+    let mut next = size;
+    for stream in &streams {
+        let Some((ids, data)) = stream
+            .as_ref()
+            .and_then(|(ids, chunk)| Some((ids, interior(chunk.as_bytes())?)))
+        else {
+            bail!(Span::detached(), "failed to write object stream – this is a bug");
+        };
+        for (index, &id) in ids.iter().enumerate() {
+            writer.entries[id as usize] = Entry::Packed(next, index);
+        }
+        writer.write_object(next, data)?;
+        next += 1;
+    }
+
+    // The encryption dictionary itself is neither encrypted nor packed.
+    let mut encrypt_id = None;
+    if let Some(handler) = &writer.handler {
+        let mut chunk = Chunk::new();
+        handler.write_dict(&mut chunk, Ref::new(next));
+        writer.entries.push(Entry::Direct(writer.buf.len()));
+        writer.buf.extend(chunk.as_bytes());
+        encrypt_id = Some(next);
+        next += 1;
+    }
+
+    if object_streams {
+        writer.write_xref_stream(next, encrypt_id, file.trailer);
+    } else {
+        writer.write_xref_table(encrypt_id, file.trailer);
+    }
+
+    Ok(writer.buf)
+}
+
+/// Pack objects into an object stream. Returns the IDs of the packed objects
+/// along with the stream, or `None` if a chunk does not hold exactly one
+/// object.
+fn pack(id: Ref, objects: &[Chunk]) -> Option<(Vec<i32>, Chunk)> {
+    let mut ids = Vec::with_capacity(objects.len());
+    let mut offsets = vec![];
+    let mut body = vec![];
+    for object in objects {
+        let mut refs = object.refs();
+        let (Some(id), None) = (refs.next(), refs.next()) else { return None };
+        ids.push(id.get());
+        write!(offsets, "{} {} ", id.get(), body.len()).unwrap();
+        body.extend(interior(object.as_bytes())?);
+        body.push(b'\n');
+    }
+
+    let first = offsets.len() as i32;
+    offsets.extend(body);
+
+    let mut chunk = Chunk::new();
+    chunk
+        .stream(id, &deflate(&offsets))
+        .filter(Filter::FlateDecode)
+        .pair(Name(b"Type"), Name(b"ObjStm"))
+        .pair(Name(b"N"), objects.len() as i32)
+        .pair(Name(b"First"), first);
+    Some((ids, chunk))
+}
+
+/// Writes the final file.
+struct Writer {
+    /// The file written so far.
+    buf: Vec<u8>,
+    /// Where each object is stored, indexed by its ID.
+    entries: Vec<Entry>,
+    /// Encrypts the top-level objects, if the file is encrypted.
+    handler: Option<Handler>,
+}
+
+/// An entry of the cross-reference table or stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Entry {
+    /// An unused ID.
+    Free,
+    /// An object at the given offset.
+    Direct(usize),
+    /// An object at the given index of an object stream.
+    Packed(i32, usize),
+}
+
+impl Writer {
+    /// Write an indirect object at the top level.
+    fn write_object(&mut self, id: i32, data: &[u8]) -> SourceResult<()> {
+        self.entries[id as usize] = Entry::Direct(self.buf.len());
+        writeln!(self.buf, "{id} 0 obj").unwrap();
+        match &mut self.handler {
+            Some(handler) => {
+                if handler.encrypt_object(id, data, &mut self.buf).is_none() {
+                    bail!(
+                        Span::detached(),
+                        "failed to encrypt PDF object {id} – this is a bug"
+                    );
+                }
+            }
+            None => self.buf.extend(data),
+        }
+        self.buf.extend(b"\nendobj\n\n");
+        Ok(())
+    }
+
+    /// The next free ID after the given one, which links the free entries of
+    /// the cross-reference table or stream into a list.
+    fn next_free(&self, id: usize) -> usize {
+        self.entries[id + 1..]
+            .iter()
+            .position(|entry| matches!(entry, Entry::Free))
+            .map_or(0, |pos| id + 1 + pos)
+    }
+
+    /// Write the cross-reference table and the trailer.
+    fn write_xref_table(&mut self, encrypt_id: Option<i32>, trailer: &[u8]) {
+        let offset = self.buf.len();
+        let size = self.entries.len();
+        write!(self.buf, "xref\n0 {size}\n").unwrap();
+        for id in 0..size {
+            match self.entries[id] {
+                Entry::Direct(offset) => {
+                    write!(self.buf, "{offset:010} 00000 n\r\n").unwrap()
+                }
+                _ => {
+                    let next = self.next_free(id);
+                    let generation = if id == 0 { 65535 } else { 0 };
+                    write!(self.buf, "{next:010} {generation:05} f\r\n").unwrap();
+                }
+            }
+        }
+
+        write!(self.buf, "trailer\n<<\n  /Size {size}\n").unwrap();
+        if let Some(encrypt_id) = encrypt_id {
+            writeln!(self.buf, "  /Encrypt {encrypt_id} 0 R").unwrap();
+        }
+        self.buf.extend(trailer);
+        write!(self.buf, ">>\nstartxref\n{offset}\n%%EOF").unwrap();
+    }
+
+    /// Write the cross-reference stream, which also takes the place of the
+    /// trailer.
+    fn write_xref_stream(&mut self, id: i32, encrypt_id: Option<i32>, trailer: &[u8]) {
+        let offset = self.buf.len();
+        self.entries.push(Entry::Direct(offset));
+
+        // The second field holds offsets and the IDs of object streams, the
+        // third one generation numbers and indices in object streams.
+        let max = offset.max(id as usize);
+        let width = (usize::BITS - max.leading_zeros()).div_ceil(8).max(1) as usize;
+
+        let mut rows = Vec::with_capacity(self.entries.len() * (width + 3));
+        for (i, entry) in self.entries.iter().enumerate() {
+            let (kind, field, extra) = match *entry {
+                Entry::Free => (0, self.next_free(i), if i == 0 { 0xFFFF } else { 0 }),
+                Entry::Direct(offset) => (1, offset, 0),
+                Entry::Packed(stream, index) => (2, stream as usize, index),
+            };
+            rows.push(kind);
+            rows.extend(&field.to_be_bytes()[size_of::<usize>() - width..]);
+            rows.extend((extra as u16).to_be_bytes());
+        }
+
+        let data = deflate(&rows);
+        let size = self.entries.len();
+        write!(self.buf, "{id} 0 obj\n<<\n  /Type /XRef\n  /Size {size}\n").unwrap();
+        write!(self.buf, "  /W [1 {width} 2]\n  /Filter /FlateDecode\n").unwrap();
+        writeln!(self.buf, "  /Length {}", data.len()).unwrap();
+        if let Some(encrypt_id) = encrypt_id {
+            writeln!(self.buf, "  /Encrypt {encrypt_id} 0 R").unwrap();
+        }
+        self.buf.extend(trailer);
+        self.buf.extend(b">>\nstream\n");
+        self.buf.extend(data);
+        self.buf.extend(b"\nendstream\nendobj\n\n");
+        write!(self.buf, "startxref\n{offset}\n%%EOF").unwrap();
+    }
+}
+
+/// The parts of a PDF file written by pdf-writer that are needed to rewrite
+/// it.
+struct File<'a> {
+    /// Everything up to the first indirect object.
+    header: &'a [u8],
+    /// The ID and the interior of each indirect object, in file order.
+    objects: Vec<(i32, &'a [u8])>,
+    /// The entries of the trailer dictionary except for `/Size`.
+    trailer: &'a [u8],
+    /// The first part of the file identifier.
+    id: Vec<u8>,
+    /// The number of entries in the cross-reference table.
+    size: i32,
+}
+
+impl<'a> File<'a> {
+    /// Parse the structure of a PDF file written by pdf-writer, which has a
+    /// single cross-reference table with one subsection.
+    fn parse(pdf: &'a [u8]) -> Option<Self> {
+        let startxref = find_last(pdf, b"startxref")?;
+        let xref_offset = parse_int(pdf[startxref + 9..].trim_ascii_start())?;
+        let trailer_offset = xref_offset + find(&pdf[xref_offset..], b"trailer")?;
+
+        let mut lines = pdf[xref_offset..trailer_offset]
+            .split(|&b| b == b'\n')
+            .map(<[u8]>::trim_ascii);
+        if lines.next()? != b"xref" {
+            return None;
+        }
+
+        let subsection = lines.next()?;
+        let size =
+            parse_int(&subsection[subsection.iter().position(|&b| b == b' ')? + 1..])?;
+
+        let mut offsets = vec![];
+        for (id, entry) in lines.take(size).enumerate() {
+            if entry.ends_with(b"n") {
+                offsets.push((parse_int(entry)?, id as i32));
+            }
+        }
+        offsets.sort();
+
+        let header = &pdf[..offsets.first().map_or(xref_offset, |&(offset, _)| offset)];
+        let mut objects = Vec::with_capacity(offsets.len());
+        for (i, &(offset, id)) in offsets.iter().enumerate() {
+            let end = offsets.get(i + 1).map_or(xref_offset, |&(next, _)| next);
+            objects.push((id, interior(&pdf[offset..end])?));
+        }
+
+        // Skip the opening of the dictionary and the `/Size` entry, which is
+        // always written first.
+        let trailer = &pdf[trailer_offset..startxref];
+        let start = find(trailer, b"/Size")?;
+        let start = start + find(&trailer[start..], b"\n")? + 1;
+        let end = find_last(trailer, b">>")?;
+        let trailer = &trailer[start..end];
+
+        let ids = &trailer[find(trailer, b"/ID")? + 3..];
+        let ids = &ids[ids.iter().position(|&b| b == b'[')? + 1..].trim_ascii_start();
+        let (id, _) = parse_string(ids)?;
+
+        Some(Self { header, objects, trailer, id, size: size as i32 })
+    }
+}
+
+/// The part of an indirect object between `obj` and `endobj`.
+pub fn interior(object: &[u8]) -> Option<&[u8]> {
+    let start = find(object, b"obj")? + 3;
+    let end = find_last(object, b"endobj")?;
+    Some(object[start..end].trim_ascii())
+}
+
+/// Parse a literal or hexadecimal string. Returns its bytes and the length of
+/// its syntax.
+pub fn parse_string(data: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut bytes = vec![];
+    let mut i = 1;
+    match data.first()? {
+        b'(' => {
+            let mut depth = 1;
+            loop {
+                let byte = *data.get(i)?;
+                i += 1;
+                match byte {
+                    b'(' => depth += 1,
+                    b')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    b'\\' => {
+                        let escaped = *data.get(i)?;
+                        i += 1;
+                        bytes.push(match escaped {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => b'\x08',
+                            b'f' => b'\x0c',
+                            b'0'..=b'7' => {
+                                let mut value = u32::from(escaped - b'0');
+                                for _ in 0..2 {
+                                    match data.get(i) {
+                                        Some(&d @ b'0'..=b'7') => {
+                                            value = 8 * value + u32::from(d - b'0');
+                                            i += 1;
+                                        }
+                                        _ => break,
+                                    }
+                                }
+                                value as u8
+                            }
+                            b'\r' | b'\n' => {
+                                if escaped == b'\r' && data.get(i) == Some(&b'\n') {
+                                    i += 1;
+                                }
+                                continue;
+                            }
+                            other => other,
+                        });
+                        continue;
+                    }
+                    _ => {}
+                }
+                bytes.push(byte);
+            }
+        }
+        b'<' => {
+            let end = i + data[i..].iter().position(|&b| b == b'>')?;
+            let digits: Vec<u8> = data[i..end]
+                .iter()
+                .filter(|b| !is_whitespace(**b))
+                .map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+                .collect::<Option<_>>()?;
+            bytes = digits
+                .chunks(2)
+                .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+                .collect();
+            i = end + 1;
+        }
+        _ => return None,
+    }
+    Some((bytes, i))
+}
+
+/// Parse the decimal integer at the start of the data.
+pub fn parse_int(data: &[u8]) -> Option<usize> {
+    let digits = data.iter().take_while(|b| b.is_ascii_digit()).count();
+    std::str::from_utf8(&data[..digits]).ok()?.parse().ok()
+}
+
+/// The position of the first occurrence of a needle.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// The position of the last occurrence of a needle.
+pub fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+/// Whether the byte is PDF whitespace.
+pub fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+/// Whether the byte is a PDF delimiter.
+pub fn is_delimiter(byte: u8) -> bool {
+    matches!(byte, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf_writer::{Rect, TextStr};
+
+    use super::*;
+    use crate::{PdfEncryption, PdfEncryptionAlgorithm, PdfVersion};
+
+    /// A document with a page and an annotation that are packed.
+    fn sample() -> (Pdf, Vec<Chunk>) {
+        let mut pdf = Pdf::new();
+        pdf.catalog(Ref::new(1)).pages(Ref::new(2));
+        pdf.pages(Ref::new(2)).kids([Ref::new(3)]).count(1);
+        pdf.document_info(Ref::new(4)).title(TextStr("(Secret) title"));
+        pdf.stream(Ref::new(5), b"BT /F1 12 Tf (Secret) Tj ET");
+        pdf.set_file_id((b"doc".to_vec(), b"instance".to_vec()));
+
+        let mut page = Chunk::new();
+        page.page(Ref::new(3))
+            .parent(Ref::new(2))
+            .contents(Ref::new(5))
+            .annotations([Ref::new(6)]);
+        let mut annotation = Chunk::new();
+        annotation.annotation(Ref::new(6)).rect(Rect::new(0.0, 0.0, 1.0, 1.0));
+        (pdf, vec![page, annotation])
+    }
+
+    fn options(
+        version: PdfVersion,
+        encryption: Option<PdfEncryption>,
+    ) -> PdfOptions<'static> {
+        let mut options = PdfOptions { encryption, ..PdfOptions::default() };
+        options.standards.version = version;
+        options
+    }
+
+    /// Decode the cross-reference stream of a file.
+    fn xref(pdf: &[u8]) -> Vec<Entry> {
+        let startxref = find_last(pdf, b"startxref").unwrap();
+        let offset = parse_int(&pdf[startxref + 10..]).unwrap();
+        let (dict, rows) = stream_at(pdf, offset);
+        let width = parse_int(&dict[find(dict, b"/W [1 ").unwrap() + 6..]).unwrap();
+        rows.chunks(width + 3)
+            .map(|row| {
+                let field = row[1..1 + width]
+                    .iter()
+                    .fold(0, |acc, &byte| (acc << 8) | usize::from(byte));
+                let extra =
+                    (usize::from(row[width + 1]) << 8) | usize::from(row[width + 2]);
+                match row[0] {
+                    0 => Entry::Free,
+                    1 => Entry::Direct(field),
+                    _ => Entry::Packed(field as i32, extra),
+                }
+            })
+            .collect()
+    }
+
+    /// The dictionary and the decompressed data of the stream at an offset.
+    fn stream_at(pdf: &[u8], offset: usize) -> (&[u8], Vec<u8>) {
+        let object = &pdf[offset..];
+        let start = find(object, b"stream\n").unwrap();
+        let len = parse_int(&object[find(object, b"/Length ").unwrap() + 8..]).unwrap();
+        let data = &object[start + 7..start + 7 + len];
+        let data = miniz_oxide::inflate::decompress_to_vec_zlib(data).unwrap_or_default();
+        (&object[..start], data)
+    }
+
+    #[test]
+    fn test_object_streams() {
+        let (pdf, packed) = sample();
+        let pdf = finish(pdf, packed, &options(PdfVersion::V1_7, None)).unwrap();
+        let entries = xref(&pdf);
+        assert_eq!(entries.len(), 9);
+        assert_eq!(entries[0], Entry::Free);
+        assert_eq!(entries[3], Entry::Packed(7, 0));
+        assert_eq!(entries[6], Entry::Packed(7, 1));
+        for id in [1, 2, 4, 5, 7] {
+            let Entry::Direct(offset) = entries[id] else { panic!() };
+            assert!(pdf[offset..].starts_with(format!("{id} 0 obj\n").as_bytes()));
+        }
+
+        // The object stream lists the IDs and offsets, followed by the
+        // objects.
+        let Entry::Direct(offset) = entries[7] else { panic!() };
+        let (dict, data) = stream_at(&pdf, offset);
+        assert!(find(dict, b"/Type /ObjStm").is_some());
+        assert!(find(dict, b"/N 2").is_some());
+        let first = parse_int(&dict[find(dict, b"/First ").unwrap() + 7..]).unwrap();
+        let numbers: Vec<usize> =
+            data[..first].split(|&b| b == b' ').filter_map(parse_int).collect();
+        assert_eq!(numbers[0], 3);
+        assert_eq!(numbers[2], 6);
+        assert!(data[first + numbers[1]..].starts_with(b"<<\n  /Type /Page\n"));
+        assert!(data[first + numbers[3]..].starts_with(b"<<\n  /Type /Annot\n"));
+
+        // The trailer entries move to the cross-reference stream.
+        let Entry::Direct(offset) = entries[8] else { panic!() };
+        let (dict, _) = stream_at(&pdf, offset);
+        assert!(pdf[offset..].starts_with(b"8 0 obj\n"));
+        assert!(find(dict, b"/Type /XRef").is_some());
+        assert!(find(dict, b"/Size 9").is_some());
+        assert!(find(dict, b"/Root 1 0 R").is_some());
+        assert!(find(dict, b"/ID [").is_some());
+    }
+
+    #[test]
+    fn test_no_object_streams() {
+        let (pdf, packed) = sample();
+        let pdf = finish(pdf, packed, &options(PdfVersion::V1_4, None)).unwrap();
+        assert!(find(&pdf, b"/ObjStm").is_none());
+        let file = File::parse(&pdf).unwrap();
+        assert_eq!(
+            file.objects.iter().map(|&(id, _)| id).collect::<Vec<_>>(),
+            [1, 2, 4, 5, 3, 6]
+        );
+    }
+
+    #[test]
+    fn test_encrypt_object_streams() {
+        for algorithm in [PdfEncryptionAlgorithm::Aes128, PdfEncryptionAlgorithm::Aes256]
+        {
+            let (pdf, packed) = sample();
+            let options = options(PdfVersion::V1_7, Some(PdfEncryption::new(algorithm)));
+            let pdf = finish(pdf, packed, &options).unwrap();
+            assert!(find(&pdf, b"Secret").is_none());
+
+            let entries = xref(&pdf);
+            assert_eq!(entries.len(), 10);
+            assert_eq!(entries[3], Entry::Packed(7, 0));
+
+            // The stream's length must match its encrypted data: The IV and
+            // two blocks for the 27 bytes of content.
+            let Entry::Direct(offset) = entries[5] else { panic!() };
+            let stream = &pdf[offset..];
+            assert!(find(stream, b"/Length 48\n").is_some());
+            let start = find(stream, b"stream\n").unwrap() + 7;
+            let end = find(stream, b"\nendstream").unwrap();
+            assert_eq!(end - start, 48);
+
+            // The object stream is encrypted as a whole.
+            let Entry::Direct(offset) = entries[7] else { panic!() };
+            let (_, data) = stream_at(&pdf, offset);
+            assert!(data.is_empty());
+
+            // The encryption dictionary is a top-level object that the
+            // cross-reference stream points to.
+            let Entry::Direct(offset) = entries[8] else { panic!() };
+            assert!(pdf[offset..].starts_with(b"8 0 obj\n"));
+            assert!(find(&pdf[offset..], b"/Filter /Standard").is_some());
+            let Entry::Direct(offset) = entries[9] else { panic!() };
+            assert!(find(&pdf[offset..], b"/Encrypt 8 0 R").is_some());
+        }
+    }
+
+    #[test]
+    fn test_encrypt_without_object_streams() {
+        let (pdf, packed) = sample();
+        let encryption = PdfEncryption::new(PdfEncryptionAlgorithm::Aes128);
+        let pdf =
+            finish(pdf, packed, &options(PdfVersion::V1_4, Some(encryption))).unwrap();
+        assert!(find(&pdf, b"Secret").is_none());
+
+        let file = File::parse(&pdf).unwrap();
+        assert_eq!(file.id, b"doc");
+        assert_eq!(file.size, 8);
+        assert_eq!(
+            file.objects.iter().map(|&(id, _)| id).collect::<Vec<_>>(),
+            [1, 2, 4, 5, 3, 6, 7]
+        );
+        assert!(find(file.trailer, b"/Encrypt 7 0 R").is_some());
+    }
+
+    #[test]
+    fn test_malformed_packed_object() {
+        let (pdf, mut packed) = sample();
+        packed.push(Chunk::new());
+        assert!(finish(pdf, packed, &options(PdfVersion::V1_7, None)).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
 use std::sync::Arc;
 
@@ -6,6 +6,7 @@ use ecow::{eco_format, EcoString};
 use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
 use pdf_writer::writers::{FontDescriptor, WMode};
 use pdf_writer::{Chunk, Filter, Finish, Name, Rect, Ref, Str};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use subsetter::GlyphRemapper;
 use ttf_parser::{name_id, GlyphId, Tag};
 use typst_library::diag::{At, SourceResult};
@@ -35,119 +36,147 @@ pub fn write_fonts(
 ) -> SourceResult<(PdfChunk, HashMap<Font, Ref>)> {
     let mut chunk = PdfChunk::new();
     let mut out = HashMap::new();
+
+    let mut fonts = vec![];
+    let mut seen = HashSet::new();
     context.resources.traverse(&mut |resources| {
         for font in resources.fonts.items() {
-            if out.contains_key(font) {
-                continue;
-            }
-
-            let type0_ref = chunk.alloc();
-            let cid_ref = chunk.alloc();
-            let descriptor_ref = chunk.alloc();
-            let cmap_ref = chunk.alloc();
-            let data_ref = chunk.alloc();
-            out.insert(font.clone(), type0_ref);
-
-            let glyph_set = resources.glyph_sets.get(font).unwrap();
-            let glyph_remapper = resources.glyph_remappers.get(font).unwrap();
-            let ttf = font.ttf();
-
-            // Do we have a TrueType or CFF font?
-            //
-            // FIXME: CFF2 must be handled differently and requires PDF 2.0
-            // (or we have to convert it to CFF).
-            let is_cff = ttf
-                .raw_face()
-                .table(CFF)
-                .or_else(|| ttf.raw_face().table(CFF2))
-                .is_some();
-
-            let base_font = base_font_name(font, glyph_set);
-            let base_font_type0 = if is_cff {
-                eco_format!("{base_font}-{IDENTITY_H}")
-            } else {
-                base_font.clone()
-            };
-
-            // Write the base font object referencing the CID font.
-            chunk
-                .type0_font(type0_ref)
-                .base_font(Name(base_font_type0.as_bytes()))
-                .encoding_predefined(Name(IDENTITY_H.as_bytes()))
-                .descendant_font(cid_ref)
-                .to_unicode(cmap_ref);
-
-            // Write the CID font referencing the font descriptor.
-            let mut cid = chunk.cid_font(cid_ref);
-            cid.subtype(if is_cff { CidFontType::Type0 } else { CidFontType::Type2 });
-            cid.base_font(Name(base_font.as_bytes()));
-            cid.system_info(SYSTEM_INFO);
-            cid.font_descriptor(descriptor_ref);
-            cid.default_width(0.0);
-            if !is_cff {
-                cid.cid_to_gid_map_predefined(Name(b"Identity"));
+            if seen.insert(font) {
+                let glyph_set = resources.glyph_sets.get(font).unwrap();
+                let glyph_remapper = resources.glyph_remappers.get(font).unwrap();
+                fonts.push((font, glyph_set, glyph_remapper));
             }
+        }
+        Ok(())
+    })?;
 
-            // Extract the widths of all glyphs.
-            // `remapped_gids` returns an iterator over the old GIDs in their new sorted
-            // order, so we can append the widths as is.
-            let widths = glyph_remapper
-                .remapped_gids()
-                .map(|gid| {
-                    let width = ttf.glyph_hor_advance(GlyphId(gid)).unwrap_or(0);
-                    font.to_em(width).to_font_units()
-                })
-                .collect::<Vec<_>>();
-
-            // Write all non-zero glyph widths.
-            let mut first = 0;
-            let mut width_writer = cid.widths();
-            for (w, group) in widths.group_by_key(|&w| w) {
-                let end = first + group.len();
-                if w != 0.0 {
-                    let last = end - 1;
-                    width_writer.same(first as u16, last as u16, w);
-                }
-                first = end;
-            }
+    // Subsetting the fonts and creating their character maps is by far the
+    // most expensive part, so it happens for all fonts in parallel.
+    let mut encoded = vec![];
+    fonts
+        .par_iter()
+        .map(|&(font, glyph_set, glyph_remapper)| {
+            (subset_font(font, glyph_remapper), create_cmap(glyph_set, glyph_remapper))
+        })
+        .collect_into_vec(&mut encoded);
+
+    for (&(font, glyph_set, glyph_remapper), (subset, cmap)) in fonts.iter().zip(encoded)
+    {
+        let type0_ref = chunk.alloc();
+        let cid_ref = chunk.alloc();
+        let descriptor_ref = chunk.alloc();
+        let cmap_ref = chunk.alloc();
+        let data_ref = chunk.alloc();
+        out.insert(font.clone(), type0_ref);
+
+        let ttf = font.ttf();
+
+        // Do we have a TrueType or CFF font?
+        //
+        // FIXME: CFF2 must be handled differently and requires PDF 2.0
+        // (or we have to convert it to CFF).
+        let is_cff = ttf
+            .raw_face()
+            .table(CFF)
+            .or_else(|| ttf.raw_face().table(CFF2))
+            .is_some();
+
+        let base_font = base_font_name(font, glyph_set);
+        let base_font_type0 = if is_cff {
+            eco_format!("{base_font}-{IDENTITY_H}")
+        } else {
+            base_font.clone()
+        };
+
+        // Write the base font object referencing the CID font.
+        chunk
+            .type0_font(type0_ref)
+            .base_font(Name(base_font_type0.as_bytes()))
+            .encoding_predefined(Name(IDENTITY_H.as_bytes()))
+            .descendant_font(cid_ref)
+            .to_unicode(cmap_ref);
+
+        // Write the CID font referencing the font descriptor.
+        let mut cid = chunk.cid_font(cid_ref);
+        cid.subtype(if is_cff { CidFontType::Type0 } else { CidFontType::Type2 });
+        cid.base_font(Name(base_font.as_bytes()));
+        cid.system_info(SYSTEM_INFO);
+        cid.font_descriptor(descriptor_ref);
+        cid.default_width(0.0);
+        if !is_cff {
+            cid.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
 
-            width_writer.finish();
-            cid.finish();
-
-            // Write the /ToUnicode character map, which maps glyph ids back to
-            // unicode codepoints to enable copying out of the PDF.
-            let cmap = create_cmap(glyph_set, glyph_remapper);
-            chunk
-                .cmap(cmap_ref, &cmap)
-                .writing_mode(WMode::Horizontal)
-                .filter(Filter::FlateDecode);
-
-            let subset = subset_font(font, glyph_remapper)
-                .map_err(|err| {
-                    let postscript_name = font.find_name(name_id::POST_SCRIPT_NAME);
-                    let name = postscript_name.as_deref().unwrap_or(&font.info().family);
-                    eco_format!("failed to process font {name}: {err}")
-                })
-                .at(Span::detached())?;
-
-            let mut stream = chunk.stream(data_ref, &subset);
-            stream.filter(Filter::FlateDecode);
-            if is_cff {
-                stream.pair(Name(b"Subtype"), Name(b"CIDFontType0C"));
-            }
-            stream.finish();
-
-            let mut font_descriptor =
-                write_font_descriptor(&mut chunk, descriptor_ref, font, &base_font);
-            if is_cff {
-                font_descriptor.font_file3(data_ref);
-            } else {
-                font_descriptor.font_file2(data_ref);
+        // Extract the widths of all glyphs.
+        // `remapped_gids` returns an iterator over the old GIDs in their new sorted
+        // order, so we can append the widths as is.
+        let widths = glyph_remapper
+            .remapped_gids()
+            .map(|gid| {
+                let width = ttf.glyph_hor_advance(GlyphId(gid)).unwrap_or(0);
+                font.to_em(width).to_font_units()
+            })
+            .collect::<Vec<_>>();
+
+        // Write all non-zero glyph widths.
+        let mut first = 0;
+        let mut width_writer = cid.widths();
+        for (w, group) in widths.group_by_key(|&w| w) {
+            let end = first + group.len();
+            if w != 0.0 {
+                let last = end - 1;
+                width_writer.same(first as u16, last as u16, w);
             }
+            first = end;
         }
 
-        Ok(())
-    })?;
+        width_writer.finish();
+        cid.finish();
+
+        // Write the /ToUnicode character map, which maps glyph ids back to
+        // unicode codepoints to enable copying out of the PDF.
+        chunk
+            .cmap(cmap_ref, &cmap)
+            .writing_mode(WMode::Horizontal)
+            .filter(Filter::FlateDecode);
+
+        let subset = subset
+            .map_err(|err| {
+                let postscript_name = font.find_name(name_id::POST_SCRIPT_NAME);
+                let name = postscript_name.as_deref().unwrap_or(&font.info().family);
+                eco_format!("failed to process font {name}: {err}")
+            })
+            .at(Span::detached())?;
+
+        let mut stream = chunk.stream(data_ref, &subset);
+        stream.filter(Filter::FlateDecode);
+        if is_cff {
+            stream.pair(Name(b"Subtype"), Name(b"CIDFontType0C"));
+        }
+        stream.finish();
+
+        // PDF/A-1 requires subset CID fonts to list the CIDs they
+        // contain. After remapping, these are exactly the first CIDs.
+        let cid_set_ref = if matches!(context.options.standards.pdfa_part, Some((1, _))) {
+            let cid_set_ref = chunk.alloc();
+            let cid_set = create_cid_set(widths.len());
+            chunk.stream(cid_set_ref, &cid_set).filter(Filter::FlateDecode);
+            Some(cid_set_ref)
+        } else {
+            None
+        };
+
+        let mut font_descriptor =
+            write_font_descriptor(&mut chunk, descriptor_ref, font, &base_font);
+        if is_cff {
+            font_descriptor.font_file3(data_ref);
+        } else {
+            font_descriptor.font_file2(data_ref);
+        }
+        if let Some(cid_set_ref) = cid_set_ref {
+            font_descriptor.pair(Name(b"CIDSet"), cid_set_ref);
+        }
+    }
 
     Ok((chunk, out))
 }
@@ -201,6 +230,16 @@ pub fn write_font_descriptor<'a>(
     font_descriptor
 }
 
+/// Create a deflated `/CIDSet` stream, marking the first `count` CIDs as
+/// present.
+fn create_cid_set(count: usize) -> Vec<u8> {
+    let mut bits = vec![0u8; count.div_ceil(8)];
+    for cid in 0..count {
+        bits[cid / 8] |= 0x80 >> (cid % 8);
+    }
+    deflate(&bits)
+}
+
 /// Subset a font to the given glyphs.
 ///
 /// - For a font with TrueType outlines, this produces the whole OpenType font.
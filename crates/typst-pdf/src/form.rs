@@ -0,0 +1,279 @@
+//! Interactive forms (AcroForm).
+//!
+//! Each form field is written as a field dictionary and each occurrence of it
+//! in the document as a widget annotation that is a kid of the field. Fields
+//! with the same name thus share their value. Radio buttons with the same
+//! name are the widgets of a single radio group field.
+
+use std::mem::discriminant;
+
+use ecow::{eco_format, EcoString};
+use indexmap::IndexMap;
+use pdf_writer::types::AnnotationFlags;
+use pdf_writer::writers::Catalog;
+use pdf_writer::{Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use typst_library::diag::{bail, SourceResult};
+use typst_library::pdf::{FormField, FormFieldKind};
+use typst_syntax::Span;
+
+use crate::content::Encoded;
+use crate::{AbsExt, PdfChunk, TextStrExt, WithRefs};
+
+/// The field is read-only.
+const READ_ONLY: i32 = 1;
+/// The field must have a value when the form is submitted.
+const REQUIRED: i32 = 1 << 1;
+/// The text field accepts multiple lines.
+const MULTILINE: i32 = 1 << 12;
+/// Exactly one radio button must be selected at all times.
+const NO_TOGGLE_TO_OFF: i32 = 1 << 14;
+/// The button field is a radio group.
+const RADIO: i32 = 1 << 15;
+/// The choice field is a dropdown instead of a list box.
+const COMBO: i32 = 1 << 17;
+/// The dropdown accepts values that are not among its options.
+const EDIT: i32 = 1 << 18;
+
+/// A form field widget in the PDF coordinate system, with its encoded
+/// appearances.
+#[derive(Clone)]
+pub struct EncodedWidget {
+    /// The field the widget belongs to.
+    pub field: FormField,
+    /// The area of the widget on the page.
+    pub rect: Rect,
+    /// The initial appearance.
+    pub appearance: Encoded,
+    /// The appearance after toggling a checkbox or radio button.
+    pub toggled: Option<Encoded>,
+    /// The widget's key in the parent tree, if the document is tagged.
+    pub key: Option<i32>,
+    /// The span of the field element.
+    pub span: Span,
+}
+
+/// The fields of the interactive form, collected while writing the pages.
+#[derive(Default)]
+pub struct Fields {
+    /// The fields by name, in order of their first appearance.
+    map: IndexMap<EcoString, Field>,
+}
+
+/// A field and its widgets.
+struct Field {
+    /// The reference of the field dictionary.
+    id: Ref,
+    /// The first widget's field, which determines the field's properties.
+    field: FormField,
+    /// The widget annotations.
+    kids: Vec<Ref>,
+    /// For radio groups, the value of the selected button.
+    selected: Option<EcoString>,
+}
+
+/// Write the widget annotation for a form field on a page and register it with
+/// its field. Returns the reference of the annotation.
+pub fn write_widget(
+    chunk: &mut PdfChunk,
+    ctx: &WithRefs,
+    fields: &mut Fields,
+    page_ref: Ref,
+    widget: &EncodedWidget,
+) -> SourceResult<Ref> {
+    let name = &widget.field.name;
+    let field = fields.map.entry(name.clone()).or_insert_with(|| Field {
+        id: chunk.alloc(),
+        field: widget.field.clone(),
+        kids: vec![],
+        selected: None,
+    });
+
+    if discriminant(&field.field.kind) != discriminant(&widget.field.kind) {
+        bail!(
+            widget.span,
+            "form field name `{name}` is already used by a field of a different type"
+        );
+    }
+
+    let id = chunk.alloc();
+    field.kids.push(id);
+
+    // Buttons have an "on" and an "off" appearance. The name of the "on"
+    // state is what the field's value is set to when the button is selected.
+    let state = match &widget.field.kind {
+        FormFieldKind::Checkbox { checked } => Some((Name(b"Yes"), *checked)),
+        FormFieldKind::Radio { value, checked } => {
+            if *checked && field.selected.is_none() {
+                field.selected = Some(value.clone());
+            }
+            Some((Name(value.as_bytes()), *checked))
+        }
+        _ => None,
+    };
+
+    let appearance = write_appearance(chunk, ctx, &widget.appearance);
+    let toggled = widget
+        .toggled
+        .as_ref()
+        .map(|toggled| write_appearance(chunk, ctx, toggled));
+
+    let mut annotation = chunk.packed().annotation(id);
+    annotation.pair(Name(b"Subtype"), Name(b"Widget"));
+    annotation.rect(widget.rect).flags(AnnotationFlags::PRINT);
+    annotation.pair(Name(b"P"), page_ref);
+    annotation.pair(Name(b"Parent"), field.id);
+
+    if let Some(key) = widget.key {
+        annotation.struct_parent(key);
+    }
+
+    let mut appearances = annotation.insert(Name(b"AP")).dict();
+    match (state, toggled) {
+        (Some((on, checked)), Some(toggled)) => {
+            let (on_ref, off_ref) =
+                if checked { (appearance, toggled) } else { (toggled, appearance) };
+            appearances
+                .insert(Name(b"N"))
+                .dict()
+                .pair(on, on_ref)
+                .pair(Name(b"Off"), off_ref);
+            appearances.finish();
+            annotation.pair(Name(b"AS"), if checked { on } else { Name(b"Off") });
+        }
+        _ => {
+            appearances.pair(Name(b"N"), appearance);
+        }
+    }
+
+    Ok(id)
+}
+
+/// Write the dictionaries of all fields. Returns their references.
+pub fn write_fields(chunk: &mut PdfChunk, fields: Fields) -> Vec<Ref> {
+    let mut refs = Vec::with_capacity(fields.map.len());
+    for field in fields.map.into_values() {
+        refs.push(field.id);
+
+        let FormField {
+            name,
+            tooltip,
+            required,
+            read_only,
+            font_size,
+            kind,
+        } = &field.field;
+        let mut flags = 0;
+        if *read_only {
+            flags |= READ_ONLY;
+        }
+        if *required {
+            flags |= REQUIRED;
+        }
+
+        let mut dict = chunk.packed().indirect(field.id).dict();
+        dict.pair(Name(b"T"), TextStr::trimmed(name));
+        if let Some(tooltip) = tooltip {
+            dict.pair(Name(b"TU"), TextStr::trimmed(tooltip));
+        }
+
+        let default_appearance = eco_format!("/Helv {} Tf 0 g", font_size.to_f32());
+        match kind {
+            FormFieldKind::Text { value, multiline, max_length } => {
+                if *multiline {
+                    flags |= MULTILINE;
+                }
+                dict.pair(Name(b"FT"), Name(b"Tx"));
+                dict.pair(Name(b"V"), TextStr::trimmed(value));
+                dict.pair(Name(b"DV"), TextStr::trimmed(value));
+                dict.pair(Name(b"DA"), Str(default_appearance.as_bytes()));
+                if let Some(max_length) = max_length {
+                    dict.pair(Name(b"MaxLen"), *max_length as i32);
+                }
+            }
+            FormFieldKind::Checkbox { checked } => {
+                let value = if *checked { Name(b"Yes") } else { Name(b"Off") };
+                dict.pair(Name(b"FT"), Name(b"Btn"));
+                dict.pair(Name(b"V"), value);
+                dict.pair(Name(b"DV"), value);
+            }
+            FormFieldKind::Radio { .. } => {
+                flags |= RADIO;
+                let value = match &field.selected {
+                    Some(selected) => {
+                        flags |= NO_TOGGLE_TO_OFF;
+                        Name(selected.as_bytes())
+                    }
+                    None => Name(b"Off"),
+                };
+                dict.pair(Name(b"FT"), Name(b"Btn"));
+                dict.pair(Name(b"V"), value);
+                dict.pair(Name(b"DV"), value);
+            }
+            FormFieldKind::Dropdown { options, selected, editable } => {
+                flags |= COMBO;
+                if *editable {
+                    flags |= EDIT;
+                }
+                dict.pair(Name(b"FT"), Name(b"Ch"));
+                dict.insert(Name(b"Opt"))
+                    .array()
+                    .items(options.iter().map(|option| TextStr::trimmed(option)));
+                if let Some(selected) = selected {
+                    dict.pair(Name(b"V"), TextStr::trimmed(selected));
+                    dict.pair(Name(b"DV"), TextStr::trimmed(selected));
+                }
+                dict.pair(Name(b"DA"), Str(default_appearance.as_bytes()));
+            }
+            FormFieldKind::Signature => {
+                dict.pair(Name(b"FT"), Name(b"Sig"));
+            }
+        }
+
+        if flags != 0 {
+            dict.pair(Name(b"Ff"), flags);
+        }
+
+        dict.insert(Name(b"Kids")).array().items(field.kids);
+    }
+    refs
+}
+
+/// Write the interactive form dictionary into the document catalog.
+pub fn write_acro_form(catalog: &mut Catalog, fields: &[Ref]) {
+    let mut form = catalog.insert(Name(b"AcroForm")).dict();
+    form.insert(Name(b"Fields")).array().items(fields.iter().copied());
+    form.pair(Name(b"DA"), Str(b"/Helv 0 Tf 0 g"));
+
+    // The font for text that is entered into fields. It is a standard font
+    // that does not need to be embedded.
+    form.insert(Name(b"DR"))
+        .dict()
+        .insert(Name(b"Font"))
+        .dict()
+        .insert(Name(b"Helv"))
+        .dict()
+        .pair(Name(b"Type"), Name(b"Font"))
+        .pair(Name(b"Subtype"), Name(b"Type1"))
+        .pair(Name(b"BaseFont"), Name(b"Helvetica"))
+        .pair(Name(b"Encoding"), Name(b"WinAnsiEncoding"));
+}
+
+/// Write an appearance stream for a widget. Returns its reference.
+fn write_appearance(chunk: &mut PdfChunk, ctx: &WithRefs, encoded: &Encoded) -> Ref {
+    let id = chunk.alloc();
+    let w = encoded.size.x.to_f32();
+    let h = encoded.size.y.to_f32();
+    let mut form = chunk.form_xobject(id, encoded.content.wait());
+    form.bbox(Rect::new(0.0, 0.0, w, h));
+    form.filter(Filter::FlateDecode);
+    if encoded.uses_opacities {
+        form.group()
+            .transparency()
+            .isolated(false)
+            .knockout(false)
+            .color_space()
+            .srgb();
+    }
+    form.pair(Name(b"Resources"), ctx.resources.reference);
+    id
+}
@@ -2,15 +2,16 @@ use std::collections::HashMap;
 use std::f32::consts::{PI, TAU};
 use std::sync::Arc;
 
-use ecow::eco_format;
+use ecow::EcoString;
 use pdf_writer::types::{ColorSpaceOperand, FunctionShadingType};
 use pdf_writer::writers::StreamShadingType;
 use pdf_writer::{Filter, Finish, Name, Ref};
-use typst_library::diag::SourceResult;
+use typst_library::diag::{bail, SourceResult};
 use typst_library::layout::{Abs, Angle, Point, Quadrant, Ratio, Transform};
 use typst_library::visualize::{
     Color, ColorSpace, Gradient, RatioOrAngle, RelativeTo, WeightedColor,
 };
+use typst_syntax::Span;
 use typst_utils::Numeric;
 
 use crate::color::{
@@ -257,8 +258,7 @@ impl PaintEncode for Gradient {
     ) -> SourceResult<()> {
         ctx.reset_fill_color_space();
 
-        let index = register_gradient(ctx, self, on_text, transforms);
-        let id = eco_format!("Gr{index}");
+        let id = register_gradient(ctx, self, on_text, transforms);
         let name = Name(id.as_bytes());
 
         ctx.content.set_fill_color_space(ColorSpaceOperand::Pattern);
@@ -274,8 +274,7 @@ impl PaintEncode for Gradient {
     ) -> SourceResult<()> {
         ctx.reset_stroke_color_space();
 
-        let index = register_gradient(ctx, self, on_text, transforms);
-        let id = eco_format!("Gr{index}");
+        let id = register_gradient(ctx, self, on_text, transforms);
         let name = Name(id.as_bytes());
 
         ctx.content.set_stroke_color_space(ColorSpaceOperand::Pattern);
@@ -284,13 +283,33 @@ impl PaintEncode for Gradient {
     }
 }
 
+/// Fails with an error if the gradient has transparent stops. Called if the
+/// PDF standard does not allow for transparency.
+///
+/// Gradient stops are always encoded without their alpha component, but
+/// silently making them opaque would not preserve the document's appearance.
+pub(crate) fn check_transparency(gradient: &Gradient, span: Span) -> SourceResult<()> {
+    if gradient
+        .stops_ref()
+        .iter()
+        .any(|(color, _)| color.alpha().is_some_and(|alpha| alpha < 1.0))
+    {
+        bail!(
+            span,
+            "gradients with transparent colors are not allowed in PDF/A-1";
+            hint: "PDF/A-2 and later support transparency"
+        );
+    }
+    Ok(())
+}
+
 /// Deduplicates a gradient to a named PDF resource.
 fn register_gradient(
     ctx: &mut content::Builder,
     gradient: &Gradient,
     on_text: bool,
     mut transforms: content::Transforms,
-) -> usize {
+) -> EcoString {
     // Edge cases for strokes.
     if transforms.size.x.is_zero() {
         transforms.size.x = Abs::pt(1.0);
@@ -4,14 +4,16 @@ use std::io::Cursor;
 use ecow::eco_format;
 use image::{DynamicImage, GenericImageView, Rgba};
 use pdf_writer::{Chunk, Filter, Finish, Ref};
-use typst_library::diag::{At, SourceResult, StrResult};
-use typst_library::foundations::Smart;
+use typst_library::diag::{bail, At, SourceResult, StrResult};
+use typst_library::foundations::{Bytes, Smart};
 use typst_library::visualize::{
     ColorSpace, ExchangeFormat, Image, ImageKind, ImageScaling, RasterFormat,
     RasterImage, SvgImage,
 };
+use typst_syntax::Span;
 use typst_utils::Deferred;
 
+use crate::pdf_image::encode_pdf;
 use crate::{color, deflate, PdfChunk, WithGlobalRefs};
 
 /// Embed all used images into the PDF.
@@ -22,13 +24,16 @@ pub fn write_images(
     let mut chunk = PdfChunk::new();
     let mut out = HashMap::new();
     context.resources.traverse(&mut |resources| {
-        for (i, image) in resources.images.items().enumerate() {
+        for image in resources.images.items() {
             if out.contains_key(image) {
                 continue;
             }
 
-            let (handle, span) = resources.deferred_images.get(&i).unwrap();
+            let (handle, span) = resources.deferred_images.get(image).unwrap();
             let encoded = handle.wait().as_ref().map_err(Clone::clone).at(*span)?;
+            if !context.options.standards.transparency {
+                check_pdfa1(image, encoded, *span)?;
+            }
 
             match encoded {
                 EncodedImage::Raster {
@@ -42,6 +47,18 @@ pub fn write_images(
                     alpha,
                     interpolate,
                 } => {
+                    if *color_space == ColorSpace::Cmyk
+                        && compressed_icc.is_none()
+                        && context.options.standards.pdfa
+                    {
+                        bail!(
+                            *span,
+                            "CMYK images without an ICC profile are not currently \
+                             supported by PDF/A export";
+                            hint: "embed a CMYK ICC profile into the image"
+                        );
+                    }
+
                     let image_ref = chunk.alloc();
                     out.insert(image.clone(), image_ref);
 
@@ -98,11 +115,15 @@ pub fn write_images(
                                 stream.n(1);
                                 stream.alternate().d65_gray();
                             }
+                            ColorSpace::Cmyk => {
+                                stream.n(4);
+                                stream.alternate().device_cmyk();
+                            }
                             _ => unimplemented!(),
                         }
                     }
                 }
-                EncodedImage::Svg(svg_chunk, id) => {
+                EncodedImage::Svg(svg_chunk, id) | EncodedImage::Pdf(svg_chunk, id) => {
                     let mut map = HashMap::new();
                     svg_chunk.renumber_into(&mut chunk.chunk, |old| {
                         *map.entry(old).or_insert_with(|| chunk.alloc.bump())
@@ -118,6 +139,52 @@ pub fn write_images(
     Ok((chunk, out))
 }
 
+/// Checks that an image can be embedded into a PDF/A-1 file.
+///
+/// PDF/A-1 forbids transparency and only supports ICC profiles up to version 2.
+/// SVGs are rejected because their conversion may produce transparency groups.
+/// PDF pages are rejected because they may contain transparency.
+fn check_pdfa1(image: &Image, encoded: &EncodedImage, span: Span) -> SourceResult<()> {
+    match encoded {
+        EncodedImage::Raster { alpha: Some(_), .. } => {
+            bail!(
+                span,
+                "images with transparency are not allowed in PDF/A-1";
+                hint: "PDF/A-2 and later support transparency"
+            );
+        }
+        EncodedImage::Raster { .. } => {
+            if let ImageKind::Raster(raster) = image.kind() {
+                if embedded_icc(raster)
+                    .is_some_and(|icc| icc.get(8).is_none_or(|&v| v > 2))
+                {
+                    bail!(
+                        span,
+                        "images with ICC profiles newer than version 2 are not \
+                         allowed in PDF/A-1";
+                        hint: "PDF/A-2 and later support ICC profiles of version 4"
+                    );
+                }
+            }
+        }
+        EncodedImage::Svg(..) => {
+            bail!(
+                span,
+                "SVG images are not currently supported in PDF/A-1";
+                hint: "PDF/A-2 and later support SVG images"
+            );
+        }
+        EncodedImage::Pdf(..) => {
+            bail!(
+                span,
+                "PDF images are not currently supported in PDF/A-1";
+                hint: "PDF/A-2 and later support PDF images"
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Creates a new PDF image from the given image.
 ///
 /// Also starts the deferred encoding of the image.
@@ -127,8 +194,8 @@ pub fn deferred_image(
     pdfa: bool,
 ) -> (Deferred<StrResult<EncodedImage>>, Option<ColorSpace>) {
     let color_space = match image.kind() {
-        ImageKind::Raster(raster) if raster.icc().is_none() => {
-            Some(to_color_space(raster.dynamic().color()))
+        ImageKind::Raster(raster) if embedded_icc(raster).is_none() => {
+            Some(raster_color_space(raster))
         }
         _ => None,
     };
@@ -144,6 +211,10 @@ pub fn deferred_image(
                 .map_err(|err| eco_format!("failed to convert SVG to PDF: {err}"))?;
             Ok(EncodedImage::Svg(chunk, id))
         }
+        ImageKind::Pdf(pdf) => {
+            let (chunk, id) = encode_pdf(pdf);
+            Ok(EncodedImage::Pdf(chunk, id))
+        }
     });
 
     (deferred, color_space)
@@ -153,28 +224,40 @@ pub fn deferred_image(
 #[typst_macros::time(name = "encode raster image")]
 fn encode_raster_image(image: &RasterImage, interpolate: bool) -> EncodedImage {
     let dynamic = image.dynamic();
-    let color_space = to_color_space(dynamic.color());
+    let color_space = raster_color_space(image);
 
-    let (filter, data, bits_per_component) =
-        if image.format() == RasterFormat::Exchange(ExchangeFormat::Jpg) {
+    let (filter, data, bits_per_component) = if let Some(cmyk) = image.cmyk() {
+        (Filter::FlateDecode, deflate(cmyk), 8)
+    } else if image.format() == RasterFormat::Exchange(ExchangeFormat::Jpg) {
+        // If the decoded pixels still match the JPEG data, we can embed it
+        // directly instead of encoding the image lossily a second time.
+        // Images with four components are usually inverted CMYK, which
+        // the decoder already converted to RGB.
+        let data = if !image.is_rotated()
+            && matches!(jpeg_components(image.data()), Some(1 | 3))
+        {
+            image.data().to_vec()
+        } else {
             let mut data = Cursor::new(vec![]);
             dynamic.write_to(&mut data, image::ImageFormat::Jpeg).unwrap();
-            (Filter::DctDecode, data.into_inner(), 8)
-        } else {
-            // TODO: Encode flate streams with PNG-predictor?
-            let (data, bits_per_component) = match (dynamic, color_space) {
-                // RGB image.
-                (DynamicImage::ImageRgb8(rgb), _) => (deflate(rgb.as_raw()), 8),
-                // Grayscale image
-                (DynamicImage::ImageLuma8(luma), _) => (deflate(luma.as_raw()), 8),
-                (_, ColorSpace::D65Gray) => (deflate(dynamic.to_luma8().as_raw()), 8),
-                // Anything else
-                _ => (deflate(dynamic.to_rgb8().as_raw()), 8),
-            };
-            (Filter::FlateDecode, data, bits_per_component)
+            data.into_inner()
+        };
+        (Filter::DctDecode, data, 8)
+    } else {
+        // TODO: Encode flate streams with PNG-predictor?
+        let (data, bits_per_component) = match (dynamic, color_space) {
+            // RGB image.
+            (DynamicImage::ImageRgb8(rgb), _) => (deflate(rgb.as_raw()), 8),
+            // Grayscale image
+            (DynamicImage::ImageLuma8(luma), _) => (deflate(luma.as_raw()), 8),
+            (_, ColorSpace::D65Gray) => (deflate(dynamic.to_luma8().as_raw()), 8),
+            // Anything else
+            _ => (deflate(dynamic.to_rgb8().as_raw()), 8),
         };
+        (Filter::FlateDecode, data, bits_per_component)
+    };
 
-    let compressed_icc = image.icc().map(|data| deflate(data));
+    let compressed_icc = embedded_icc(image).map(|data| deflate(data));
     let alpha = dynamic.color().has_alpha().then(|| encode_alpha(dynamic));
 
     EncodedImage::Raster {
@@ -190,6 +273,60 @@ fn encode_raster_image(image: &RasterImage, interpolate: bool) -> EncodedImage {
     }
 }
 
+/// The color space a raster image is embedded in.
+fn raster_color_space(image: &RasterImage) -> ColorSpace {
+    if image.cmyk().is_some() {
+        ColorSpace::Cmyk
+    } else {
+        to_color_space(image.dynamic().color())
+    }
+}
+
+/// The ICC profile to embed for a raster image, if any.
+///
+/// PDF requires the profile to describe the color space the image is embedded
+/// in, so a mismatching profile is ignored.
+fn embedded_icc(image: &RasterImage) -> Option<&Bytes> {
+    let expected: &[u8] = match raster_color_space(image) {
+        ColorSpace::D65Gray => b"GRAY",
+        ColorSpace::Cmyk => b"CMYK",
+        _ => b"RGB ",
+    };
+    image.icc().filter(|icc| icc.get(16..20) == Some(expected))
+}
+
+/// Determine the number of color components of a JPEG image by reading its
+/// frame header.
+fn jpeg_components(data: &[u8]) -> Option<u8> {
+    let mut i = 2;
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    loop {
+        if *data.get(i)? != 0xFF {
+            return None;
+        }
+
+        match *data.get(i + 1)? {
+            // Fill byte before a marker.
+            0xFF => i += 1,
+            // Markers without a segment.
+            0x01 | 0xD0..=0xD7 => i += 2,
+            // Start of frame, except for DHT, JPG, and DAC.
+            marker @ 0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return data.get(i + 9).copied();
+            }
+            // Start of scan or end of image before any frame header.
+            0xDA | 0xD9 => return None,
+            _ => {
+                let len = u16::from_be_bytes(data.get(i + 2..i + 4)?.try_into().ok()?);
+                i += 2 + usize::from(len);
+            }
+        }
+    }
+}
+
 /// Encode an image's alpha channel if present.
 #[typst_macros::time(name = "encode alpha")]
 fn encode_alpha(image: &DynamicImage) -> (Vec<u8>, Filter) {
@@ -236,6 +373,10 @@ pub enum EncodedImage {
     ///
     /// The chunk is the SVG converted to PDF objects.
     Svg(Chunk, Ref),
+    /// A page of a PDF document.
+    ///
+    /// The chunk is the page as a form XObject and its dependencies.
+    Pdf(Chunk, Ref),
 }
 
 /// Matches an [`image::ColorType`] to [`ColorSpace`].
@@ -1,18 +1,24 @@
 //! Exporting of Typst documents into PDFs.
 
+mod annot;
 mod catalog;
 mod color;
 mod color_font;
 mod content;
 mod embed;
+mod encrypt;
 mod extg;
+mod file;
 mod font;
+mod form;
 mod gradient;
 mod image;
 mod named_destination;
 mod outline;
 mod page;
+mod pdf_image;
 mod resources;
+mod tags;
 mod tiling;
 
 use std::collections::{BTreeMap, HashMap};
@@ -41,10 +47,13 @@ use crate::font::write_fonts;
 use crate::gradient::{write_gradients, PdfGradient};
 use crate::image::write_images;
 use crate::named_destination::{write_named_destinations, NamedDestinations};
-use crate::page::{alloc_page_refs, traverse_pages, write_page_tree, EncodedPage};
+use crate::page::{
+    alloc_page_refs, traverse_pages, write_page_tree, EncodedPage, PageTreeRefs,
+};
 use crate::resources::{
     alloc_resources_refs, write_resource_dictionaries, Resources, ResourcesRefs,
 };
+use crate::tags::Tags;
 use crate::tiling::{write_tilings, PdfTiling};
 
 /// Export a document into a PDF file.
@@ -52,6 +61,10 @@ use crate::tiling::{write_tilings, PdfTiling};
 /// Returns the raw bytes making up the PDF file.
 #[typst_macros::time(name = "pdf")]
 pub fn pdf(document: &PagedDocument, options: &PdfOptions) -> SourceResult<Vec<u8>> {
+    if let Some(encryption) = &options.encryption {
+        encrypt::validate(encryption, &options.standards)?;
+    }
+
     PdfBuilder::new(document, options)
         .phase(|builder| builder.run(traverse_pages))?
         .phase(|builder| {
@@ -76,6 +89,7 @@ pub fn pdf(document: &PagedDocument, options: &PdfOptions) -> SourceResult<Vec<u
         .phase(|builder| builder.run(write_page_tree))?
         .phase(|builder| builder.run(write_resource_dictionaries))?
         .export_with(write_catalog)
+        .and_then(|(pdf, packed)| file::finish(pdf, packed, options))
 }
 
 /// Settings for PDF export.
@@ -101,6 +115,9 @@ pub struct PdfOptions<'a> {
     pub page_ranges: Option<PageRanges>,
     /// A list of PDF standards that Typst will enforce conformance with.
     pub standards: PdfStandards,
+    /// If not `None`, the PDF will be encrypted with the given passwords and
+    /// permissions.
+    pub encryption: Option<PdfEncryption>,
 }
 
 /// A timestamp with timezone information.
@@ -149,35 +166,101 @@ pub enum Timezone {
 }
 
 /// Encapsulates a list of compatible PDF standards.
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 pub struct PdfStandards {
     /// For now, we simplify to just PDF/A. But it can be more fine-grained in
     /// the future.
     pub(crate) pdfa: bool,
     /// Whether the standard allows for embedding any kind of file into the PDF.
-    /// We disallow this for PDF/A-2, since it only allows embedding
-    /// PDF/A-1 and PDF/A-2 documents.
+    /// We disallow this for PDF/A-2 and PDF/A-4, since they only allow
+    /// embedding PDF/A documents, and for PDF/A-1, which forbids embedded
+    /// files altogether.
     pub(crate) embedded_files: bool,
-    /// Part of the PDF/A standard.
+    /// Part of the PDF/A standard and its conformance level. The level is
+    /// empty for PDF/A-4 without a conformance level.
     pub(crate) pdfa_part: Option<(i32, &'static str)>,
+    /// The version of the PDF specification the file is written against.
+    pub(crate) version: PdfVersion,
+    /// Whether the standard allows for transparency. PDF/A-1 does not.
+    pub(crate) transparency: bool,
+    /// Whether the PDF must be tagged, i.e. contain a structure tree.
+    pub(crate) tagged: bool,
+    /// Whether the PDF must conform to PDF/UA-1.
+    pub(crate) ua: bool,
 }
 
 impl PdfStandards {
     /// Validates a list of PDF standards for compatibility and returns their
     /// encapsulated representation.
     pub fn new(list: &[PdfStandard]) -> StrResult<Self> {
+        let v17 = list.contains(&PdfStandard::V_1_7);
+        let v20 = list.contains(&PdfStandard::V_2_0);
+        let a1b = list.contains(&PdfStandard::A_1b);
         let a2b = list.contains(&PdfStandard::A_2b);
+        let a2a = list.contains(&PdfStandard::A_2a);
         let a3b = list.contains(&PdfStandard::A_3b);
+        let a3a = list.contains(&PdfStandard::A_3a);
+        let a4 = list.contains(&PdfStandard::A_4);
+        let a4f = list.contains(&PdfStandard::A_4f);
+        let ua = list.contains(&PdfStandard::UA_1);
+
+        let parts = [
+            (a1b, 1, "B"),
+            (a2b, 2, "B"),
+            (a2a, 2, "A"),
+            (a3b, 3, "B"),
+            (a3a, 3, "A"),
+            (a4, 4, ""),
+            (a4f, 4, "F"),
+        ];
+        let mut selected = parts.into_iter().filter(|&(on, ..)| on);
+        let pdfa_part = selected.next().map(|(_, part, level)| (part, level));
+        if let Some((_, part, level)) = selected.next() {
+            let (first_part, first_level) = pdfa_part.unwrap();
+            bail!(
+                "PDF cannot conform to A-{first_part}{first_level} and \
+                 A-{part}{level} at the same time"
+            );
+        }
 
-        if a2b && a3b {
-            bail!("PDF cannot conform to A-2B and A-3B at the same time")
+        if v17 && v20 {
+            bail!("PDF cannot conform to 1.7 and 2.0 at the same time");
         }
 
-        let pdfa = a2b || a3b;
+        // PDF/A-1 to PDF/A-3 are based on PDF 1.x, while PDF/A-4 is based on
+        // PDF 2.0.
+        let version = match pdfa_part {
+            Some((4, _)) if v17 => {
+                bail!("PDF/A-4 requires PDF 2.0 and cannot conform to 1.7")
+            }
+            Some((part, level)) if v20 && part < 4 => {
+                bail!("PDF/A-{part}{level} cannot conform to PDF 2.0")
+            }
+            Some((1, _)) => PdfVersion::V1_4,
+            Some((4, _)) => PdfVersion::V2_0,
+            _ if v20 => PdfVersion::V2_0,
+            _ => PdfVersion::V1_7,
+        };
+
+        // PDF/UA-1 is based on PDF 1.7.
+        if ua && version != PdfVersion::V1_7 {
+            match pdfa_part {
+                Some((part, level)) => bail!(
+                    "PDF cannot conform to UA-1 and A-{part}{level} at the same time"
+                ),
+                None => bail!("PDF/UA-1 requires PDF 1.7 and cannot conform to 2.0"),
+            }
+        }
+
+        let pdfa = pdfa_part.is_some();
         Ok(Self {
             pdfa,
-            embedded_files: !a2b,
-            pdfa_part: pdfa.then_some((if a2b { 2 } else { 3 }, "B")),
+            embedded_files: !a1b && !a2b && !a2a && !a4,
+            pdfa_part,
+            version,
+            transparency: !a1b,
+            tagged: a2a || a3a || ua,
+            ua,
         })
     }
 }
@@ -190,12 +273,24 @@ impl Debug for PdfStandards {
 
 impl Default for PdfStandards {
     fn default() -> Self {
-        Self { pdfa: false, embedded_files: true, pdfa_part: None }
+        Self {
+            pdfa: false,
+            embedded_files: true,
+            pdfa_part: None,
+            version: PdfVersion::V1_7,
+            transparency: true,
+            tagged: false,
+            ua: false,
+        }
     }
 }
 
 /// A PDF standard that Typst can enforce conformance with.
 ///
+/// PDF/A-2a, PDF/A-3a, and PDF/UA-1 files are tagged. Exporting to them fails
+/// unless the document was compiled with the
+/// [`PdfTags`](typst_library::Feature::PdfTags) feature enabled.
+///
 /// Support for more standards is planned.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
@@ -204,12 +299,148 @@ pub enum PdfStandard {
     /// PDF 1.7.
     #[serde(rename = "1.7")]
     V_1_7,
+    /// PDF 2.0.
+    #[serde(rename = "2.0")]
+    V_2_0,
+    /// PDF/A-1b.
+    #[serde(rename = "a-1b")]
+    A_1b,
     /// PDF/A-2b.
     #[serde(rename = "a-2b")]
     A_2b,
+    /// PDF/A-2a.
+    #[serde(rename = "a-2a")]
+    A_2a,
     /// PDF/A-3b.
     #[serde(rename = "a-3b")]
     A_3b,
+    /// PDF/A-3a.
+    #[serde(rename = "a-3a")]
+    A_3a,
+    /// PDF/A-4.
+    #[serde(rename = "a-4")]
+    A_4,
+    /// PDF/A-4f.
+    #[serde(rename = "a-4f")]
+    A_4f,
+    /// PDF/UA-1.
+    #[serde(rename = "ua-1")]
+    UA_1,
+}
+
+/// A version of the PDF specification.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub(crate) enum PdfVersion {
+    /// PDF 1.4, which PDF/A-1 is based on.
+    V1_4,
+    /// PDF 1.7.
+    V1_7,
+    /// PDF 2.0.
+    V2_0,
+}
+
+impl PdfVersion {
+    /// The major and minor version numbers.
+    pub fn numbers(self) -> (u8, u8) {
+        match self {
+            Self::V1_4 => (1, 4),
+            Self::V1_7 => (1, 7),
+            Self::V2_0 => (2, 0),
+        }
+    }
+
+    /// The version as written in the XMP metadata.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1_4 => "1.4",
+            Self::V1_7 => "1.7",
+            Self::V2_0 => "2.0",
+        }
+    }
+}
+
+/// Settings for encrypting a PDF with the standard security handler.
+#[derive(Clone)]
+pub struct PdfEncryption {
+    /// The algorithm to encrypt the document with.
+    pub algorithm: PdfEncryptionAlgorithm,
+    /// The password needed to open the document. If empty, viewers open the
+    /// document without asking for a password, but still enforce the
+    /// permissions.
+    pub user_password: String,
+    /// The password that grants full access to the document. If empty, the
+    /// user password is used instead.
+    pub owner_password: String,
+    /// What users that opened the document with the user password may do.
+    pub permissions: PdfPermissions,
+}
+
+impl PdfEncryption {
+    /// Encryption with the given algorithm, empty passwords, and all
+    /// permissions granted.
+    pub fn new(algorithm: PdfEncryptionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            user_password: String::new(),
+            owner_password: String::new(),
+            permissions: PdfPermissions::all(),
+        }
+    }
+}
+
+impl Debug for PdfEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PdfEncryption")
+            .field("algorithm", &self.algorithm)
+            .field("permissions", &self.permissions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An algorithm that Typst can encrypt PDFs with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PdfEncryptionAlgorithm {
+    /// AES with a 128-bit key (revision 4 of the standard security handler).
+    /// PDF 1.6+, deprecated in PDF 2.0.
+    #[serde(rename = "aes-128")]
+    Aes128,
+    /// AES with a 256-bit key (revision 6 of the standard security handler).
+    /// PDF 2.0, and Adobe extension level 8 to PDF 1.7.
+    #[serde(rename = "aes-256")]
+    Aes256,
+}
+
+bitflags::bitflags! {
+    /// Actions that users of an encrypted PDF may perform unless they opened
+    /// it with the owner password.
+    ///
+    /// The values of the flags are the bits of the `/P` entry of the
+    /// encryption dictionary.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    pub struct PdfPermissions: u32 {
+        /// Print the document, possibly only in low quality.
+        const PRINT = 1 << 2;
+        /// Modify the document's contents.
+        const MODIFY = 1 << 3;
+        /// Copy or otherwise extract text and graphics.
+        const COPY = 1 << 4;
+        /// Add or modify annotations and fill in form fields.
+        const ANNOTATE = 1 << 5;
+        /// Fill in form fields, even if `ANNOTATE` is not granted.
+        const FILL_FORMS = 1 << 8;
+        /// Insert, rotate, or delete pages and create bookmarks.
+        const ASSEMBLE = 1 << 10;
+        /// Print the document in full quality. Without it, `PRINT` only
+        /// permits printing a low-quality rendition.
+        const PRINT_HIGH_QUALITY = 1 << 11;
+    }
+}
+
+impl Default for PdfPermissions {
+    fn default() -> Self {
+        Self::all()
+    }
 }
 
 /// A struct to build a PDF following a fixed succession of phases.
@@ -237,6 +468,9 @@ struct PdfBuilder<S> {
     alloc: Ref,
     /// The PDF document that is being written.
     pdf: Pdf,
+    /// Objects that are stored in object streams of the final file, one per
+    /// chunk.
+    packed: Vec<Chunk>,
 }
 
 /// The initial state: we are exploring the document, collecting all resources
@@ -264,6 +498,8 @@ struct WithResources<'a> {
     pages: Vec<Option<EncodedPage>>,
     /// The PDF resources that are used in the content of the pages.
     resources: Resources<()>,
+    /// The structure tree, if the document is tagged.
+    tags: Option<Tags>,
 }
 
 /// Global references.
@@ -279,13 +515,15 @@ struct GlobalRefs {
     resources: ResourcesRefs,
 }
 
-impl<'a> From<(WithDocument<'a>, (Vec<Option<EncodedPage>>, Resources<()>))>
+#[allow(clippy::type_complexity)]
+impl<'a>
+    From<(WithDocument<'a>, ((Vec<Option<EncodedPage>>, Option<Tags>), Resources<()>))>
     for WithResources<'a>
 {
     fn from(
-        (previous, (pages, resources)): (
+        (previous, ((pages, tags), resources)): (
             WithDocument<'a>,
-            (Vec<Option<EncodedPage>>, Resources<()>),
+            ((Vec<Option<EncodedPage>>, Option<Tags>), Resources<()>),
         ),
     ) -> Self {
         Self {
@@ -293,6 +531,7 @@ impl<'a> From<(WithDocument<'a>, (Vec<Option<EncodedPage>>, Resources<()>))>
             options: previous.options,
             pages,
             resources,
+            tags,
         }
     }
 }
@@ -308,6 +547,7 @@ struct WithGlobalRefs<'a> {
     pages: Vec<Option<EncodedPage>>,
     /// Resources are the same as in previous phases, but each dictionary now has a reference.
     resources: Resources,
+    tags: Option<Tags>,
     /// Global references that were just allocated.
     globals: GlobalRefs,
 }
@@ -319,6 +559,7 @@ impl<'a> From<(WithResources<'a>, GlobalRefs)> for WithGlobalRefs<'a> {
             options: previous.options,
             pages: previous.pages,
             resources: previous.resources.with_refs(&globals.resources),
+            tags: previous.tags,
             globals,
         }
     }
@@ -353,6 +594,7 @@ struct WithRefs<'a> {
     globals: GlobalRefs,
     pages: Vec<Option<EncodedPage>>,
     resources: Resources,
+    tags: Option<Tags>,
     /// References that were allocated for resources.
     references: References,
 }
@@ -365,6 +607,7 @@ impl<'a> From<(WithGlobalRefs<'a>, References)> for WithRefs<'a> {
             globals: previous.globals,
             pages: previous.pages,
             resources: previous.resources,
+            tags: previous.tags,
             references,
         }
     }
@@ -382,6 +625,11 @@ struct WithEverything<'a> {
     references: References,
     /// Reference that was allocated for the page tree.
     page_tree_ref: Ref,
+    /// Reference that was allocated for the structure tree, if the document
+    /// is tagged.
+    struct_tree_ref: Option<Ref>,
+    /// References to the top-level fields of the interactive form.
+    form_fields: Vec<Ref>,
 }
 
 impl<'a> From<(WithEverything<'a>, ())> for WithEverything<'a> {
@@ -390,8 +638,8 @@ impl<'a> From<(WithEverything<'a>, ())> for WithEverything<'a> {
     }
 }
 
-impl<'a> From<(WithRefs<'a>, Ref)> for WithEverything<'a> {
-    fn from((previous, page_tree_ref): (WithRefs<'a>, Ref)) -> Self {
+impl<'a> From<(WithRefs<'a>, PageTreeRefs)> for WithEverything<'a> {
+    fn from((previous, refs): (WithRefs<'a>, PageTreeRefs)) -> Self {
         Self {
             document: previous.document,
             options: previous.options,
@@ -399,7 +647,9 @@ impl<'a> From<(WithRefs<'a>, Ref)> for WithEverything<'a> {
             resources: previous.resources,
             references: previous.references,
             pages: previous.pages,
-            page_tree_ref,
+            page_tree_ref: refs.page_tree,
+            struct_tree_ref: refs.struct_tree,
+            form_fields: refs.fields,
         }
     }
 }
@@ -407,9 +657,13 @@ impl<'a> From<(WithRefs<'a>, Ref)> for WithEverything<'a> {
 impl<'a> PdfBuilder<WithDocument<'a>> {
     /// Start building a PDF for a Typst document.
     fn new(document: &'a PagedDocument, options: &'a PdfOptions<'a>) -> Self {
+        let mut pdf = Pdf::new();
+        let (major, minor) = options.standards.version.numbers();
+        pdf.set_version(major, minor);
         Self {
             alloc: Ref::new(1),
-            pdf: Pdf::new(),
+            pdf,
+            packed: vec![],
             state: WithDocument { document, options },
         }
     }
@@ -429,6 +683,7 @@ impl<S> PdfBuilder<S> {
             state: NS::from((self.state, output)),
             alloc: self.alloc,
             pdf: self.pdf,
+            packed: self.packed,
         })
     }
 
@@ -452,6 +707,12 @@ impl<S> PdfBuilder<S> {
 
             r
         });
+        self.packed.extend(chunk.packed.iter().map(|packed| {
+            packed.renumber(|mut r| {
+                r.renumber(offset);
+                r
+            })
+        }));
 
         // Also update the references in the output
         output.renumber(offset);
@@ -461,14 +722,15 @@ impl<S> PdfBuilder<S> {
         Ok(output)
     }
 
-    /// Finalize the PDF export and returns the buffer representing the
-    /// document.
-    fn export_with<P>(mut self, process: P) -> SourceResult<Vec<u8>>
+    /// Finalize the PDF export and return the document, along with the
+    /// objects that go into object streams. They are assembled into the final
+    /// file by [`file::finish`].
+    fn export_with<P>(mut self, process: P) -> SourceResult<(Pdf, Vec<Chunk>)>
     where
-        P: Fn(S, &mut Pdf, &mut Ref) -> SourceResult<()>,
+        P: Fn(S, &mut Pdf, &[Chunk], &mut Ref) -> SourceResult<()>,
     {
-        process(self.state, &mut self.pdf, &mut self.alloc)?;
-        Ok(self.pdf.finish())
+        process(self.state, &mut self.pdf, &self.packed, &mut self.alloc)?;
+        Ok((self.pdf, self.packed))
     }
 }
 
@@ -533,6 +795,9 @@ impl<T, R: Renumber> Renumber for (T, R) {
 struct PdfChunk {
     /// The actual chunk.
     chunk: Chunk,
+    /// Objects that are stored in object streams of the final file, one per
+    /// chunk.
+    packed: Vec<Chunk>,
     /// A local allocator.
     alloc: Ref,
 }
@@ -552,6 +817,7 @@ impl PdfChunk {
     fn new() -> Self {
         PdfChunk {
             chunk: Chunk::new(),
+            packed: vec![],
             alloc: Ref::new(TEMPORARY_REFS_START),
         }
     }
@@ -564,6 +830,17 @@ impl PdfChunk {
     fn alloc(&mut self) -> Ref {
         self.alloc.bump()
     }
+
+    /// Start writing an object that is stored in an object stream of the
+    /// final file, if the PDF version supports them.
+    ///
+    /// Exactly one object that is not a stream must be written to the
+    /// returned chunk. This is meant for the many small dictionaries of long
+    /// documents, like pages, annotations, and structure elements.
+    fn packed(&mut self) -> &mut Chunk {
+        self.packed.push(Chunk::new());
+        self.packed.last_mut().unwrap()
+    }
 }
 
 impl Deref for PdfChunk {
@@ -694,6 +971,15 @@ fn transform_to_array(ts: Transform) -> [f32; 6] {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use typst_library::foundations::Content;
+    use typst_library::layout::{Angle, Frame, FrameItem, Page, Point, Ratio, Size};
+    use typst_library::visualize::{
+        Color, ColorSpace, Geometry, Gradient, LinearGradient, Paint,
+    };
+    use typst_syntax::{FileId, VirtualPath};
+
     use super::*;
 
     #[test]
@@ -728,4 +1014,124 @@ mod tests {
         assert!(Timestamp::new_local(dummy_datetime, i32::MAX).is_none());
         assert!(Timestamp::new_local(dummy_datetime, i32::MIN).is_none());
     }
+
+    #[test]
+    fn test_standards_conflicts() {
+        let error = |list: &[PdfStandard]| PdfStandards::new(list).err().unwrap();
+        assert_eq!(
+            error(&[PdfStandard::A_1b, PdfStandard::A_4]),
+            "PDF cannot conform to A-1B and A-4 at the same time"
+        );
+        assert_eq!(
+            error(&[PdfStandard::V_1_7, PdfStandard::V_2_0]),
+            "PDF cannot conform to 1.7 and 2.0 at the same time"
+        );
+        assert_eq!(
+            error(&[PdfStandard::V_1_7, PdfStandard::A_4f]),
+            "PDF/A-4 requires PDF 2.0 and cannot conform to 1.7"
+        );
+        assert_eq!(
+            error(&[PdfStandard::V_2_0, PdfStandard::A_1b]),
+            "PDF/A-1B cannot conform to PDF 2.0"
+        );
+        assert_eq!(
+            error(&[PdfStandard::UA_1, PdfStandard::A_4]),
+            "PDF cannot conform to UA-1 and A-4 at the same time"
+        );
+        assert_eq!(
+            error(&[PdfStandard::UA_1, PdfStandard::V_2_0]),
+            "PDF/UA-1 requires PDF 1.7 and cannot conform to 2.0"
+        );
+
+        let version = |list: &[PdfStandard]| PdfStandards::new(list).unwrap().version;
+        assert_eq!(version(&[PdfStandard::A_1b]), PdfVersion::V1_4);
+        assert_eq!(version(&[PdfStandard::A_4]), PdfVersion::V2_0);
+        assert_eq!(version(&[PdfStandard::A_4f, PdfStandard::V_2_0]), PdfVersion::V2_0);
+        assert_eq!(version(&[PdfStandard::UA_1, PdfStandard::A_2a]), PdfVersion::V1_7);
+    }
+
+    #[test]
+    fn test_export_versions() {
+        let doc = document(Color::BLACK.into());
+        let export = |list: &[PdfStandard]| export(&doc, list).unwrap();
+
+        let pdf = export(&[PdfStandard::V_2_0]);
+        assert!(pdf.starts_with(b"%PDF-2.0"));
+        assert!(!contains(&pdf, b"pdfaid:part"));
+
+        let pdf = export(&[PdfStandard::A_1b]);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(contains(&pdf, b"<pdfaid:part>1</pdfaid:part>"));
+        assert!(contains(&pdf, b"<pdfaid:conformance>B</pdfaid:conformance>"));
+
+        let pdf = export(&[PdfStandard::A_4]);
+        assert!(pdf.starts_with(b"%PDF-2.0"));
+        assert!(contains(&pdf, b"<pdfaid:part>4</pdfaid:part>"));
+        assert!(contains(&pdf, b"<pdfaid:rev>2020</pdfaid:rev>"));
+        assert!(!contains(&pdf, b"pdfaid:conformance"));
+
+        let pdf = export(&[PdfStandard::A_4f]);
+        assert!(pdf.starts_with(b"%PDF-2.0"));
+        assert!(contains(&pdf, b"<pdfaid:part>4</pdfaid:part>"));
+        assert!(contains(&pdf, b"<pdfaid:conformance>F</pdfaid:conformance>"));
+    }
+
+    #[test]
+    fn test_export_a1_transparency() {
+        let transparent = Color::BLACK.with_alpha(0.5);
+        for fill in [
+            transparent.into(),
+            Gradient::Linear(Arc::new(LinearGradient {
+                stops: vec![(Color::BLACK, Ratio::zero()), (transparent, Ratio::one())],
+                angle: Angle::zero(),
+                space: ColorSpace::Oklab,
+                relative: Smart::Auto,
+                anti_alias: true,
+            }))
+            .into(),
+        ] {
+            let doc = document(fill);
+            assert!(export(&doc, &[PdfStandard::A_2b]).is_ok());
+
+            let errors = export(&doc, &[PdfStandard::A_1b]).unwrap_err();
+            assert!(errors[0].message.contains("are not allowed in PDF/A-1"));
+            assert_eq!(errors[0].span, span());
+        }
+    }
+
+    /// Export a document as PDF conforming to the given standards.
+    fn export(doc: &PagedDocument, list: &[PdfStandard]) -> SourceResult<Vec<u8>> {
+        let options = PdfOptions {
+            standards: PdfStandards::new(list).unwrap(),
+            ..Default::default()
+        };
+        pdf(doc, &options)
+    }
+
+    /// A document with a single page that contains a square with the given
+    /// fill.
+    fn document(fill: Paint) -> PagedDocument {
+        let size = Size::splat(Abs::pt(10.0));
+        let mut frame = Frame::hard(size);
+        let shape = Geometry::Rect(size).filled(fill);
+        frame.push(Point::zero(), FrameItem::Shape(shape, span()));
+        let page = Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+        };
+        PagedDocument { pages: vec![page], ..Default::default() }
+    }
+
+    /// The span of the square in the document.
+    fn span() -> Span {
+        Span::from_range(FileId::new(None, VirtualPath::new("main.typ")), 0..6)
+    }
+
+    /// Whether the data contains the needle.
+    fn contains(data: &[u8], needle: &[u8]) -> bool {
+        data.windows(needle.len()).any(|window| window == needle)
+    }
 }
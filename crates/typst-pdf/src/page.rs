@@ -1,18 +1,30 @@
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 
+use comemo::{Track, Tracked};
 use ecow::EcoString;
-use pdf_writer::types::{ActionType, AnnotationFlags, AnnotationType, NumberingStyle};
-use pdf_writer::{Filter, Finish, Name, Rect, Ref, Str};
+use pdf_writer::types::{
+    ActionType, AnnotationFlags, AnnotationType, NumberingStyle, TabOrder,
+    TransitionStyle,
+};
+use pdf_writer::{Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use typst_library::diag::SourceResult;
-use typst_library::foundations::Label;
+use typst_library::foundations::{Label, Smart};
 use typst_library::introspection::Location;
-use typst_library::layout::{Abs, Page};
-use typst_library::model::{Destination, Numbering};
-
+use typst_library::layout::{Abs, Frame, Page};
+use typst_library::model::{Destination, Numbering, PageTransition};
+use typst_library::visualize::Paint;
+
+use crate::annot;
+use crate::catalog;
+use crate::content::Cids;
+use crate::form::{self, Fields};
+use crate::tags::{self, Mark, Tags};
 use crate::{
-    content, AbsExt, PdfChunk, PdfOptions, Resources, WithDocument, WithRefs,
-    WithResources,
+    content, AbsExt, PdfChunk, PdfOptions, PdfStandards, Renumber, Resources, TextStrExt,
+    WithDocument, WithRefs, WithResources,
 };
 
 /// Construct page objects.
@@ -20,60 +32,165 @@ use crate::{
 #[allow(clippy::type_complexity)]
 pub fn traverse_pages(
     state: &WithDocument,
-) -> SourceResult<(PdfChunk, (Vec<Option<EncodedPage>>, Resources<()>))> {
+) -> SourceResult<(PdfChunk, ((Vec<Option<EncodedPage>>, Option<Tags>), Resources<()>))> {
+    let mut tags = None;
+    if state.options.standards.tagged {
+        tags::check(state.options, state.document)?;
+        tags = Some(Tags::default());
+    }
+
     let mut resources = Resources::default();
+    let exported: Vec<Option<&Page>> = state
+        .document
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let excluded = state
+                .options
+                .page_ranges
+                .as_ref()
+                .is_some_and(|ranges| !ranges.includes_page_index(i));
+            (!excluded).then_some(page)
+        })
+        .collect();
+
+    // Fix the CIDs of all glyphs in order of their first use before encoding
+    // any page, so that pages don't depend on the pages before them.
+    let scans: Vec<_> = exported
+        .iter()
+        .map(|page| page.map(|page| content::scan(&page.frame)))
+        .collect();
+    for scan in scans.iter().flatten() {
+        for (font, glyphs) in &scan.glyphs {
+            let glyph_remapper =
+                resources.glyph_remappers.entry(font.clone()).or_default();
+            for &glyph in glyphs {
+                glyph_remapper.remap(glyph);
+            }
+        }
+    }
+
+    // Build the structure tree of a tagged document page by page upfront and
+    // record how each page's content must be marked.
+    let mut marks: Vec<Option<(i32, Vec<Mark>)>> = vec![None; exported.len()];
+    if let Some(tags) = &mut tags {
+        for (i, page) in exported.iter().enumerate() {
+            if let Some(page) = page {
+                marks[i] = Some(tags.record(state.options, i, page)?);
+            }
+        }
+        tags.finish();
+    }
+
+    // Encode the pages that don't share any state with the rest of the
+    // document in parallel.
+    let cids = Cids(std::mem::take(&mut resources.glyph_remappers));
+    let mut standalone = vec![];
+    exported
+        .par_iter()
+        .zip(&scans)
+        .zip(&marks)
+        .map(|((page, scan), marks)| {
+            let (Some(page), Some(scan)) = (page, scan) else { return None };
+            let fill = page.fill_or_transparent();
+            if !scan.standalone || matches!(fill, Some(Paint::Tiling(_))) {
+                return None;
+            }
+            Some(encode_standalone(
+                &state.options.standards,
+                &page.frame,
+                fill,
+                marks.as_ref().map(|(_, marks)| marks.as_slice()),
+                cids.track(),
+            ))
+        })
+        .collect_into_vec(&mut standalone);
+    resources.glyph_remappers = cids.0;
+
     let mut pages = Vec::with_capacity(state.document.pages.len());
     let mut skipped_pages = 0;
-    for (i, page) in state.document.pages.iter().enumerate() {
-        if state
-            .options
-            .page_ranges
-            .as_ref()
-            .is_some_and(|ranges| !ranges.includes_page_index(i))
-        {
-            // Don't export this page.
-            pages.push(None);
-            skipped_pages += 1;
-        } else {
-            let mut encoded = construct_page(state.options, &mut resources, page)?;
+    for (i, ((page, standalone), marks)) in
+        exported.into_iter().zip(standalone).zip(marks).enumerate()
+    {
+        if let Some(page) = page {
+            let (struct_parents, marks) = marks.unzip();
+            let content = match standalone {
+                Some(result) => {
+                    let (content, page_resources) = &*result?;
+                    resources.merge(page_resources);
+                    content.clone()
+                }
+                None => {
+                    construct_page(state.options, &mut resources, page, marks.as_deref())?
+                }
+            };
+            let mut encoded = EncodedPage { content, label: None, struct_parents };
             encoded.label = page
                 .numbering
                 .as_ref()
                 .and_then(|num| PdfPageLabel::generate(num, page.number))
                 .or_else(|| {
-                    // When some pages were ignored from export, we show a page label with
-                    // the correct real (not logical) page number.
-                    // This is for consistency with normal output when pages have no numbering
-                    // and all are exported: the final PDF page numbers always correspond to
-                    // the real (not logical) page numbers. Here, the final PDF page number
-                    // will differ, but we can at least use labels to indicate what was
-                    // the corresponding real page number in the Typst document.
+                    // When some pages were ignored from export, we show a page
+                    // label with the correct real (not logical) page number.
+                    // This is for consistency with normal output when pages
+                    // have no numbering and all are exported: the final PDF
+                    // page numbers always correspond to the real (not logical)
+                    // page numbers. Here, the final PDF page number will
+                    // differ, but we can at least use labels to indicate what
+                    // was the corresponding real page number in the Typst
+                    // document.
                     (skipped_pages > 0).then(|| PdfPageLabel::arabic(i + 1))
                 });
             pages.push(Some(encoded));
+        } else {
+            // Don't export this page.
+            pages.push(None);
+            skipped_pages += 1;
         }
     }
 
-    Ok((PdfChunk::new(), (pages, resources)))
+    Ok((PdfChunk::new(), ((pages, tags), resources)))
+}
+
+/// Encode a page that does not share any state with other pages.
+///
+/// The page is encoded with resources of its own, which are merged into the
+/// document's resources afterwards. The result is cached, so that unchanged
+/// pages are not encoded again when the document is exported repeatedly.
+#[comemo::memoize]
+fn encode_standalone(
+    standards: &PdfStandards,
+    frame: &Frame,
+    fill: Option<Paint>,
+    marks: Option<&[Mark]>,
+    cids: Tracked<Cids>,
+) -> SourceResult<Arc<(content::Encoded, Resources<()>)>> {
+    // Content streams only depend on the standards among the options.
+    let options = PdfOptions { standards: standards.clone(), ..Default::default() };
+    let mut resources = Resources::default();
+    let encoded =
+        content::build(&options, &mut resources, frame, fill, None, marks, Some(cids))?;
+    Ok(Arc::new((encoded, resources)))
 }
 
-/// Construct a page object.
+/// Encode a page with the document's resources.
 #[typst_macros::time(name = "construct page")]
 fn construct_page(
     options: &PdfOptions,
     out: &mut Resources<()>,
     page: &Page,
-) -> SourceResult<EncodedPage> {
-    Ok(EncodedPage {
-        content: content::build(
-            options,
-            out,
-            &page.frame,
-            page.fill_or_transparent(),
-            None,
-        )?,
-        label: None,
-    })
+    marks: Option<&[Mark]>,
+) -> SourceResult<content::Encoded> {
+    content::build(
+        options,
+        out,
+        &page.frame,
+        page.fill_or_transparent(),
+        None,
+        marks,
+        None,
+    )
 }
 
 /// Allocate a reference for each exported page.
@@ -89,10 +206,49 @@ pub fn alloc_page_refs(
     Ok((chunk, page_refs))
 }
 
+/// References to the page tree and, for tagged PDFs, the structure tree.
+pub struct PageTreeRefs {
+    /// The root of the page tree.
+    pub page_tree: Ref,
+    /// The root of the structure tree.
+    pub struct_tree: Option<Ref>,
+    /// The top-level fields of the interactive form.
+    pub fields: Vec<Ref>,
+}
+
+impl Renumber for PageTreeRefs {
+    fn renumber(&mut self, offset: i32) {
+        self.page_tree.renumber(offset);
+        self.struct_tree.renumber(offset);
+        for field in &mut self.fields {
+            field.renumber(offset);
+        }
+    }
+}
+
 /// Write the page tree.
-pub fn write_page_tree(ctx: &WithRefs) -> SourceResult<(PdfChunk, Ref)> {
+///
+/// The structure tree and the form fields are written alongside it, since they
+/// refer to the annotations of the pages.
+pub fn write_page_tree(ctx: &WithRefs) -> SourceResult<(PdfChunk, PageTreeRefs)> {
     let mut chunk = PdfChunk::new();
     let page_tree_ref = chunk.alloc.bump();
+    let mut annotations = HashMap::new();
+    let mut fields = Fields::default();
+
+    if let Some(transition) = ctx.document.info.transition {
+        if !matches!(
+            transition,
+            PageTransition::Split
+                | PageTransition::Blinds
+                | PageTransition::Box
+                | PageTransition::Wipe
+                | PageTransition::Dissolve
+                | PageTransition::Glitter
+        ) {
+            catalog::check_version(ctx.options, "this page transition", 5)?;
+        }
+    }
 
     for i in 0..ctx.pages.len() {
         let content_id = chunk.alloc.bump();
@@ -102,8 +258,10 @@ pub fn write_page_tree(ctx: &WithRefs) -> SourceResult<(PdfChunk, Ref)> {
             content_id,
             page_tree_ref,
             &ctx.references.named_destinations.loc_to_dest,
+            &mut annotations,
+            &mut fields,
             i,
-        );
+        )?;
     }
 
     let page_kids = ctx.globals.pages.iter().filter_map(Option::as_ref).copied();
@@ -113,32 +271,60 @@ pub fn write_page_tree(ctx: &WithRefs) -> SourceResult<(PdfChunk, Ref)> {
         .count(page_kids.clone().count() as i32)
         .kids(page_kids);
 
-    Ok((chunk, page_tree_ref))
+    let struct_tree = ctx
+        .tags
+        .as_ref()
+        .map(|tags| tags::write_struct_tree(&mut chunk, ctx, tags, &annotations));
+
+    let fields = form::write_fields(&mut chunk, fields);
+
+    Ok((chunk, PageTreeRefs { page_tree: page_tree_ref, struct_tree, fields }))
 }
 
 /// Write a page tree node.
+#[allow(clippy::too_many_arguments)]
 fn write_page(
     chunk: &mut PdfChunk,
     ctx: &WithRefs,
     content_id: Ref,
     page_tree_ref: Ref,
     loc_to_dest: &HashMap<Location, Label>,
+    tagged_annotations: &mut HashMap<i32, Ref>,
+    fields: &mut Fields,
     i: usize,
-) {
+) -> SourceResult<()> {
     let Some((page, page_ref)) = ctx.pages[i].as_ref().zip(ctx.globals.pages[i]) else {
         // Page excluded from export.
-        return;
+        return Ok(());
     };
 
-    let mut annotations = Vec::with_capacity(page.content.links.len());
-    for (dest, rect) in &page.content.links {
+    let mut annotations = Vec::with_capacity(
+        page.content.links.len()
+            + page.content.widgets.len()
+            + page.content.annotations.len(),
+    );
+    for (dest, rect, tag) in &page.content.links {
         let id = chunk.alloc();
         annotations.push(id);
 
-        let mut annotation = chunk.annotation(id);
+        let mut annotation = chunk.packed().annotation(id);
         annotation.subtype(AnnotationType::Link).rect(*rect);
         annotation.border(0.0, 0.0, 0.0, None).flags(AnnotationFlags::PRINT);
 
+        if let Some(tag) = tag {
+            annotation.struct_parent(tag.key);
+            tagged_annotations.insert(tag.key, id);
+
+            let alt = match (&tag.alt, dest) {
+                (Some(alt), _) => Some(alt.as_str()),
+                (None, Destination::Url(url)) => Some(url.as_str()),
+                (None, _) => None,
+            };
+            if let Some(alt) = alt {
+                annotation.contents(TextStr::trimmed(alt));
+            }
+        }
+
         let pos = match dest {
             Destination::Url(uri) => {
                 annotation
@@ -178,7 +364,25 @@ fn write_page(
         }
     }
 
-    let mut page_writer = chunk.page(page_ref);
+    // Widgets are written in document order, which determines the tab order
+    // of untagged documents.
+    for widget in &page.content.widgets {
+        let id = form::write_widget(chunk, ctx, fields, page_ref, widget)?;
+        annotations.push(id);
+        if let Some(key) = widget.key {
+            tagged_annotations.insert(key, id);
+        }
+    }
+
+    for annotation in &page.content.annotations {
+        let id = annot::write_annotation(chunk, page_ref, annotation);
+        annotations.push(id);
+        if let Some(key) = annotation.key {
+            tagged_annotations.insert(key, id);
+        }
+    }
+
+    let mut page_writer = chunk.packed().page(page_ref);
     page_writer.parent(page_tree_ref);
 
     let w = page.content.size.x.to_f32();
@@ -187,6 +391,11 @@ fn write_page(
     page_writer.contents(content_id);
     page_writer.pair(Name(b"Resources"), ctx.resources.reference);
 
+    if let Some(key) = page.struct_parents {
+        page_writer.struct_parents(key);
+        page_writer.tab_order(TabOrder::StructureOrder);
+    }
+
     if page.content.uses_opacities {
         page_writer
             .group()
@@ -197,6 +406,26 @@ fn write_page(
             .srgb();
     }
 
+    if let Some(transition) = ctx.document.info.transition {
+        let mut writer = page_writer.transition();
+        writer.style(match transition {
+            PageTransition::Split => TransitionStyle::Split,
+            PageTransition::Blinds => TransitionStyle::Blinds,
+            PageTransition::Box => TransitionStyle::Box,
+            PageTransition::Wipe => TransitionStyle::Wipe,
+            PageTransition::Dissolve => TransitionStyle::Dissolve,
+            PageTransition::Glitter => TransitionStyle::Glitter,
+            PageTransition::Fly => TransitionStyle::Fly,
+            PageTransition::Push => TransitionStyle::Push,
+            PageTransition::Cover => TransitionStyle::Cover,
+            PageTransition::Uncover => TransitionStyle::Uncover,
+            PageTransition::Fade => TransitionStyle::Fade,
+        });
+        if let Smart::Custom(duration) = ctx.document.info.transition_duration {
+            writer.duration(duration.seconds() as f32);
+        }
+    }
+
     page_writer.annotations(annotations);
 
     page_writer.finish();
@@ -204,6 +433,8 @@ fn write_page(
     chunk
         .stream(content_id, page.content.content.wait())
         .filter(Filter::FlateDecode);
+
+    Ok(())
 }
 
 /// Specification for a PDF page label.
@@ -259,7 +490,7 @@ impl PdfPageLabel {
                 Kind::LowerRoman => Some(Style::LowerRoman),
                 Kind::UpperRoman => Some(Style::UpperRoman),
                 Kind::LowerLatin if number <= 26 => Some(Style::LowerAlpha),
-                Kind::LowerLatin if number <= 26 => Some(Style::UpperAlpha),
+                Kind::UpperLatin if number <= 26 => Some(Style::UpperAlpha),
                 _ => None,
             }
         } else {
@@ -307,4 +538,6 @@ impl PdfPageLabelStyle {
 pub struct EncodedPage {
     pub content: content::Encoded,
     pub label: Option<PdfPageLabel>,
+    /// The page's key in the parent tree, if the document is tagged.
+    pub struct_parents: Option<i32>,
 }
@@ -0,0 +1,265 @@
+//! Embedding of PDF pages as form XObjects.
+//!
+//! The page's content stream is written into a form XObject whose resources
+//! and their dependencies are copied over object by object. References are
+//! renumbered as they are encountered, so only objects that are actually
+//! reachable from the page end up in the output.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+
+use hayro_syntax::object::{self, Dict, MaybeRef, ObjRef, Object};
+use hayro_syntax::page::{Page, Resources, Rotation};
+use hayro_syntax::xref::XRef;
+use pdf_writer::{Chunk, Filter, Finish, Name, Null, Obj, Rect, Ref, Str};
+use typst_library::visualize::PdfImage;
+
+use crate::deflate;
+
+/// Keys that refer to document-level structures of the source PDF (like its
+/// structure tree or optional content) and would dangle in the output.
+const IGNORED_KEYS: &[&[u8]] = &[
+    b"AF",
+    b"LastModified",
+    b"Metadata",
+    b"OC",
+    b"OPI",
+    b"PieceInfo",
+    b"PtData",
+    b"Ref",
+    b"StructParent",
+    b"StructParents",
+];
+
+/// Encode a page of a PDF document into a chunk of PDF objects.
+///
+/// The returned reference points to a form XObject that draws the page into
+/// the unit square, just like an image XObject.
+#[typst_macros::time(name = "encode pdf")]
+pub fn encode_pdf(image: &PdfImage) -> (Chunk, Ref) {
+    let page = image.page();
+    let mut chunk = Chunk::new();
+    let mut ctx = Copier::new(page.xref());
+
+    let id = ctx.alloc.bump();
+    write_xobject(&mut chunk, &mut ctx, page, id);
+
+    // Copy everything the page refers to, including transitive dependencies.
+    let xref = ctx.xref;
+    while let Some(source) = ctx.queue.pop() {
+        if !ctx.visited.insert(source) {
+            continue;
+        }
+
+        let id = ctx.map(source);
+        match xref.get::<Object>(source.into()) {
+            Some(Object::Stream(stream)) => {
+                let data = stream.raw_data();
+                let mut writer = chunk.stream(id, &data);
+                write_dict(stream.dict(), writer.deref_mut(), &mut ctx, true);
+            }
+            Some(object) => write_object(&object, chunk.indirect(id), &mut ctx),
+            None => {
+                chunk.indirect(id).primitive(Null);
+            }
+        }
+    }
+
+    (chunk, id)
+}
+
+/// Tracks which objects of the source PDF were copied and how they were
+/// renumbered.
+struct Copier<'a> {
+    /// The cross-reference table of the source PDF.
+    xref: &'a XRef,
+    /// Allocates references in the output chunk.
+    alloc: Ref,
+    /// Maps from references in the source PDF to those in the output.
+    refs: HashMap<ObjRef, Ref>,
+    /// Whether a reference in the source PDF points to an existing object.
+    valid: HashMap<ObjRef, bool>,
+    /// References that still need to be copied.
+    queue: Vec<ObjRef>,
+    /// References that were already copied.
+    visited: HashSet<ObjRef>,
+}
+
+impl<'a> Copier<'a> {
+    /// Create a new copier for a PDF.
+    fn new(xref: &'a XRef) -> Self {
+        Self {
+            xref,
+            alloc: Ref::new(1),
+            refs: HashMap::new(),
+            valid: HashMap::new(),
+            queue: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// The output reference for a reference in the source PDF.
+    fn map(&mut self, source: ObjRef) -> Ref {
+        *self.refs.entry(source).or_insert_with(|| self.alloc.bump())
+    }
+}
+
+/// Selects the dictionary of one resource category.
+type Category<'a> = for<'r> fn(&'r Resources<'a>) -> &'r Dict<'a>;
+
+/// Write the page's content stream as a form XObject.
+fn write_xobject<'a>(chunk: &mut Chunk, ctx: &mut Copier, page: &Page<'a>, id: Ref) {
+    let content = deflate(page.page_stream().unwrap_or_default());
+    let mut xobject = chunk.form_xobject(id, &content);
+    xobject.filter(Filter::FlateDecode);
+
+    let crop = page.intersected_crop_box();
+    xobject.bbox(Rect::new(
+        crop.x0 as f32,
+        crop.y0 as f32,
+        crop.x1 as f32,
+        crop.y1 as f32,
+    ));
+    xobject.matrix(unit_transform(page));
+
+    if let Some(group) = page.raw().get_raw::<Object>(b"Group".as_slice()) {
+        write_maybe_ref(&group, xobject.insert(Name(b"Group")), ctx);
+    }
+
+    // Resources may be inherited from the page tree, so they are merged into
+    // a single dictionary.
+    let resources = page.resources();
+    let categories: [(&[u8], Category<'a>); 7] = [
+        (b"ExtGState", |r| &r.ext_g_states),
+        (b"ColorSpace", |r| &r.color_spaces),
+        (b"Pattern", |r| &r.patterns),
+        (b"Shading", |r| &r.shadings),
+        (b"XObject", |r| &r.x_objects),
+        (b"Font", |r| &r.fonts),
+        (b"Properties", |r| &r.properties),
+    ];
+
+    let mut writer = xobject.resources();
+    for (key, get) in categories {
+        let mut entries = BTreeMap::new();
+        collect_resources(resources, get, &mut entries);
+        if entries.is_empty() {
+            continue;
+        }
+
+        let mut dict = writer.insert(Name(key)).dict();
+        for (name, value) in entries {
+            write_maybe_ref(&value, dict.insert(Name(name.deref())), ctx);
+        }
+    }
+    writer.finish();
+    xobject.finish();
+}
+
+/// The transform that maps the page's crop box to the unit square, taking
+/// the page's rotation into account.
+fn unit_transform(page: &Page) -> [f32; 6] {
+    let crop = page.intersected_crop_box();
+    let (x0, y0) = (crop.x0 as f32, crop.y0 as f32);
+    let (w, h) = page.render_dimensions();
+
+    // Rotation is clockwise, as the page is shown.
+    let [a, b, c, d, e, f] = match page.rotation() {
+        Rotation::None => [1.0, 0.0, 0.0, 1.0, -x0, -y0],
+        Rotation::Horizontal => [0.0, -1.0, 1.0, 0.0, -y0, h + x0],
+        Rotation::Flipped => [-1.0, 0.0, 0.0, -1.0, w + x0, h + y0],
+        Rotation::FlippedHorizontal => [0.0, 1.0, -1.0, 0.0, w + y0, -x0],
+    };
+
+    [a / w, b / h, c / w, d / h, e / w, f / h]
+}
+
+/// Collect the resources of one category, with entries closer to the page
+/// taking precedence over inherited ones.
+fn collect_resources<'a>(
+    resources: &Resources<'a>,
+    get: Category<'a>,
+    entries: &mut BTreeMap<object::Name<'a>, MaybeRef<Object<'a>>>,
+) {
+    if let Some(parent) = resources.parent() {
+        collect_resources(parent, get, entries);
+    }
+
+    entries.extend(get(resources).entries());
+}
+
+/// Write a direct object or a reference to a copied object.
+fn write_maybe_ref(value: &MaybeRef<Object>, obj: Obj, ctx: &mut Copier) {
+    match value {
+        MaybeRef::Ref(source) => write_ref(*source, obj, ctx),
+        MaybeRef::NotRef(object) => write_object(object, obj, ctx),
+    }
+}
+
+/// Write a reference, queueing the referenced object for copying.
+///
+/// References to objects that don't exist become null, as they would
+/// otherwise dangle in the output.
+fn write_ref(source: ObjRef, obj: Obj, ctx: &mut Copier) {
+    let xref = ctx.xref;
+    let valid = *ctx
+        .valid
+        .entry(source)
+        .or_insert_with(|| xref.get::<Object>(source.into()).is_some());
+
+    if valid {
+        ctx.queue.push(source);
+        obj.primitive(ctx.map(source));
+    } else {
+        obj.primitive(Null);
+    }
+}
+
+/// Write a direct object.
+fn write_object(object: &Object, obj: Obj, ctx: &mut Copier) {
+    match object {
+        Object::Null(_) => obj.primitive(Null),
+        Object::Boolean(b) => obj.primitive(*b),
+        Object::Number(number) => {
+            let value = number.as_f64();
+            if value.fract() == 0.0 && value.abs() <= i32::MAX as f64 {
+                obj.primitive(value as i32);
+            } else {
+                obj.primitive(value as f32);
+            }
+        }
+        Object::String(string) => obj.primitive(Str(&string.get())),
+        Object::Name(name) => obj.primitive(Name(name.deref())),
+        Object::Dict(dict) => {
+            let mut writer = obj.dict();
+            write_dict(dict, &mut writer, ctx, false);
+        }
+        Object::Array(array) => {
+            let mut writer = obj.array();
+            for item in array.raw_iter() {
+                write_maybe_ref(&item, writer.push(), ctx);
+            }
+        }
+        // Streams can only appear as indirect objects.
+        Object::Stream(_) => obj.primitive(Null),
+    }
+}
+
+/// Write the entries of a dictionary.
+fn write_dict(
+    dict: &Dict,
+    writer: &mut pdf_writer::Dict,
+    ctx: &mut Copier,
+    is_stream: bool,
+) {
+    for (name, value) in dict.entries() {
+        // The length of a stream is written by pdf-writer.
+        if IGNORED_KEYS.contains(&name.deref())
+            || (is_stream && name.deref() == b"Length")
+        {
+            continue;
+        }
+
+        write_maybe_ref(&value, writer.insert(Name(name.deref())), ctx);
+    }
+}
@@ -1,6 +1,6 @@
 //! PDF resources.
 //!
-//! Resources are defined in dictionaries. They map identifiers such as `Im0` to
+//! Resources are defined in dictionaries. They map identifiers such as `Im5e1b3f9c` to
 //! a PDF reference. Each [content stream] is associated with a resource dictionary.
 //! The identifiers defined in the resources can then be used in content streams.
 //!
@@ -63,7 +63,7 @@ pub struct Resources<R = Ref> {
     /// Deduplicates images used across the document.
     pub images: Remapper<Image>,
     /// Handles to deferred image conversions.
-    pub deferred_images: HashMap<usize, (Deferred<StrResult<EncodedImage>>, Span)>,
+    pub deferred_images: HashMap<Image, (Deferred<StrResult<EncodedImage>>, Span)>,
     /// Deduplicates gradients used across the document.
     pub gradients: Remapper<PdfGradient>,
     /// Deduplicates tilings used across the document.
@@ -161,12 +161,47 @@ impl Resources<()> {
     }
 }
 
+impl Resources<()> {
+    /// Add the resources of a page that was encoded on its own.
+    ///
+    /// Such pages use neither tilings nor color fonts, and the CIDs of their
+    /// glyphs were fixed in these resources beforehand. Hence, only the
+    /// remaining resources need to be merged.
+    pub fn merge(&mut self, other: &Resources<()>) {
+        self.colors.merge(&other.colors);
+        for font in other.fonts.items() {
+            self.fonts.insert(font.clone());
+        }
+        for image in other.images.items() {
+            self.images.insert(image.clone());
+            self.deferred_images
+                .entry(image.clone())
+                .or_insert_with(|| other.deferred_images[image].clone());
+        }
+        for gradient in other.gradients.items() {
+            self.gradients.insert(gradient.clone());
+        }
+        for ext_gs in other.ext_gs.items() {
+            self.ext_gs.insert(*ext_gs);
+        }
+        for (&lang, count) in &other.languages {
+            *self.languages.entry(lang).or_insert(0) += count;
+        }
+        for (font, glyphs) in &other.glyph_sets {
+            let glyph_set = self.glyph_sets.entry(font.clone()).or_default();
+            for (&glyph, text) in glyphs {
+                glyph_set.entry(glyph).or_insert_with(|| text.clone());
+            }
+        }
+    }
+}
+
 impl<R> Resources<R> {
     /// Run a function on this resource dictionary and all
     /// of its sub-resources.
-    pub fn traverse<P>(&self, process: &mut P) -> SourceResult<()>
+    pub fn traverse<'a, P>(&'a self, process: &mut P) -> SourceResult<()>
     where
-        P: FnMut(&Self) -> SourceResult<()>,
+        P: FnMut(&'a Self) -> SourceResult<()>,
     {
         process(self)?;
         if let Some(color_fonts) = &self.color_fonts {
@@ -244,20 +279,6 @@ pub fn write_resource_dictionaries(ctx: &WithEverything) -> SourceResult<(PdfChu
         let ext_gs_states_ref = chunk.alloc.bump();
         let color_spaces_ref = chunk.alloc.bump();
 
-        let mut color_font_slices = Vec::new();
-        let mut color_font_numbers = HashMap::new();
-        if let Some(color_fonts) = &resources.color_fonts {
-            for (_, font_slice) in color_fonts.iter() {
-                color_font_numbers.insert(font_slice.clone(), color_font_slices.len());
-                color_font_slices.push(font_slice);
-            }
-        }
-        let color_font_remapper = Remapper {
-            prefix: "Cf",
-            to_pdf: color_font_numbers,
-            to_items: color_font_slices,
-        };
-
         resources
             .images
             .write(&ctx.references.images, &mut chunk.indirect(images_ref).dict());
@@ -286,7 +307,13 @@ pub fn write_resource_dictionaries(ctx: &WithEverything) -> SourceResult<(PdfChu
         // TODO: can't this be an indirect reference too?
         let mut fonts_dict = res_dict.fonts();
         resources.fonts.write(&ctx.references.fonts, &mut fonts_dict);
-        color_font_remapper.write(&ctx.references.color_fonts, &mut fonts_dict);
+        if let Some(color_fonts) = &resources.color_fonts {
+            for (number, (_, font_slice)) in color_fonts.iter().enumerate() {
+                let name = eco_format!("Cf{number}");
+                fonts_dict
+                    .pair(Name(name.as_bytes()), ctx.references.color_fonts[&font_slice]);
+            }
+        }
         fonts_dict.finish();
 
         res_dict.finish();
@@ -299,18 +326,26 @@ pub fn write_resource_dictionaries(ctx: &WithEverything) -> SourceResult<(PdfChu
         Ok(())
     })?;
 
-    used_color_spaces.write_functions(&mut chunk, &ctx.globals.color_functions);
+    used_color_spaces.write_functions(
+        &mut chunk,
+        &ctx.globals.color_functions,
+        ctx.options,
+    );
 
     Ok((chunk, ()))
 }
 
-/// Assigns new, consecutive PDF-internal indices to items.
+/// Assigns PDF-internal names to items.
+///
+/// The names are derived from the items' hashes rather than from the order in
+/// which they were inserted. This way, content streams that were encoded with
+/// different remappers agree on the names of shared resources.
 pub struct Remapper<T> {
     /// The prefix to use when naming these resources.
     prefix: &'static str,
-    /// Forwards from the items to the pdf indices.
-    to_pdf: HashMap<T, usize>,
-    /// Backwards from the pdf indices to the items.
+    /// Forwards from the items to their names.
+    to_pdf: HashMap<T, EcoString>,
+    /// The items in order of their insertion.
     to_items: Vec<T>,
 }
 
@@ -323,27 +358,30 @@ where
         Self { prefix, to_pdf: HashMap::new(), to_items: vec![] }
     }
 
-    /// Insert an item in the mapping if it was not already present.
-    pub fn insert(&mut self, item: T) -> usize {
-        let to_layout = &mut self.to_items;
-        *self.to_pdf.entry(item.clone()).or_insert_with(|| {
-            let pdf_index = to_layout.len();
-            to_layout.push(item);
-            pdf_index
-        })
+    /// Insert an item in the mapping if it was not already present and return
+    /// its name.
+    pub fn insert(&mut self, item: T) -> EcoString {
+        if let Some(name) = self.to_pdf.get(&item) {
+            return name.clone();
+        }
+
+        // The full hash keeps the chance of two items sharing a name negligible.
+        let name = eco_format!("{}{:032x}", self.prefix, typst_utils::hash128(&item));
+        self.to_items.push(item.clone());
+        self.to_pdf.insert(item, name.clone());
+        name
     }
 
-    /// All items in this
+    /// All items in this mapping, in order of their insertion.
     pub fn items(&self) -> impl Iterator<Item = &T> + '_ {
         self.to_items.iter()
     }
 
     /// Write this list of items in a Resource dictionary.
     fn write(&self, mapping: &HashMap<T, Ref>, dict: &mut Dict) {
-        for (number, item) in self.items().enumerate() {
-            let name = eco_format!("{}{}", self.prefix, number);
-            let reference = mapping[item];
-            dict.pair(Name(name.as_bytes()), reference);
+        for item in self.items() {
+            let name = &self.to_pdf[item];
+            dict.pair(Name(name.as_bytes()), mapping[item]);
         }
     }
 }
@@ -0,0 +1,732 @@
+//! Tagged PDF: Building and writing the structure tree.
+//!
+//! The structure tree is built from the introspection tags in the frames before
+//! the page contents are encoded. Text and images are wrapped in marked-content
+//! sequences that are referenced by the structure elements. Everything that is
+//! not part of the document's logical content is marked as an artifact.
+//!
+//! Building the tree records a [`Mark`] for each item of a page that needs one.
+//! The page is then encoded by replaying these marks, so that pages can still
+//! be encoded in parallel and cached.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroUsize;
+
+use ecow::{eco_format, EcoString};
+use pdf_writer::types::{
+    ArtifactSubtype, ArtifactType, ListNumbering, StructRole, TableHeaderScope,
+};
+use pdf_writer::writers::StructTreeRoot;
+use pdf_writer::{Content, Finish, Name, Ref, TextStr};
+use typst_library::diag::{bail, SourceResult};
+use typst_library::foundations::{Content as TypstContent, StyleChain};
+use typst_library::introspection::{Location, Tag};
+use typst_library::layout::{Frame, FrameItem, Page, PagedDocument};
+use typst_library::math::EquationElem;
+use typst_library::model::{
+    BibliographyElem, EnumElem, EnumItem, FigureCaption, FigureElem, FootnoteElem,
+    FootnoteEntry, HeadingElem, LinkElem, ListElem, ListItem, OutlineElem, OutlineEntry,
+    ParElem, QuoteElem, TableCell, TableChild, TableElem, TableItem,
+};
+use typst_library::pdf::{ArtifactElem, ArtifactKind};
+use typst_syntax::Span;
+
+use crate::{PdfChunk, PdfOptions, TextStrExt, WithRefs};
+
+/// The structure tree of a tagged PDF, as it is being built.
+pub struct Tags {
+    /// All structure elements. The first one is the document root.
+    nodes: Vec<StructNode>,
+    /// The currently open structure elements and artifacts.
+    stack: Vec<(Location, Open)>,
+    /// Locations of elements that were already closed. If an element starts
+    /// again (e.g. a repeated table header), its content is an artifact.
+    closed: HashSet<Location>,
+    /// The values of the parent tree, indexed by their key.
+    parent_tree: Vec<ParentTreeEntry>,
+    /// The index of the page that is currently encoded and its parent tree
+    /// key.
+    page: Option<(usize, i32)>,
+}
+
+/// Something that was opened by a start tag.
+enum Open {
+    /// A structure element.
+    Node(usize),
+    /// An artifact.
+    Artifact(ArtifactKind),
+}
+
+/// A structure element.
+struct StructNode {
+    /// The role of the element.
+    kind: StructKind,
+    /// The index of the parent element.
+    parent: usize,
+    /// The element's children in reading order.
+    children: Vec<StructChild>,
+}
+
+/// A child of a structure element.
+enum StructChild {
+    /// Another structure element.
+    Node(usize),
+    /// A marked-content sequence on the page with the given index.
+    Content { page: usize, mcid: i32 },
+    /// An annotation on the page with the given index, identified by its key
+    /// in the parent tree.
+    Annotation { page: usize, key: i32 },
+}
+
+/// A value in the parent tree.
+enum ParentTreeEntry {
+    /// Maps the MCIDs of a page to structure elements.
+    Page(Vec<usize>),
+    /// The structure element an annotation belongs to.
+    Annotation(usize),
+}
+
+/// The role of a structure element.
+#[derive(Debug, Clone, PartialEq)]
+enum StructKind {
+    Document,
+    Heading(NonZeroUsize),
+    Paragraph,
+    List(ListNumbering),
+    ListItem,
+    ListBody,
+    Table { header: HashSet<Span> },
+    TableRow,
+    TableCell { header: bool, row: usize, rowspan: NonZeroUsize, colspan: NonZeroUsize },
+    Figure,
+    Caption,
+    Image(Option<EcoString>),
+    Formula(Option<EcoString>),
+    Link(Option<EcoString>),
+    Form(Option<EcoString>),
+    Annot(Option<EcoString>),
+    Footnote,
+    FootnoteRef,
+    Quote { block: bool },
+    Outline,
+    OutlineEntry,
+    Bibliography,
+}
+
+/// The tagging decision for an item of a page, in the order in which the
+/// items are encoded.
+#[derive(Clone, Hash)]
+pub enum Mark {
+    /// How to mark a text run, shape, or image.
+    Content(Marked),
+    /// The tagging information for a link annotation.
+    Link(Option<LinkTag>),
+    /// The parent tree key of a form field widget or another annotation.
+    Annotation(Option<i32>),
+}
+
+/// How the content of a leaf item should be marked.
+#[derive(Clone, Hash)]
+pub enum Marked {
+    /// As belonging to a structure element. The tag of the marked-content
+    /// sequence is purely informational, the structure element determines
+    /// the semantics.
+    Content { tag: Name<'static>, mcid: i32 },
+    /// As an artifact.
+    Artifact(ArtifactKind),
+}
+
+/// Tagging information for a link annotation.
+#[derive(Clone, Hash)]
+pub struct LinkTag {
+    /// The annotation's key in the parent tree.
+    pub key: i32,
+    /// A textual description of the link.
+    pub alt: Option<EcoString>,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            nodes: vec![StructNode {
+                kind: StructKind::Document,
+                parent: 0,
+                children: vec![],
+            }],
+            stack: vec![],
+            closed: HashSet::new(),
+            parent_tree: vec![],
+            page: None,
+        }
+    }
+}
+
+impl Tags {
+    /// Extend the structure tree with the page with the given index.
+    ///
+    /// Returns the key of the page in the parent tree and the marks with which
+    /// the page's items must be encoded.
+    pub fn record(
+        &mut self,
+        options: &PdfOptions,
+        index: usize,
+        page: &Page,
+    ) -> SourceResult<(i32, Vec<Mark>)> {
+        let key = self.parent_tree.len() as i32;
+        self.parent_tree.push(ParentTreeEntry::Page(vec![]));
+        self.page = Some((index, key));
+
+        let mut marks = vec![];
+        if page.fill_or_transparent().is_some() {
+            marks.push(Mark::Content(self.mark_decoration()));
+        }
+        self.record_frame(options, &page.frame, &mut marks, &mut HashSet::new())?;
+        Ok((key, marks))
+    }
+
+    /// Record the marks for the items of a frame in the order in which
+    /// [`write_frame`](crate::content::write_frame) encodes them.
+    fn record_frame(
+        &mut self,
+        options: &PdfOptions,
+        frame: &Frame,
+        marks: &mut Vec<Mark>,
+        annotations: &mut HashSet<Location>,
+    ) -> SourceResult<()> {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    self.record_frame(options, &group.frame, marks, annotations)?
+                }
+                FrameItem::Text(_) => marks.push(Mark::Content(self.mark_text())),
+                FrameItem::Shape(..) => marks.push(Mark::Content(self.mark_decoration())),
+                FrameItem::Image(image, ..) => {
+                    marks.push(Mark::Content(self.mark_image(image.alt())))
+                }
+                FrameItem::Link(..) => marks.push(Mark::Link(self.link())),
+                FrameItem::Widget(widget, _) => {
+                    let key = self.widget(widget.field.tooltip.as_deref());
+                    marks.push(Mark::Annotation(key));
+                }
+                FrameItem::Annotation(annot, _) => {
+                    // Fragments of a split annotation are merged into the
+                    // first one.
+                    if annotations.insert(annot.location) {
+                        let key = self.annotation(annot.contents.as_deref());
+                        marks.push(Mark::Annotation(key));
+                    }
+                }
+                FrameItem::Tag(tag) => self.handle(options, tag)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Process an introspection tag.
+    fn handle(&mut self, options: &PdfOptions, tag: &Tag) -> SourceResult<()> {
+        match tag {
+            Tag::Start(elem) => self.start(options, elem),
+            Tag::End(loc, _) => {
+                if let Some(i) = self.stack.iter().rposition(|(l, _)| l == loc) {
+                    self.stack.truncate(i);
+                    self.closed.insert(*loc);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Process the start tag of an element.
+    fn start(&mut self, options: &PdfOptions, elem: &TypstContent) -> SourceResult<()> {
+        let loc = elem.location().unwrap();
+        if self.closed.contains(&loc) || self.stack.iter().any(|(l, _)| *l == loc) {
+            // The element was laid out repeatedly, for example as part of a
+            // repeated table header.
+            self.stack.push((loc, Open::Artifact(ArtifactKind::Other)));
+            return Ok(());
+        }
+
+        if let Some(artifact) = elem.to_packed::<ArtifactElem>() {
+            let kind = artifact.kind(StyleChain::default());
+            self.stack.push((loc, Open::Artifact(kind)));
+            return Ok(());
+        }
+
+        // Nothing within artifacts is tagged.
+        let Some(parent) = self.current() else { return Ok(()) };
+        let Some(kind) = self.kind_of(options, parent, elem)? else { return Ok(()) };
+
+        let mut node = self.push(parent, kind);
+        if self.nodes[node].kind == StructKind::ListItem {
+            // The list item's marker and body both go into its body.
+            node = self.push(node, StructKind::ListBody);
+        }
+
+        self.stack.push((loc, Open::Node(node)));
+        Ok(())
+    }
+
+    /// Determine the structure element kind of a Typst element.
+    ///
+    /// Returns `None` for elements that do not map to a structure element.
+    /// Their content is added to the surrounding structure element instead.
+    fn kind_of(
+        &self,
+        options: &PdfOptions,
+        parent: usize,
+        elem: &TypstContent,
+    ) -> SourceResult<Option<StructKind>> {
+        let styles = StyleChain::default();
+        Ok(Some(if let Some(heading) = elem.to_packed::<HeadingElem>() {
+            StructKind::Heading(heading.resolve_level(styles))
+        } else if elem.is::<ParElem>() {
+            StructKind::Paragraph
+        } else if elem.is::<ListElem>() {
+            StructKind::List(ListNumbering::Disc)
+        } else if elem.is::<EnumElem>() {
+            StructKind::List(ListNumbering::Decimal)
+        } else if elem.is::<ListItem>() || elem.is::<EnumItem>() {
+            StructKind::ListItem
+        } else if let Some(table) = elem.to_packed::<TableElem>() {
+            let header = table
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    TableChild::Header(header) => Some(header),
+                    _ => None,
+                })
+                .flat_map(|header| header.children.iter())
+                .filter_map(|item| match item {
+                    TableItem::Cell(cell) => Some(cell.span()),
+                    _ => None,
+                })
+                .filter(|span| !span.is_detached())
+                .collect();
+            StructKind::Table { header }
+        } else if let Some(cell) = elem.to_packed::<TableCell>() {
+            let header = match &self.nodes[parent].kind {
+                StructKind::Table { header } => header.contains(&cell.span()),
+                _ => false,
+            };
+            StructKind::TableCell {
+                header,
+                row: cell.y(styles).custom().unwrap_or(0),
+                rowspan: cell.rowspan(styles),
+                colspan: cell.colspan(styles),
+            }
+        } else if elem.is::<FigureElem>() {
+            StructKind::Figure
+        } else if elem.is::<FigureCaption>() {
+            StructKind::Caption
+        } else if let Some(equation) = elem.to_packed::<EquationElem>() {
+            let alt = equation.alt(styles).clone();
+            if options.standards.ua && alt.is_none() {
+                bail!(
+                    equation.span(),
+                    "PDF/UA-1 requires equations to have alt text";
+                    hint: "use the `alt` parameter of `math.equation` to describe it"
+                );
+            }
+            StructKind::Formula(alt)
+        } else if let Some(link) = elem.to_packed::<LinkElem>() {
+            let text = link.body.plain_text();
+            StructKind::Link((!text.trim().is_empty()).then(|| text.trim().into()))
+        } else if elem.is::<FootnoteElem>() {
+            StructKind::FootnoteRef
+        } else if elem.is::<FootnoteEntry>() {
+            StructKind::Footnote
+        } else if let Some(quote) = elem.to_packed::<QuoteElem>() {
+            StructKind::Quote { block: quote.block(styles) }
+        } else if elem.is::<OutlineElem>() {
+            StructKind::Outline
+        } else if elem.is::<OutlineEntry>() {
+            StructKind::OutlineEntry
+        } else if elem.is::<BibliographyElem>() {
+            StructKind::Bibliography
+        } else {
+            return Ok(None);
+        }))
+    }
+
+    /// Add a new structure element as the last child of `parent`.
+    fn push(&mut self, parent: usize, kind: StructKind) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(StructNode { kind, parent, children: vec![] });
+        self.nodes[parent].children.push(StructChild::Node(id));
+        id
+    }
+
+    /// The structure element that content currently belongs to, or `None` if
+    /// the content is an artifact.
+    fn current(&self) -> Option<usize> {
+        match self.stack.last() {
+            Some((_, Open::Node(id))) => Some(*id),
+            Some((_, Open::Artifact(_))) => None,
+            None => Some(0),
+        }
+    }
+
+    /// The kind of artifact the current content is in, if any.
+    fn artifact(&self) -> Option<ArtifactKind> {
+        match self.stack.last() {
+            Some((_, Open::Artifact(kind))) => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Allocate a marked-content identifier on the current page for content
+    /// belonging to the given structure element.
+    fn mark(&mut self, node: usize, tag: Name<'static>) -> Marked {
+        let (page, key) = self.page.expect("no page was started");
+        let ParentTreeEntry::Page(mcids) = &mut self.parent_tree[key as usize] else {
+            unreachable!()
+        };
+        let mcid = mcids.len() as i32;
+        mcids.push(node);
+        self.nodes[node].children.push(StructChild::Content { page, mcid });
+        Marked::Content { tag, mcid }
+    }
+
+    /// Determine how to mark text content.
+    fn mark_text(&mut self) -> Marked {
+        match self.current() {
+            Some(node) => self.mark(node, Name(b"Span")),
+            None => Marked::Artifact(self.artifact().unwrap()),
+        }
+    }
+
+    /// Determine how to mark decorative content, like shapes.
+    fn mark_decoration(&mut self) -> Marked {
+        Marked::Artifact(self.artifact().unwrap_or(ArtifactKind::Other))
+    }
+
+    /// Determine how to mark an image with the given alternative description.
+    fn mark_image(&mut self, alt: Option<&str>) -> Marked {
+        let Some(parent) = self.current() else {
+            return Marked::Artifact(self.artifact().unwrap());
+        };
+        let node = self.push(parent, StructKind::Image(alt.map(Into::into)));
+        self.mark(node, Name(b"Figure"))
+    }
+
+    /// Register a link annotation on the current page.
+    ///
+    /// Returns `None` if the link is within an artifact.
+    fn link(&mut self) -> Option<LinkTag> {
+        let (page, _) = self.page.expect("no page was started");
+        let mut node = self.current()?;
+        if !matches!(self.nodes[node].kind, StructKind::Link(_)) {
+            node = self.push(node, StructKind::Link(None));
+        }
+
+        let key = self.parent_tree.len() as i32;
+        self.parent_tree.push(ParentTreeEntry::Annotation(node));
+        self.nodes[node].children.push(StructChild::Annotation { page, key });
+
+        let StructKind::Link(alt) = &self.nodes[node].kind else { unreachable!() };
+        Some(LinkTag { key, alt: alt.clone() })
+    }
+
+    /// Register the widget annotation of a form field on the current page.
+    ///
+    /// Returns the annotation's key in the parent tree or `None` if the field
+    /// is within an artifact.
+    fn widget(&mut self, alt: Option<&str>) -> Option<i32> {
+        let (page, _) = self.page.expect("no page was started");
+        let parent = self.current()?;
+        let node = self.push(parent, StructKind::Form(alt.map(Into::into)));
+
+        let key = self.parent_tree.len() as i32;
+        self.parent_tree.push(ParentTreeEntry::Annotation(node));
+        self.nodes[node].children.push(StructChild::Annotation { page, key });
+        Some(key)
+    }
+
+    /// Register an annotation on the current page.
+    ///
+    /// Returns the annotation's key in the parent tree or `None` if the
+    /// annotation is within an artifact.
+    fn annotation(&mut self, alt: Option<&str>) -> Option<i32> {
+        let (page, _) = self.page.expect("no page was started");
+        let parent = self.current()?;
+        let node = self.push(parent, StructKind::Annot(alt.map(Into::into)));
+
+        let key = self.parent_tree.len() as i32;
+        self.parent_tree.push(ParentTreeEntry::Annotation(node));
+        self.nodes[node].children.push(StructChild::Annotation { page, key });
+        Some(key)
+    }
+
+    /// Finish building the structure tree once all pages were recorded.
+    ///
+    /// This groups the cells of each table into rows. They can't be grouped
+    /// while building the tree because cells spanning multiple rows may be
+    /// laid out after cells of later rows.
+    pub fn finish(&mut self) {
+        for table in 0..self.nodes.len() {
+            if !matches!(self.nodes[table].kind, StructKind::Table { .. }) {
+                continue;
+            }
+
+            let mut rows: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            let mut rest = vec![];
+            for child in std::mem::take(&mut self.nodes[table].children) {
+                match child {
+                    StructChild::Node(id) => match self.nodes[id].kind {
+                        StructKind::TableCell { row, .. } => {
+                            rows.entry(row).or_default().push(id)
+                        }
+                        _ => rest.push(child),
+                    },
+                    _ => rest.push(child),
+                }
+            }
+
+            for cells in rows.into_values() {
+                let row = self.push(table, StructKind::TableRow);
+                for cell in cells {
+                    self.nodes[cell].parent = row;
+                    self.nodes[row].children.push(StructChild::Node(cell));
+                }
+            }
+
+            self.nodes[table].children.extend(rest);
+        }
+    }
+}
+
+impl StructKind {
+    /// The name of the structure type. Might be a custom type that is mapped
+    /// to a standard one in the role map.
+    fn name(&self) -> Result<StructRole, EcoString> {
+        Ok(match self {
+            Self::Document => StructRole::Document,
+            Self::Heading(level) => match level.get() {
+                1 => StructRole::H1,
+                2 => StructRole::H2,
+                3 => StructRole::H3,
+                4 => StructRole::H4,
+                5 => StructRole::H5,
+                6 => StructRole::H6,
+                n => return Err(eco_format!("H{n}")),
+            },
+            Self::Paragraph => StructRole::P,
+            Self::List(_) => StructRole::L,
+            Self::ListItem => StructRole::LI,
+            Self::ListBody => StructRole::LBody,
+            Self::Table { .. } => StructRole::Table,
+            Self::TableRow => StructRole::TR,
+            Self::TableCell { header: true, .. } => StructRole::TH,
+            Self::TableCell { header: false, .. } => StructRole::TD,
+            Self::Figure => StructRole::Div,
+            Self::Caption => StructRole::Caption,
+            Self::Image(_) => StructRole::Figure,
+            Self::Formula(_) => StructRole::Formula,
+            Self::Link(_) => StructRole::Link,
+            Self::Form(_) => StructRole::Form,
+            Self::Annot(_) => StructRole::Annot,
+            Self::Footnote => return Err("Footnote".into()),
+            Self::FootnoteRef => return Err("FootnoteRef".into()),
+            Self::Quote { block: true } => StructRole::BlockQuote,
+            Self::Quote { block: false } => StructRole::Quote,
+            Self::Outline => StructRole::TOC,
+            Self::OutlineEntry => StructRole::TOCI,
+            Self::Bibliography => StructRole::Div,
+        })
+    }
+
+    /// The standard structure type that this kind is or is mapped to.
+    fn standard_role(&self) -> StructRole {
+        match self.name() {
+            Ok(role) => role,
+            Err(_) => match self {
+                Self::Heading(_) => StructRole::H6,
+                Self::Footnote => StructRole::Note,
+                Self::FootnoteRef => StructRole::Reference,
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+/// Write the marked-content operator for a leaf item.
+pub fn begin_marked(content: &mut Content, marked: &Marked) {
+    match marked {
+        Marked::Content { tag, mcid } => {
+            content
+                .begin_marked_content_with_properties(*tag)
+                .properties()
+                .identify(*mcid);
+        }
+        Marked::Artifact(kind) => {
+            let mut op = content.begin_marked_content_with_properties(Name(b"Artifact"));
+            let mut artifact = op.properties().artifact();
+            match kind {
+                ArtifactKind::Header => {
+                    artifact.kind(ArtifactType::Pagination);
+                    artifact.subtype(ArtifactSubtype::Header);
+                }
+                ArtifactKind::Footer => {
+                    artifact.kind(ArtifactType::Pagination);
+                    artifact.subtype(ArtifactSubtype::Footer);
+                }
+                ArtifactKind::Page => {
+                    artifact.kind(ArtifactType::Pagination);
+                }
+                ArtifactKind::Other => {
+                    artifact.kind(ArtifactType::Layout);
+                }
+            }
+        }
+    }
+}
+
+/// Ensure that the document can be exported as a tagged PDF.
+pub fn check(options: &PdfOptions, document: &PagedDocument) -> SourceResult<()> {
+    if !document.tagged {
+        let standard = match options.standards.pdfa_part {
+            Some((part, level)) if !options.standards.ua => {
+                eco_format!("PDF/A-{part}{level}")
+            }
+            _ => "PDF/UA-1".into(),
+        };
+        bail!(
+            Span::detached(),
+            "{standard} requires the document to be compiled for tagged PDF export";
+            hint: "enable the `pdf-tags` feature"
+        );
+    }
+
+    if options.standards.ua && document.info.title.is_none() {
+        bail!(
+            Span::detached(),
+            "PDF/UA-1 requires a document title";
+            hint: "set the title with `set document(title: ..)`"
+        );
+    }
+    Ok(())
+}
+
+/// Write the structure tree and its parent tree.
+///
+/// `annotations` maps the parent tree keys of link and widget annotations to
+/// their references. Returns the reference of the structure tree root.
+pub fn write_struct_tree(
+    chunk: &mut PdfChunk,
+    ctx: &WithRefs,
+    tags: &Tags,
+    annotations: &HashMap<i32, Ref>,
+) -> Ref {
+    let root_ref = chunk.alloc();
+    let refs: Vec<Ref> = tags.nodes.iter().map(|_| chunk.alloc()).collect();
+    let page_ref = |page: usize| ctx.globals.pages[page];
+
+    let mut custom_roles = vec![];
+    for (i, node) in tags.nodes.iter().enumerate() {
+        let mut elem = chunk.packed().struct_element(refs[i]);
+        match node.kind.name() {
+            Ok(role) => {
+                elem.kind(role);
+            }
+            Err(name) => {
+                elem.custom_kind(Name(name.as_bytes()));
+                custom_roles.push((name, node.kind.standard_role()));
+            }
+        }
+
+        elem.parent(if i == 0 { root_ref } else { refs[node.parent] });
+
+        match &node.kind {
+            StructKind::Image(Some(alt))
+            | StructKind::Formula(Some(alt))
+            | StructKind::Form(Some(alt))
+            | StructKind::Annot(Some(alt)) => {
+                elem.alt(TextStr::trimmed(alt));
+            }
+            _ => {}
+        }
+
+        match &node.kind {
+            StructKind::List(numbering) => {
+                elem.attributes().push().list().list_numbering(*numbering);
+            }
+            StructKind::TableCell { header, rowspan, colspan, .. } => {
+                let mut attributes = elem.attributes();
+                let mut table = attributes.push().table();
+                if rowspan.get() > 1 {
+                    table.row_span(rowspan.get() as i32);
+                }
+                if colspan.get() > 1 {
+                    table.col_span(colspan.get() as i32);
+                }
+                if *header {
+                    table.scope(TableHeaderScope::Column);
+                }
+            }
+            _ => {}
+        }
+
+        let mut children = elem.children();
+        for child in &node.children {
+            match *child {
+                StructChild::Node(id) => {
+                    children.struct_element(refs[id]);
+                }
+                StructChild::Content { page, mcid } => {
+                    if let Some(page_ref) = page_ref(page) {
+                        children
+                            .marked_content_ref()
+                            .page(page_ref)
+                            .marked_content_id(mcid);
+                    }
+                }
+                StructChild::Annotation { page, key } => {
+                    if let Some((page_ref, annot)) =
+                        page_ref(page).zip(annotations.get(&key))
+                    {
+                        children.object_ref().page(page_ref).object(*annot);
+                    }
+                }
+            }
+        }
+    }
+
+    // Write the parent tree. The MCIDs of each page are mapped by an array.
+    let mut entries = vec![];
+    for (key, entry) in tags.parent_tree.iter().enumerate() {
+        match entry {
+            ParentTreeEntry::Page(mcids) => {
+                let array_ref = chunk.alloc();
+                chunk
+                    .packed()
+                    .indirect(array_ref)
+                    .array()
+                    .items(mcids.iter().map(|&id| refs[id]));
+                entries.push((key as i32, array_ref));
+            }
+            ParentTreeEntry::Annotation(id) => entries.push((key as i32, refs[*id])),
+        }
+    }
+
+    let mut root = chunk.indirect(root_ref).start::<StructTreeRoot>();
+    root.child(refs[0]);
+    let mut parent_tree = root.parent_tree();
+    let mut nums = parent_tree.nums();
+    for (key, value) in entries {
+        nums.insert(key, value);
+    }
+    nums.finish();
+    parent_tree.finish();
+    root.parent_tree_next_key(tags.parent_tree.len() as i32);
+
+    if !custom_roles.is_empty() {
+        custom_roles.sort_by(|a, b| a.0.cmp(&b.0));
+        custom_roles.dedup_by(|a, b| a.0 == b.0);
+        let mut role_map = root.role_map();
+        for (name, role) in &custom_roles {
+            role_map.insert(Name(name.as_bytes()), *role);
+        }
+    }
+
+    root_ref
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use ecow::eco_format;
+use ecow::EcoString;
 use pdf_writer::types::{ColorSpaceOperand, PaintType, TilingType};
 use pdf_writer::{Filter, Name, Rect, Ref};
 use typst_library::diag::SourceResult;
@@ -84,7 +84,7 @@ fn register_pattern(
     pattern: &Tiling,
     on_text: bool,
     mut transforms: content::Transforms,
-) -> SourceResult<usize> {
+) -> SourceResult<EcoString> {
     let patterns = ctx
         .resources
         .tilings
@@ -111,6 +111,8 @@ fn register_pattern(
         pattern.frame(),
         None,
         None,
+        None,
+        None,
     )?;
 
     let pdf_pattern = PdfTiling {
@@ -131,8 +133,7 @@ impl PaintEncode for Tiling {
     ) -> SourceResult<()> {
         ctx.reset_fill_color_space();
 
-        let index = register_pattern(ctx, self, on_text, transforms)?;
-        let id = eco_format!("P{index}");
+        let id = register_pattern(ctx, self, on_text, transforms)?;
         let name = Name(id.as_bytes());
 
         ctx.content.set_fill_color_space(ColorSpaceOperand::Pattern);
@@ -148,8 +149,7 @@ impl PaintEncode for Tiling {
     ) -> SourceResult<()> {
         ctx.reset_stroke_color_space();
 
-        let index = register_pattern(ctx, self, on_text, transforms)?;
-        let id = eco_format!("P{index}");
+        let id = register_pattern(ctx, self, on_text, transforms)?;
         let name = Name(id.as_bytes());
 
         ctx.content.set_stroke_color_space(ColorSpaceOperand::Pattern);
@@ -18,8 +18,8 @@ use typst_library::foundations::{
     SequenceElem, Show, ShowSet, Style, StyleChain, StyledElem, Styles, SymbolElem,
     Synthesize, Transformation,
 };
-use typst_library::html::{tag, HtmlElem};
-use typst_library::introspection::{Locatable, SplitLocator, Tag, TagElem};
+use typst_library::html::{attr, tag, HtmlElem};
+use typst_library::introspection::{Locatable, SplitLocator, Tag, TagElem, Tagged};
 use typst_library::layout::{
     AlignElem, BoxElem, HElem, InlineElem, PageElem, PagebreakElem, VElem,
 };
@@ -503,7 +503,7 @@ fn verdict<'a>(
             target.label().is_none()
                 && target.location().is_none()
                 && !target.can::<dyn ShowSet>()
-                && !target.can::<dyn Locatable>()
+                && !is_locatable(engine, target)
                 && !target.can::<dyn Synthesize>()
         })
     {
@@ -513,6 +513,14 @@ fn verdict<'a>(
     Some(Verdict { prepared, map, step })
 }
 
+/// Whether an element should be located even if it isn't labelled.
+///
+/// Elements that are only needed to build the structure of tagged PDFs are
+/// located only if those are requested.
+fn is_locatable(engine: &Engine, target: &Content) -> bool {
+    target.can::<dyn Locatable>() || (target.can::<dyn Tagged>() && engine.pdf_tags())
+}
+
 /// This is only executed the first time an element is visited.
 fn prepare(
     engine: &mut Engine,
@@ -529,7 +537,7 @@ fn prepare(
     // when it stems from a query.
     let key = typst_utils::hash128(&target);
     if target.location().is_none()
-        && (target.can::<dyn Locatable>() || target.label().is_some())
+        && (is_locatable(engine, target) || target.label().is_some())
     {
         let loc = locator.next_location(engine.introspector, key);
         target.set_location(loc);
@@ -874,15 +882,26 @@ static PAR: GroupingRule = GroupingRule {
             || elem == InlineElem::elem()
             || elem == BoxElem::elem()
             || (kind.is_html()
-                && content
-                    .to_packed::<HtmlElem>()
-                    .is_some_and(|elem| tag::is_inline_by_default(elem.tag)))
+                && content.to_packed::<HtmlElem>().is_some_and(|elem| {
+                    tag::is_inline_by_default(elem.tag) && !is_block_math(elem)
+                }))
     },
     inner: |content| content.elem() == SpaceElem::elem(),
     interrupt: |elem| elem == ParElem::elem() || elem == AlignElem::elem(),
     finish: finish_par,
 };
 
+/// Whether an HTML element is a block-level equation. While `<math>` is inline
+/// by default, block equations are displayed as blocks and thus don't belong
+/// into a paragraph.
+fn is_block_math(elem: &HtmlElem) -> bool {
+    elem.tag == tag::math
+        && elem
+            .attrs(StyleChain::default())
+            .get(attr::display)
+            .is_some_and(|display| display == "block")
+}
+
 /// Collects `CiteElem`s into `CiteGroup`s.
 static CITES: GroupingRule = GroupingRule {
     priority: 2,
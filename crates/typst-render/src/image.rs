@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use hayro::hayro_interpret::InterpreterSettings;
 use image::imageops::FilterType;
 use image::{GenericImageView, Rgba};
 use tiny_skia as sk;
@@ -9,7 +10,7 @@ use typst_library::visualize::{Image, ImageKind, ImageScaling};
 
 use crate::{AbsExt, State};
 
-/// Render a raster or SVG image into the canvas.
+/// Render a raster, SVG, or PDF image into the canvas.
 pub fn render_image(
     canvas: &mut sk::Pixmap,
     state: State,
@@ -94,6 +95,24 @@ fn build_texture(image: &Image, w: u32, h: u32) -> Option<Arc<sk::Pixmap>> {
             );
             resvg::render(tree, ts, &mut texture.as_mut());
         }
+        ImageKind::Pdf(pdf) => {
+            let settings = hayro::RenderSettings {
+                x_scale: w as f32 / pdf.width() as f32,
+                y_scale: h as f32 / pdf.height() as f32,
+                width: Some(u16::try_from(w).ok()?),
+                height: Some(u16::try_from(h).ok()?),
+                ..Default::default()
+            };
+
+            // Annotations are not part of the embedded page in PDF export
+            // either.
+            let interpreter =
+                InterpreterSettings { render_annotations: false, ..Default::default() };
+
+            // Both pixmaps store premultiplied RGBA.
+            let pixmap = hayro::render(pdf.page(), &interpreter, &settings);
+            texture.data_mut().copy_from_slice(pixmap.data_as_u8_slice());
+        }
     }
     Some(Arc::new(texture))
 }
@@ -167,7 +167,11 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
             FrameItem::Image(image, size, _) => {
                 image::render_image(canvas, state.pre_translate(*pos), image, *size);
             }
+            FrameItem::Widget(widget, _) => {
+                render_frame(canvas, state.pre_translate(*pos), &widget.appearance);
+            }
             FrameItem::Link(_, _) => {}
+            FrameItem::Annotation(_, _) => {}
             FrameItem::Tag(_) => {}
         }
     }
@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use base64::Engine;
+use ecow::{eco_format, EcoString};
+use subsetter::GlyphRemapper;
+use ttf_parser::{GlyphId, Tag};
+use typst_library::layout::{Frame, FrameItem};
+use typst_library::text::{Font, TextItem};
+use typst_library::visualize::Paint;
+
+/// Fonts for the text that is kept as text in SVGs embedded into HTML.
+///
+/// All frames of an HTML document share one subset per font, which contains
+/// the characters used by all of them. The subsets are embedded into CSS as
+/// WOFF2 data URLs.
+#[derive(Debug, Default, Clone)]
+pub struct SvgFonts {
+    /// The CSS family names of the embedded fonts.
+    pub(crate) families: HashMap<Font, EcoString>,
+    /// The `@font-face` rules that embed the fonts.
+    css: String,
+}
+
+impl SvgFonts {
+    /// Embed subsets of the fonts used by the text in the frames.
+    #[typst_macros::time(name = "svg fonts")]
+    pub fn new<'a>(frames: impl IntoIterator<Item = &'a Frame>) -> Self {
+        let mut usage = vec![];
+        for frame in frames {
+            collect_chars(frame, &mut usage);
+        }
+
+        let mut fonts = Self::default();
+        for (font, chars) in usage {
+            let Some(data) = embed_font(&font, &chars) else { continue };
+            let family = eco_format!("typst-font-{}", fonts.families.len() + 1);
+            writeln!(
+                fonts.css,
+                "@font-face {{ font-family: {family}; src: url(data:font/woff2;base64,{}); }}",
+                base64::engine::general_purpose::STANDARD.encode(data),
+            )
+            .unwrap();
+            fonts.families.insert(font, family);
+        }
+        fonts
+    }
+
+    /// The CSS rules that make the fonts available, if any fonts are used.
+    pub fn css(&self) -> Option<&str> {
+        (!self.css.is_empty()).then_some(self.css.as_str())
+    }
+}
+
+/// Whether a text item can be rendered as SVG text with a font subset that
+/// maps characters to glyphs only through its `cmap`.
+///
+/// This is only the case if the glyphs map one-to-one to the characters of
+/// the text, in logical order, and each glyph is its character's default
+/// glyph. Text shaped with ligatures, decompositions, or contextual
+/// alternates cannot be reproduced by the browser's shaping with the subset,
+/// so it is rendered as outlines instead, like text with color glyphs or
+/// non-solid paints.
+pub(crate) fn is_representable(text: &TextItem) -> bool {
+    let ttf = text.font.ttf();
+    let tables = ttf.tables();
+    if tables.colr.is_some()
+        || tables.sbix.is_some()
+        || tables.svg.is_some()
+        || tables.cbdt.is_some()
+    {
+        return false;
+    }
+
+    if !matches!(text.fill, Paint::Solid(_))
+        || text
+            .stroke
+            .as_ref()
+            .is_some_and(|stroke| !matches!(stroke.paint, Paint::Solid(_)))
+    {
+        return false;
+    }
+
+    let mut end = 0;
+    for glyph in &text.glyphs {
+        let range = glyph.range();
+        if range.start != end {
+            return false;
+        }
+        end = range.end;
+
+        let mut chars = text.text[range].chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if ttf.glyph_index(c) == Some(GlyphId(glyph.id)) => {}
+            _ => return false,
+        }
+    }
+
+    end == text.text.len()
+}
+
+/// Collect the characters of the representable text in a frame per font.
+fn collect_chars(frame: &Frame, usage: &mut Vec<(Font, BTreeSet<char>)>) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_chars(&group.frame, usage),
+            FrameItem::Widget(widget, _) => collect_chars(&widget.appearance, usage),
+            FrameItem::Text(text) if is_representable(text) => {
+                let index = match usage.iter().position(|(font, _)| *font == text.font) {
+                    Some(index) => index,
+                    None => {
+                        usage.push((text.font.clone(), BTreeSet::new()));
+                        usage.len() - 1
+                    }
+                };
+                usage[index].1.extend(text.text.chars());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Subset a font to the glyphs of the given characters and encode it as
+/// WOFF2.
+fn embed_font(font: &Font, chars: &BTreeSet<char>) -> Option<Vec<u8>> {
+    let ttf = font.ttf();
+    let mut remapper = GlyphRemapper::new();
+    let mut mapping = BTreeMap::new();
+    for &c in chars {
+        if let Some(id) = ttf.glyph_index(c) {
+            mapping.insert(c, remapper.remap(id.0));
+        }
+    }
+
+    // The subset lacks a `cmap`, which we need because browsers map the
+    // characters to glyphs themselves.
+    let subset = subsetter::subset(font.data(), font.index(), &remapper).ok()?;
+    let raw = ttf_parser::RawFace::parse(&subset, 0).ok()?;
+    let mut tables = vec![];
+    for record in raw.table_records {
+        if record.tag != CMAP {
+            tables.push((record.tag, raw.table(record.tag)?.to_vec()));
+        }
+    }
+    tables.push((CMAP, write_cmap(&mapping)));
+
+    // Browsers reject fonts without these tables, which don't depend on the
+    // glyphs and are thus simply copied.
+    let original = ttf_parser::RawFace::parse(font.data(), font.index()).ok()?;
+    for tag in [NAME, OS2] {
+        if !tables.iter().any(|(t, _)| *t == tag) {
+            tables.push((tag, original.table(tag)?.to_vec()));
+        }
+    }
+
+    let flavor = subset.get(..4)?.try_into().ok()?;
+    Some(write_woff2(flavor, tables))
+}
+
+const CMAP: Tag = Tag::from_bytes(b"cmap");
+const GLYF: Tag = Tag::from_bytes(b"glyf");
+const LOCA: Tag = Tag::from_bytes(b"loca");
+const NAME: Tag = Tag::from_bytes(b"name");
+const OS2: Tag = Tag::from_bytes(b"OS/2");
+
+/// Write a `cmap` table with a format 4 subtable for the Basic Multilingual
+/// Plane and a format 12 subtable for all characters.
+fn write_cmap(mapping: &BTreeMap<char, u16>) -> Vec<u8> {
+    // Ranges of consecutive characters mapped to consecutive glyphs.
+    let mut ranges: Vec<(u32, u32, u16)> = vec![];
+    for (&c, &glyph) in mapping {
+        let c = c as u32;
+        match ranges.last_mut() {
+            Some((start, end, first))
+                if *end + 1 == c
+                    && u32::from(*first) + (c - *start) == u32::from(glyph) =>
+            {
+                *end = c;
+            }
+            _ => ranges.push((c, c, glyph)),
+        }
+    }
+
+    let format4 = write_cmap_format4(&ranges);
+    let mut format12 = vec![];
+    push_u16(&mut format12, 12);
+    push_u16(&mut format12, 0);
+    push_u32(&mut format12, 16 + 12 * ranges.len() as u32);
+    push_u32(&mut format12, 0);
+    push_u32(&mut format12, ranges.len() as u32);
+    for &(start, end, glyph) in &ranges {
+        push_u32(&mut format12, start);
+        push_u32(&mut format12, end);
+        push_u32(&mut format12, glyph.into());
+    }
+
+    let subtables: Vec<(u16, Vec<u8>)> = format4
+        .map(|data| (1, data))
+        .into_iter()
+        .chain([(10, format12)])
+        .collect();
+
+    let mut data = vec![];
+    push_u16(&mut data, 0);
+    push_u16(&mut data, subtables.len() as u16);
+    let mut offset = 4 + 8 * subtables.len() as u32;
+    for (encoding, subtable) in &subtables {
+        push_u16(&mut data, 3);
+        push_u16(&mut data, *encoding);
+        push_u32(&mut data, offset);
+        offset += subtable.len() as u32;
+    }
+    for (_, subtable) in subtables {
+        data.extend(subtable);
+    }
+    data
+}
+
+/// Write a format 4 `cmap` subtable for the ranges in the Basic Multilingual
+/// Plane, unless it would be too large.
+fn write_cmap_format4(ranges: &[(u32, u32, u16)]) -> Option<Vec<u8>> {
+    let mut segments: Vec<(u16, u16, u16)> = ranges
+        .iter()
+        .filter(|&&(start, _, _)| start < 0xFFFF)
+        .map(|&(start, end, glyph)| {
+            let end = end.min(0xFFFE) as u16;
+            let start = start as u16;
+            (start, end, glyph.wrapping_sub(start))
+        })
+        .collect();
+    segments.push((0xFFFF, 0xFFFF, 1));
+
+    let count = segments.len();
+    let length = 16 + 8 * count;
+    if length > usize::from(u16::MAX) {
+        return None;
+    }
+
+    let log = count.ilog2();
+    let search_range = 2 * (1 << log);
+    let mut data = vec![];
+    push_u16(&mut data, 4);
+    push_u16(&mut data, length as u16);
+    push_u16(&mut data, 0);
+    push_u16(&mut data, 2 * count as u16);
+    push_u16(&mut data, search_range as u16);
+    push_u16(&mut data, log as u16);
+    push_u16(&mut data, (2 * count - search_range) as u16);
+    for &(_, end, _) in &segments {
+        push_u16(&mut data, end);
+    }
+    push_u16(&mut data, 0);
+    for &(start, _, _) in &segments {
+        push_u16(&mut data, start);
+    }
+    for &(_, _, delta) in &segments {
+        push_u16(&mut data, delta);
+    }
+    for _ in &segments {
+        push_u16(&mut data, 0);
+    }
+    Some(data)
+}
+
+/// Encode the tables of a font as WOFF2.
+///
+/// The tables are not transformed, so the font data is just compressed.
+fn write_woff2(flavor: [u8; 4], mut tables: Vec<(Tag, Vec<u8>)>) -> Vec<u8> {
+    // The `loca` table must directly follow the `glyf` table.
+    tables.sort_by_key(|&(tag, _)| match tag {
+        LOCA => (GLYF, 1),
+        _ => (tag, 0),
+    });
+
+    let mut directory = vec![];
+    let mut stream = vec![];
+    let mut sfnt_size = 12 + 16 * tables.len() as u32;
+    for (tag, data) in &tables {
+        // An arbitrary tag follows. For `glyf` and `loca`, transformation
+        // version 3 is the null transform, for all others it is version 0.
+        let transform = if matches!(*tag, GLYF | LOCA) { 0xC0 } else { 0 };
+        directory.push(0x3F | transform);
+        directory.extend(tag.to_bytes());
+        push_base128(&mut directory, data.len() as u32);
+        stream.extend_from_slice(data);
+        sfnt_size += (data.len() as u32).next_multiple_of(4);
+    }
+
+    let mut compressed = vec![];
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        writer.write_all(&stream).unwrap();
+    }
+
+    let length = (48 + directory.len() + compressed.len()).next_multiple_of(4);
+    let mut data = Vec::with_capacity(length);
+    data.extend(b"wOF2");
+    data.extend(flavor);
+    push_u32(&mut data, length as u32);
+    push_u16(&mut data, tables.len() as u16);
+    push_u16(&mut data, 0);
+    push_u32(&mut data, sfnt_size);
+    push_u32(&mut data, compressed.len() as u32);
+    push_u16(&mut data, 1);
+    push_u16(&mut data, 0);
+    for _ in 0..5 {
+        push_u32(&mut data, 0);
+    }
+    data.extend(directory);
+    data.extend(compressed);
+    data.resize(length, 0);
+    data
+}
+
+/// Write a number in WOFF2's variable-length `UIntBase128` encoding.
+fn push_base128(data: &mut Vec<u8>, value: u32) {
+    let digits = (32 - value.leading_zeros()).div_ceil(7).max(1);
+    for i in (0..digits).rev() {
+        let continuation = if i > 0 { 0x80 } else { 0 };
+        data.push(((value >> (7 * i)) & 0x7F) as u8 | continuation);
+    }
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend(value.to_be_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend(value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_embed_font() {
+        let data = typst_assets::fonts().next().unwrap();
+        let font = Font::new(Bytes::new(data), 0).unwrap();
+        let chars = BTreeSet::from(['H', 'e', 'l', 'o', '!']);
+        let woff2 = embed_font(&font, &chars).unwrap();
+        assert_eq!(woff2.len() % 4, 0);
+
+        // The decoded subset maps the characters to glyphs with the same
+        // metrics as in the original font.
+        let sfnt = decode_woff2(&woff2);
+        let subset = ttf_parser::Face::parse(&sfnt, 0).unwrap();
+        let original = font.ttf();
+        assert!(subset.number_of_glyphs() < original.number_of_glyphs());
+        assert!(subset.tables().name.is_some());
+        assert!(subset.tables().os2.is_some());
+        for c in chars {
+            let id = subset.glyph_index(c).unwrap();
+            let expected = original.glyph_index(c).unwrap();
+            assert_eq!(
+                subset.glyph_hor_advance(id),
+                original.glyph_hor_advance(expected)
+            );
+            assert_eq!(
+                subset.glyph_bounding_box(id),
+                original.glyph_bounding_box(expected)
+            );
+        }
+        assert_eq!(subset.glyph_index('x'), None);
+    }
+
+    /// Decode WOFF2 data written by [`write_woff2`] back into an SFNT font.
+    fn decode_woff2(data: &[u8]) -> Vec<u8> {
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        assert_eq!(&data[..4], b"wOF2");
+        assert_eq!(u32_at(8) as usize, data.len());
+
+        let count = usize::from(u16_at(12));
+        let mut cursor = 48;
+        let mut entries = vec![];
+        for _ in 0..count {
+            // Only arbitrary tags and untransformed tables are written.
+            assert_eq!(data[cursor] & 0x3F, 0x3F);
+            let tag = &data[cursor + 1..cursor + 5];
+            cursor += 5;
+            let mut length = 0u32;
+            loop {
+                let byte = data[cursor];
+                cursor += 1;
+                length = (length << 7) | u32::from(byte & 0x7F);
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            entries.push((tag, length as usize));
+        }
+
+        let compressed = &data[cursor..cursor + u32_at(20) as usize];
+        let mut stream = vec![];
+        std::io::Read::read_to_end(
+            &mut brotli::Decompressor::new(compressed, 4096),
+            &mut stream,
+        )
+        .unwrap();
+
+        // The table records must be sorted by tag, but WOFF2 stores `loca`
+        // directly after `glyf`.
+        let mut records = vec![];
+        let mut tables = vec![];
+        let mut start = 0;
+        for (tag, length) in entries {
+            records.push((tag, 12 + 16 * count + tables.len(), length));
+            tables.extend(&stream[start..start + length]);
+            tables.resize(tables.len().next_multiple_of(4), 0);
+            start += length;
+        }
+        assert_eq!(start, stream.len());
+        records.sort_by_key(|&(tag, _, _)| tag);
+
+        let mut sfnt = data[4..8].to_vec();
+        push_u16(&mut sfnt, count as u16);
+        sfnt.extend([0; 6]);
+        for (tag, offset, length) in records {
+            sfnt.extend(tag);
+            push_u32(&mut sfnt, 0);
+            push_u32(&mut sfnt, offset as u32);
+            push_u32(&mut sfnt, length as u32);
+        }
+        sfnt.extend(tables);
+        sfnt
+    }
+
+    #[test]
+    fn test_push_base128() {
+        let encode = |value| {
+            let mut data = vec![];
+            push_base128(&mut data, value);
+            data
+        };
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(63), [0x3F]);
+        assert_eq!(encode(128), [0x81, 0x00]);
+        assert_eq!(encode(u32::MAX), [0x8F, 0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_write_cmap() {
+        let mapping = BTreeMap::from([('a', 3), ('b', 4), ('d', 1), ('😀', 2)]);
+        let data = write_cmap(&mapping);
+        let cmap = ttf_parser::cmap::Table::parse(&data).unwrap();
+        assert_eq!(cmap.subtables.len(), 2);
+        for (c, glyph) in mapping {
+            let found = cmap
+                .subtables
+                .into_iter()
+                .filter_map(|subtable| subtable.glyph_index(c as u32))
+                .collect::<Vec<_>>();
+            assert!(!found.is_empty());
+            assert!(found.iter().all(|&id| id == GlyphId(glyph)));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use base64::Engine;
 use ecow::{eco_format, EcoString};
+use hayro_svg::hayro_interpret::InterpreterSettings;
 use image::{codecs::png::PngEncoder, ImageEncoder};
 use typst_library::foundations::Smart;
 use typst_library::layout::{Abs, Axes};
@@ -40,25 +41,37 @@ pub fn convert_image_to_base64_url(image: &Image) -> EcoString {
     let mut buf;
     let (format, data): (&str, &[u8]) = match image.kind() {
         ImageKind::Raster(raster) => match raster.format() {
-            RasterFormat::Exchange(format) => (
-                match format {
-                    ExchangeFormat::Png => "png",
-                    ExchangeFormat::Jpg => "jpeg",
-                    ExchangeFormat::Gif => "gif",
-                },
-                raster.data(),
-            ),
-            RasterFormat::Pixel(_) => ("png", {
-                buf = vec![];
-                let mut encoder = PngEncoder::new(&mut buf);
-                if let Some(icc_profile) = raster.icc() {
-                    encoder.set_icc_profile(icc_profile.to_vec()).ok();
-                }
-                raster.dynamic().write_with_encoder(encoder).unwrap();
-                buf.as_slice()
-            }),
+            RasterFormat::Exchange(ExchangeFormat::Png) => ("png", raster.data()),
+            RasterFormat::Exchange(ExchangeFormat::Jpg) => ("jpeg", raster.data()),
+            RasterFormat::Exchange(ExchangeFormat::Gif) => ("gif", raster.data()),
+            RasterFormat::Exchange(ExchangeFormat::Webp) => ("webp", raster.data()),
+            RasterFormat::Exchange(ExchangeFormat::Avif) => ("avif", raster.data()),
+            RasterFormat::Exchange(ExchangeFormat::Bmp) => ("bmp", raster.data()),
+            // Browsers generally can't display TIFFs.
+            RasterFormat::Exchange(ExchangeFormat::Tiff) | RasterFormat::Pixel(_) => {
+                ("png", {
+                    buf = vec![];
+                    let mut encoder = PngEncoder::new(&mut buf);
+                    // The pixels of CMYK images were already converted to sRGB.
+                    if let Some(icc_profile) =
+                        raster.icc().filter(|_| raster.cmyk().is_none())
+                    {
+                        encoder.set_icc_profile(icc_profile.to_vec()).ok();
+                    }
+                    raster.dynamic().write_with_encoder(encoder).unwrap();
+                    buf.as_slice()
+                })
+            }
         },
         ImageKind::Svg(svg) => ("svg+xml", svg.data()),
+        ImageKind::Pdf(pdf) => {
+            // Annotations are not part of the embedded page in PDF export
+            // either.
+            let settings =
+                InterpreterSettings { render_annotations: false, ..Default::default() };
+            buf = hayro_svg::convert(pdf.page(), &settings).into_bytes();
+            ("svg+xml", buf.as_slice())
+        }
     };
 
     let mut url = eco_format!("data:image/{format};base64,");
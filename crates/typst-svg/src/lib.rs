@@ -1,5 +1,6 @@
 //! Rendering of Typst documents into SVG images.
 
+mod font;
 mod image;
 mod paint;
 mod shape;
@@ -14,10 +15,13 @@ use typst_library::layout::{
     Abs, Frame, FrameItem, FrameKind, GroupItem, Page, PagedDocument, Point, Ratio, Size,
     Transform,
 };
+use typst_library::text::Font;
 use typst_library::visualize::{Geometry, Gradient, Tiling};
 use typst_utils::hash128;
 use xmlwriter::XmlWriter;
 
+pub use self::font::SvgFonts;
+
 use crate::paint::{GradientRef, SVGSubGradient, TilingRef};
 use crate::text::RenderedGlyph;
 
@@ -43,6 +47,29 @@ pub fn svg_frame(frame: &Frame) -> String {
     renderer.finalize()
 }
 
+/// Export a frame into an SVG that is embedded into an HTML document.
+///
+/// Text whose font is part of the given fonts is kept as text, so that it
+/// remains selectable and searchable. The fonts' CSS must be included in the
+/// HTML document. If an `alt` text is given, the SVG is exposed as an image
+/// with that description to assistive technology.
+#[typst_macros::time(name = "svg html frame")]
+pub fn svg_html_frame(frame: &Frame, fonts: &SvgFonts, alt: Option<&str>) -> String {
+    let mut renderer = SVGRenderer::new();
+    renderer.text_fonts = fonts.families.clone();
+    renderer.xml.start_element("svg");
+    renderer.xml.write_attribute("style", "overflow: visible;");
+    if let Some(alt) = alt {
+        renderer.xml.write_attribute("role", "img");
+        renderer.xml.write_attribute("aria-label", alt);
+    }
+    renderer.write_header_attrs(frame.size());
+
+    let state = State::new(frame.size(), Transform::identity());
+    renderer.render_frame(state, Transform::identity(), frame);
+    renderer.finalize()
+}
+
 /// Export a document with potentially multiple pages into a single SVG file.
 ///
 /// The padding will be added around and between the individual frames.
@@ -113,6 +140,9 @@ struct SVGRenderer {
     tilings: Deduplicator<Tiling>,
     /// These are the gradients that compose a conic gradient.
     conic_subgradients: Deduplicator<SVGSubGradient>,
+    /// The CSS font families of fonts whose text is kept as text instead of
+    /// being converted into glyphs.
+    text_fonts: HashMap<Font, EcoString>,
 }
 
 /// Contextual information for rendering.
@@ -165,6 +195,7 @@ impl SVGRenderer {
             conic_subgradients: Deduplicator::new('s'),
             tiling_refs: Deduplicator::new('p'),
             tilings: Deduplicator::new('t'),
+            text_fonts: HashMap::new(),
         }
     }
 
@@ -172,6 +203,11 @@ impl SVGRenderer {
     /// attributes.
     fn write_header(&mut self, size: Size) {
         self.xml.start_element("svg");
+        self.write_header_attrs(size);
+    }
+
+    /// Write the attributes of the SVG header.
+    fn write_header_attrs(&mut self, size: Size) {
         self.xml.write_attribute("class", "typst-doc");
         self.xml.write_attribute_fmt(
             "viewBox",
@@ -207,7 +243,10 @@ impl SVGRenderer {
         for (pos, item) in frame.items() {
             // File size optimization.
             // TODO: SVGs could contain links, couldn't they?
-            if matches!(item, FrameItem::Link(_, _) | FrameItem::Tag(_)) {
+            if matches!(
+                item,
+                FrameItem::Link(_, _) | FrameItem::Annotation(_, _) | FrameItem::Tag(_)
+            ) {
                 continue;
             }
 
@@ -228,7 +267,13 @@ impl SVGRenderer {
                     self.render_shape(state.pre_translate(*pos), shape)
                 }
                 FrameItem::Image(image, size, _) => self.render_image(image, size),
+                FrameItem::Widget(widget, _) => self.render_frame(
+                    state.pre_translate(*pos),
+                    Transform::identity(),
+                    &widget.appearance,
+                ),
                 FrameItem::Link(_, _) => unreachable!(),
+                FrameItem::Annotation(_, _) => unreachable!(),
                 FrameItem::Tag(_) => unreachable!(),
             };
 
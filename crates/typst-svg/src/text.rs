@@ -18,6 +18,13 @@ impl SVGRenderer {
     /// try to render the text as SVG first, then bitmap, then outline. If none
     /// of them works, we will skip the text.
     pub(super) fn render_text(&mut self, state: State, text: &TextItem) {
+        if let Some(family) = self.text_fonts.get(&text.font).cloned() {
+            if crate::font::is_representable(text) {
+                self.render_text_as_text(text, &family);
+                return;
+            }
+        }
+
         let scale: f64 = text.size.to_pt() / text.font.units_per_em();
 
         self.xml.start_element("g");
@@ -49,6 +56,45 @@ impl SVGRenderer {
         self.xml.end_element();
     }
 
+    /// Render a text item as an SVG `<text>` element with an embedded font, so
+    /// that it remains selectable and accessible.
+    fn render_text_as_text(&mut self, text: &TextItem, family: &str) {
+        self.xml.start_element("text");
+        self.xml.write_attribute("class", "typst-text");
+        self.xml.write_attribute("font-family", family);
+        self.xml.write_attribute("font-size", &text.size.to_pt());
+        self.xml.write_attribute("style", "white-space: pre");
+        self.write_fill(
+            &text.fill,
+            FillRule::NonZero,
+            Size::zero(),
+            Transform::identity(),
+        );
+        if let Some(stroke) = &text.stroke {
+            self.write_stroke(stroke, Size::zero(), Transform::identity());
+        } else {
+            self.xml.write_attribute("stroke", "none");
+        }
+
+        // Position each character individually, so that the text lines up
+        // with the shaped glyphs. Representable text has exactly one glyph
+        // per character.
+        let mut positions = String::new();
+        let mut x: f64 = 0.0;
+        for glyph in &text.glyphs {
+            if !positions.is_empty() {
+                positions.push(' ');
+            }
+            let offset = x + glyph.x_offset.at(text.size).to_pt();
+            positions.push_str(&offset.to_string());
+            x += glyph.x_advance.at(text.size).to_pt();
+        }
+
+        self.xml.write_attribute("x", &positions);
+        self.xml.write_text(&text.text);
+        self.xml.end_element();
+    }
+
     /// Render a glyph defined by an SVG.
     fn render_svg_glyph(
         &mut self,
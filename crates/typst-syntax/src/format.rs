@@ -0,0 +1,674 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{parse, split_newlines, SyntaxKind, SyntaxNode};
+
+/// Configuration for [`format`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FormatConfig {
+    /// The width in characters beyond which argument lists and other
+    /// collections are broken into one item per line.
+    pub width: usize,
+    /// The number of spaces per indentation level.
+    pub indent: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self { width: 80, indent: 2 }
+    }
+}
+
+/// An error that prevents a file from being formatted.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FormatError {
+    /// The file has syntax errors.
+    Syntax,
+    /// The formatted file would not mean the same as the original one.
+    Changed,
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax => f.write_str("the file contains syntax errors"),
+            Self::Changed => {
+                f.write_str("formatting would change the meaning of the file")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Format Typst source code.
+///
+/// Normalizes the spacing in code, indents nested blocks and list items,
+/// and lays out argument lists and other collections on a single line if
+/// they fit into the configured width or with one item per line and trailing
+/// commas otherwise. Whitespace in markup is only changed where it does not
+/// matter, and equations and raw text are kept as they are.
+///
+/// The formatted text is reparsed to ensure that it is equivalent to the
+/// original one, which fails with [`FormatError::Changed`] otherwise.
+pub fn format(text: &str, config: &FormatConfig) -> Result<String, FormatError> {
+    let root = parse(text);
+    if root.erroneous() {
+        return Err(FormatError::Syntax);
+    }
+
+    let mut printer = Printer { config, out: String::new(), level: 0, flat: false };
+    printer.markup(&root, End::File);
+
+    if !equivalent(&root, &parse(&printer.out)) {
+        return Err(FormatError::Changed);
+    }
+
+    Ok(printer.out)
+}
+
+/// How to treat whitespace at the end of a markup node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum End {
+    /// The markup is a whole file, so trailing whitespace becomes a single
+    /// newline.
+    File,
+    /// The markup is the body of a block, so trailing whitespace is indented
+    /// for the closing delimiter.
+    Block,
+    /// Trailing whitespace is treated like any other.
+    Inner,
+}
+
+/// Writes the formatted code of a syntax tree.
+struct Printer<'a> {
+    config: &'a FormatConfig,
+    out: String,
+    /// The current indentation level.
+    level: usize,
+    /// Whether collections must be laid out on a single line.
+    flat: bool,
+}
+
+impl Printer<'_> {
+    /// Write any node.
+    fn node(&mut self, node: &SyntaxNode) {
+        match node.kind() {
+            SyntaxKind::Markup => self.markup(node, End::Inner),
+            SyntaxKind::ContentBlock => self.content_block(node),
+            SyntaxKind::CodeBlock => self.code_block(node),
+            SyntaxKind::Code => self.code(node),
+            SyntaxKind::ListItem | SyntaxKind::EnumItem | SyntaxKind::TermItem => {
+                self.item(node)
+            }
+            SyntaxKind::Heading
+            | SyntaxKind::Strong
+            | SyntaxKind::Emph
+            | SyntaxKind::Ref => self.markup_node(node),
+            SyntaxKind::Args
+            | SyntaxKind::Params
+            | SyntaxKind::Array
+            | SyntaxKind::Dict
+            | SyntaxKind::Destructuring => self.collection(node),
+            SyntaxKind::Equation | SyntaxKind::Raw => {
+                self.out.push_str(&node.clone().into_text())
+            }
+            _ if node.children().len() == 0 => self.out.push_str(node.text()),
+            _ => self.expr(node),
+        }
+    }
+
+    /// Write a markup node. Whitespace in markup only matters up to whether
+    /// it contains newlines, so it is reduced to a space or reindented.
+    fn markup(&mut self, node: &SyntaxNode, end: End) {
+        let count = node.children().len();
+        for (i, child) in node.children().enumerate() {
+            if !matches!(child.kind(), SyntaxKind::Space | SyntaxKind::Parbreak) {
+                self.node(child);
+                continue;
+            }
+
+            // Trailing whitespace is reduced as far as possible without
+            // turning a paragraph break into a space.
+            let newlines = count_newlines(child.text());
+            let least =
+                newlines.min(if child.kind() == SyntaxKind::Parbreak { 2 } else { 1 });
+            match end {
+                End::File if i + 1 == count => {
+                    if newlines > 0 {
+                        self.newline(least, 0);
+                    }
+                }
+                End::Block if i + 1 == count && newlines > 0 => {
+                    self.newline(least, self.level.saturating_sub(1));
+                }
+                _ if newlines > 0 => self.newline(newlines.min(2), self.level),
+                _ => self.out.push(' '),
+            }
+        }
+    }
+
+    /// Write a markup node other than a markup sequence, e.g. a heading.
+    fn markup_node(&mut self, node: &SyntaxNode) {
+        for child in node.children() {
+            match child.kind() {
+                SyntaxKind::Space if count_newlines(child.text()) > 0 => {
+                    self.newline(1, self.level)
+                }
+                SyntaxKind::Space => self.out.push(' '),
+                _ => self.node(child),
+            }
+        }
+    }
+
+    /// Write a list, enum, or term item. Its body is indented by one level,
+    /// so that continuation lines and nested items stay within the item.
+    fn item(&mut self, node: &SyntaxNode) {
+        self.level += 1;
+        self.markup_node(node);
+        self.level -= 1;
+    }
+
+    /// Write a content block, indenting its lines by one level.
+    fn content_block(&mut self, node: &SyntaxNode) {
+        for child in node.children() {
+            if child.kind() == SyntaxKind::Markup {
+                self.level += 1;
+                self.markup(child, End::Block);
+                self.level -= 1;
+            } else {
+                self.node(child);
+            }
+        }
+    }
+
+    /// Write a code block, indenting its statements by one level.
+    fn code_block(&mut self, node: &SyntaxNode) {
+        let mut children = node.children().peekable();
+        while let Some(child) = children.next() {
+            match child.kind() {
+                SyntaxKind::Space if count_newlines(child.text()) > 0 => {
+                    let closing = children
+                        .peek()
+                        .is_some_and(|next| next.kind() == SyntaxKind::RightBrace);
+                    self.newline(1, if closing { self.level } else { self.level + 1 });
+                }
+                SyntaxKind::Space => self.out.push(' '),
+                SyntaxKind::Code => {
+                    self.level += 1;
+                    self.code(child);
+                    self.level -= 1;
+                }
+                _ => self.node(child),
+            }
+        }
+    }
+
+    /// Write a sequence of statements.
+    fn code(&mut self, node: &SyntaxNode) {
+        let mut children = node.children().peekable();
+        let mut prev = None;
+        while let Some(child) = children.next() {
+            match child.kind() {
+                SyntaxKind::Space => {
+                    let newlines = count_newlines(child.text());
+                    let next = children.peek().map(|next| next.kind());
+                    if newlines > 0 {
+                        self.newline(newlines.min(2), self.level);
+                    } else if next != Some(SyntaxKind::Semicolon) {
+                        self.out.push(' ');
+                    }
+                }
+                _ => {
+                    if prev == Some(SyntaxKind::Semicolon) {
+                        self.out.push(' ');
+                    }
+                    self.node(child);
+                }
+            }
+            prev = Some(child.kind());
+        }
+    }
+
+    /// Write an expression, normalizing the spacing between its parts.
+    fn expr(&mut self, node: &SyntaxNode) {
+        let mut prev: Option<&SyntaxNode> = None;
+        let mut space: Option<&SyntaxNode> = None;
+        for child in node.children() {
+            if child.kind() == SyntaxKind::Space {
+                space = Some(child);
+                continue;
+            }
+
+            if let Some(prev) = prev {
+                self.separate(node.kind(), prev, space, child);
+            }
+
+            self.node(child);
+            prev = Some(child);
+            space = None;
+        }
+
+        if let Some(space) = space {
+            if count_newlines(space.text()) > 0 {
+                self.newline(1, self.level);
+            } else {
+                self.out.push(' ');
+            }
+        }
+    }
+
+    /// Write the whitespace between two parts of an expression.
+    fn separate(
+        &mut self,
+        parent: SyntaxKind,
+        prev: &SyntaxNode,
+        space: Option<&SyntaxNode>,
+        next: &SyntaxNode,
+    ) {
+        if space.is_some_and(|space| count_newlines(space.text()) > 0) {
+            let level = match next.kind() {
+                SyntaxKind::Else => self.level,
+                _ => self.level + 1,
+            };
+            self.newline(1, level);
+            return;
+        }
+
+        let (prev, next) = (prev.kind(), next.kind());
+        let spaced = if is_unspaced(parent, prev, next) {
+            false
+        } else if is_spaced(parent, prev, next) {
+            true
+        } else {
+            space.is_some()
+        };
+
+        if spaced {
+            self.out.push(' ');
+        }
+    }
+
+    /// Write a parenthesized collection of items, e.g. arguments.
+    fn collection(&mut self, node: &SyntaxNode) {
+        let children = node.children().as_slice();
+        let Some(close) = children
+            .iter()
+            .position(|child| child.kind() == SyntaxKind::RightParen)
+            .filter(|_| children[0].kind() == SyntaxKind::LeftParen)
+        else {
+            // Argument lists may consist of trailing content blocks only.
+            self.expr(node);
+            return;
+        };
+
+        let entries = entries(&children[1..close]);
+        if entries.is_empty() {
+            for child in children.iter().filter(|child| child.kind() != SyntaxKind::Space)
+            {
+                self.node(child);
+            }
+            return;
+        }
+
+        let start = self.out.len();
+        let commented = entries.iter().any(|entry| entry.node.kind().is_trivia());
+        if !commented {
+            let flat = std::mem::replace(&mut self.flat, true);
+            let last = self.flat_collection(node, &entries, &children[..close]);
+            self.flat = flat;
+
+            // A block as the last item may span multiple lines as long as
+            // the collection starts on a line that fits.
+            let first_line =
+                self.out[start..].find('\n').map_or(self.out.len(), |i| start + i);
+            let hugging = matches!(
+                entries.last().map(|entry| entry.node.kind()),
+                Some(SyntaxKind::CodeBlock | SyntaxKind::ContentBlock)
+            ) && !self.out[start..last].contains('\n');
+            if flat
+                || ((first_line == self.out.len() || hugging)
+                    && self.column_at(first_line) <= self.config.width)
+            {
+                self.trailing_blocks(&children[close + 1..]);
+                return;
+            }
+            self.out.truncate(start);
+        }
+
+        self.broken_collection(&entries, &children[..close]);
+        self.trailing_blocks(&children[close + 1..]);
+    }
+
+    /// Write a collection on a single line, except for blocks in it. Returns
+    /// the offset at which the last item starts.
+    fn flat_collection(
+        &mut self,
+        node: &SyntaxNode,
+        entries: &[Entry],
+        children: &[SyntaxNode],
+    ) -> usize {
+        self.open(children);
+        let mut last = self.out.len();
+        for (i, entry) in entries.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            last = self.out.len();
+            self.node(entry.node);
+        }
+
+        // A single item needs a trailing comma to be an array or pattern.
+        let trailing = children
+            .iter()
+            .rev()
+            .find(|child| !child.kind().is_trivia())
+            .is_some_and(|last| last.kind() == SyntaxKind::Comma);
+        if entries.len() == 1
+            && trailing
+            && matches!(node.kind(), SyntaxKind::Array | SyntaxKind::Destructuring)
+        {
+            self.out.push(',');
+        }
+
+        self.out.push(')');
+        last
+    }
+
+    /// Write a collection with one item per line.
+    fn broken_collection(&mut self, entries: &[Entry], children: &[SyntaxNode]) {
+        self.open(children);
+        self.level += 1;
+        for entry in entries {
+            if entry.node.kind().is_trivia() && entry.trailing {
+                self.out.push(' ');
+                self.node(entry.node);
+                continue;
+            }
+
+            self.newline(if entry.blank { 2 } else { 1 }, self.level);
+            self.node(entry.node);
+            if !entry.node.kind().is_trivia() {
+                self.out.push(',');
+            }
+        }
+        self.level -= 1;
+        self.newline(1, self.level);
+        self.out.push(')');
+    }
+
+    /// Write the opening parenthesis of a collection and the colon that marks
+    /// a dictionary which only consists of spreads.
+    fn open(&mut self, children: &[SyntaxNode]) {
+        self.out.push('(');
+        if children.iter().any(|child| child.kind() == SyntaxKind::Colon) {
+            self.out.push(':');
+        }
+    }
+
+    /// Write the content blocks that follow an argument list.
+    fn trailing_blocks(&mut self, children: &[SyntaxNode]) {
+        for child in children {
+            self.node(child);
+        }
+    }
+
+    /// Write newlines and the indentation for the given level.
+    fn newline(&mut self, count: usize, level: usize) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        for _ in 0..count {
+            self.out.push('\n');
+        }
+        for _ in 0..level * self.config.indent {
+            self.out.push(' ');
+        }
+    }
+
+    /// The column at the given offset in the output.
+    fn column_at(&self, offset: usize) -> usize {
+        self.out[..offset]
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+    }
+}
+
+/// An item or a comment in a collection.
+struct Entry<'a> {
+    node: &'a SyntaxNode,
+    /// Whether the entry is preceded by a blank line.
+    blank: bool,
+    /// Whether the entry is a comment on the same line as the previous item.
+    trailing: bool,
+}
+
+/// Split the children between the parentheses of a collection into entries.
+fn entries(children: &[SyntaxNode]) -> Vec<Entry<'_>> {
+    let mut entries = vec![];
+    let mut newlines = 0;
+    for child in children {
+        match child.kind() {
+            SyntaxKind::Space => newlines += count_newlines(child.text()),
+            SyntaxKind::Comma | SyntaxKind::Colon => {}
+            _ => {
+                let trailing =
+                    child.kind().is_trivia() && newlines == 0 && !entries.is_empty();
+                entries.push(Entry { node: child, blank: newlines >= 2, trailing });
+                newlines = 0;
+            }
+        }
+    }
+    entries
+}
+
+/// Whether there must not be a space between two parts of an expression.
+fn is_unspaced(parent: SyntaxKind, prev: SyntaxKind, next: SyntaxKind) -> bool {
+    matches!(
+        next,
+        SyntaxKind::Comma
+            | SyntaxKind::Semicolon
+            | SyntaxKind::Colon
+            | SyntaxKind::Dot
+            | SyntaxKind::RightParen
+            | SyntaxKind::Args
+            | SyntaxKind::Params
+    ) || matches!(
+        prev,
+        SyntaxKind::LeftParen | SyntaxKind::Dot | SyntaxKind::Dots | SyntaxKind::Hash
+    ) || (parent == SyntaxKind::Unary
+        && matches!(prev, SyntaxKind::Plus | SyntaxKind::Minus))
+}
+
+/// Whether there must be a space between two parts of an expression.
+fn is_spaced(parent: SyntaxKind, prev: SyntaxKind, next: SyntaxKind) -> bool {
+    prev == SyntaxKind::Comma
+        || prev == SyntaxKind::Colon
+        || is_keyword(prev)
+        || is_keyword(next)
+        || is_operator(parent, prev)
+        || is_operator(parent, next)
+        || (matches!(
+            parent,
+            SyntaxKind::Conditional | SyntaxKind::WhileLoop | SyntaxKind::ForLoop
+        ) && next == SyntaxKind::CodeBlock)
+}
+
+/// Whether a kind is a keyword that needs to be set apart by spaces.
+fn is_keyword(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Not
+            | SyntaxKind::And
+            | SyntaxKind::Or
+            | SyntaxKind::Let
+            | SyntaxKind::Set
+            | SyntaxKind::Show
+            | SyntaxKind::Context
+            | SyntaxKind::If
+            | SyntaxKind::Else
+            | SyntaxKind::For
+            | SyntaxKind::In
+            | SyntaxKind::While
+            | SyntaxKind::Return
+            | SyntaxKind::Import
+            | SyntaxKind::Include
+            | SyntaxKind::As
+    )
+}
+
+/// Whether a kind is an infix operator that needs to be set apart by spaces.
+fn is_operator(parent: SyntaxKind, kind: SyntaxKind) -> bool {
+    match kind {
+        SyntaxKind::Eq
+        | SyntaxKind::EqEq
+        | SyntaxKind::ExclEq
+        | SyntaxKind::Lt
+        | SyntaxKind::LtEq
+        | SyntaxKind::Gt
+        | SyntaxKind::GtEq
+        | SyntaxKind::PlusEq
+        | SyntaxKind::HyphEq
+        | SyntaxKind::StarEq
+        | SyntaxKind::SlashEq
+        | SyntaxKind::Arrow => true,
+        SyntaxKind::Plus | SyntaxKind::Minus | SyntaxKind::Star | SyntaxKind::Slash => {
+            parent == SyntaxKind::Binary
+        }
+        _ => false,
+    }
+}
+
+/// The number of newlines in whitespace.
+fn count_newlines(text: &str) -> usize {
+    split_newlines(text).len() - 1
+}
+
+/// Whether two syntax trees are equivalent, i.e. differ at most in
+/// whitespace and optional commas.
+fn equivalent(a: &SyntaxNode, b: &SyntaxNode) -> bool {
+    if a.kind() != b.kind() {
+        return false;
+    }
+
+    if a.children().len() == 0 && b.children().len() == 0 {
+        return a.kind() == SyntaxKind::Parbreak || a.text() == b.text();
+    }
+
+    let (left, right) = (parts(a), parts(b));
+    left.len() == right.len()
+        && left.iter().zip(&right).all(|pair| match pair {
+            (Part::Node(a), Part::Node(b)) => equivalent(a, b),
+            (Part::Text(a), Part::Text(b)) => a == b,
+            _ => false,
+        })
+}
+
+/// A meaningful part of a syntax node.
+enum Part<'a> {
+    /// A child node.
+    Node(&'a SyntaxNode),
+    /// A run of text and spaces in markup, with spaces collapsed.
+    Text(String),
+}
+
+/// Split a node's children into the parts that carry meaning. Whitespace
+/// outside of markup and commas don't matter on their own. In markup, the
+/// parser merges text with single spaces, so runs of text and spaces are
+/// compared as a whole.
+fn parts(node: &SyntaxNode) -> Vec<Part<'_>> {
+    let markup = node.kind() == SyntaxKind::Markup;
+    let mut parts = vec![];
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Space | SyntaxKind::Text if markup => {
+                let text = match child.kind() {
+                    SyntaxKind::Space => " ",
+                    _ => child.text().as_str(),
+                };
+                if !matches!(parts.last(), Some(Part::Text(_))) {
+                    parts.push(Part::Text(String::new()));
+                }
+                let Some(Part::Text(run)) = parts.last_mut() else { unreachable!() };
+                for c in text.chars() {
+                    if c != ' ' || !run.ends_with(' ') {
+                        run.push(c);
+                    }
+                }
+            }
+            SyntaxKind::Space | SyntaxKind::Comma => {}
+            _ => parts.push(Part::Node(child)),
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, FormatConfig, FormatError};
+
+    #[track_caller]
+    fn test(text: &str, expected: &str) {
+        test_with(text, expected, &FormatConfig::default());
+    }
+
+    #[track_caller]
+    fn test_with(text: &str, expected: &str, config: &FormatConfig) {
+        let formatted = format(text, config).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted, config).unwrap(), expected, "not idempotent");
+    }
+
+    #[test]
+    fn test_format_code_spacing() {
+        test("#let  x=1+2*  3", "#let x = 1 + 2 * 3");
+        test("#let f( a,b )=a", "#let f(a, b) = a");
+        test("#if x==1{a}else{ b }", "#if x == 1 {a} else { b }");
+        test("#{a;b}", "#{a; b}");
+        test("#(-x , not y, ..z , a.b)", "#(-x, not y, ..z, a.b)");
+        test("#text(fill:red )[Hi]", "#text(fill: red)[Hi]");
+    }
+
+    #[test]
+    fn test_format_trailing_commas() {
+        test("#let (a,) = (1,)", "#let (a,) = (1,)");
+        test("#f(a, b,)", "#f(a, b)");
+        test("#(:)", "#(:)");
+        test("#f()[x]", "#f()[x]");
+        test("#f(a, {\n  b\n})", "#f(a, {\n  b\n})");
+    }
+
+    #[test]
+    fn test_format_wrapping() {
+        let config = FormatConfig { width: 20, ..Default::default() };
+        test_with(
+            "#figure(image(\"a.png\"), caption: [A])",
+            "#figure(\n  image(\"a.png\"),\n  caption: [A],\n)",
+            &config,
+        );
+        test_with("#f(a, // first\n  b)", "#f(\n  a, // first\n  b,\n)", &config);
+    }
+
+    #[test]
+    fn test_format_indentation() {
+        test("#{\nlet x = 1\n\n\n    x\n   }\n", "#{\n  let x = 1\n\n  x\n}\n");
+        test("#block[\n      Hello\n    World\n]", "#block[\n  Hello\n  World\n]");
+        test("- a\n    - b\n      c\n- d\n\n\n\nText", "- a\n  - b\n    c\n- d\n\nText");
+    }
+
+    #[test]
+    fn test_format_verbatim() {
+        test("$x+y  = z$ `a  =b`", "$x+y  = z$ `a  =b`");
+        test("```\n  a  b\n```", "```\n  a  b\n```");
+        test("Some *text* here   with   spaces.", "Some *text* here with spaces.");
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert_eq!(
+            format("#let x = ", &FormatConfig::default()),
+            Err(FormatError::Syntax)
+        );
+    }
+}
@@ -4,6 +4,7 @@ pub mod ast;
 pub mod package;
 
 mod file;
+mod format;
 mod highlight;
 mod kind;
 mod lexer;
@@ -16,6 +17,7 @@ mod source;
 mod span;
 
 pub use self::file::FileId;
+pub use self::format::{format, FormatConfig, FormatError};
 pub use self::highlight::{highlight, highlight_html, Tag};
 pub use self::kind::SyntaxKind;
 pub use self::lexer::{
@@ -219,6 +219,9 @@ mod exceptions {
         "h5",
         "h6",
         "historical-ligatures",
+        "linethickness",
+        "mmultiscripts",
+        "movablelimits",
         "number-clearance",
         "number-margin",
         "numbering-scope",
@@ -356,4 +356,5 @@ pub static ROUTINES: Routines = Routines {
     layout_image: typst_layout::layout_image,
     layout_equation_block: typst_layout::layout_equation_block,
     layout_equation_inline: typst_layout::layout_equation_inline,
+    layout_widget: typst_layout::layout_widget,
 };
@@ -1,11 +1,27 @@
+use std::collections::HashMap;
 use std::fmt::Write;
+use std::io::{Cursor, Read};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
 
-use typst::foundations::Smart;
-use typst::layout::PagedDocument;
-use typst::model::DocumentInfo;
+use hayro_syntax::object::dict::keys;
+use hayro_syntax::object::{Array, Dict, Name as HayroName, ObjectIdentifier};
+use hayro_syntax::Pdf;
+
+use tiny_skia as sk;
+use typst::foundations::{NativeElement, Smart};
+use typst::html::HtmlDocument;
+use typst::layout::{Abs, PagedDocument};
+use typst::model::{DocumentInfo, ParElem};
+use typst::syntax::Source;
+use typst::visualize::Color;
 use typst::World;
+use typst_html::{EpubOptions, SiteOptions};
+use typst_pdf::{PdfOptions, PdfStandard, PdfStandards};
+use zip::{CompressionMethod, ZipArchive};
 
 use crate::collect::Test;
+use crate::run::approx_equal;
 use crate::world::TestWorld;
 
 /// We don't want to panic when there is a failure.
@@ -36,11 +52,667 @@ pub fn check(test: &Test, world: &TestWorld, doc: Option<&PagedDocument>) -> Str
             test_eq!(sink, info.author, ["Changed"]);
             test_eq!(sink, info.title.as_deref(), Some("Alternative"));
         }
+        "pdf-tags-structure" => {
+            let pdf = inflate(&tagged_pdf(&mut sink, world));
+            for role in [
+                "/Type /StructTreeRoot",
+                "/S /Document",
+                "/S /H1",
+                "/S /P",
+                "/S /L",
+                "/S /LI",
+                "/S /LBody",
+                "/S /Table",
+                "/S /TR",
+                "/S /TH",
+                "/S /TD",
+            ] {
+                test_eq!(sink, pdf.contains(&format!("{role}\n")), true);
+            }
+        }
+        "pdf-tags-artifact" => {
+            // The heading in the page header and the one in the explicit
+            // artifact are not part of the structure tree.
+            let pdf = inflate(&tagged_pdf(&mut sink, world));
+            test_eq!(sink, pdf.contains("/S /H1\n"), true);
+            test_eq!(sink, pdf.contains("/S /H2\n"), false);
+            test_eq!(sink, pdf.contains("/S /H3\n"), false);
+        }
+        "pdf-tags-tree" => {
+            let data = tagged_pdf(&mut sink, world);
+            let tree = struct_tree(&mut sink, &data);
+            test_eq!(
+                sink,
+                tree,
+                "Document\n\
+                 \x20 H1\n\
+                 \x20 P\n\
+                 \x20 Div\n\
+                 \x20   Figure \"Ein Quadrat\"\n\
+                 \x20   Caption\n\
+                 \x20 Formula \"x hoch zwei\"\n"
+            );
+            test_eq!(sink, inflate(&data).contains("/Lang (de)\n"), true);
+        }
+        "pdf-tags-ua-errors" => {
+            for (text, message) in UA_ERRORS {
+                check_ua_error(&mut sink, text, true, message);
+            }
+            check_ua_error(
+                &mut sink,
+                "#set document(title: \"T\")\nText",
+                false,
+                "PDF/UA-1 requires the document to be compiled for tagged PDF export",
+            );
+        }
+        "pdf-tags-disabled" => {
+            // Paragraphs are only located if tagged PDFs are requested.
+            let select = ParElem::elem().select();
+            test_eq!(sink, doc.map(|doc| doc.introspector.query(&select).len()), Some(0));
+            let tagged = typst::compile::<PagedDocument>(&world.with_pdf_tags()).output;
+            test_eq!(
+                sink,
+                tagged.as_ref().map(|doc| doc.introspector.query(&select).len()).ok(),
+                Some(1)
+            );
+        }
+        "document-set-viewer" => {
+            let pdf = pdf_text(&mut sink, doc, &PdfOptions::default());
+            for entry in [
+                "/PageLayout /TwoPageRight",
+                "/PageMode /UseOutlines",
+                "/HideToolbar true",
+                "/DisplayDocTitle true",
+            ] {
+                test_eq!(sink, pdf.contains(&format!("{entry}\n")), true);
+            }
+        }
+        "document-set-transition" => {
+            let pdf = pdf_text(&mut sink, doc, &PdfOptions::default());
+            test_eq!(sink, pdf.contains("/Trans <<"), true);
+            test_eq!(sink, pdf.contains("/S /Push\n"), true);
+            test_eq!(sink, pdf.contains("/D 2\n"), true);
+        }
+        "pdf-annot" => {
+            let pdf = pdf_text(&mut sink, doc, &PdfOptions::default());
+            for entry in [
+                "/Subtype /Text",
+                "/Contents (Add a chart here.)",
+                "/T (Reviewer)",
+                "/Subtype /FileAttachment",
+                "/Contents (The raw data)",
+                "/UF (hello.txt)",
+                "/Type /EmbeddedFile",
+            ] {
+                test_eq!(sink, pdf.contains(&format!("{entry}\n")), true);
+            }
+        }
+        "pdf-annot-render" => {
+            for (kind, subtype) in [("text", "/Text"), ("highlight", "/Highlight")] {
+                check_annot_render(&mut sink, kind, subtype);
+            }
+        }
+        "html-frame-text" => check_html_frames(&mut sink),
+        "pdf-form-render" => {
+            for (field, plain) in FORM_FIELDS {
+                check_form_render(&mut sink, field, plain);
+            }
+        }
         _ => {}
     }
     sink
 }
 
+/// Run special checks for specific HTML tests.
+pub fn check_html(test: &Test, world: &TestWorld, doc: Option<&HtmlDocument>) -> String {
+    let mut sink = String::new();
+    if test.name == "epub-package" {
+        check_epub(
+            &mut sink,
+            world,
+            doc,
+            1,
+            &["index.xhtml", "outro.xhtml"],
+            &["index.xhtml#intro", "index.xhtml#detail", "outro.xhtml#outro"],
+        );
+        check_epub(
+            &mut sink,
+            world,
+            doc,
+            2,
+            &["index.xhtml", "detail.xhtml", "outro.xhtml"],
+            &["index.xhtml#intro", "detail.xhtml#detail", "outro.xhtml#outro"],
+        );
+    }
+    if test.name == "html-site" {
+        check_site(&mut sink, doc, 0, &[("index.html", &["<a href=\"#outro\">"])]);
+        check_site(
+            &mut sink,
+            doc,
+            1,
+            &[
+                (
+                    "index.html",
+                    &[
+                        "<title>Intro</title>",
+                        "<a href=\"index.html\" aria-current=\"page\">Intro</a>",
+                        "<a href=\"details.html\">Details</a>",
+                        "<a href=\"outro.html\">Outro</a>",
+                        "<a href=\"outro.html#outro\">",
+                        "<a href=\"details.html\" rel=\"next\">Details</a>",
+                    ],
+                ),
+                (
+                    "details.html",
+                    &[
+                        "<title>Details</title>",
+                        "<a href=\"details.html\" aria-current=\"page\">Details</a>",
+                        "<h3 id=\"nested\">",
+                        "<a href=\"index.html\" rel=\"prev\">Intro</a>",
+                        "<a href=\"outro.html\" rel=\"next\">Outro</a>",
+                    ],
+                ),
+                (
+                    "outro.html",
+                    &[
+                        "<title>Outro</title>",
+                        "<h2 id=\"outro\">",
+                        "<a href=\"details.html\" rel=\"prev\">Details</a>",
+                    ],
+                ),
+            ],
+        );
+    }
+    sink
+}
+
+/// Check the files of a multi-file HTML site. Each file must contain the given
+/// snippets and link to its neighbours, but not beyond the first or last file.
+fn check_site(
+    sink: &mut String,
+    doc: Option<&HtmlDocument>,
+    split_level: usize,
+    files: &[(&str, &[&str])],
+) {
+    let options = SiteOptions { split_level: NonZeroUsize::new(split_level) };
+    let Some(Ok(output)) = doc.map(|doc| typst_html::html_site(doc, &options)) else {
+        writeln!(sink, "failed to export the HTML site").unwrap();
+        return;
+    };
+
+    let paths: Vec<_> = output.iter().map(|file| file.path.as_str()).collect();
+    let expected: Vec<_> = files.iter().map(|&(path, _)| path).collect();
+    test_eq!(*sink, paths, expected);
+
+    for (i, (file, (_, snippets))) in output.iter().zip(files).enumerate() {
+        for snippet in *snippets {
+            if !file.html.contains(snippet) {
+                writeln!(sink, "{} does not contain {snippet:?}", file.path).unwrap();
+            }
+        }
+        test_eq!(*sink, file.html.contains("role=\"doc-toc\""), output.len() > 1);
+        test_eq!(*sink, file.html.contains("rel=\"prev\""), i > 0);
+        test_eq!(*sink, file.html.contains("rel=\"next\""), i + 1 < output.len());
+    }
+}
+
+/// Check the layout of an EPUB archive and that its package and navigation
+/// documents list the chapters in order.
+fn check_epub(
+    sink: &mut String,
+    world: &TestWorld,
+    doc: Option<&HtmlDocument>,
+    split_level: usize,
+    chapters: &[&str],
+    links: &[&str],
+) {
+    let options = EpubOptions {
+        split_level: NonZeroUsize::new(split_level),
+        ..Default::default()
+    };
+    let Some(Ok(data)) = doc.map(|doc| typst_html::epub(doc, world, &options)) else {
+        writeln!(sink, "failed to export the EPUB").unwrap();
+        return;
+    };
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(data)) else {
+        writeln!(sink, "the EPUB is not a valid ZIP archive").unwrap();
+        return;
+    };
+
+    // Reading systems expect the mimetype as the first, uncompressed entry.
+    let mut mimetype = String::new();
+    if let Ok(mut file) = archive.by_index(0) {
+        test_eq!(*sink, file.name(), "mimetype");
+        test_eq!(*sink, file.compression(), CompressionMethod::Stored);
+        file.read_to_string(&mut mimetype).unwrap();
+    }
+    test_eq!(*sink, mimetype, "application/epub+zip");
+
+    let mut read = |name: &str| {
+        let mut text = String::new();
+        match archive.by_name(name) {
+            Ok(mut file) => {
+                file.read_to_string(&mut text).unwrap();
+            }
+            Err(_) => {
+                writeln!(sink, "{name} is missing (split level {split_level})").unwrap();
+            }
+        }
+        text
+    };
+    let container = read("META-INF/container.xml");
+    let package = read("EPUB/package.opf");
+    let nav = read("EPUB/nav.xhtml");
+    let pages: Vec<String> = chapters
+        .iter()
+        .map(|chapter| read(&format!("EPUB/{chapter}")))
+        .collect();
+
+    if let Some(container) = parse_xml(sink, "container.xml", &container) {
+        let rootfile = container
+            .descendants()
+            .find(|node| node.has_tag_name("rootfile"))
+            .and_then(|node| node.attribute("full-path"));
+        test_eq!(*sink, rootfile, Some("EPUB/package.opf"));
+    }
+
+    if let Some(package) = parse_xml(sink, "package.opf", &package) {
+        let item = |f: &dyn Fn(roxmltree::Node) -> bool| {
+            package
+                .descendants()
+                .find(|&node| node.has_tag_name("item") && f(node))
+        };
+
+        // The spine refers to the chapters through their manifest items.
+        let spine: Vec<_> = package
+            .descendants()
+            .filter(|node| node.has_tag_name("itemref"))
+            .map(|itemref| {
+                item(&|node| node.attribute("id") == itemref.attribute("idref"))
+                    .and_then(|node| node.attribute("href"))
+            })
+            .collect();
+        let expected: Vec<_> = chapters.iter().map(|&chapter| Some(chapter)).collect();
+        test_eq!(*sink, spine, expected);
+
+        let nav = item(&|node| node.attribute("href") == Some("nav.xhtml"));
+        test_eq!(*sink, nav.and_then(|node| node.attribute("properties")), Some("nav"));
+    }
+
+    if let Some(nav) = parse_xml(sink, "nav.xhtml", &nav) {
+        let hrefs: Vec<_> = nav
+            .descendants()
+            .filter(|node| node.has_tag_name("a"))
+            .filter_map(|node| node.attribute("href"))
+            .collect();
+        test_eq!(*sink, hrefs, links);
+    }
+
+    for (chapter, text) in chapters.iter().zip(&pages) {
+        parse_xml(sink, chapter, text);
+    }
+}
+
+/// Parse an XML file from an EPUB, logging an error if it is malformed.
+fn parse_xml<'a>(
+    sink: &mut String,
+    name: &str,
+    text: &'a str,
+) -> Option<roxmltree::Document<'a>> {
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    match roxmltree::Document::parse_with_options(text, options) {
+        Ok(doc) => Some(doc),
+        Err(err) => {
+            writeln!(sink, "{name} is not well-formed ({err})").unwrap();
+            None
+        }
+    }
+}
+
+/// Form fields and plain content that looks like their appearance.
+const FORM_FIELDS: &[(&str, &str)] = &[
+    (
+        r#"#pdf.text-field("a", value: "Hi", width: 60pt)"#,
+        "#field(width: 60pt, height: 1.6em, clip: true)[Hi]",
+    ),
+    (
+        r#"#pdf.checkbox("b", checked: true)"#,
+        "#field(width: 0.8em, height: 0.8em, polygon(
+          fill: black,
+          stroke: none,
+          (0%, 55%), (15%, 40%), (38%, 62%), (85%, 10%), (100%, 25%), (38%, 90%),
+        ))",
+    ),
+    (r#"#pdf.radio("c", "x")"#, "#field(width: 0.8em, height: 0.8em, radius: 50%)"),
+    (
+        r#"#pdf.dropdown("d", ("a", "b"), selected: "a", width: 60pt)"#,
+        "#field(width: 60pt, height: 1.6em, clip: true)[a#h(1fr)#box(polygon(
+          fill: black,
+          stroke: none,
+          (0pt, 0pt), (0.6em, 0pt), (0.3em, 0.4em),
+        ))]",
+    ),
+    (r#"#pdf.signature("e", width: 60pt)"#, "#field(width: 60pt, height: 3em)"),
+];
+
+/// Check that an annotation is written to PDF, but leaves PNG and SVG export
+/// unaffected.
+fn check_annot_render(sink: &mut String, kind: &str, subtype: &str) {
+    let annotated = format!(r#"#pdf.annot(kind: "{kind}", contents: "Note")[Some text]"#);
+    let (Some(annot_doc), Some(plain_doc)) =
+        (compile(annotated), compile("Some text".into()))
+    else {
+        writeln!(sink, "failed to compile the {kind} annotation").unwrap();
+        return;
+    };
+
+    let pdf = pdf_text(sink, Some(&annot_doc), &PdfOptions::default());
+    if !pdf.contains(&format!("/Subtype {subtype}\n")) {
+        writeln!(sink, "the {kind} annotation is missing in PDF").unwrap();
+    }
+
+    if !approx_equal(&render(&annot_doc), &render(&plain_doc)) {
+        writeln!(sink, "the {kind} annotation changes PNG export").unwrap();
+    }
+
+    let svg = |doc: &PagedDocument| typst_svg::svg_merged(doc, Abs::zero());
+    if svg(&annot_doc) != svg(&plain_doc) {
+        writeln!(sink, "the {kind} annotation changes SVG export").unwrap();
+    }
+}
+
+/// Check that a form field is drawn as its appearance in PNG and SVG export.
+fn check_form_render(sink: &mut String, field: &str, plain: &str) {
+    let prelude =
+        "#let field = box.with(fill: white, stroke: 0.5pt + gray, inset: 0.2em)";
+    let (Some(field_doc), Some(plain_doc)) =
+        (compile(field.into()), compile(format!("{prelude}\n{plain}")))
+    else {
+        writeln!(sink, "failed to compile {field}").unwrap();
+        return;
+    };
+
+    if !approx_equal(&render(&field_doc), &render(&plain_doc)) {
+        writeln!(sink, "{field} does not render as its appearance").unwrap();
+    }
+
+    let svg = typst_svg::svg_merged(&field_doc, Abs::zero());
+    let stroke = format!("stroke=\"{}\"", Color::GRAY.to_hex());
+    if !svg.contains(&stroke) {
+        writeln!(sink, "{field} is missing its border in SVG").unwrap();
+    }
+}
+
+/// Check that text in HTML frames is kept as text where its shaping can be
+/// reproduced with the embedded font subsets.
+fn check_html_frames(sink: &mut String) {
+    let Some(html) = compile_html(
+        r#"#html.frame(alt: "A greeting")[Hello] #html.frame[World]"#.into(),
+    ) else {
+        writeln!(sink, "failed to compile the frames").unwrap();
+        return;
+    };
+    test_eq!(*sink, html.matches(r#"role="img" aria-label="A greeting""#).count(), 1);
+    let texts: Vec<_> = html
+        .split("</text>")
+        .filter_map(|part| Some(part.rsplit_once('>')?.1.trim()))
+        .filter(|text| !text.is_empty())
+        .collect();
+    test_eq!(*sink, texts, ["Hello", "World"]);
+    // Both frames share the subset of the same font.
+    test_eq!(*sink, html.matches("@font-face").count(), 1);
+
+    // Ligatures and gradients can't be reproduced by the browser, so such text
+    // is drawn as outlines and needs no font.
+    for source in
+        ["#html.frame[fi]", "#html.frame(text(fill: gradient.linear(red, blue))[Hi])"]
+    {
+        let Some(html) = compile_html(source.into()) else {
+            writeln!(sink, "failed to compile {source}").unwrap();
+            continue;
+        };
+        if html.contains("<text") || html.contains("@font-face") {
+            writeln!(sink, "{source} is kept as text").unwrap();
+        }
+    }
+}
+
+/// Compile a standalone source to HTML.
+fn compile_html(text: String) -> Option<String> {
+    let world = TestWorld::new(Source::detached(text));
+    let doc = typst::compile::<HtmlDocument>(&world).output.ok()?;
+    typst_html::html(&doc).ok()
+}
+
+/// Compile a standalone source.
+fn compile(text: String) -> Option<PagedDocument> {
+    let world = TestWorld::new(Source::detached(text));
+    typst::compile::<PagedDocument>(&world).output.ok()
+}
+
+/// Render a document to a pixel image.
+fn render(doc: &PagedDocument) -> sk::Pixmap {
+    typst_render::render_merged(doc, 2.0, Abs::zero(), None)
+}
+
+/// Snippets that violate PDF/UA-1 and the resulting export errors.
+const UA_ERRORS: &[(&str, &str)] = &[
+    ("Text", "PDF/UA-1 requires a document title"),
+    (
+        r#"#set document(title: "T")
+           #image(bytes("<svg xmlns='http://www.w3.org/2000/svg'/>"), format: "svg")"#,
+        "PDF/UA-1 requires images to have alt text",
+    ),
+    (
+        r#"#set document(title: "T")
+           $x$"#,
+        "PDF/UA-1 requires equations to have alt text",
+    ),
+    (
+        r#"#set document(title: "T")
+           #pdf.text-field("a")"#,
+        "PDF/UA-1 requires form fields to have a tooltip",
+    ),
+    (
+        r#"#set document(title: "T")
+           #pdf.annot[Text]"#,
+        "PDF/UA-1 requires annotations to have contents",
+    ),
+];
+
+/// Check that exporting a standalone source as PDF/UA-1 fails with the given
+/// message.
+fn check_ua_error(sink: &mut String, text: &str, pdf_tags: bool, message: &str) {
+    let mut world = TestWorld::new(Source::detached(text));
+    if pdf_tags {
+        world = world.with_pdf_tags();
+    }
+
+    let Ok(doc) = typst::compile::<PagedDocument>(&world).output else {
+        writeln!(sink, "failed to compile {text:?}").unwrap();
+        return;
+    };
+
+    match typst_pdf::pdf(&doc, &ua_options()) {
+        Ok(_) => writeln!(sink, "exporting {text:?} did not fail").unwrap(),
+        Err(errors) => test_eq!(*sink, errors[0].message.as_str(), message),
+    }
+}
+
+/// Compile the test with the tags for tagged PDFs enabled and export it as
+/// PDF/UA-1.
+fn tagged_pdf(sink: &mut String, world: &TestWorld) -> Vec<u8> {
+    let Ok(doc) = typst::compile::<PagedDocument>(&world.with_pdf_tags()).output else {
+        writeln!(sink, "failed to compile the tagged document").unwrap();
+        return vec![];
+    };
+    export(sink, Some(&doc), &ua_options())
+}
+
+/// Options for exporting as PDF/UA-1.
+fn ua_options() -> PdfOptions<'static> {
+    PdfOptions {
+        standards: PdfStandards::new(&[PdfStandard::UA_1]).unwrap(),
+        ..Default::default()
+    }
+}
+
+/// Describe the structure tree of a tagged PDF as an outline with one line
+/// per structure element, indented by its depth and followed by its alt text,
+/// if any.
+///
+/// Also checks that each marked-content sequence on the pages belongs to
+/// exactly one element, which the parent tree maps it back to.
+fn struct_tree(sink: &mut String, data: &[u8]) -> String {
+    let Ok(pdf) = Pdf::new(Arc::new(data.to_vec())) else {
+        writeln!(sink, "failed to parse the PDF").unwrap();
+        return String::new();
+    };
+
+    let xref = pdf.xref();
+    let catalog = xref.get::<Dict>(xref.root_id()).unwrap();
+    let Some(root) = catalog.get::<Dict>(keys::STRUCT_TREE_ROOT) else {
+        writeln!(sink, "the structure tree is missing").unwrap();
+        return String::new();
+    };
+
+    // Walk the tree and collect the marked-content references.
+    let pages: Vec<_> = pdf.pages().iter().map(|page| page.raw().obj_id()).collect();
+    let mut outline = String::new();
+    let mut marked = HashMap::new();
+    let mut stack: Vec<(Dict, usize)> = root
+        .get::<Dict>(keys::K)
+        .map(|document| (document, 0))
+        .into_iter()
+        .collect();
+    while let Some((elem, depth)) = stack.pop() {
+        let role = elem.get::<HayroName>(keys::S).unwrap();
+        write!(outline, "{}{}", "  ".repeat(depth), role.as_str()).unwrap();
+        if let Some(alt) = elem.get::<hayro_syntax::object::String>(keys::ALT) {
+            write!(outline, " {:?}", text_string(&alt.get())).unwrap();
+        }
+        outline.push('\n');
+
+        let mut children = vec![];
+        for kid in elem.get::<Array>(keys::K).iter().flat_map(|kids| kids.iter::<Dict>())
+        {
+            if kid.contains_key(keys::S) {
+                children.push((kid, depth + 1));
+            } else if let Some(mcid) = kid.get::<i32>(keys::MCID) {
+                let page = kid.get_ref(keys::PG).map(ObjectIdentifier::from);
+                let page = pages.iter().position(|&id| id == page);
+                if marked.insert((page, mcid), elem.obj_id()).is_some() {
+                    writeln!(sink, "MCID {mcid} is referenced twice").unwrap();
+                }
+            }
+        }
+        stack.extend(children.into_iter().rev());
+    }
+
+    // Each marked-content sequence must be referenced by the element that
+    // the parent tree maps it to.
+    let parent_tree: HashMap<i32, Array> = root
+        .get::<Dict>(keys::PARENT_TREE)
+        .and_then(|tree| tree.get::<Array>(keys::NUMS))
+        .map(|nums| {
+            let mut iter = nums.flex_iter();
+            std::iter::from_fn(|| Some((iter.next::<i32>()?, iter.next::<Array>()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    for (i, page) in pdf.pages().iter().enumerate() {
+        let content = page.page_stream().unwrap_or_default();
+        let parents = page
+            .raw()
+            .get::<i32>(keys::STRUCT_PARENTS)
+            .and_then(|key| parent_tree.get(&key));
+        let mut rest = content;
+        while let Some(start) = find(rest, b"/MCID ") {
+            rest = &rest[start + 6..];
+            let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+            let mcid: i32 =
+                std::str::from_utf8(&rest[..digits]).unwrap().parse().unwrap();
+            let parent = parents
+                .and_then(|parents| parents.iter::<Dict>().nth(mcid as usize))
+                .and_then(|parent| parent.obj_id());
+            match marked.remove(&(Some(i), mcid)) {
+                Some(elem) if elem == parent => {}
+                Some(_) => writeln!(sink, "MCID {mcid} has the wrong parent").unwrap(),
+                None => writeln!(sink, "MCID {mcid} is not referenced").unwrap(),
+            }
+        }
+    }
+    for (_, mcid) in marked.keys() {
+        writeln!(sink, "MCID {mcid} is not on the page").unwrap();
+    }
+
+    outline
+}
+
+/// Decode a PDF text string.
+fn text_string(data: &[u8]) -> String {
+    match data.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => String::from_utf16_lossy(
+            &utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        None => data.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Export the document as PDF and return the file with all its streams
+/// decompressed.
+fn pdf_text(
+    sink: &mut String,
+    doc: Option<&PagedDocument>,
+    options: &PdfOptions,
+) -> String {
+    inflate(&export(sink, doc, options))
+}
+
+/// Export the document as PDF.
+fn export(
+    sink: &mut String,
+    doc: Option<&PagedDocument>,
+    options: &PdfOptions,
+) -> Vec<u8> {
+    match doc.map(|doc| typst_pdf::pdf(doc, options)) {
+        Some(Ok(data)) => data,
+        _ => {
+            writeln!(sink, "failed to export the PDF").unwrap();
+            vec![]
+        }
+    }
+}
+
+/// Return the PDF file with all its streams decompressed.
+fn inflate(data: &[u8]) -> String {
+    // Object streams and content streams are compressed, so they are
+    // inflated in place to be able to search them.
+    let mut text = String::new();
+    let mut rest = data;
+    while let Some(start) = find(rest, b"\nstream\n") {
+        text.push_str(&String::from_utf8_lossy(&rest[..start]));
+        rest = &rest[start + 8..];
+        let end = find(rest, b"\nendstream").unwrap_or(rest.len());
+        match miniz_oxide::inflate::decompress_to_vec_zlib(&rest[..end]) {
+            Ok(inflated) => text.push_str(&String::from_utf8_lossy(&inflated)),
+            Err(_) => text.push_str(&String::from_utf8_lossy(&rest[..end])),
+        }
+        text.push('\n');
+        rest = &rest[(end + 10).min(rest.len())..];
+    }
+    text.push_str(&String::from_utf8_lossy(rest));
+    text
+}
+
+/// Find the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
 /// Extract the document information.
 fn info(doc: Option<&PagedDocument>) -> DocumentInfo {
     doc.map(|doc| doc.info.clone()).unwrap_or_default()
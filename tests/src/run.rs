@@ -372,7 +372,7 @@ impl OutputType for PagedDocument {
         fn skippable_frame(frame: &Frame) -> bool {
             frame.items().all(|(_, item)| match item {
                 FrameItem::Group(group) => skippable_frame(&group.frame),
-                FrameItem::Tag(_) => true,
+                FrameItem::Tag(_) | FrameItem::Annotation(..) => true,
                 _ => false,
             })
         }
@@ -432,12 +432,7 @@ impl OutputType for PagedDocument {
 
     fn check_custom(runner: &mut Runner, doc: Option<&Self>) {
         let errors = crate::custom::check(runner.test, &runner.world, doc);
-        if !errors.is_empty() {
-            log!(runner, "custom check failed");
-            for line in errors.lines() {
-                log!(runner, "  {line}");
-            }
-        }
+        log_custom(runner, &errors);
     }
 }
 
@@ -468,6 +463,21 @@ impl OutputType for HtmlDocument {
     fn matches(live: &Self::Live, ref_data: &[u8]) -> bool {
         live.as_bytes() == ref_data
     }
+
+    fn check_custom(runner: &mut Runner, doc: Option<&Self>) {
+        let errors = crate::custom::check_html(runner.test, &runner.world, doc);
+        log_custom(runner, &errors);
+    }
+}
+
+/// Log the failures of a custom check.
+fn log_custom(runner: &mut Runner, errors: &str) {
+    if !errors.is_empty() {
+        log!(runner, "custom check failed");
+        for line in errors.lines() {
+            log!(runner, "  {line}");
+        }
+    }
 }
 
 /// Draw all frames into one image with padding in between.
@@ -519,7 +529,7 @@ fn render_links(canvas: &mut sk::Pixmap, ts: sk::Transform, frame: &Frame) {
 }
 
 /// Whether two pixel images are approximately equal.
-fn approx_equal(a: &sk::Pixmap, b: &sk::Pixmap) -> bool {
+pub fn approx_equal(a: &sk::Pixmap, b: &sk::Pixmap) -> bool {
     a.width() == b.width()
         && a.height() == b.height()
         && a.data().iter().zip(b.data()).all(|(&a, &b)| a.abs_diff(b) <= 1)
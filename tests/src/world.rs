@@ -26,6 +26,7 @@ use typst::{Feature, Library, World};
 pub struct TestWorld {
     main: Source,
     base: &'static TestBase,
+    pdf_tags: bool,
 }
 
 impl TestWorld {
@@ -37,13 +38,24 @@ impl TestWorld {
         Self {
             main: source,
             base: singleton!(TestBase, TestBase::default()),
+            pdf_tags: false,
         }
     }
+
+    /// The same world, but with the introspection tags that tagged PDF export
+    /// needs enabled.
+    pub fn with_pdf_tags(&self) -> Self {
+        Self { pdf_tags: true, ..self.clone() }
+    }
 }
 
 impl World for TestWorld {
     fn library(&self) -> &LazyHash<Library> {
-        &self.base.library
+        if self.pdf_tags {
+            &self.base.tagged_library
+        } else {
+            &self.base.library
+        }
     }
 
     fn book(&self) -> &LazyHash<FontBook> {
@@ -89,6 +101,7 @@ impl TestWorld {
 /// Shared foundation of all test worlds.
 struct TestBase {
     library: LazyHash<Library>,
+    tagged_library: LazyHash<Library>,
     book: LazyHash<FontBook>,
     fonts: Vec<Font>,
     slots: Mutex<HashMap<FileId, FileSlot>>,
@@ -102,7 +115,8 @@ impl Default for TestBase {
             .collect();
 
         Self {
-            library: LazyHash::new(library()),
+            library: LazyHash::new(library(false)),
+            tagged_library: LazyHash::new(library(true)),
             book: LazyHash::new(FontBook::from_fonts(&fonts)),
             fonts,
             slots: Mutex::new(HashMap::new()),
@@ -176,12 +190,17 @@ fn read(path: &Path) -> FileResult<Cow<'static, [u8]>> {
 }
 
 /// The extended standard library for testing.
-fn library() -> Library {
+fn library(pdf_tags: bool) -> Library {
     // Set page width to 120pt with 10pt margins, so that the inner page is
     // exactly 100pt wide. Page height is unbounded and font size is 10pt so
     // that it multiplies to nice round numbers.
+    let mut features = vec![Feature::Html];
+    if pdf_tags {
+        features.push(Feature::PdfTags);
+    }
+
     let mut lib = Library::builder()
-        .with_features([Feature::Html].into_iter().collect())
+        .with_features(features.into_iter().collect())
         .build();
 
     // Hook up helpers into the global scope.
@@ -0,0 +1,17 @@
+--- html-css-text html ---
+#text(fill: red)[Red] and #text(size: 14pt, weight: "bold")[big bold] text.
+
+--- html-css-body html ---
+// Styles shared by the whole document are declared on the body.
+#set text(fill: red, size: 12pt)
+= Heading
+Red text and #text(fill: black)[black] text.
+
+--- html-css-container html ---
+#block(fill: luma(230), inset: 8pt, width: 50%)[Boxed]
+#align(center)[Centered]
+#pad(left: 1em)[Padded]
+
+--- html-head html ---
+#html.head(html.elem("link", attrs: (rel: "stylesheet", href: "style.css")))
+Text
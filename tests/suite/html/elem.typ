@@ -13,3 +13,9 @@ Text
   val
 })
 #metadata("Hi") <l>
+
+--- html-split-single-file html ---
+// Splits have no effect when exporting a single file.
+= One
+#html.split()
+= Two
@@ -0,0 +1,8 @@
+// The archive layout, package document, and navigation document of EPUB
+// export are checked in `custom.rs`.
+
+--- epub-package html ---
+= Intro
+Hello
+== Detail
+= Outro
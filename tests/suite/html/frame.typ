@@ -0,0 +1,5 @@
+// Test frames in HTML export. Their output embeds font subsets, so the tests
+// check it in `custom.rs`.
+
+--- html-frame-text ---
+// Text is kept as text if its shaping can be reproduced with the subsets.
@@ -0,0 +1,9 @@
+// The files of multi-file HTML sites are checked in `custom.rs`.
+
+--- html-site html ---
+#set heading(numbering: "1.")
+= Intro
+See @outro.
+= Details
+== Nested
+= Outro <outro>
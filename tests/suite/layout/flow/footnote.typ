@@ -358,3 +358,7 @@ C
 #set footnote.entry(separator: v(5em))
 
 #footnote[]
+
+--- footnote-html html ---
+Hello#footnote[First] and world#footnote[Second]<second>.
+Again @second.
@@ -57,3 +57,22 @@
   [d], [e], [f],
   [g], [h], [i]
 )
+
+--- table-html-align-fill html ---
+#table(
+  columns: 2,
+  align: (left, right + horizon),
+  fill: (x, y) => if y == 0 { luma(230) },
+  table.header[Name][Value],
+  [a], [1],
+)
+
+--- table-html-gutter-header-footer html ---
+#table(
+  columns: 2,
+  gutter: 3pt,
+  table.header[A][B],
+  [1], [2],
+  [3], [4],
+  table.footer[C][D],
+)
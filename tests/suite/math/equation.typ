@@ -297,3 +297,9 @@ Looks at the @quadratic formula.
 #set page(width: 150pt)
 #set text(lang: "he")
 תהא סדרה $a_n$: $[a_n: 1, 1/2, 1/3, dots]$
+
+--- math-equation-html-inline html ---
+Let $a^2 + b^2 = c^2$ hold.
+
+--- math-equation-html-block html ---
+$ sum_(k=1)^n k = (n(n+1)) / 2 $
@@ -0,0 +1,28 @@
+// Test the MathML that equations are exported as in HTML.
+
+--- math-html-frac html ---
+Fractions $a/b$ and $1/(x + y)$ and $binom(n, k)$.
+
+--- math-html-root html ---
+$sqrt(x) + root(3, x + 1)$
+
+--- math-html-attach html ---
+$x_1^2 + a_(i j) + a' + attach(X, tl: a, br: b)$
+
+--- math-html-accent html ---
+$hat(x) + arrow(v)$
+
+--- math-html-mat html ---
+$mat(1, 2; 3, 4) + vec(a, b)$
+
+--- math-html-cases html ---
+$ cases(1 &"if" x > 0, 0 &"otherwise") $
+
+--- math-html-align html ---
+$ a &= b + c \ &= d $
+
+--- math-html-block-in-par html ---
+// Block equations interrupt the paragraph.
+Hence
+$ x = 1 $
+holds.
@@ -83,3 +83,18 @@ Now we have multiple bibliographies containing @glacier-melt @keshav2007read
 @Zee04
 
 #bibliography("/assets/bib/works_too.bib")
+
+--- bibliography-html html ---
+// Keys with spaces are turned into valid ids.
+#let data = bytes(
+  ```yaml
+  Doe 2020:
+    type: book
+    title: A Book
+    author: Doe, Jane
+    date: 2020
+  ```.text,
+)
+
+Hello #cite(label("Doe 2020")).
+#bibliography(data)
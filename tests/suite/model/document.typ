@@ -16,6 +16,25 @@ What's up?
 #set document(author: (123,))
 What's up?
 
+--- document-set-viewer ---
+#set document(
+  page-layout: "two-page-right",
+  initial-view: "outline",
+  hide-toolbar: true,
+  display-title: true,
+)
+
+--- document-set-transition ---
+#set document(transition: "push", transition-duration: duration(seconds: 2))
+
+--- document-set-transition-duration-negative ---
+// Error: 36-57 transition duration must not be negative
+#set document(transition-duration: duration(seconds: -1))
+
+--- document-page-layout-bad ---
+// Error: 28-34 expected "single", "one-column", "two-column-left", "two-column-right", "two-page-left", "two-page-right", or auto
+#set document(page-layout: "book")
+
 --- document-set-after-content ---
 // Document set rules can appear anywhere in top-level realization, also after
 // content.
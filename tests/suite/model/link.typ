@@ -86,3 +86,15 @@ Text <hey>
 Inline: #link(url, body)
 
 #link(url, block(inset: 4pt, [Block: ] + body))
+
+--- link-html-label html ---
+= Intro
+= Intro
+#link(<target>)[Go] to the #strong[target]<target>.
+
+--- link-html-no-element html ---
+#metadata(none) <meta>
+
+// Warning: 2-20 link target does not generate an HTML element
+// Hint: 2-20 only elements that are exported as HTML elements can be linked to
+#link(<meta>)[Meta]
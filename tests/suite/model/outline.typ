@@ -301,3 +301,10 @@ A
 
 = 测
 = 很
+
+--- outline-html html ---
+#outline()
+
+= Introduction
+== Background
+= Conclusion
@@ -85,3 +85,8 @@ Text seen on #ref(<text>, form: "page", supplement: "Page").
 // Test reference with non-whitespace before it.
 #figure[] <1>
 #test([(#ref(<1>))], [(@1)])
+
+--- ref-html html ---
+#set heading(numbering: "1.")
+= Introduction <intro>
+See @intro.
@@ -0,0 +1,35 @@
+// Test annotations. They are only visible in PDF export, so these tests check
+// their arguments and that other exports are unaffected.
+
+--- pdf-annot ---
+#{
+  pdf.annot(
+    author: "Reviewer",
+    date: datetime(year: 2025, month: 3, day: 14),
+    contents: "Add a chart here.",
+  )
+  pdf.annot(file: "/assets/text/hello.txt", contents: "The raw data")
+}
+
+--- pdf-annot-file-missing ---
+// Error: 2-25 file annotations need a file
+// Hint: 2-25 specify the file with the `file` argument
+#pdf.annot(kind: "file")
+
+--- pdf-annot-file-wrong-kind ---
+// Error: 2-65 only file annotations can have a file
+// Hint: 2-65 set `kind` to `"file"` or remove the `file` argument
+#pdf.annot(kind: "highlight", file: "/assets/text/hello.txt")[A]
+
+--- pdf-annot-highlight-without-body ---
+// Error: 2-30 highlight annotations need a body
+// Hint: 2-30 pass the content to highlight as the body
+#pdf.annot(kind: "highlight")
+
+--- pdf-annot-invalid-kind ---
+// Error: 18-26 expected "text", "highlight", "free-text", "file", or auto
+#pdf.annot(kind: "sticky")
+
+--- pdf-annot-render ---
+// Annotations with a body are written to PDF, but don't change PNG and SVG
+// export. The comparison is in `custom.rs`.
@@ -0,0 +1,7 @@
+--- pdf-artifact ---
+#pdf.artifact[]
+#pdf.artifact(kind: "header")[]
+
+--- pdf-artifact-invalid-kind ---
+// Error: 21-26 expected "header", "footer", "page", or "other"
+#pdf.artifact(kind: "foo")[]
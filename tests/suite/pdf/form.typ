@@ -0,0 +1,15 @@
+// Test form fields. Their interactive part is only visible in PDF export, so
+// these tests check their arguments and how they are drawn elsewhere.
+
+--- pdf-dropdown-selected-invalid ---
+// Error: 2-46 selected value is not one of the options
+// Hint: 2-46 set `editable: true` to allow arbitrary values
+#pdf.dropdown("c", ("a", "b"), selected: "c")
+
+--- pdf-text-field-max-length-invalid ---
+// Error: 34-38 expected integer or none, found string
+#pdf.text-field("a", max-length: "10")
+
+--- pdf-form-render ---
+// Form fields are drawn as their appearance in PNG and SVG export. The
+// comparison with equivalent boxes is in `custom.rs`.
@@ -0,0 +1,51 @@
+// Test the structure tree of tagged PDFs. The checks are in `custom.rs`. Unless
+// a test checks text or images, its content is hidden so that it has no
+// visible output.
+
+--- pdf-tags-structure ---
+#set document(title: "Structure")
+#place(hide[
+  = Heading
+  A paragraph.
+
+  - Bullet
+  + Number
+
+  #table(
+    columns: 2,
+    table.header[A][B],
+    [1], [2],
+  )
+])
+
+--- pdf-tags-artifact ---
+#set document(title: "Artifacts")
+#set page(header: hide[== Header])
+#place(hide[
+  = Heading
+  #pdf.artifact[=== Decoration]
+])
+
+--- pdf-tags-disabled ---
+#hide[A paragraph.]
+
+--- pdf-tags-tree ---
+#set document(title: "Baum")
+#set text(lang: "de")
+#set math.equation(alt: "x hoch zwei")
+= Kapitel
+Ein Absatz.
+
+#figure(
+  image(
+    bytes("<svg xmlns='http://www.w3.org/2000/svg' width='10' height='10'/>"),
+    format: "svg",
+    alt: "Ein Quadrat",
+  ),
+  caption: [Bild],
+)
+
+$ x^2 $
+
+--- pdf-tags-ua-errors ---
+// The snippets that are exported as PDF/UA-1 are in `custom.rs`.
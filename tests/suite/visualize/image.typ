@@ -76,6 +76,119 @@ A #box(image("/assets/images/tiger.jpg", height: 1cm, width: 80%)) B
   ```.text
 ))
 
+--- image-pdf ---
+// Test PDF pages, including a rotated one.
+#let pdf = bytes(
+  ```
+  %PDF-1.7
+  1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj
+  2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox [0 0 20 10] >> endobj
+  3 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R >> endobj
+  4 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R /Rotate 90 >> endobj
+  5 0 obj << /Length 46 >> stream
+  1 0 0 rg 0 0 20 10 re f 0 0 1 rg 0 0 5 5 re f
+  endstream endobj
+  %%EOF
+  ```.text
+)
+
+#image(pdf, width: 40pt)
+#image(pdf, page: 2, height: 40pt)
+
+--- image-pdf-page-out-of-range ---
+#let pdf = bytes(
+  ```
+  %PDF-1.7
+  1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj
+  2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox [0 0 20 10] >> endobj
+  3 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R >> endobj
+  4 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R /Rotate 90 >> endobj
+  5 0 obj << /Length 46 >> stream
+  1 0 0 rg 0 0 20 10 re f 0 0 1 rg 0 0 5 5 re f
+  endstream endobj
+  %%EOF
+  ```.text
+)
+
+// Error: 2-21 page 3 does not exist
+// Hint: 2-21 the document has 2 pages
+#image(pdf, page: 3)
+
+--- image-pdf-invalid ---
+// Error: 2-26 the PDF could not be loaded
+#image(bytes("%PDF-1.7"))
+
+--- image-pdf-leading-whitespace ---
+// Error: 2-29 the PDF could not be loaded
+#image(bytes(" \n%PDF-1.7"))
+
+--- image-pdf-header-not-at-start ---
+// Error: 2-34 unknown image format
+#image(bytes("garbage %PDF-1.7"))
+
+--- image-eps ---
+// Error: 2-41 EPS images are not supported
+// Hint: 2-41 convert the image to PDF or SVG, for example with `ps2pdf -dEPSCrop`
+#image(bytes("%!PS-Adobe-3.0 EPSF-3.0"))
+
+--- image-webp ---
+// A lossless 2x2 WebP image.
+#image(
+  bytes((
+    0x52, 0x49, 0x46, 0x46, 0x98, 0x00, 0x00, 0x00, 0x57, 0x45, 0x42, 0x50,
+    0x56, 0x50, 0x38, 0x4C, 0x8C, 0x00, 0x00, 0x00, 0x2F, 0x01, 0x40, 0x00,
+    0x00, 0xCD, 0x55, 0x20, 0x22, 0x02, 0x1E, 0x48, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00,
+    0x00, 0x0F, 0x44, 0x02, 0x00, 0x00, 0x00, 0x00, 0xE0, 0xFC, 0x3D, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xE4, 0x81, 0x48, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x9C, 0x7F, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80,
+    0x23, 0xF1, 0x34, 0x00,
+  )),
+  width: 2cm,
+)
+
+--- image-bmp ---
+// A 2x2 BMP image with a resolution of 144 DPI.
+#let bmp = bytes((
+  0x42, 0x4D, 0x46, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x36, 0x00,
+  0x00, 0x00, 0x28, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00,
+  0x00, 0x00, 0x01, 0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00,
+  0x00, 0x00, 0x25, 0x16, 0x00, 0x00, 0x25, 0x16, 0x00, 0x00, 0x00, 0x00,
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0xFF,
+  0x00, 0x00, 0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0x00, 0x00,
+))
+
+#image(bmp, width: 2cm)
+#context test(calc.round(measure(image(bmp)).width.pt(), digits: 2), 1.0)
+
+--- image-tiff-cmyk ---
+// A 2x2 uncompressed CMYK TIFF image.
+#image(
+  bytes((
+    0x49, 0x49, 0x2A, 0x00, 0x08, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x01,
+    0x03, 0x00, 0x04, 0x00, 0x00, 0x00, 0x86, 0x00, 0x00, 0x00, 0x03, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x11, 0x01,
+    0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 0x8E, 0x00, 0x00, 0x00, 0x15, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x16, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x17, 0x01,
+    0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x1C, 0x01,
+    0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x08, 0x00, 0x08, 0x00, 0x08, 0x00, 0x08, 0x00, 0xFF, 0x00,
+    0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00,
+    0x00, 0xFF,
+  )),
+  width: 2cm,
+)
+
 --- image-pixmap-rgb8 ---
 #image(
   bytes((
@@ -243,7 +356,7 @@ A #box(image("/assets/images/tiger.jpg", height: 1cm, width: 80%)) B
 --- image-png-but-pixmap-format ---
 #image(
   read("/assets/images/tiger.jpg", encoding: none),
-  // Error: 11-18 expected "png", "jpg", "gif", dictionary, "svg", or auto
+  // Error: 11-18 expected "png", "jpg", "gif", "webp", "avif", "tiff", "bmp", dictionary, "svg", "pdf", or auto
   format: "rgba8",
 )
 
@@ -267,3 +380,10 @@ A #box(image("/assets/images/tiger.jpg", height: 1cm, width: 80%)) B
 --- issue-3733-dpi-svg ---
 #set page(width: 200pt, height: 200pt, margin: 0pt)
 #image("/assets/images/relative.svg")
+
+--- image-html html ---
+#let square = bytes(
+  `<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="10" height="10" fill="red"/></svg>`.text
+)
+#image(square, alt: "A red square", width: 30pt)
+#image(square, width: 50%)